@@ -45,6 +45,10 @@ pub struct CoordinatorSettings {
     pub ipc_socket_path: String,
     pub peer_id_whitelist: HashSet<String>,
     pub keystore_path: PathBuf,
+    pub session_resume_delay: u64,
+    pub audit_log_path: PathBuf,
+    pub idempotency_window: u64,
+    pub outcome_retention: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -57,6 +61,10 @@ pub struct SignerSettings {
     pub ecdsa_port: u16,
     pub ecdsa_dkg_timeout: u64,
     pub ecdsa_sign_timeout: u64,
+    pub approval_timeout: u64,
+    pub wasm_policy_path: String,
+    pub wasm_policy_fuel: u64,
+    pub wasm_policy_max_memory: usize,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -79,6 +87,7 @@ pub struct SessionSettings {
     pub state_channel_retry_interval: u64,
     pub signing_round1_timeout: u64,
     pub signing_round2_timeout: u64,
+    pub audit_checkpoint_interval: u64,
 }
 
 lazy_static! {
@@ -0,0 +1,402 @@
+//! Append-only, hash-chained audit log of the sessions a coordinator or signer took part in.
+//!
+//! Every line is `<hash> <record>`, where the hash is the hex SHA-256 of the record JSON
+//! exactly as written and every record names the hash of the one before it, so editing,
+//! removing or reordering a record breaks the chain from there on. Every
+//! `checkpoint_interval` records, and whenever the log is opened with unsigned records
+//! at its end, a checkpoint record signs the hash of the previous record with the
+//! writer's identity key. [`verify_audit_log`] checks the chain and the checkpoint
+//! signatures without needing anything but the file.
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{
+    CryptoType, PkId, ValidatorIdentity, ValidatorIdentityIdentity, ValidatorIdentityKeypair,
+    ValidatorIdentityPublicKey,
+};
+use crate::types::{KeyState, SessionRequest, SessionResult};
+use crate::utils::now;
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditError {
+    #[error("audit log io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("audit record on line {0} is malformed: {1}")]
+    Malformed(usize, String),
+    #[error("audit record on line {0} breaks the chain: {1}")]
+    Broken(usize, String),
+    #[error("failed to encode audit record: {0}")]
+    Encode(#[from] serde_json::Error),
+    #[error("failed to sign audit checkpoint: {0}")]
+    Sign(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AuditEntry<VII: ValidatorIdentityIdentity> {
+    /// A DKG or signing session a node asked the coordinator for.
+    Session {
+        requester: VII,
+        request: SessionRequest<VII>,
+        /// The DKG session that generated the key, if known.
+        dkg_session_id: Option<String>,
+        /// Unix seconds.
+        requested_at: u64,
+        finished_at: u64,
+        /// Holds the signature suite of a successful signing.
+        result: SessionResult<VII>,
+    },
+    /// A signer's decision on a request carrying a message to sign.
+    Signing {
+        pkid: PkId,
+        crypto_type: CryptoType,
+        subsession_id: String,
+        /// Hex encoded.
+        message: String,
+        /// Hex encoded.
+        tweak_data: Option<String>,
+        requester: Option<VII>,
+        decided_at: u64,
+        /// `Err` with the reason if the signer refused to sign.
+        decision: Result<(), String>,
+    },
+    /// A key moving to another lifecycle state.
+    KeyLifecycle {
+        pkid: PkId,
+        from: KeyState,
+        to: KeyState,
+        /// The node that asked for it, `None` for the operator and on signers.
+        requester: Option<VII>,
+        /// The signers that confirmed erasing their share of a destroyed key.
+        confirmed_by: Vec<VII>,
+        changed_at: u64,
+    },
+    /// The operator adding a signer to the whitelist, or removing it.
+    Whitelist {
+        signer: VII,
+        label: Option<String>,
+        added: bool,
+        changed_at: u64,
+    },
+    /// The writer vouching for every record before this one.
+    Checkpoint {
+        /// The writer's identity.
+        signer: String,
+        /// Hex encoded identity public key.
+        public_key: String,
+        /// Hex encoded signature of the previous record's hash.
+        signature: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct AuditRecord<E> {
+    seq: u64,
+    prev_hash: String,
+    timestamp: u64,
+    entry: E,
+}
+
+// a checkpoint as it appears in `AuditEntry`, readable without knowing the identity type
+#[derive(Serialize, Deserialize)]
+struct CheckpointEntry {
+    #[serde(rename = "Checkpoint")]
+    checkpoint: Checkpoint,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    signer: String,
+    public_key: String,
+    signature: String,
+}
+
+// splits a line into its hash and record, checking the hash
+fn parse_line(
+    index: usize,
+    line: &str,
+) -> Result<(String, AuditRecord<serde_json::Value>), AuditError> {
+    let (hash, body) = line
+        .split_once(' ')
+        .ok_or_else(|| AuditError::Malformed(index + 1, "missing hash".to_string()))?;
+    if hex::encode(Sha256::digest(body.as_bytes())) != hash {
+        return Err(AuditError::Broken(
+            index + 1,
+            "hash does not match the record".to_string(),
+        ));
+    }
+    let record =
+        serde_json::from_str(body).map_err(|e| AuditError::Malformed(index + 1, e.to_string()))?;
+    Ok((hash.to_string(), record))
+}
+
+pub(crate) struct AuditLog<K: ValidatorIdentityKeypair> {
+    file: File,
+    keypair: K,
+    checkpoint_interval: u64,
+    next_seq: u64,
+    head: String,
+    // records appended since the last checkpoint
+    unsigned: u64,
+}
+
+impl<K: ValidatorIdentityKeypair> AuditLog<K> {
+    /// Opens the log at `path`, creating it if needed, and checkpoints any unsigned
+    /// records left by the previous run.
+    pub(crate) fn open(
+        path: impl AsRef<Path>,
+        keypair: K,
+        checkpoint_interval: u64,
+    ) -> Result<Self, AuditError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut next_seq = 0;
+        let mut head = GENESIS_HASH.to_string();
+        let mut unsigned = 0;
+        if path.exists() {
+            let data = std::fs::read_to_string(path)?;
+            let complete = data.rfind('\n').map_or(0, |end| end + 1);
+            if complete != data.len() {
+                // the writer stopped in the middle of an append
+                tracing::warn!(
+                    "Discarding {} bytes of truncated audit record",
+                    data.len() - complete
+                );
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)?
+                    .set_len(complete as u64)?;
+            }
+            for (index, line) in data[..complete].lines().enumerate() {
+                let (hash, record) = parse_line(index, line)?;
+                if record.prev_hash != head {
+                    return Err(AuditError::Broken(
+                        index + 1,
+                        "previous hash does not match".to_string(),
+                    ));
+                }
+                if record.entry.get("Checkpoint").is_some() {
+                    unsigned = 0;
+                } else {
+                    unsigned += 1;
+                }
+                next_seq = record.seq + 1;
+                head = hash;
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut log = Self {
+            file,
+            keypair,
+            checkpoint_interval: checkpoint_interval.max(1),
+            next_seq,
+            head,
+            unsigned,
+        };
+        if log.unsigned > 0 {
+            log.checkpoint()?;
+        }
+        Ok(log)
+    }
+    fn write<E: Serialize>(&mut self, entry: &E) -> Result<(), AuditError> {
+        let body = serde_json::to_string(&AuditRecord {
+            seq: self.next_seq,
+            prev_hash: self.head.clone(),
+            timestamp: now(),
+            entry,
+        })?;
+        let hash = hex::encode(Sha256::digest(body.as_bytes()));
+        self.file
+            .write_all(format!("{} {}\n", hash, body).as_bytes())?;
+        self.file.sync_data()?;
+        self.next_seq += 1;
+        self.head = hash;
+        Ok(())
+    }
+    fn checkpoint(&mut self) -> Result<(), AuditError> {
+        let head = hex::decode(&self.head).map_err(|e| AuditError::Sign(e.to_string()))?;
+        let signature = self
+            .keypair
+            .sign(head)
+            .map_err(|e| AuditError::Sign(e.to_string()))?;
+        let public_key = self.keypair.to_public_key();
+        self.write(&CheckpointEntry {
+            checkpoint: Checkpoint {
+                signer: public_key.to_identity().to_fmt_string(),
+                public_key: hex::encode(public_key.to_bytes()),
+                signature: hex::encode(signature),
+            },
+        })?;
+        self.unsigned = 0;
+        Ok(())
+    }
+    pub(crate) fn append<VII: ValidatorIdentityIdentity + Serialize>(
+        &mut self,
+        entry: &AuditEntry<VII>,
+    ) -> Result<(), AuditError> {
+        self.write(entry)?;
+        self.unsigned += 1;
+        if self.unsigned >= self.checkpoint_interval {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+}
+
+/// What [`verify_audit_log`] found in an intact log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSummary {
+    pub records: u64,
+    pub checkpoints: u64,
+    /// Identities that signed the checkpoints.
+    pub signers: BTreeSet<String>,
+    /// Records after the last checkpoint, chained but not signed yet.
+    pub unsigned: u64,
+}
+
+impl Display for AuditSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} records, {} checkpoints signed by [{}], {} records after the last checkpoint",
+            self.records,
+            self.checkpoints,
+            self.signers.iter().cloned().collect::<Vec<_>>().join(", "),
+            self.unsigned
+        )
+    }
+}
+
+/// Checks that the log at `path` is an unbroken chain and that every checkpoint is
+/// signed by the key it names, which must belong to `signer` if given.
+pub fn verify_audit_log<VI: ValidatorIdentity>(
+    path: impl AsRef<Path>,
+    signer: Option<&VI::Identity>,
+) -> Result<AuditSummary, AuditError> {
+    let data = std::fs::read_to_string(path)?;
+    if !data.is_empty() && !data.ends_with('\n') {
+        return Err(AuditError::Malformed(
+            data.lines().count(),
+            "truncated record".to_string(),
+        ));
+    }
+    let mut summary = AuditSummary {
+        records: 0,
+        checkpoints: 0,
+        signers: BTreeSet::new(),
+        unsigned: 0,
+    };
+    let mut head = GENESIS_HASH.to_string();
+    for (index, line) in data.lines().enumerate() {
+        let (hash, record) = parse_line(index, line)?;
+        let broken = |reason: &str| AuditError::Broken(index + 1, reason.to_string());
+        if record.seq != summary.records {
+            return Err(broken("records are missing or out of order"));
+        }
+        if record.prev_hash != head {
+            return Err(broken("previous hash does not match"));
+        }
+        if record.entry.get("Checkpoint").is_some() {
+            let Checkpoint {
+                signer: identity,
+                public_key,
+                signature,
+            } = serde_json::from_value::<CheckpointEntry>(record.entry)
+                .map_err(|e| AuditError::Malformed(index + 1, e.to_string()))?
+                .checkpoint;
+            let public_key = hex::decode(public_key)
+                .ok()
+                .and_then(|bytes| VI::PublicKey::from_bytes(bytes).ok())
+                .ok_or_else(|| broken("invalid checkpoint public key"))?;
+            let signature = hex::decode(signature).map_err(|_| broken("invalid signature"))?;
+            if !public_key.verify(hex::decode(&head).unwrap(), signature) {
+                return Err(broken("checkpoint signature does not verify"));
+            }
+            let key_identity = public_key.to_identity();
+            if key_identity.to_fmt_string() != identity {
+                return Err(broken("checkpoint signer does not own the key"));
+            }
+            if signer.is_some_and(|signer| signer != &key_identity) {
+                return Err(broken("checkpoint signed by an unexpected identity"));
+            }
+            summary.checkpoints += 1;
+            summary.signers.insert(identity);
+            summary.unsigned = 0;
+        } else {
+            summary.unsigned += 1;
+        }
+        summary.records += 1;
+        head = hash;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::validator_identity::p2p_identity::P2pIdentity;
+    use libp2p::PeerId;
+
+    fn signing_entry(message: &[u8]) -> AuditEntry<PeerId> {
+        AuditEntry::Signing {
+            pkid: PkId::new(vec![CryptoType::Ed25519.into(), 1]),
+            crypto_type: CryptoType::Ed25519,
+            subsession_id: "subsession".to_string(),
+            message: hex::encode(message),
+            tweak_data: None,
+            requester: Some(PeerId::random()),
+            decided_at: now(),
+            decision: Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_audit_log_chain() {
+        let dir = std::env::temp_dir().join(format!("audit_log_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("audit.log");
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let identity = keypair.public().to_peer_id();
+
+        let mut log = AuditLog::open(&path, keypair.clone(), 2).unwrap();
+        for message in [&b"one"[..], b"two", b"three"] {
+            log.append(&signing_entry(message)).unwrap();
+        }
+        drop(log);
+        let summary = verify_audit_log::<P2pIdentity>(&path, Some(&identity)).unwrap();
+        assert_eq!(
+            (summary.records, summary.checkpoints, summary.unsigned),
+            (4, 1, 1)
+        );
+        assert!(verify_audit_log::<P2pIdentity>(&path, Some(&PeerId::random())).is_err());
+
+        // reopening signs what the previous run left unsigned
+        AuditLog::open(&path, keypair, 2).unwrap();
+        let summary = verify_audit_log::<P2pIdentity>(&path, Some(&identity)).unwrap();
+        assert_eq!(
+            (summary.records, summary.checkpoints, summary.unsigned),
+            (5, 2, 0)
+        );
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        let tampered = data.replacen(&hex::encode(b"two"), &hex::encode(b"TWO"), 1);
+        std::fs::write(&path, tampered).unwrap();
+        assert!(matches!(
+            verify_audit_log::<P2pIdentity>(&path, None),
+            Err(AuditError::Broken(_, _))
+        ));
+        let mut lines = data.lines().collect::<Vec<_>>();
+        lines.remove(1);
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        assert!(verify_audit_log::<P2pIdentity>(&path, None).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
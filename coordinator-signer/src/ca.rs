@@ -0,0 +1,386 @@
+//! Threshold X.509 certificate authority.
+//!
+//! The CA key is a group key generated by DKG, so it never exists in one place: the
+//! TBSCertificate is built locally and signed by the signers through [`Node::sign_async`].
+//!
+//! Supported signature algorithm identifiers for the CA key:
+//! - `id-Ed25519` (1.3.101.112, RFC 8410), for [`CryptoType::Ed25519`] group keys. FROST
+//!   (Ed25519, SHA-512) signatures are plain RFC 8032 signatures, so the certificates verify
+//!   with any X.509 implementation.
+//!
+//! [`CryptoType::P256`] group keys are rejected: FROST(P-256, SHA-256) produces Schnorr
+//! signatures, which cannot be encoded as `ecdsa-with-SHA256` (1.2.840.10045.4.3.2) and
+//! would not verify anywhere. Use an Ed25519 group key until threshold ECDSA over P-256 exists.
+//!
+//! Supported subject keys in PKCS#10 requests:
+//! - `id-Ed25519` (1.3.101.112), request signed with `id-Ed25519`
+//! - `id-ecPublicKey` (1.2.840.10045.2.1) on `prime256v1` (1.2.840.10045.3.1.7), request signed
+//!   with `ecdsa-with-SHA256` (1.2.840.10045.4.3.2)
+//!
+//! The same group key can also act as an OpenSSH certificate authority (`ssh-ed25519`),
+//! see [`SshCertificateAuthority`].
+mod certificate;
+mod csr;
+pub(crate) mod der;
+mod ssh;
+
+use std::time::Duration;
+
+use certificate::{certificate_der, TbsCertificate};
+pub use certificate::{
+    CertificatePolicy, DistinguishedName, ExtendedKeyUsage, KeyUsage, SubjectAltName,
+};
+pub use csr::CertificateRequest;
+pub use ssh::{SshCertType, SshCertificateAuthority, SshCertificateOptions, SshPublicKey};
+
+use common::Settings;
+use ed25519_dalek::Verifier as _;
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentity};
+use crate::node::Node;
+
+pub(crate) const OID_ED25519: &[u64] = &[1, 3, 101, 112];
+pub(crate) const OID_ED448: &[u64] = &[1, 3, 101, 113];
+pub(crate) const OID_EC_PUBLIC_KEY: &[u64] = &[1, 2, 840, 10045, 2, 1];
+pub(crate) const OID_PRIME256V1: &[u64] = &[1, 2, 840, 10045, 3, 1, 7];
+pub(crate) const OID_SECP256K1: &[u64] = &[1, 3, 132, 0, 10];
+pub(crate) const OID_ECDSA_WITH_SHA256: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+
+pub(crate) const OID_COMMON_NAME: &[u64] = &[2, 5, 4, 3];
+pub(crate) const OID_COUNTRY: &[u64] = &[2, 5, 4, 6];
+pub(crate) const OID_ORGANIZATION: &[u64] = &[2, 5, 4, 10];
+pub(crate) const OID_ORGANIZATIONAL_UNIT: &[u64] = &[2, 5, 4, 11];
+
+pub(crate) const OID_SUBJECT_KEY_IDENTIFIER: &[u64] = &[2, 5, 29, 14];
+pub(crate) const OID_KEY_USAGE: &[u64] = &[2, 5, 29, 15];
+pub(crate) const OID_SUBJECT_ALT_NAME: &[u64] = &[2, 5, 29, 17];
+pub(crate) const OID_BASIC_CONSTRAINTS: &[u64] = &[2, 5, 29, 19];
+pub(crate) const OID_AUTHORITY_KEY_IDENTIFIER: &[u64] = &[2, 5, 29, 35];
+pub(crate) const OID_EXT_KEY_USAGE: &[u64] = &[2, 5, 29, 37];
+
+pub(crate) const OID_SERVER_AUTH: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 1];
+pub(crate) const OID_CLIENT_AUTH: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 2];
+pub(crate) const OID_CODE_SIGNING: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 3];
+pub(crate) const OID_EMAIL_PROTECTION: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 4];
+pub(crate) const OID_TIME_STAMPING: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 8];
+pub(crate) const OID_OCSP_SIGNING: &[u64] = &[1, 3, 6, 1, 5, 5, 7, 3, 9];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CaError {
+    #[error("DER error: {0}")]
+    Der(String),
+    #[error("Invalid CSR: {0}")]
+    InvalidCsr(String),
+    #[error("Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("crypto type {0} cannot sign certificates: {1}")]
+    UnsupportedCryptoType(CryptoType, String),
+    #[error("Invalid SSH key: {0}")]
+    InvalidSshKey(String),
+    #[error("Invalid policy: {0}")]
+    InvalidPolicy(String),
+    #[error("Signing error: {0}")]
+    SigningError(String),
+}
+
+// Ed25519 group key behind a PkId; signing goes through the coordinator and every
+// returned signature is checked against the key before it is used.
+pub(crate) struct GroupKey<'a, VI: ValidatorIdentity> {
+    node: &'a Node<VI>,
+    pkid: PkId,
+    public_key: ed25519_dalek::VerifyingKey,
+}
+impl<'a, VI: ValidatorIdentity> GroupKey<'a, VI> {
+    pub(crate) async fn fetch(node: &'a Node<VI>, pkid: PkId) -> Result<Self, CaError> {
+        let crypto_type = pkid
+            .crypto_type()
+            .map_err(|e| CaError::SigningError(e.to_string()))?;
+        match crypto_type {
+            CryptoType::Ed25519 => {}
+            CryptoType::P256 => {
+                return Err(CaError::UnsupportedCryptoType(
+                    crypto_type,
+                    "FROST P256 produces Schnorr signatures, not ECDSA; use an ed25519 key"
+                        .to_string(),
+                ));
+            }
+            _ => {
+                return Err(CaError::UnsupportedCryptoType(
+                    crypto_type,
+                    "only ed25519 group keys produce standard signatures".to_string(),
+                ));
+            }
+        }
+        let group_public_key = node
+            .pk_async(
+                pkid.clone(),
+                None,
+                Some(Duration::from_secs(
+                    Settings::global().node.connection_timeout,
+                )),
+            )
+            .await
+            .map_err(|e| CaError::SigningError(e.to_string()))?
+            .group_public_key_info
+            .group_public_key_tweak;
+        let public_key = <[u8; 32]>::try_from(group_public_key.as_slice())
+            .map_err(|_| CaError::SigningError("ed25519 group key must be 32 bytes".to_string()))
+            .and_then(|bytes| {
+                ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+                    .map_err(|e| CaError::SigningError(e.to_string()))
+            })?;
+        Ok(Self {
+            node,
+            pkid,
+            public_key,
+        })
+    }
+    pub(crate) fn public_key(&self) -> &[u8; 32] {
+        self.public_key.as_bytes()
+    }
+    pub(crate) async fn sign(&self, message: &[u8]) -> Result<[u8; 64], CaError> {
+        let signature_suite = self
+            .node
+            .sign_async(
+                self.pkid.clone(),
+                message.to_vec(),
+                None,
+                Some(Duration::from_secs(
+                    Settings::global().node.connection_timeout,
+                )),
+            )
+            .await
+            .map_err(|e| CaError::SigningError(e.to_string()))?;
+        let signature = ed25519_dalek::Signature::from_slice(&signature_suite.signature())
+            .map_err(|e| CaError::SigningError(e.to_string()))?;
+        self.public_key
+            .verify(message, &signature)
+            .map_err(|e| CaError::SigningError(format!("group signature is invalid: {}", e)))?;
+        Ok(signature.to_bytes())
+    }
+}
+
+/// A certificate authority whose key is the group key behind `pkid`.
+pub struct CertificateAuthority<'a, VI: ValidatorIdentity> {
+    key: GroupKey<'a, VI>,
+    issuer: DistinguishedName,
+}
+impl<'a, VI: ValidatorIdentity> CertificateAuthority<'a, VI> {
+    pub async fn new(
+        node: &'a Node<VI>,
+        pkid: PkId,
+        issuer: DistinguishedName,
+    ) -> Result<Self, CaError> {
+        // validate the name early so that a bad issuer does not surface on every issuance
+        issuer.to_der()?;
+        Ok(Self {
+            key: GroupKey::fetch(node, pkid).await?,
+            issuer,
+        })
+    }
+    pub fn pkid(&self) -> &PkId {
+        &self.key.pkid
+    }
+    /// The CA's SubjectPublicKeyInfo in DER.
+    pub fn subject_public_key_info(&self) -> Vec<u8> {
+        ed25519_spki(self.key.public_key())
+    }
+    /// Self-signed root certificate for the group key, PEM encoded.
+    pub async fn self_signed_root(&self, policy: &CertificatePolicy) -> Result<String, CaError> {
+        if !policy.is_ca {
+            return Err(CaError::InvalidPolicy(
+                "root certificate policy must be a ca policy".to_string(),
+            ));
+        }
+        let name = self.issuer.to_der()?;
+        let spki = self.subject_public_key_info();
+        self.sign_certificate(
+            TbsCertificate {
+                signature_algorithm: &ed25519_algorithm_identifier(),
+                issuer: &name,
+                issuer_public_key: self.key.public_key(),
+                subject: &name,
+                subject_public_key_info: &spki,
+                subject_public_key: self.key.public_key(),
+            },
+            policy,
+        )
+        .await
+    }
+    /// Issues a certificate for a PKCS#10 request (PEM or DER), PEM encoded.
+    pub async fn issue(&self, csr: &[u8], policy: &CertificatePolicy) -> Result<String, CaError> {
+        let request = CertificateRequest::parse(csr)?;
+        let issuer = self.issuer.to_der()?;
+        self.sign_certificate(
+            TbsCertificate {
+                signature_algorithm: &ed25519_algorithm_identifier(),
+                issuer: &issuer,
+                issuer_public_key: self.key.public_key(),
+                subject: &request.subject,
+                subject_public_key_info: &request.subject_public_key_info,
+                subject_public_key: &request.subject_public_key,
+            },
+            policy,
+        )
+        .await
+    }
+    async fn sign_certificate(
+        &self,
+        tbs: TbsCertificate<'_>,
+        policy: &CertificatePolicy,
+    ) -> Result<String, CaError> {
+        let tbs_der = tbs.to_der(policy)?;
+        let signature = self.key.sign(&tbs_der).await?;
+        let certificate = certificate_der(&tbs_der, tbs.signature_algorithm, &signature);
+        Ok(pem::encode(&pem::Pem::new("CERTIFICATE", certificate)))
+    }
+}
+fn ed25519_algorithm_identifier() -> Vec<u8> {
+    // RFC 8410: parameters MUST be absent
+    der::sequence(&[der::oid(OID_ED25519)])
+}
+fn ed25519_spki(public_key: &[u8]) -> Vec<u8> {
+    der::sequence(&[ed25519_algorithm_identifier(), der::bit_string(public_key)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer as _, SigningKey, Verifier as _};
+
+    fn build_csr(key: &SigningKey, common_name: &str) -> Vec<u8> {
+        let info = der::sequence(&[
+            der::integer(0),
+            DistinguishedName::new(common_name).to_der().unwrap(),
+            ed25519_spki(key.verifying_key().as_bytes()),
+            der::explicit(0, &[]),
+        ]);
+        let signature = key.sign(&info);
+        der::sequence(&[
+            info,
+            ed25519_algorithm_identifier(),
+            der::bit_string(&signature.to_bytes()),
+        ])
+    }
+
+    #[test]
+    fn test_csr_roundtrip() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let csr = build_csr(&key, "leaf");
+        let pem = pem::encode(&pem::Pem::new("CERTIFICATE REQUEST", csr.clone()));
+        let request = CertificateRequest::parse(pem.as_bytes()).unwrap();
+        assert_eq!(request.subject_public_key(), key.verifying_key().as_bytes());
+        assert_eq!(request, CertificateRequest::parse(&csr).unwrap());
+
+        let mut tampered = csr.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(matches!(
+            CertificateRequest::from_der(&tampered),
+            Err(CaError::InvalidCsr(_))
+        ));
+    }
+    #[test]
+    fn test_p256_csr_parameters() {
+        use p256::ecdsa::signature::Signer as _;
+
+        let key = p256::ecdsa::SigningKey::from_slice(&[9u8; 32]).unwrap();
+        let point = key.verifying_key().to_encoded_point(false);
+        let info = der::sequence(&[
+            der::integer(0),
+            DistinguishedName::new("leaf").to_der().unwrap(),
+            der::sequence(&[
+                der::sequence(&[der::oid(OID_EC_PUBLIC_KEY), der::oid(OID_PRIME256V1)]),
+                der::bit_string(point.as_bytes()),
+            ]),
+            der::explicit(0, &[]),
+        ]);
+        let signature: p256::ecdsa::Signature = key.sign(&info);
+        let csr = |algorithm: Vec<u8>| {
+            der::sequence(&[
+                info.clone(),
+                algorithm,
+                der::bit_string(signature.to_der().as_bytes()),
+            ])
+        };
+        let request =
+            CertificateRequest::from_der(&csr(der::sequence(&[der::oid(OID_ECDSA_WITH_SHA256)])))
+                .unwrap();
+        assert_eq!(request.subject_public_key(), point.as_bytes());
+        // RFC 5758 requires the parameters to be absent, even as NULL
+        assert!(matches!(
+            CertificateRequest::from_der(&csr(der::sequence(&[
+                der::oid(OID_ECDSA_WITH_SHA256),
+                der::tlv(0x05, &[]),
+            ]))),
+            Err(CaError::UnsupportedAlgorithm(_))
+        ));
+    }
+    #[test]
+    fn test_issue_with_local_key() {
+        let ca_key = SigningKey::from_bytes(&[1u8; 32]);
+        let leaf_key = SigningKey::from_bytes(&[2u8; 32]);
+        let request = CertificateRequest::from_der(&build_csr(&leaf_key, "leaf")).unwrap();
+        let issuer = DistinguishedName::new("veritss test ca").to_der().unwrap();
+        let algorithm = ed25519_algorithm_identifier();
+        let policy = CertificatePolicy {
+            subject_alt_names: vec![SubjectAltName::Dns("leaf.example".to_string())],
+            extended_key_usage: vec![ExtendedKeyUsage::ServerAuth],
+            ..Default::default()
+        };
+        let tbs = TbsCertificate {
+            signature_algorithm: &algorithm,
+            issuer: &issuer,
+            issuer_public_key: ca_key.verifying_key().as_bytes(),
+            subject: &request.subject,
+            subject_public_key_info: &request.subject_public_key_info,
+            subject_public_key: &request.subject_public_key,
+        }
+        .to_der(&policy)
+        .unwrap();
+        let signature = ca_key.sign(&tbs);
+        let certificate = certificate_der(&tbs, &algorithm, &signature.to_bytes());
+
+        let (certificate, rest) = der::expect_tlv(&certificate, der::TAG_SEQUENCE).unwrap();
+        assert!(rest.is_empty());
+        let (parsed_tbs, rest) = der::expect_tlv(certificate.content, der::TAG_SEQUENCE).unwrap();
+        let (parsed_algorithm, rest) = der::expect_tlv(rest, der::TAG_SEQUENCE).unwrap();
+        let (parsed_signature, _) = der::expect_tlv(rest, der::TAG_BIT_STRING).unwrap();
+        assert_eq!(parsed_tbs.raw, tbs.as_slice());
+        assert_eq!(parsed_algorithm.raw, algorithm.as_slice());
+        let parsed_signature =
+            ed25519_dalek::Signature::from_slice(der::bit_string_bytes(&parsed_signature).unwrap())
+                .unwrap();
+        ca_key
+            .verifying_key()
+            .verify(parsed_tbs.raw, &parsed_signature)
+            .unwrap();
+
+        // version [0] EXPLICIT v3
+        let (version, _) = der::read_tlv(parsed_tbs.content).unwrap();
+        assert_eq!(version.raw, &[0xa0, 0x03, 0x02, 0x01, 0x02]);
+    }
+    #[test]
+    fn test_policy_validation() {
+        let policy = CertificatePolicy {
+            key_usage: vec![KeyUsage::KeyCertSign],
+            ..Default::default()
+        };
+        let algorithm = ed25519_algorithm_identifier();
+        let tbs = TbsCertificate {
+            signature_algorithm: &algorithm,
+            issuer: &[],
+            issuer_public_key: &[],
+            subject: &[],
+            subject_public_key_info: &[],
+            subject_public_key: &[],
+        };
+        assert!(matches!(
+            tbs.to_der(&policy),
+            Err(CaError::InvalidPolicy(_))
+        ));
+        assert!(tbs
+            .to_der(&CertificatePolicy::root(Duration::from_secs(3600)))
+            .is_ok());
+    }
+}
@@ -0,0 +1,323 @@
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use super::der;
+use super::{
+    CaError, OID_AUTHORITY_KEY_IDENTIFIER, OID_BASIC_CONSTRAINTS, OID_CLIENT_AUTH,
+    OID_CODE_SIGNING, OID_COMMON_NAME, OID_COUNTRY, OID_EMAIL_PROTECTION, OID_EXT_KEY_USAGE,
+    OID_KEY_USAGE, OID_OCSP_SIGNING, OID_ORGANIZATION, OID_ORGANIZATIONAL_UNIT, OID_SERVER_AUTH,
+    OID_SUBJECT_ALT_NAME, OID_SUBJECT_KEY_IDENTIFIER, OID_TIME_STAMPING,
+};
+
+/// Distinguished name of the CA, used as issuer and as subject of the self-signed root.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DistinguishedName {
+    pub common_name: String,
+    pub organization: Option<String>,
+    pub organizational_unit: Option<String>,
+    /// Two-letter ISO 3166 country code
+    pub country: Option<String>,
+}
+impl DistinguishedName {
+    pub fn new(common_name: impl Into<String>) -> Self {
+        Self {
+            common_name: common_name.into(),
+            ..Default::default()
+        }
+    }
+    pub(crate) fn to_der(&self) -> Result<Vec<u8>, CaError> {
+        let mut rdns = Vec::new();
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(CaError::InvalidPolicy(format!(
+                    "country must be a two-letter uppercase code, got {}",
+                    country
+                )));
+            }
+            rdns.push(rdn(OID_COUNTRY, der::printable_string(country)));
+        }
+        if let Some(organization) = &self.organization {
+            rdns.push(rdn(OID_ORGANIZATION, der::utf8_string(organization)));
+        }
+        if let Some(unit) = &self.organizational_unit {
+            rdns.push(rdn(OID_ORGANIZATIONAL_UNIT, der::utf8_string(unit)));
+        }
+        if self.common_name.is_empty() {
+            return Err(CaError::InvalidPolicy(
+                "common name must not be empty".to_string(),
+            ));
+        }
+        rdns.push(rdn(OID_COMMON_NAME, der::utf8_string(&self.common_name)));
+        Ok(der::sequence(&rdns))
+    }
+}
+fn rdn(oid: &[u64], value: Vec<u8>) -> Vec<u8> {
+    der::set(&[der::sequence(&[der::oid(oid), value])])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectAltName {
+    Dns(String),
+    Ip(IpAddr),
+    Email(String),
+    Uri(String),
+}
+impl SubjectAltName {
+    fn to_der(&self) -> Result<Vec<u8>, CaError> {
+        let check_ia5 = |s: &str| {
+            if s.is_ascii() && !s.is_empty() {
+                Ok(())
+            } else {
+                Err(CaError::InvalidPolicy(format!(
+                    "subject alt name must be non-empty ascii: {}",
+                    s
+                )))
+            }
+        };
+        // GeneralName is an implicitly tagged CHOICE
+        Ok(match self {
+            SubjectAltName::Email(email) => {
+                check_ia5(email)?;
+                der::implicit_primitive(1, email.as_bytes())
+            }
+            SubjectAltName::Dns(dns) => {
+                check_ia5(dns)?;
+                der::implicit_primitive(2, dns.as_bytes())
+            }
+            SubjectAltName::Uri(uri) => {
+                check_ia5(uri)?;
+                der::implicit_primitive(6, uri.as_bytes())
+            }
+            SubjectAltName::Ip(IpAddr::V4(ip)) => der::implicit_primitive(7, &ip.octets()),
+            SubjectAltName::Ip(IpAddr::V6(ip)) => der::implicit_primitive(7, &ip.octets()),
+        })
+    }
+}
+
+/// Key usage bits, numbered as in RFC 5280 4.2.1.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyUsage {
+    DigitalSignature = 0,
+    NonRepudiation = 1,
+    KeyEncipherment = 2,
+    DataEncipherment = 3,
+    KeyAgreement = 4,
+    KeyCertSign = 5,
+    CrlSign = 6,
+    EncipherOnly = 7,
+    DecipherOnly = 8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtendedKeyUsage {
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OcspSigning,
+}
+impl ExtendedKeyUsage {
+    fn oid(&self) -> &'static [u64] {
+        match self {
+            ExtendedKeyUsage::ServerAuth => OID_SERVER_AUTH,
+            ExtendedKeyUsage::ClientAuth => OID_CLIENT_AUTH,
+            ExtendedKeyUsage::CodeSigning => OID_CODE_SIGNING,
+            ExtendedKeyUsage::EmailProtection => OID_EMAIL_PROTECTION,
+            ExtendedKeyUsage::TimeStamping => OID_TIME_STAMPING,
+            ExtendedKeyUsage::OcspSigning => OID_OCSP_SIGNING,
+        }
+    }
+}
+
+/// What the CA puts into a certificate, independent of what the CSR asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificatePolicy {
+    /// Start of validity, defaults to now
+    pub not_before: Option<SystemTime>,
+    pub validity: Duration,
+    pub subject_alt_names: Vec<SubjectAltName>,
+    pub key_usage: Vec<KeyUsage>,
+    pub extended_key_usage: Vec<ExtendedKeyUsage>,
+    pub is_ca: bool,
+    pub path_len_constraint: Option<u8>,
+}
+impl Default for CertificatePolicy {
+    fn default() -> Self {
+        Self {
+            not_before: None,
+            validity: Duration::from_secs(90 * 24 * 3600),
+            subject_alt_names: vec![],
+            key_usage: vec![KeyUsage::DigitalSignature],
+            extended_key_usage: vec![],
+            is_ca: false,
+            path_len_constraint: None,
+        }
+    }
+}
+impl CertificatePolicy {
+    /// Policy of a root CA certificate: `keyCertSign` and `cRLSign`, no path length limit.
+    pub fn root(validity: Duration) -> Self {
+        Self {
+            validity,
+            key_usage: vec![
+                KeyUsage::DigitalSignature,
+                KeyUsage::KeyCertSign,
+                KeyUsage::CrlSign,
+            ],
+            is_ca: true,
+            ..Default::default()
+        }
+    }
+    fn validate(&self) -> Result<(), CaError> {
+        if self.validity.is_zero() {
+            return Err(CaError::InvalidPolicy(
+                "validity must not be zero".to_string(),
+            ));
+        }
+        if self.path_len_constraint.is_some() && !self.is_ca {
+            return Err(CaError::InvalidPolicy(
+                "path length constraint requires a ca certificate".to_string(),
+            ));
+        }
+        if self.is_ca && !self.key_usage.contains(&KeyUsage::KeyCertSign) {
+            return Err(CaError::InvalidPolicy(
+                "ca certificate requires keyCertSign key usage".to_string(),
+            ));
+        }
+        if !self.is_ca && self.key_usage.contains(&KeyUsage::KeyCertSign) {
+            return Err(CaError::InvalidPolicy(
+                "keyCertSign key usage requires a ca certificate".to_string(),
+            ));
+        }
+        Ok(())
+    }
+    fn extensions(
+        &self,
+        subject_public_key: &[u8],
+        issuer_public_key: &[u8],
+    ) -> Result<Vec<u8>, CaError> {
+        let mut extensions = Vec::new();
+        let mut basic_constraints = Vec::new();
+        if self.is_ca {
+            basic_constraints.push(der::boolean(true));
+            if let Some(path_len) = self.path_len_constraint {
+                basic_constraints.push(der::integer(path_len as u64));
+            }
+        }
+        extensions.push(extension(
+            OID_BASIC_CONSTRAINTS,
+            true,
+            der::sequence(&basic_constraints),
+        ));
+        if !self.key_usage.is_empty() {
+            let bits = self
+                .key_usage
+                .iter()
+                .map(|usage| *usage as usize)
+                .collect::<Vec<_>>();
+            extensions.push(extension(OID_KEY_USAGE, true, der::named_bit_string(&bits)));
+        }
+        if !self.extended_key_usage.is_empty() {
+            let oids = self
+                .extended_key_usage
+                .iter()
+                .map(|usage| der::oid(usage.oid()))
+                .collect::<Vec<_>>();
+            extensions.push(extension(OID_EXT_KEY_USAGE, false, der::sequence(&oids)));
+        }
+        if !self.subject_alt_names.is_empty() {
+            let names = self
+                .subject_alt_names
+                .iter()
+                .map(|name| name.to_der())
+                .collect::<Result<Vec<_>, _>>()?;
+            extensions.push(extension(
+                OID_SUBJECT_ALT_NAME,
+                false,
+                der::sequence(&names),
+            ));
+        }
+        extensions.push(extension(
+            OID_SUBJECT_KEY_IDENTIFIER,
+            false,
+            der::octet_string(&key_identifier(subject_public_key)),
+        ));
+        extensions.push(extension(
+            OID_AUTHORITY_KEY_IDENTIFIER,
+            false,
+            der::sequence(&[der::implicit_primitive(
+                0,
+                &key_identifier(issuer_public_key),
+            )]),
+        ));
+        Ok(der::explicit(3, &der::sequence(&extensions)))
+    }
+}
+fn extension(oid: &[u64], critical: bool, value: Vec<u8>) -> Vec<u8> {
+    let mut items = vec![der::oid(oid)];
+    if critical {
+        items.push(der::boolean(true));
+    }
+    items.push(der::octet_string(&value));
+    der::sequence(&items)
+}
+// RFC 7093 method 1: leftmost 160 bits of the SHA-256 hash of the subjectPublicKey
+fn key_identifier(public_key: &[u8]) -> Vec<u8> {
+    Sha256::digest(public_key)[..20].to_vec()
+}
+
+pub(crate) struct TbsCertificate<'a> {
+    pub(crate) signature_algorithm: &'a [u8],
+    pub(crate) issuer: &'a [u8],
+    pub(crate) issuer_public_key: &'a [u8],
+    pub(crate) subject: &'a [u8],
+    pub(crate) subject_public_key_info: &'a [u8],
+    pub(crate) subject_public_key: &'a [u8],
+}
+impl TbsCertificate<'_> {
+    pub(crate) fn to_der(&self, policy: &CertificatePolicy) -> Result<Vec<u8>, CaError> {
+        policy.validate()?;
+        let not_before = policy
+            .not_before
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| CaError::InvalidPolicy(e.to_string()))?
+            .as_secs();
+        let not_after = not_before
+            .checked_add(policy.validity.as_secs())
+            .ok_or(CaError::InvalidPolicy("validity overflows".to_string()))?;
+        Ok(der::sequence(&[
+            der::explicit(0, &der::integer(2)),
+            der::unsigned_integer(&serial_number()),
+            self.signature_algorithm.to_vec(),
+            self.issuer.to_vec(),
+            der::sequence(&[der::time(not_before), der::time(not_after)]),
+            self.subject.to_vec(),
+            self.subject_public_key_info.to_vec(),
+            policy.extensions(self.subject_public_key, self.issuer_public_key)?,
+        ]))
+    }
+}
+// random positive serial of at most 20 octets, RFC 5280 4.1.2.2
+fn serial_number() -> [u8; 16] {
+    let mut serial = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut serial);
+    serial[0] &= 0x7f;
+    serial[0] |= 0x40;
+    serial
+}
+pub(crate) fn certificate_der(
+    tbs_certificate: &[u8],
+    signature_algorithm: &[u8],
+    signature: &[u8],
+) -> Vec<u8> {
+    der::sequence(&[
+        tbs_certificate.to_vec(),
+        signature_algorithm.to_vec(),
+        der::bit_string(signature),
+    ])
+}
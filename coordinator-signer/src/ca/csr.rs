@@ -0,0 +1,143 @@
+use ed25519_dalek::Verifier as _;
+use p256::ecdsa::signature::Verifier as _;
+
+use super::der::{self, Tlv};
+use super::{CaError, OID_ECDSA_WITH_SHA256, OID_EC_PUBLIC_KEY, OID_ED25519, OID_PRIME256V1};
+
+/// A parsed PKCS#10 certification request (RFC 2986) whose self-signature has been
+/// verified.
+///
+/// Only the subject and the subject public key are taken from the request. Extensions
+/// requested through the `extensionRequest` attribute are ignored, the issuing
+/// [`CertificatePolicy`](super::CertificatePolicy) decides what goes into the certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateRequest {
+    pub(crate) subject: Vec<u8>,
+    pub(crate) subject_public_key_info: Vec<u8>,
+    pub(crate) subject_public_key: Vec<u8>,
+}
+impl CertificateRequest {
+    /// Parses a CSR from either PEM (`CERTIFICATE REQUEST`) or raw DER and verifies its
+    /// signature.
+    pub fn parse(input: &[u8]) -> Result<Self, CaError> {
+        match pem::parse(input) {
+            Ok(pem) => {
+                if pem.tag() != "CERTIFICATE REQUEST" && pem.tag() != "NEW CERTIFICATE REQUEST" {
+                    return Err(CaError::InvalidCsr(format!(
+                        "unexpected pem tag: {}",
+                        pem.tag()
+                    )));
+                }
+                Self::from_der(pem.contents())
+            }
+            Err(_) => Self::from_der(input),
+        }
+    }
+    pub fn from_der(bytes: &[u8]) -> Result<Self, CaError> {
+        let (csr, rest) = der::expect_tlv(bytes, der::TAG_SEQUENCE)?;
+        if !rest.is_empty() {
+            return Err(CaError::InvalidCsr("trailing data after csr".to_string()));
+        }
+        let (info, rest) = der::expect_tlv(csr.content, der::TAG_SEQUENCE)?;
+        let (signature_algorithm, rest) = der::expect_tlv(rest, der::TAG_SEQUENCE)?;
+        let (signature, _) = der::expect_tlv(rest, der::TAG_BIT_STRING)?;
+
+        let (version, rest) = der::expect_tlv(info.content, der::TAG_INTEGER)?;
+        if version.content != [0] {
+            return Err(CaError::InvalidCsr(format!(
+                "unsupported csr version: {}",
+                hex::encode(version.content)
+            )));
+        }
+        let (subject, rest) = der::expect_tlv(rest, der::TAG_SEQUENCE)?;
+        let (spki, _) = der::expect_tlv(rest, der::TAG_SEQUENCE)?;
+        let (key_algorithm, subject_public_key) = parse_spki(&spki)?;
+
+        let (sig_oid, sig_params) = parse_algorithm_identifier(&signature_algorithm)?;
+        let signature = der::bit_string_bytes(&signature)?;
+        match (key_algorithm, sig_oid.as_slice()) {
+            (SubjectKeyAlgorithm::Ed25519, OID_ED25519) if sig_params.is_none() => {
+                let key_bytes: [u8; 32] =
+                    subject_public_key.as_slice().try_into().map_err(|_| {
+                        CaError::InvalidCsr("ed25519 public key must be 32 bytes".to_string())
+                    })?;
+                let key = ed25519_dalek::VerifyingKey::from_bytes(&key_bytes)
+                    .map_err(|e| CaError::InvalidCsr(e.to_string()))?;
+                let signature = ed25519_dalek::Signature::from_slice(signature)
+                    .map_err(|e| CaError::InvalidCsr(e.to_string()))?;
+                key.verify(info.raw, &signature)
+                    .map_err(|e| CaError::InvalidCsr(format!("invalid csr signature: {}", e)))?;
+            }
+            // RFC 5758 requires the parameters to be absent
+            (SubjectKeyAlgorithm::P256, OID_ECDSA_WITH_SHA256) if sig_params.is_none() => {
+                let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&subject_public_key)
+                    .map_err(|e| CaError::InvalidCsr(e.to_string()))?;
+                let signature = p256::ecdsa::Signature::from_der(signature)
+                    .map_err(|e| CaError::InvalidCsr(e.to_string()))?;
+                key.verify(info.raw, &signature)
+                    .map_err(|e| CaError::InvalidCsr(format!("invalid csr signature: {}", e)))?;
+            }
+            (key_algorithm, sig_oid) => {
+                return Err(CaError::UnsupportedAlgorithm(format!(
+                    "csr signed with {} for a {:?} key",
+                    format_oid(sig_oid),
+                    key_algorithm
+                )));
+            }
+        }
+        Ok(Self {
+            subject: subject.raw.to_vec(),
+            subject_public_key_info: spki.raw.to_vec(),
+            subject_public_key,
+        })
+    }
+    pub fn subject_public_key(&self) -> &[u8] {
+        &self.subject_public_key
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubjectKeyAlgorithm {
+    Ed25519,
+    P256,
+}
+fn parse_spki(spki: &Tlv<'_>) -> Result<(SubjectKeyAlgorithm, Vec<u8>), CaError> {
+    let (algorithm, rest) = der::expect_tlv(spki.content, der::TAG_SEQUENCE)?;
+    let (key, _) = der::expect_tlv(rest, der::TAG_BIT_STRING)?;
+    let key = der::bit_string_bytes(&key)?.to_vec();
+    let (oid, params) = parse_algorithm_identifier(&algorithm)?;
+    match (oid.as_slice(), params) {
+        (OID_ED25519, None) => Ok((SubjectKeyAlgorithm::Ed25519, key)),
+        (OID_EC_PUBLIC_KEY, Some(params)) if params.tag == der::TAG_OID => {
+            let curve = der::decode_oid(params.content)?;
+            if curve != OID_PRIME256V1 {
+                return Err(CaError::UnsupportedAlgorithm(format!(
+                    "ec public key on curve {}",
+                    format_oid(&curve)
+                )));
+            }
+            Ok((SubjectKeyAlgorithm::P256, key))
+        }
+        (oid, _) => Err(CaError::UnsupportedAlgorithm(format!(
+            "subject public key algorithm {}",
+            format_oid(oid)
+        ))),
+    }
+}
+fn parse_algorithm_identifier<'a>(
+    algorithm: &Tlv<'a>,
+) -> Result<(Vec<u64>, Option<Tlv<'a>>), CaError> {
+    let (oid, rest) = der::expect_tlv(algorithm.content, der::TAG_OID)?;
+    let oid = der::decode_oid(oid.content)?;
+    if rest.is_empty() {
+        return Ok((oid, None));
+    }
+    let (params, _) = der::read_tlv(rest)?;
+    Ok((oid, Some(params)))
+}
+pub(crate) fn format_oid(oid: &[u64]) -> String {
+    oid.iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
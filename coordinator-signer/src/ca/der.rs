@@ -0,0 +1,303 @@
+// Minimal DER encoder/decoder, covering only the ASN.1 subset needed for X.509
+// certificates and PKCS#10 certification requests.
+use super::CaError;
+
+pub(crate) const TAG_BOOLEAN: u8 = 0x01;
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_UTF8_STRING: u8 = 0x0c;
+pub(crate) const TAG_PRINTABLE_STRING: u8 = 0x13;
+pub(crate) const TAG_UTC_TIME: u8 = 0x17;
+pub(crate) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+
+pub(crate) fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(content.len() + 6);
+    out.push(tag);
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+pub(crate) fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &items.concat())
+}
+pub(crate) fn set(items: &[Vec<u8>]) -> Vec<u8> {
+    // DER requires SET OF elements to be sorted by their encoding
+    let mut items = items.to_vec();
+    items.sort();
+    tlv(TAG_SET, &items.concat())
+}
+pub(crate) fn boolean(value: bool) -> Vec<u8> {
+    tlv(TAG_BOOLEAN, &[if value { 0xff } else { 0x00 }])
+}
+// encodes big-endian unsigned bytes as a non-negative INTEGER
+pub(crate) fn unsigned_integer(bytes: &[u8]) -> Vec<u8> {
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    let bytes = &bytes[skip..];
+    let mut content = Vec::with_capacity(bytes.len() + 1);
+    if bytes.is_empty() || bytes[0] & 0x80 != 0 {
+        content.push(0);
+    }
+    content.extend_from_slice(bytes);
+    tlv(TAG_INTEGER, &content)
+}
+pub(crate) fn integer(value: u64) -> Vec<u8> {
+    unsigned_integer(&value.to_be_bytes())
+}
+pub(crate) fn oid(arcs: &[u64]) -> Vec<u8> {
+    assert!(arcs.len() >= 2, "oid must have at least two arcs");
+    let mut content = Vec::new();
+    push_base128(arcs[0] * 40 + arcs[1], &mut content);
+    for arc in &arcs[2..] {
+        push_base128(*arc, &mut content);
+    }
+    tlv(TAG_OID, &content)
+}
+fn push_base128(mut value: u64, out: &mut Vec<u8>) {
+    let mut buf = vec![(value & 0x7f) as u8];
+    value >>= 7;
+    while value > 0 {
+        buf.push(0x80 | (value & 0x7f) as u8);
+        value >>= 7;
+    }
+    buf.reverse();
+    out.extend(buf);
+}
+pub(crate) fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = Vec::with_capacity(bytes.len() + 1);
+    content.push(0);
+    content.extend_from_slice(bytes);
+    tlv(TAG_BIT_STRING, &content)
+}
+// encodes a NamedBitList, bit 0 being the most significant bit of the first byte;
+// DER requires trailing zero bits to be removed
+pub(crate) fn named_bit_string(bits: &[usize]) -> Vec<u8> {
+    let Some(max) = bits.iter().max() else {
+        return tlv(TAG_BIT_STRING, &[0]);
+    };
+    let mut bytes = vec![0u8; max / 8 + 1];
+    for bit in bits {
+        bytes[bit / 8] |= 0x80 >> (bit % 8);
+    }
+    let unused = (7 - max % 8) as u8;
+    let mut content = vec![unused];
+    content.extend(bytes);
+    tlv(TAG_BIT_STRING, &content)
+}
+pub(crate) fn octet_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(TAG_OCTET_STRING, bytes)
+}
+pub(crate) fn utf8_string(s: &str) -> Vec<u8> {
+    tlv(TAG_UTF8_STRING, s.as_bytes())
+}
+pub(crate) fn printable_string(s: &str) -> Vec<u8> {
+    tlv(TAG_PRINTABLE_STRING, s.as_bytes())
+}
+pub(crate) fn explicit(tag_number: u8, content: &[u8]) -> Vec<u8> {
+    tlv(0xa0 | tag_number, content)
+}
+pub(crate) fn implicit_primitive(tag_number: u8, content: &[u8]) -> Vec<u8> {
+    tlv(0x80 | tag_number, content)
+}
+// RFC 5280 4.1.2.5: UTCTime through 2049, GeneralizedTime from 2050 on
+pub(crate) fn time(unix_secs: u64) -> Vec<u8> {
+    let (year, month, day, hour, minute, second) = civil_from_unix(unix_secs);
+    if (1950..2050).contains(&year) {
+        tlv(
+            TAG_UTC_TIME,
+            format!(
+                "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+                year % 100,
+                month,
+                day,
+                hour,
+                minute,
+                second
+            )
+            .as_bytes(),
+        )
+    } else {
+        tlv(
+            TAG_GENERALIZED_TIME,
+            format!(
+                "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+                year, month, day, hour, minute, second
+            )
+            .as_bytes(),
+        )
+    }
+}
+// days-from-civil inverse (Howard Hinnant), proleptic Gregorian calendar in UTC
+pub(crate) fn civil_from_unix(unix_secs: u64) -> (u64, u32, u32, u32, u32, u32) {
+    let days = (unix_secs / 86400) as i64;
+    let secs_of_day = (unix_secs % 86400) as u32;
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as u64;
+    (
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub(crate) tag: u8,
+    pub(crate) content: &'a [u8],
+    // the complete encoding, including tag and length
+    pub(crate) raw: &'a [u8],
+}
+pub(crate) fn read_tlv(input: &[u8]) -> Result<(Tlv<'_>, &[u8]), CaError> {
+    if input.len() < 2 {
+        return Err(CaError::Der("unexpected end of input".to_string()));
+    }
+    let tag = input[0];
+    if tag & 0x1f == 0x1f {
+        return Err(CaError::Der(
+            "high tag numbers are not supported".to_string(),
+        ));
+    }
+    let (len, header_len) = match input[1] {
+        l if l < 0x80 => (l as usize, 2),
+        0x80 => return Err(CaError::Der("indefinite length is not allowed".to_string())),
+        l => {
+            let n = (l & 0x7f) as usize;
+            if n > 4 || input.len() < 2 + n {
+                return Err(CaError::Der(format!("invalid length of length: {}", n)));
+            }
+            let len = input[2..2 + n]
+                .iter()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            if len < 0x80 || input[2] == 0 {
+                return Err(CaError::Der("non-minimal length encoding".to_string()));
+            }
+            (len, 2 + n)
+        }
+    };
+    let end = header_len
+        .checked_add(len)
+        .filter(|end| *end <= input.len())
+        .ok_or(CaError::Der(format!(
+            "length {} exceeds remaining input {}",
+            len,
+            input.len() - header_len
+        )))?;
+    Ok((
+        Tlv {
+            tag,
+            content: &input[header_len..end],
+            raw: &input[..end],
+        },
+        &input[end..],
+    ))
+}
+pub(crate) fn expect_tlv(input: &[u8], tag: u8) -> Result<(Tlv<'_>, &[u8]), CaError> {
+    let (tlv, rest) = read_tlv(input)?;
+    if tlv.tag != tag {
+        return Err(CaError::Der(format!(
+            "unexpected tag: expected {:#04x}, got {:#04x}",
+            tag, tlv.tag
+        )));
+    }
+    Ok((tlv, rest))
+}
+pub(crate) fn decode_oid(content: &[u8]) -> Result<Vec<u64>, CaError> {
+    if content.is_empty() || content[content.len() - 1] & 0x80 != 0 {
+        return Err(CaError::Der("invalid object identifier".to_string()));
+    }
+    let mut values = Vec::new();
+    let mut value: u64 = 0;
+    for b in content {
+        if value > (u64::MAX >> 7) {
+            return Err(CaError::Der("object identifier arc overflow".to_string()));
+        }
+        value = (value << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            values.push(value);
+            value = 0;
+        }
+    }
+    let first = values[0];
+    let mut arcs = match first {
+        0..=39 => vec![0, first],
+        40..=79 => vec![1, first - 40],
+        _ => vec![2, first - 80],
+    };
+    arcs.extend_from_slice(&values[1..]);
+    Ok(arcs)
+}
+// returns the content of a BIT STRING that has no unused bits
+pub(crate) fn bit_string_bytes<'a>(tlv: &Tlv<'a>) -> Result<&'a [u8], CaError> {
+    if tlv.tag != TAG_BIT_STRING {
+        return Err(CaError::Der(format!(
+            "unexpected tag: expected bit string, got {:#04x}",
+            tlv.tag
+        )));
+    }
+    match tlv.content.split_first() {
+        Some((0, bytes)) => Ok(bytes),
+        _ => Err(CaError::Der(
+            "bit string with unused bits is not supported here".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_encoding() {
+        assert_eq!(civil_from_unix(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(civil_from_unix(951_827_696), (2000, 2, 29, 12, 34, 56));
+        assert_eq!(time(951_827_696), tlv(TAG_UTC_TIME, b"000229123456Z"));
+        // 2050-01-01T00:00:00Z switches to GeneralizedTime
+        assert_eq!(
+            time(2_524_608_000),
+            tlv(TAG_GENERALIZED_TIME, b"20500101000000Z")
+        );
+    }
+    #[test]
+    fn test_primitive_encoding() {
+        assert_eq!(oid(&[1, 3, 101, 112]), vec![0x06, 0x03, 0x2b, 0x65, 0x70]);
+        assert_eq!(
+            oid(&[1, 2, 840, 10045, 2, 1]),
+            vec![0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]
+        );
+        assert_eq!(
+            decode_oid(&oid(&[2, 5, 29, 17])[2..]).unwrap(),
+            vec![2, 5, 29, 17]
+        );
+        assert_eq!(integer(0), vec![0x02, 0x01, 0x00]);
+        assert_eq!(integer(128), vec![0x02, 0x02, 0x00, 0x80]);
+        // digitalSignature | keyCertSign | cRLSign
+        assert_eq!(named_bit_string(&[0, 5, 6]), vec![0x03, 0x02, 0x01, 0x86]);
+        let long = vec![0u8; 300];
+        let encoded = octet_string(&long);
+        assert_eq!(&encoded[..4], &[0x04, 0x82, 0x01, 0x2c]);
+        let (tlv, rest) = read_tlv(&encoded).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(tlv.content.len(), 300);
+    }
+}
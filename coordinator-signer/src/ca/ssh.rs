@@ -0,0 +1,540 @@
+// OpenSSH certificates as specified in PROTOCOL.certkeys of the OpenSSH source tree.
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use rand::RngCore;
+
+use super::{CaError, GroupKey};
+use crate::crypto::{PkId, ValidatorIdentity};
+use crate::node::Node;
+
+const SSH_ED25519: &str = "ssh-ed25519";
+// key types that can be certified, with their certificate type names
+const CERTIFIABLE_KEY_TYPES: &[(&str, &str)] = &[
+    ("ssh-ed25519", "ssh-ed25519-cert-v01@openssh.com"),
+    ("ssh-rsa", "ssh-rsa-cert-v01@openssh.com"),
+    (
+        "ecdsa-sha2-nistp256",
+        "ecdsa-sha2-nistp256-cert-v01@openssh.com",
+    ),
+    (
+        "ecdsa-sha2-nistp384",
+        "ecdsa-sha2-nistp384-cert-v01@openssh.com",
+    ),
+    (
+        "ecdsa-sha2-nistp521",
+        "ecdsa-sha2-nistp521-cert-v01@openssh.com",
+    ),
+];
+
+/// An OpenSSH public key, as found in `authorized_keys` or `*.pub` files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshPublicKey {
+    key_type: String,
+    blob: Vec<u8>,
+    comment: Option<String>,
+}
+impl SshPublicKey {
+    /// Parses `<key type> <base64 blob> [comment]`.
+    pub fn parse(line: &str) -> Result<Self, CaError> {
+        let mut parts = line.split_whitespace();
+        let key_type = parts
+            .next()
+            .ok_or(CaError::InvalidSshKey("empty public key".to_string()))?;
+        let blob = parts
+            .next()
+            .ok_or(CaError::InvalidSshKey("missing key data".to_string()))?;
+        let blob = BASE64
+            .decode(blob)
+            .map_err(|e| CaError::InvalidSshKey(e.to_string()))?;
+        let comment = parts.collect::<Vec<_>>().join(" ");
+        let (inner_type, fields) = read_string(&blob)?;
+        if inner_type != key_type.as_bytes() {
+            return Err(CaError::InvalidSshKey(format!(
+                "key type {} does not match encoded type {}",
+                key_type,
+                String::from_utf8_lossy(inner_type)
+            )));
+        }
+        check_key_fields(key_type, fields)?;
+        Ok(Self {
+            key_type: key_type.to_string(),
+            blob,
+            comment: if comment.is_empty() {
+                None
+            } else {
+                Some(comment)
+            },
+        })
+    }
+    pub(crate) fn ed25519(public_key: &[u8; 32]) -> Self {
+        let mut blob = Vec::new();
+        put_string(&mut blob, SSH_ED25519.as_bytes());
+        put_string(&mut blob, public_key);
+        Self {
+            key_type: SSH_ED25519.to_string(),
+            blob,
+            comment: None,
+        }
+    }
+    // `point` is an uncompressed SEC1 point
+    pub(crate) fn ecdsa_nistp256(point: &[u8]) -> Self {
+        let mut blob = Vec::new();
+        put_string(&mut blob, b"ecdsa-sha2-nistp256");
+        put_string(&mut blob, b"nistp256");
+        put_string(&mut blob, point);
+        Self {
+            key_type: "ecdsa-sha2-nistp256".to_string(),
+            blob,
+            comment: None,
+        }
+    }
+    pub fn key_type(&self) -> &str {
+        &self.key_type
+    }
+    /// The `<key type> <base64 blob>` form used by `authorized_keys` and `TrustedUserCAKeys`.
+    pub fn to_openssh(&self) -> String {
+        match &self.comment {
+            Some(comment) => format!(
+                "{} {} {}",
+                self.key_type,
+                BASE64.encode(&self.blob),
+                comment
+            ),
+            None => format!("{} {}", self.key_type, BASE64.encode(&self.blob)),
+        }
+    }
+    // the key fields that follow the key type, copied verbatim into a certificate
+    fn key_fields(&self) -> &[u8] {
+        let (_, rest) = read_string(&self.blob).expect("blob is validated on construction");
+        rest
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SshCertType {
+    User = 1,
+    Host = 2,
+}
+
+/// Contents of an OpenSSH certificate, apart from the certified key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshCertificateOptions {
+    pub cert_type: SshCertType,
+    pub key_id: String,
+    /// Empty means valid for any principal, which OpenSSH only accepts for host certificates
+    pub principals: Vec<String>,
+    /// Start of validity, defaults to now
+    pub valid_after: Option<SystemTime>,
+    pub validity: Duration,
+    /// Option name to value, an empty value is encoded as a flag
+    pub critical_options: BTreeMap<String, String>,
+    pub extensions: BTreeMap<String, String>,
+    /// Random when not set
+    pub serial: Option<u64>,
+}
+impl SshCertificateOptions {
+    /// User certificate with the extensions `ssh-keygen` grants by default.
+    pub fn user(key_id: impl Into<String>, principals: Vec<String>, validity: Duration) -> Self {
+        Self {
+            cert_type: SshCertType::User,
+            key_id: key_id.into(),
+            principals,
+            valid_after: None,
+            validity,
+            critical_options: BTreeMap::new(),
+            extensions: [
+                "permit-X11-forwarding",
+                "permit-agent-forwarding",
+                "permit-port-forwarding",
+                "permit-pty",
+                "permit-user-rc",
+            ]
+            .into_iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect(),
+            serial: None,
+        }
+    }
+    pub fn host(key_id: impl Into<String>, hostnames: Vec<String>, validity: Duration) -> Self {
+        Self {
+            cert_type: SshCertType::Host,
+            key_id: key_id.into(),
+            principals: hostnames,
+            valid_after: None,
+            validity,
+            critical_options: BTreeMap::new(),
+            extensions: BTreeMap::new(),
+            serial: None,
+        }
+    }
+    fn validate(&self) -> Result<(), CaError> {
+        if self.validity.is_zero() {
+            return Err(CaError::InvalidPolicy(
+                "validity must not be zero".to_string(),
+            ));
+        }
+        if self.cert_type == SshCertType::User && self.principals.is_empty() {
+            return Err(CaError::InvalidPolicy(
+                "user certificate requires at least one principal".to_string(),
+            ));
+        }
+        if self.cert_type == SshCertType::Host
+            && !(self.critical_options.is_empty() && self.extensions.is_empty())
+        {
+            return Err(CaError::InvalidPolicy(
+                "host certificates carry no critical options or extensions".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Everything up to and including the signature key; the signature is computed over these bytes.
+pub(crate) fn certificate_to_be_signed(
+    subject: &SshPublicKey,
+    options: &SshCertificateOptions,
+    ca_public_key: &[u8; 32],
+) -> Result<Vec<u8>, CaError> {
+    options.validate()?;
+    let cert_key_type = CERTIFIABLE_KEY_TYPES
+        .iter()
+        .find(|(key_type, _)| *key_type == subject.key_type)
+        .map(|(_, cert_type)| *cert_type)
+        .ok_or(CaError::UnsupportedAlgorithm(format!(
+            "ssh key type {} cannot be certified",
+            subject.key_type
+        )))?;
+    let valid_after = options
+        .valid_after
+        .unwrap_or_else(SystemTime::now)
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| CaError::InvalidPolicy(e.to_string()))?
+        .as_secs();
+    let valid_before = valid_after
+        .checked_add(options.validity.as_secs())
+        .ok_or(CaError::InvalidPolicy("validity overflows".to_string()))?;
+    let mut nonce = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut buf = Vec::new();
+    put_string(&mut buf, cert_key_type.as_bytes());
+    put_string(&mut buf, &nonce);
+    buf.extend_from_slice(subject.key_fields());
+    buf.extend_from_slice(
+        &options
+            .serial
+            .unwrap_or_else(|| rand::thread_rng().next_u64())
+            .to_be_bytes(),
+    );
+    buf.extend_from_slice(&(options.cert_type as u32).to_be_bytes());
+    put_string(&mut buf, options.key_id.as_bytes());
+    let mut principals = Vec::new();
+    for principal in &options.principals {
+        put_string(&mut principals, principal.as_bytes());
+    }
+    put_string(&mut buf, &principals);
+    buf.extend_from_slice(&valid_after.to_be_bytes());
+    buf.extend_from_slice(&valid_before.to_be_bytes());
+    put_string(&mut buf, &encode_options(&options.critical_options));
+    put_string(&mut buf, &encode_options(&options.extensions));
+    // reserved
+    put_string(&mut buf, &[]);
+    put_string(&mut buf, &SshPublicKey::ed25519(ca_public_key).blob);
+    Ok(buf)
+}
+pub(crate) fn certificate_line(
+    to_be_signed: Vec<u8>,
+    signature: &[u8; 64],
+    key_id: &str,
+) -> String {
+    let mut signature_blob = Vec::new();
+    put_string(&mut signature_blob, SSH_ED25519.as_bytes());
+    put_string(&mut signature_blob, signature);
+    let mut certificate = to_be_signed;
+    put_string(&mut certificate, &signature_blob);
+    let (cert_key_type, _) =
+        read_string(&certificate).expect("certificate starts with its key type");
+    format!(
+        "{} {} {}",
+        String::from_utf8_lossy(cert_key_type),
+        BASE64.encode(&certificate),
+        key_id
+    )
+}
+// BTreeMap keeps names in the lexical order required by the format; values are wrapped in a
+// second string, flags have empty data
+fn encode_options(options: &BTreeMap<String, String>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for (name, value) in options {
+        put_string(&mut buf, name.as_bytes());
+        if value.is_empty() {
+            put_string(&mut buf, &[]);
+        } else {
+            let mut data = Vec::new();
+            put_string(&mut data, value.as_bytes());
+            put_string(&mut buf, &data);
+        }
+    }
+    buf
+}
+// checks that the fields after the key type are those of a `key_type` key, so that a
+// certificate never carries a key that does not match its type
+fn check_key_fields(key_type: &str, fields: &[u8]) -> Result<(), CaError> {
+    let invalid = |reason: &str| CaError::InvalidSshKey(format!("{} key {}", key_type, reason));
+    let rest = match key_type {
+        SSH_ED25519 => {
+            let (public_key, rest) = read_string(fields)?;
+            if public_key.len() != 32 {
+                return Err(invalid("is not 32 bytes"));
+            }
+            rest
+        }
+        "ssh-rsa" => {
+            let (exponent, rest) = read_string(fields)?;
+            let (modulus, rest) = read_string(rest)?;
+            if exponent.iter().all(|b| *b == 0) {
+                return Err(invalid("has no public exponent"));
+            }
+            // mpints carry a leading zero byte when the high bit is set
+            let modulus = &modulus[modulus.iter().take_while(|b| **b == 0).count()..];
+            if modulus.len() < 1024 / 8 {
+                return Err(invalid("is shorter than 1024 bits"));
+            }
+            rest
+        }
+        "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" | "ecdsa-sha2-nistp521" => {
+            let curve = &key_type["ecdsa-sha2-".len()..];
+            // uncompressed SEC1 points of the curve
+            let point_len = match curve {
+                "nistp256" => 65,
+                "nistp384" => 97,
+                _ => 133,
+            };
+            let (identifier, rest) = read_string(fields)?;
+            if identifier != curve.as_bytes() {
+                return Err(invalid(&format!(
+                    "is on curve {}",
+                    String::from_utf8_lossy(identifier)
+                )));
+            }
+            let (point, rest) = read_string(rest)?;
+            if point.len() != point_len || point[0] != 0x04 {
+                return Err(invalid("is not an uncompressed point of its curve"));
+            }
+            rest
+        }
+        key_type => {
+            return Err(CaError::UnsupportedAlgorithm(format!(
+                "SSH key type {}",
+                key_type
+            )))
+        }
+    };
+    if !rest.is_empty() {
+        return Err(invalid("has trailing data"));
+    }
+    Ok(())
+}
+fn put_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+fn read_string(input: &[u8]) -> Result<(&[u8], &[u8]), CaError> {
+    let len = input
+        .get(..4)
+        .map(|len| u32::from_be_bytes(len.try_into().unwrap()) as usize)
+        .ok_or(CaError::InvalidSshKey(
+            "truncated string length".to_string(),
+        ))?;
+    let data = input
+        .get(4..4 + len)
+        .ok_or(CaError::InvalidSshKey("truncated string".to_string()))?;
+    Ok((data, &input[4 + len..]))
+}
+
+/// SSH certificate authority whose `ssh-ed25519` key is the group key behind `pkid`.
+pub struct SshCertificateAuthority<'a, VI: ValidatorIdentity> {
+    key: GroupKey<'a, VI>,
+}
+impl<'a, VI: ValidatorIdentity> SshCertificateAuthority<'a, VI> {
+    pub async fn new(node: &'a Node<VI>, pkid: PkId) -> Result<Self, CaError> {
+        Ok(Self {
+            key: GroupKey::fetch(node, pkid).await?,
+        })
+    }
+    pub fn public_key(&self) -> SshPublicKey {
+        SshPublicKey::ed25519(self.key.public_key())
+    }
+    /// Line for `TrustedUserCAKeys` in `sshd_config`.
+    pub fn trusted_user_ca_key(&self) -> String {
+        self.public_key().to_openssh()
+    }
+    /// `cert-authority` line for `authorized_keys`, optionally restricted to principals.
+    pub fn authorized_keys_entry(&self, principals: &[String]) -> String {
+        if principals.is_empty() {
+            format!("cert-authority {}", self.public_key().to_openssh())
+        } else {
+            format!(
+                "cert-authority,principals=\"{}\" {}",
+                principals.join(","),
+                self.public_key().to_openssh()
+            )
+        }
+    }
+    /// `@cert-authority` line for `known_hosts`, trusting host certificates for `host_pattern`.
+    pub fn known_hosts_entry(&self, host_pattern: &str) -> String {
+        format!(
+            "@cert-authority {} {}",
+            host_pattern,
+            self.public_key().to_openssh()
+        )
+    }
+    /// Certifies `public_key` (an OpenSSH public key line) and returns the `*-cert.pub` line.
+    pub async fn issue(
+        &self,
+        public_key: &str,
+        options: &SshCertificateOptions,
+    ) -> Result<String, CaError> {
+        let subject = SshPublicKey::parse(public_key)?;
+        let to_be_signed = certificate_to_be_signed(&subject, options, self.key.public_key())?;
+        let signature = self.key.sign(&to_be_signed).await?;
+        Ok(certificate_line(to_be_signed, &signature, &options.key_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer as _, SigningKey, Verifier as _};
+
+    #[test]
+    fn test_user_certificate() {
+        let ca_key = SigningKey::from_bytes(&[3u8; 32]);
+        let user_key = SigningKey::from_bytes(&[4u8; 32]);
+        let user_line = format!(
+            "{} alice@laptop",
+            SshPublicKey::ed25519(user_key.verifying_key().as_bytes()).to_openssh()
+        );
+        let subject = SshPublicKey::parse(&user_line).unwrap();
+        assert_eq!(subject.comment.as_deref(), Some("alice@laptop"));
+
+        let mut options = SshCertificateOptions::user(
+            "alice",
+            vec!["alice".to_string(), "admin".to_string()],
+            Duration::from_secs(3600),
+        );
+        options.serial = Some(42);
+        options.valid_after = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        options
+            .critical_options
+            .insert("source-address".to_string(), "10.0.0.0/8".to_string());
+        let to_be_signed =
+            certificate_to_be_signed(&subject, &options, ca_key.verifying_key().as_bytes())
+                .unwrap();
+        let signature = ca_key.sign(&to_be_signed).to_bytes();
+        let line = certificate_line(to_be_signed.clone(), &signature, "alice");
+
+        let mut parts = line.split(' ');
+        assert_eq!(parts.next(), Some("ssh-ed25519-cert-v01@openssh.com"));
+        let certificate = BASE64.decode(parts.next().unwrap()).unwrap();
+        assert!(certificate.starts_with(&to_be_signed));
+
+        let (cert_type, rest) = read_string(&certificate).unwrap();
+        assert_eq!(cert_type, b"ssh-ed25519-cert-v01@openssh.com");
+        let (nonce, rest) = read_string(rest).unwrap();
+        assert_eq!(nonce.len(), 32);
+        let (pk, rest) = read_string(rest).unwrap();
+        assert_eq!(pk, user_key.verifying_key().as_bytes());
+        assert_eq!(u64::from_be_bytes(rest[..8].try_into().unwrap()), 42);
+        assert_eq!(u32::from_be_bytes(rest[8..12].try_into().unwrap()), 1);
+        let (key_id, rest) = read_string(&rest[12..]).unwrap();
+        assert_eq!(key_id, b"alice");
+        let (principals, rest) = read_string(rest).unwrap();
+        let (first, others) = read_string(principals).unwrap();
+        assert_eq!(first, b"alice");
+        assert_eq!(read_string(others).unwrap().0, b"admin");
+        assert_eq!(
+            u64::from_be_bytes(rest[..8].try_into().unwrap()),
+            1_700_000_000
+        );
+        assert_eq!(
+            u64::from_be_bytes(rest[8..16].try_into().unwrap()),
+            1_700_003_600
+        );
+        let (critical_options, rest) = read_string(&rest[16..]).unwrap();
+        let (name, data) = read_string(critical_options).unwrap();
+        assert_eq!(name, b"source-address");
+        let (data, _) = read_string(data).unwrap();
+        assert_eq!(read_string(data).unwrap().0, b"10.0.0.0/8");
+        let (extensions, rest) = read_string(rest).unwrap();
+        assert_eq!(read_string(extensions).unwrap().0, b"permit-X11-forwarding");
+        let (reserved, rest) = read_string(rest).unwrap();
+        assert!(reserved.is_empty());
+        let (signature_key, rest) = read_string(rest).unwrap();
+        assert_eq!(
+            signature_key,
+            SshPublicKey::ed25519(ca_key.verifying_key().as_bytes()).blob
+        );
+        let (signature_blob, rest) = read_string(rest).unwrap();
+        assert!(rest.is_empty());
+        let (algorithm, signature) = read_string(signature_blob).unwrap();
+        assert_eq!(algorithm, b"ssh-ed25519");
+        let signature =
+            ed25519_dalek::Signature::from_slice(read_string(signature).unwrap().0).unwrap();
+        ca_key
+            .verifying_key()
+            .verify(&to_be_signed, &signature)
+            .unwrap();
+    }
+    #[test]
+    fn test_rejects_invalid_requests() {
+        let key = SshPublicKey::ed25519(&[5u8; 32]);
+        assert!(matches!(
+            SshPublicKey::parse(&key.to_openssh().replace("ssh-ed25519 ", "ssh-rsa ")),
+            Err(CaError::InvalidSshKey(_))
+        ));
+        // the key fields must be those of the key type
+        let line = |key_type: &str, fields: &[&[u8]]| {
+            let mut blob = Vec::new();
+            put_string(&mut blob, key_type.as_bytes());
+            for field in fields {
+                put_string(&mut blob, field);
+            }
+            format!("{} {}", key_type, BASE64.encode(blob))
+        };
+        let mut point = vec![0x04; 65];
+        assert!(SshPublicKey::parse(&line("ecdsa-sha2-nistp256", &[b"nistp256", &point])).is_ok());
+        for invalid in [
+            line("ssh-ed25519", &[&[5u8; 31]]),
+            line("ssh-ed25519", &[&[5u8; 32], b"extra"]),
+            line("ssh-rsa", &[&[1, 0, 1], &[0xff; 64]]),
+            line("ssh-rsa", &[&[], &[0xff; 256]]),
+            line("ssh-rsa", &[&[1, 0, 1]]),
+            line("ecdsa-sha2-nistp256", &[b"nistp384", &point]),
+            line("ecdsa-sha2-nistp384", &[b"nistp384", &point]),
+        ] {
+            assert!(matches!(
+                SshPublicKey::parse(&invalid),
+                Err(CaError::InvalidSshKey(_))
+            ));
+        }
+        point[0] = 0x02;
+        assert!(matches!(
+            SshPublicKey::parse(&line("ecdsa-sha2-nistp256", &[b"nistp256", &point])),
+            Err(CaError::InvalidSshKey(_))
+        ));
+        assert!(matches!(
+            SshPublicKey::parse(&line("ssh-dss", &[&[1u8; 20]])),
+            Err(CaError::UnsupportedAlgorithm(_))
+        ));
+
+        let options = SshCertificateOptions::user("nobody", vec![], Duration::from_secs(60));
+        assert!(matches!(
+            certificate_to_be_signed(&key, &options, &[6u8; 32]),
+            Err(CaError::InvalidPolicy(_))
+        ));
+    }
+}
@@ -1,7 +1,19 @@
+mod auto_dkg_store;
 mod command;
+mod idempotency;
+mod journal;
+mod key_lifecycle;
+mod key_metadata;
 mod manager;
+mod monitor;
+mod namespace;
+mod policy;
+mod scheduler;
 mod session;
 mod session_ex;
+mod signature_store;
+mod whitelist;
+use crate::audit::{AuditEntry, AuditLog};
 use crate::keystore::Keystore;
 use crate::types::error::SessionError;
 use crate::types::message::{
@@ -11,14 +23,23 @@ use crate::types::message::{
     SigningResponseWrap, SigningResponseWrapEx, TargetOrBroadcast, ValidatorIdentityRequest,
     ValidatorIdentityResponse,
 };
-use crate::types::{AutoDKG, GroupPublicKeyInfo, SignatureSuiteInfo, Validator};
+use crate::types::{
+    AppId, AutoDKG, AutoDKGOptions, CancelTarget, Deadline, KeyInfo, KeyMetadata,
+    KeyMetadataUpdate, KeyState, PublicKeyInfo, PublicKeyPackageInfo, SessionRequest,
+    SessionResult, SignItem, SignatureFormat, SignatureSuiteInfo, SignerSelection, Validator,
+    DEFAULT_APP,
+};
 use crate::utils::*;
 use crate::{crypto::*, utils};
 use anyhow::anyhow;
+use auto_dkg_store::AutoDKGStore;
 use command::Command;
 use common::Settings;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
+use idempotency::{IdempotencyStore, Replay};
+use journal::SessionJournal;
+use key_lifecycle::{DestroyOutcome, KeyDestroyer, KeyStateResponder};
 use libp2p::request_response::{
     InboundRequestId, OutboundRequestId, ProtocolSupport, ResponseChannel,
 };
@@ -31,10 +52,14 @@ use libp2p::{
 use libp2p::{ping, rendezvous, request_response, PeerId, StreamProtocol};
 use manager::CoordinatorStateEx;
 use manager::Instruction;
+use monitor::SessionMonitor;
+use namespace::{KeyScope, Namespaces};
+use policy::SigningPolicy;
+use scheduler::SignerScheduler;
+use serde::{Deserialize, Serialize};
 use session::SessionWrap;
+use signature_store::SignatureStore;
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufWriter;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -46,7 +71,80 @@ use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::{oneshot, RwLock};
 use tokio::time::Instant;
+use whitelist::SignerWhitelist;
+
+// largest batch a node may sign in one request
+const MAX_SIGN_BATCH: usize = 256;
+// signing sessions of batches running at the same time, over all nodes; the other items
+// wait for one of them to finish
+const MAX_BATCH_SESSIONS: usize = 32;
+
+type SignatureReceiver<VII> = oneshot::Receiver<Result<SignatureSuiteInfo<VII>, SessionError>>;
+
+// a retry attached to a running session by its idempotency key
+enum IdempotentWaiter<VII: ValidatorIdentityIdentity> {
+    Request(ResponseChannel<NodeToCoorResponse<VII>>, SignatureFormat),
+    BatchItem(oneshot::Sender<Result<SignatureSuiteInfo<VII>, String>>),
+}
+// how an item of a batch gets its result
+enum BatchItem<VII: ValidatorIdentityIdentity> {
+    // a new session, its instruction is sent once a batch slot is free
+    Started(u64, Instruction<VII>, SignatureReceiver<VII>),
+    // a session started by an earlier request with the same idempotency key
+    Attached(oneshot::Receiver<Result<SignatureSuiteInfo<VII>, String>>),
+    Done(Result<SignatureSuiteInfo<VII>, String>),
+}
+// a format the signatures of the key cannot be encoded in is refused before signing
+fn check_signature_format(pkid: &PkId, signature_format: SignatureFormat) -> Result<(), String> {
+    pkid.crypto_type()
+        .map_err(|e| e.to_string())
+        .and_then(|crypto_type| signature_format.check(crypto_type))
+}
 
+// `signature_format` is the one the node asked for, signatures are kept in their native one
+fn session_result_response<VII: ValidatorIdentityIdentity>(
+    result: SessionResult<VII>,
+    signature_format: SignatureFormat,
+) -> NodeToCoorResponse<VII> {
+    match result.with_signature_format(signature_format) {
+        SessionResult::Dkg(Ok(pkid)) => NodeToCoorResponse::DKGResponse { pkid },
+        SessionResult::Signing(Ok(signature_suite_info)) => NodeToCoorResponse::SigningResponse {
+            signature_suite_info,
+        },
+        SessionResult::Dkg(Err(e)) | SessionResult::Signing(Err(e)) => {
+            NodeToCoorResponse::Failure(e)
+        }
+    }
+}
+// resumes a journaled signing session as the node that requested it, so that it stays
+// within the node's namespace and the node can still cancel it
+fn resumed_signing<VII: ValidatorIdentityIdentity + for<'de> Deserialize<'de>>(
+    namespaces: &Namespaces<VII>,
+    requester: VII,
+    request: SessionRequest<VII>,
+    signature_response_oneshot: oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>,
+) -> Option<Instruction<VII>> {
+    let SessionRequest::Signing {
+        pkid,
+        msg,
+        tweak_data,
+        deadline,
+        selection,
+    } = request
+    else {
+        return None;
+    };
+    Some(Instruction::Sign {
+        pkid,
+        msg,
+        tweak_data,
+        scope: namespaces.scope_of(&requester),
+        requester: Some(requester),
+        deadline,
+        selection,
+        signature_response_oneshot,
+    })
+}
 macro_rules! handle_err {
     ($e:expr, $msg:expr) => {
         if let Err(e) = $e {
@@ -54,15 +152,67 @@ macro_rules! handle_err {
         }
     };
 }
+// generates the key of `crypto_type` for the AutoDKG slot `app`, retrying until it
+// succeeds, and saves the AutoDKG state with the new key
+async fn run_auto_dkg<VII: ValidatorIdentityIdentity + Serialize>(
+    instruction_sender: UnboundedSender<Instruction<VII>>,
+    auto_dkg: Arc<RwLock<AutoDKG<VII>>>,
+    auto_dkg_store: Arc<std::sync::Mutex<AutoDKGStore>>,
+    app: AppId,
+    crypto_type: CryptoType,
+    participants: Vec<(u16, VII)>,
+) {
+    loop {
+        let (pkid_sender, pkid_receiver) = oneshot::channel();
+        let instruction = Instruction::NewKey {
+            crypto_type,
+            participants: participants.clone(),
+            min_signers: auto_dkg.read().await.min_signers,
+            requester: None,
+            deadline: None,
+            metadata: KeyMetadataUpdate {
+                label: Some(format!("auto-dkg/{}", app)),
+                ..Default::default()
+            },
+            namespace: None,
+            pkid_response_oneshot: pkid_sender,
+        };
+        instruction_sender.send(instruction).unwrap();
+        match pkid_receiver.await {
+            Ok(Ok(pkid)) => {
+                // saved while still locked so that a later state is never overwritten
+                let mut auto_dkg_value = auto_dkg.write().await;
+                let (done, _) = auto_dkg_value.update_new_dkg_result(&app, crypto_type, pkid);
+                handle_err!(
+                    auto_dkg_store.lock().unwrap().save(&*auto_dkg_value),
+                    "Error saving AutoDKG state: {}"
+                );
+                if done {
+                    tracing::info!("All DKGs of AutoDKG slot {} are done", app);
+                }
+                return;
+            }
+            Ok(Err(e)) => {
+                tracing::error!("Error starting DKG: {}", e);
+            }
+            Err(e) => {
+                tracing::error!("Error starting DKG: {}", e);
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(
+            Settings::global().session.state_channel_retry_interval,
+        ))
+        .await;
+    }
+}
 pub struct Coordinator<VI: ValidatorIdentity> {
     p2p_keypair: libp2p::identity::Keypair,
-    signer_whitelist: Option<HashSet<VI::Identity>>,
+    signer_whitelist: SignerWhitelist<VI::Identity>,
     listen_port: u16,
     swarm: libp2p::Swarm<CoorBehaviour<VI::Identity>>,
     ipc_path: PathBuf,
     valid_validators: HashMap<VI::Identity, Validator<VI>>,
     p2ppeerid_2_endpoint: HashMap<PeerId, Multiaddr>,
-    base_path: PathBuf,
     dkg_request_mapping:
         HashMap<OutboundRequestId, (oneshot::Sender<DKGResponseWrap<VI::Identity>>, PeerId)>,
     signing_request_mapping:
@@ -101,33 +251,91 @@ pub struct Coordinator<VI: ValidatorIdentity> {
     instruction_sender: UnboundedSender<Instruction<VI::Identity>>,
     dkg_response_futures_for_node: FuturesUnordered<
         oneshot::Receiver<(
+            u64,
             Result<PkId, SessionError>,
             ResponseChannel<NodeToCoorResponse<VI::Identity>>,
         )>,
     >,
     signing_response_futures_for_node: FuturesUnordered<
         oneshot::Receiver<(
+            u64,
+            SignatureFormat,
             Result<SignatureSuiteInfo<VI::Identity>, SessionError>,
             ResponseChannel<NodeToCoorResponse<VI::Identity>>,
         )>,
     >,
+    // journal id, if the item was started, requested format and result of each item
+    // of a batch
+    signing_batch_response_futures_for_node: FuturesUnordered<
+        oneshot::Receiver<(
+            Vec<(
+                Option<u64>,
+                SignatureFormat,
+                Result<SignatureSuiteInfo<VI::Identity>, String>,
+            )>,
+            ResponseChannel<NodeToCoorResponse<VI::Identity>>,
+        )>,
+    >,
     lspk_response_futures_for_node: FuturesUnordered<
         oneshot::Receiver<(
-            Result<HashMap<CryptoType, Vec<PkId>>, SessionError>,
+            Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, SessionError>,
             ResponseChannel<NodeToCoorResponse<VI::Identity>>,
         )>,
     >,
     pk_response_futures_for_node: FuturesUnordered<
         oneshot::Receiver<(
-            Result<GroupPublicKeyInfo, SessionError>,
+            Result<PublicKeyInfo<VI::Identity>, SessionError>,
+            ResponseChannel<NodeToCoorResponse<VI::Identity>>,
+        )>,
+    >,
+    package_response_futures_for_node: FuturesUnordered<
+        oneshot::Receiver<(
+            Result<PublicKeyPackageInfo<VI::Identity>, SessionError>,
+            ResponseChannel<NodeToCoorResponse<VI::Identity>>,
+        )>,
+    >,
+    key_metadata_response_futures_for_node: FuturesUnordered<
+        oneshot::Receiver<(
+            Result<KeyMetadata<VI::Identity>, SessionError>,
+            ResponseChannel<NodeToCoorResponse<VI::Identity>>,
+        )>,
+    >,
+    cancel_response_futures_for_node: FuturesUnordered<
+        oneshot::Receiver<(
+            CancelTarget,
+            Result<Vec<VI::Identity>, SessionError>,
             ResponseChannel<NodeToCoorResponse<VI::Identity>>,
         )>,
     >,
+    journal: SessionJournal<VI::Identity>,
+    idempotency: IdempotencyStore<VI::Identity>,
+    // retries attached to a running session by their idempotency key, by journal id
+    idempotent_waiters: HashMap<u64, Vec<IdempotentWaiter<VI::Identity>>>,
+    // slots of the signing sessions of batches, see MAX_BATCH_SESSIONS
+    batch_sessions: Arc<tokio::sync::Semaphore>,
+    // namespace of the key of each running signing session, by journal id; the key is
+    // in the namespace the session was started for
+    signing_namespaces: HashMap<u64, Option<String>>,
+    // sessions resumed from the journal, their node response channels are gone
+    resumed_session_futures:
+        FuturesUnordered<oneshot::Receiver<(u64, SessionResult<VI::Identity>)>>,
+    session_monitor: SessionMonitor<VI::Identity>,
+    signer_scheduler: SignerScheduler<VI::Identity>,
+    signing_policy: SigningPolicy<VI::Identity>,
+    namespaces: Namespaces<VI::Identity>,
+    audit_log: AuditLog<libp2p::identity::Keypair>,
+    signature_store: SignatureStore<VI::Identity>,
+    key_destroyer: KeyDestroyer<VI::Identity>,
+    // outstanding destroy requests to signers
+    key_destroy_requests: HashMap<OutboundRequestId, PkId>,
 
-    // !WARNING: auto_dkg should not be used in multi-application scenarios.
-    // !WARNING: It only generates one base key for each key type, and other keys are derived through tweaking.
-    // !WARNING: Note that these are tweaked keys, not derived keys - this is highly insecure for multi-application use cases since tweaking does not provide proper key isolation between applications.
+    // !WARNING: tweaks of one base key give no key isolation between applications, every
+    // !WARNING: application should ask for its own AutoDKG slot instead of sharing the default one.
     auto_dkg: Option<Arc<RwLock<AutoDKG<VI::Identity>>>>,
+    auto_dkg_store: Arc<std::sync::Mutex<AutoDKGStore>>,
+    // nodes waiting for the keys of a new AutoDKG slot, by slot
+    auto_dkg_waiters: HashMap<AppId, Vec<ResponseChannel<NodeToCoorResponse<VI::Identity>>>>,
+    auto_dkg_slot_futures: FuturesUnordered<oneshot::Receiver<AppId>>,
 }
 impl<VI: ValidatorIdentity> Coordinator<VI> {
     pub fn new(
@@ -135,7 +343,7 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
         base_path: PathBuf,
         signer_whitelist: Option<HashSet<VI::Identity>>,
         port: u16,
-        automatic_dkg: Option<u16>,
+        automatic_dkg: Option<AutoDKGOptions<VI::Identity>>,
     ) -> anyhow::Result<Self> {
         let swarm = libp2p::SwarmBuilder::with_existing_identity(p2p_keypair.clone())
             .with_tokio()
@@ -193,6 +401,44 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
 
         let keystore =
             Arc::new(Keystore::new(p2p_keypair.derive_secret(b"keystore").unwrap(), None).unwrap());
+        let journal = SessionJournal::open(
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+            Settings::global().coordinator.outcome_retention,
+        )?;
+        let signature_store = SignatureStore::open(
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+        )?;
+        let idempotency = IdempotencyStore::open(
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+            Settings::global().coordinator.idempotency_window,
+        )?;
+        // the saved whitelist wins over the one passed in
+        let signer_whitelist = SignerWhitelist::open(
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+            signer_whitelist,
+        )?;
+        let (auto_dkg_store, stored_auto_dkg) = AutoDKGStore::open(
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+            base_path.join("auto_dkg.json"),
+        )?;
+        let session_monitor = SessionMonitor::new();
+        let signer_scheduler = SignerScheduler::new();
+        let signing_policy = SigningPolicy::load(
+            base_path.join("signing_policy.json"),
+            keystore.clone(),
+            base_path.join(Settings::global().coordinator.keystore_path),
+        )?;
+        let namespaces = Namespaces::load(base_path.join("namespaces.json"))?;
+        let audit_log = AuditLog::open(
+            base_path.join(Settings::global().coordinator.audit_log_path),
+            p2p_keypair.clone(),
+            Settings::global().session.audit_checkpoint_interval,
+        )?;
         manager::CoordiantorSessionManager::new(
             instruction_receiver,
             dkg_session_sender,
@@ -203,36 +449,54 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
             signing_in_final_channel_receiver,
             keystore,
             &base_path,
+            session_monitor.clone(),
+            signer_scheduler.clone(),
         )?
         .listening();
-        // if auto_dkg file exists, load it
-        let auto_dkg_path = base_path.join("auto_dkg.json");
-        let auto_dkg = if auto_dkg_path.exists() {
-            let auto_dkg_file = File::open(auto_dkg_path).unwrap();
-            let auto_dkg_reader = std::io::BufReader::new(auto_dkg_file);
-            let auto_dkg: Option<AutoDKG<VI::Identity>> =
-                serde_json::from_reader(auto_dkg_reader).unwrap();
-            auto_dkg
-        } else {
-            if let Some(min_signers) = automatic_dkg {
-                if signer_whitelist.clone().is_none() {
+        // the saved state wins, the options only apply to a coordinator without one
+        let auto_dkg = match (stored_auto_dkg, automatic_dkg) {
+            (Some(auto_dkg), options) => {
+                if options.is_some() {
+                    tracing::warn!("AutoDKG options are ignored, the saved AutoDKG state is used");
+                }
+                Some(auto_dkg)
+            }
+            (None, Some(options)) => {
+                if !signer_whitelist.is_started() {
                     return Err(anyhow::anyhow!(
                         "signer_whitelist is required when automatic_dkg is enabled"
                     ));
                 }
-                if min_signers == 0
-                    || signer_whitelist.clone().unwrap().len() < min_signers as usize
+                if let Some(outsider) = options
+                    .participants
+                    .iter()
+                    .find(|participant| !signer_whitelist.allows(participant))
                 {
                     return Err(anyhow::anyhow!(
-                    "min_signers must be greater than 0 and less than or equal to the number of signers in the whitelist, min signer:{:?}, whitelist:{:?}",
+                        "auto dkg participant {} is not in the signer whitelist",
+                        outsider.to_fmt_string()
+                    ));
+                }
+                let participants = if options.participants.is_empty() {
+                    signer_whitelist.identities()
+                } else {
+                    options.participants
+                };
+                let min_signers = options.min_signers;
+                if min_signers == 0 || participants.len() < min_signers as usize {
+                    return Err(anyhow::anyhow!(
+                    "min_signers must be greater than 0 and less than or equal to the number of auto dkg participants, min signer:{:?}, participants:{:?}",
                     min_signers,
-                    signer_whitelist.clone().unwrap().len()
+                    participants.len()
                 ));
                 }
-                Some(AutoDKG::new(min_signers, signer_whitelist.clone().unwrap()))
-            } else {
-                None
+                Some(AutoDKG::new(
+                    min_signers,
+                    participants,
+                    options.crypto_types,
+                ))
             }
+            (None, None) => None,
         };
         Ok(Self {
             p2p_keypair,
@@ -255,18 +519,40 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
             instruction_sender,
             dkg_response_futures_for_node: FuturesUnordered::new(),
             signing_response_futures_for_node: FuturesUnordered::new(),
+            signing_batch_response_futures_for_node: FuturesUnordered::new(),
             lspk_response_futures_for_node: FuturesUnordered::new(),
             pk_response_futures_for_node: FuturesUnordered::new(),
+            package_response_futures_for_node: FuturesUnordered::new(),
+            key_metadata_response_futures_for_node: FuturesUnordered::new(),
+            cancel_response_futures_for_node: FuturesUnordered::new(),
+            journal,
+            idempotency,
+            idempotent_waiters: HashMap::new(),
+            batch_sessions: Arc::new(tokio::sync::Semaphore::new(MAX_BATCH_SESSIONS)),
+            signing_namespaces: HashMap::new(),
+            resumed_session_futures: FuturesUnordered::new(),
+            session_monitor,
+            signer_scheduler,
+            signing_policy,
+            namespaces,
+            audit_log,
+            signature_store,
+            key_destroyer: KeyDestroyer::new(),
+            key_destroy_requests: HashMap::new(),
             auto_dkg: auto_dkg.map(|dkg| Arc::new(RwLock::new(dkg))),
-            base_path,
+            auto_dkg_store: Arc::new(std::sync::Mutex::new(auto_dkg_store)),
+            auto_dkg_waiters: HashMap::new(),
+            auto_dkg_slot_futures: FuturesUnordered::new(),
         })
     }
-    // if automatic_dkg is Some(min_signers), the coordinator will do dkg for all ciphersuites,
+    // if automatic_dkg is Some, the coordinator will do dkg for the chosen ciphersuites,
     // if automatic_dkg is None, the coordinator will do dkg manually,
     pub async fn start_listening(mut self) -> Result<(), anyhow::Error> {
         self.swarm
             .listen_on(format!("/ip4/0.0.0.0/tcp/{}", self.listen_port).parse()?)?;
         let listener = self.start_ipc_listening().await?;
+        self.recover_sessions();
+        self.resume_auto_dkg().await;
         loop {
             tokio::select! {
                 event = self.swarm.select_next_some()=> {
@@ -317,39 +603,74 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         tracing::error!("Error handling command: {}", e);
                     }
                 }
-                Some(Ok((result, channel))) = self.dkg_response_futures_for_node.next()=> {
-                    match result {
-                        Ok(pkid) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::DKGResponse { pkid }) {
-                                tracing::error!("Error sending DKG response to node: {:?}", e);
-                            }
-                        }
-                        Err(e) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::Failure(e.to_string())) {
-                                tracing::error!("Error sending DKG failure response to node: {:?}", e);
-                            }
-                        }
+                Some(Ok((journal_id, result, channel))) = self.dkg_response_futures_for_node.next()=> {
+                    let result = result.map_err(|e| e.to_string());
+                    let response = match result.clone() {
+                        Ok(pkid) => NodeToCoorResponse::DKGResponse { pkid },
+                        Err(e) => NodeToCoorResponse::Failure(e),
+                    };
+                    let delivered = self.swarm.behaviour_mut().node2coor.send_response(channel, response);
+                    if let Err(e) = &delivered {
+                        tracing::error!("Error sending DKG response to node: {:?}", e);
                     }
+                    let result = SessionResult::Dkg(result);
+                    let delivered = self.answer_idempotent_waiters(journal_id, &result) || delivered.is_ok();
+                    self.audit_session(journal_id, &result);
+                    handle_err!(self.idempotency.finish(journal_id, &result), "Error recording idempotent result: {}");
+                    handle_err!(self.journal.finish(journal_id, result, delivered), "Error journaling DKG result: {}");
                 }
-                Some(Ok((result, channel))) = self.signing_response_futures_for_node.next()=> {
+                Some(Ok((journal_id, signature_format, result, channel))) = self.signing_response_futures_for_node.next()=> {
                     tracing::info!("{}",channel.is_open());
-                    match result {
-                        Ok(signature_suite_info) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::SigningResponse { signature_suite_info }) {
-                                tracing::error!("Error sending signing response to node: {:?}", e);
-                            }
-                        }
-                        Err(e) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::Failure(e.to_string())) {
-                                tracing::error!("Error sending signing failure response to node: {:?}", e);
-                            }
-                        }
+                    let result = SessionResult::Signing(result.map_err(|e| e.to_string()));
+                    let response = session_result_response(result.clone(), signature_format);
+                    let delivered = self.swarm.behaviour_mut().node2coor.send_response(channel, response);
+                    if let Err(e) = &delivered {
+                        tracing::error!("Error sending signing response to node: {:?}", e);
                     }
+                    let delivered = self.answer_idempotent_waiters(journal_id, &result) || delivered.is_ok();
+                    self.audit_session(journal_id, &result);
+                    self.store_signature(journal_id, &result);
+                    handle_err!(self.idempotency.finish(journal_id, &result), "Error recording idempotent result: {}");
+                    handle_err!(self.journal.finish(journal_id, result, delivered), "Error journaling signing result: {}");
+                }
+                Some(Ok((results, channel))) = self.signing_batch_response_futures_for_node.next()=> {
+                    let response = NodeToCoorResponse::SigningBatchResponse {
+                        results: results
+                            .iter()
+                            .map(|(_, signature_format, result)| {
+                                result.clone().and_then(|info| info.with_format(*signature_format))
+                            })
+                            .collect(),
+                    };
+                    let batch_delivered = self.swarm.behaviour_mut().node2coor.send_response(channel, response);
+                    if let Err(e) = &batch_delivered {
+                        tracing::error!("Error sending signing batch response to node: {:?}", e);
+                    }
+                    for (journal_id, _, result) in results {
+                        let Some(journal_id) = journal_id else {
+                            continue;
+                        };
+                        let result = SessionResult::Signing(result);
+                        let delivered = self.answer_idempotent_waiters(journal_id, &result) || batch_delivered.is_ok();
+                        self.audit_session(journal_id, &result);
+                        self.store_signature(journal_id, &result);
+                        handle_err!(self.idempotency.finish(journal_id, &result), "Error recording idempotent result: {}");
+                        handle_err!(self.journal.finish(journal_id, result, delivered), "Error journaling signing result: {}");
+                    }
+                }
+                Some(Ok((journal_id, result))) = self.resumed_session_futures.next()=> {
+                    tracing::info!("Resumed session {} finished", journal_id);
+                    // without a retry attached, the outcome is kept until the node polls for it
+                    let delivered = self.answer_idempotent_waiters(journal_id, &result);
+                    self.audit_session(journal_id, &result);
+                    self.store_signature(journal_id, &result);
+                    handle_err!(self.idempotency.finish(journal_id, &result), "Error recording idempotent result: {}");
+                    handle_err!(self.journal.finish(journal_id, result, delivered), "Error journaling resumed session result: {}");
                 }
                 Some(Ok((result, channel))) = self.lspk_response_futures_for_node.next()=> {
                     match result {
-                        Ok(pkids) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::LsPkResponse { pkids }) {
+                        Ok(keys) => {
+                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::LsPkResponse { keys }) {
                                 tracing::error!("Error sending LsPk response to node: {:?}", e);
                             }
                         }
@@ -360,10 +681,22 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         }
                     }
                 }
+                Some(Ok((target, result, channel))) = self.cancel_response_futures_for_node.next()=> {
+                    let response = match result {
+                        Ok(participants) => {
+                            self.notify_cancel(target, participants);
+                            NodeToCoorResponse::CancelResponse { session_id: target.to_string() }
+                        }
+                        Err(e) => NodeToCoorResponse::Failure(e.to_string()),
+                    };
+                    if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, response) {
+                        tracing::error!("Error sending cancel response to node: {:?}", e);
+                    }
+                }
                 Some(Ok((result, channel))) = self.pk_response_futures_for_node.next()=> {
                     match result {
-                        Ok(group_public_key_info) => {
-                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::PkTweakResponse { group_public_key_info }) {
+                        Ok(public_key_info) => {
+                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, NodeToCoorResponse::PkTweakResponse { public_key_info }) {
                                 tracing::error!("Error sending PK response to node: {:?}", e);
                             }
                         }
@@ -374,6 +707,81 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         }
                     }
                 }
+                Some(Ok((result, channel))) = self.package_response_futures_for_node.next()=> {
+                    let response = match result {
+                        Ok(package) => NodeToCoorResponse::PublicKeyPackageResponse { package },
+                        Err(e) => NodeToCoorResponse::Failure(e.to_string()),
+                    };
+                    if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, response) {
+                        tracing::error!("Error sending public key package response to node: {:?}", e);
+                    }
+                }
+                Some(Ok(app)) = self.auto_dkg_slot_futures.next()=> {
+                    self.answer_auto_dkg_waiters(&app).await;
+                }
+                Some(Ok((result, channel))) = self.key_metadata_response_futures_for_node.next()=> {
+                    let response = match result {
+                        Ok(metadata) => NodeToCoorResponse::KeyMetadataResponse { metadata },
+                        Err(e) => NodeToCoorResponse::Failure(e.to_string()),
+                    };
+                    if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(channel, response) {
+                        tracing::error!("Error sending key metadata response to node: {:?}", e);
+                    }
+                }
+            }
+        }
+    }
+    // Signing sessions that were running when the coordinator stopped are started again
+    // once signers had time to reconnect; DKG sessions are aborted since a second run
+    // would create a different key than the one signers may already have stored.
+    fn recover_sessions(&mut self) {
+        for (journal_id, requester, request) in self.journal.pending() {
+            match request {
+                SessionRequest::Dkg { .. } => {
+                    tracing::warn!("Aborting DKG session {} interrupted by restart", journal_id);
+                    let result =
+                        SessionResult::Dkg(Err("DKG aborted by coordinator restart".to_string()));
+                    handle_err!(
+                        self.idempotency.finish(journal_id, &result),
+                        "Error recording idempotent result: {}"
+                    );
+                    handle_err!(
+                        self.journal.finish(journal_id, result, false),
+                        "Error journaling aborted DKG session: {}"
+                    );
+                }
+                request @ SessionRequest::Signing { .. } => {
+                    self.signing_namespaces
+                        .insert(journal_id, self.namespaces.namespace_of(&requester));
+                    let (signature_sender, signature_receiver) = oneshot::channel();
+                    let Some(instruction) =
+                        resumed_signing(&self.namespaces, requester, request, signature_sender)
+                    else {
+                        continue;
+                    };
+                    tracing::info!("Resuming signing session {}", journal_id);
+                    let instruction_sender = self.instruction_sender.clone();
+                    let (result_sender, result_receiver) = oneshot::channel();
+                    self.resumed_session_futures.push(result_receiver);
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_secs(
+                            Settings::global().coordinator.session_resume_delay,
+                        ))
+                        .await;
+                        let result = match instruction_sender.send(instruction) {
+                            Ok(()) => match signature_receiver.await {
+                                Ok(result) => result.map_err(|e| e.to_string()),
+                                Err(e) => Err(e.to_string()),
+                            },
+                            Err(e) => Err(e.to_string()),
+                        };
+                        if let Err(e) =
+                            result_sender.send((journal_id, SessionResult::Signing(result)))
+                        {
+                            tracing::error!("Error sending resumed session result: {:?}", e);
+                        }
+                    });
+                }
             }
         }
     }
@@ -433,6 +841,7 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                 if let Some(addr) = validator.address {
                     self.swarm.add_peer_address(validator.p2p_peer_id, addr);
                 }
+                self.signer_scheduler.request_sent(peer);
                 let outbound_request_id = self.swarm.behaviour_mut().coor2sig.send_request(
                     &validator.p2p_peer_id,
                     CoorToSigRequest::SigningRequest(request),
@@ -511,6 +920,7 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                 if let Some(addr) = validator.address {
                     self.swarm.add_peer_address(validator.p2p_peer_id, addr);
                 }
+                self.signer_scheduler.request_sent(peer);
                 let outbound_request_id = self.swarm.behaviour_mut().coor2sig.send_request(
                     &validator.p2p_peer_id,
                     CoorToSigRequest::SigningRequestEx(request),
@@ -544,10 +954,16 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                 tracing::info!("Coordinator connected to {}", peer_id);
                 self.p2ppeerid_2_endpoint
                     .insert(peer_id, endpoint.get_remote_address().clone());
+                if let Some(identity) = self.identity_of(&peer_id) {
+                    self.signer_scheduler.connection_changed(&identity, true);
+                }
             }
             SwarmEvent::ConnectionClosed { peer_id, .. } => {
                 tracing::warn!("Coordinator disconnected from {}", peer_id);
                 // self.p2ppeerid_2_endpoint.remove(&peer_id);
+                if let Some(identity) = self.identity_of(&peer_id) {
+                    self.signer_scheduler.connection_changed(&identity, false);
+                }
             }
             SwarmEvent::OutgoingConnectionError { error, .. } => {
                 tracing::error!("Coordinator outgoing connection error: {:?}", error);
@@ -625,6 +1041,10 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         return Ok(());
                     }
                     if let Some((sender, _)) = self.signing_request_mapping.remove(&request_id) {
+                        if let Some(identity) = self.identity_of(&peer) {
+                            self.signer_scheduler
+                                .request_finished(&identity, !response.is_failure());
+                        }
                         tracing::debug!("Sending response {:?} to session", response);
                         if let Err(e) = sender.send(response) {
                             tracing::error!("Error sending response: {:?}", e);
@@ -665,6 +1085,22 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         tracing::debug!("Request id {:?} not found in request mapping", request_id);
                     }
                 }
+                CoorToSigResponse::Cancelled => {
+                    tracing::info!(
+                        "Coordinator received cancel acknowledgement from {} with request_id {}",
+                        peer,
+                        request_id
+                    );
+                }
+                CoorToSigResponse::KeyStateApplied(result) => {
+                    if let Err(e) = &result {
+                        tracing::warn!("Signer {} failed to apply key state: {}", peer, e);
+                    }
+                    if let Some(pkid) = self.key_destroy_requests.remove(&request_id) {
+                        let signer = self.identity_of(&peer);
+                        self.destroy_answered(&pkid, signer, result.is_ok()).await;
+                    }
+                }
                 CoorToSigResponse::SigningResponseEx(signing_response_wrap_ex) => {
                     tracing::info!(
                         "Coordinator received signing response from {} with request_id {}, data:{:?}",
@@ -682,6 +1118,15 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         if let Some((sender, _)) =
                             self.signing_request_mapping_ex.remove(&request_id)
                         {
+                            if let Some(identity) = self.identity_of(&peer) {
+                                self.signer_scheduler.request_finished(
+                                    &identity,
+                                    !matches!(
+                                        signing_response_wrap_ex,
+                                        SigningResponseWrapEx::Failure(_)
+                                    ),
+                                );
+                            }
                             tracing::debug!(
                                 "Sending response {:?} to session",
                                 signing_response_wrap_ex
@@ -717,6 +1162,8 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
             )) => {
                 let request_instruction = request.clone();
                 let request = request.get_validator_identity();
+                let requester = VI::PublicKey::from_bytes(request.public_key.clone())
+                    .map(|public_key| public_key.to_identity());
                 if let Err(e) = self
                     .handle_vi_request(peer, request_id, request, false)
                     .await
@@ -732,11 +1179,16 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                     }
                     return Ok(());
                 }
+                // the identity was verified by handle_vi_request
+                let requester = requester.map_err(|e| anyhow!("Invalid public key: {}", e))?;
                 match request_instruction {
                     NodeToCoorRequest::DKGRequest {
                         crypto_type,
                         participants,
                         min_signers,
+                        deadline,
+                        metadata,
+                        idempotency_key,
                         ..
                     } => {
                         if self.auto_dkg.is_some() {
@@ -759,11 +1211,57 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                             }
                             return Ok(());
                         }
-                        let participants = participants
+                        let participants: Vec<(u16, VI::Identity)> = participants
                             .iter()
                             .enumerate()
                             .map(|(i, v)| ((i + 1) as u16, v.clone()))
                             .collect();
+                        let session_request = SessionRequest::Dkg {
+                            crypto_type,
+                            participants: participants.clone(),
+                            min_signers,
+                            deadline,
+                        };
+                        let Some(channel) = self.replay_idempotent(
+                            &requester,
+                            &idempotency_key,
+                            &session_request,
+                            SignatureFormat::Compact,
+                            channel,
+                        ) else {
+                            return Ok(());
+                        };
+                        let journal_id = match self.journal.start(
+                            requester.clone(),
+                            session_request.clone(),
+                            SignatureFormat::Compact,
+                        ) {
+                            Ok(journal_id) => journal_id,
+                            Err(e) => {
+                                tracing::error!("Error journaling DKG request: {}", e);
+                                if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                                    channel,
+                                    NodeToCoorResponse::Failure(e.to_string()),
+                                ) {
+                                    tracing::error!(
+                                        "Error sending failure response to node: {:?}",
+                                        e
+                                    );
+                                }
+                                return Ok(());
+                            }
+                        };
+                        if let Some(key) = idempotency_key {
+                            handle_err!(
+                                self.idempotency.start(
+                                    requester.clone(),
+                                    key,
+                                    &session_request,
+                                    journal_id
+                                ),
+                                "Error recording idempotency key: {}"
+                            );
+                        }
                         let (instruction_sender, instruction_receiver) = oneshot::channel();
                         let (node_response_sender, node_response_receiver) = oneshot::channel();
                         self.dkg_response_futures_for_node
@@ -772,6 +1270,10 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                             crypto_type,
                             participants,
                             min_signers,
+                            namespace: self.namespaces.namespace_of(&requester),
+                            requester: Some(requester),
+                            deadline,
+                            metadata,
                             pkid_response_oneshot: instruction_sender,
                         };
                         self.instruction_sender.send(instruction).unwrap();
@@ -779,14 +1281,17 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                             let result = instruction_receiver.await;
                             match result {
                                 Ok(pkid_result) => {
-                                    if let Err(e) =
-                                        node_response_sender.send((pkid_result, channel))
-                                    {
+                                    if let Err(e) = node_response_sender.send((
+                                        journal_id,
+                                        pkid_result,
+                                        channel,
+                                    )) {
                                         tracing::error!("Error sending response to node: {:?}", e);
                                     }
                                 }
                                 Err(e) => {
                                     if let Err(e) = node_response_sender.send((
+                                        journal_id,
                                         Err(SessionError::InstructionResponseError(e.to_string())),
                                         channel,
                                     )) {
@@ -804,32 +1309,98 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         pkid,
                         msg,
                         tweak_data,
+                        deadline,
+                        selection,
+                        idempotency_key,
+                        signature_format,
                         ..
                     } => {
                         // tracing::warn!("{}", hex::encode(msg.clone()));
-                        let (instruction_sender, instruction_receiver) = oneshot::channel();
+                        // refused before signing, a signature that cannot be encoded is useless
+                        if let Err(e) = check_signature_format(&pkid, signature_format) {
+                            if let Err(e) = self
+                                .swarm
+                                .behaviour_mut()
+                                .node2coor
+                                .send_response(channel, NodeToCoorResponse::Failure(e))
+                            {
+                                tracing::error!("Error sending failure response to node: {:?}", e);
+                            }
+                            return Ok(());
+                        }
+                        let session_request = SessionRequest::Signing {
+                            pkid: pkid.clone(),
+                            msg: msg.clone(),
+                            tweak_data: tweak_data.clone(),
+                            deadline,
+                            selection: selection.clone(),
+                        };
+                        // a retry must not count against the signing policy again
+                        let Some(channel) = self.replay_idempotent(
+                            &requester,
+                            &idempotency_key,
+                            &session_request,
+                            signature_format,
+                            channel,
+                        ) else {
+                            return Ok(());
+                        };
+                        let (journal_id, instruction, instruction_receiver) = match self
+                            .journal_signing(
+                                &requester,
+                                SignItem {
+                                    signature_format,
+                                    ..SignItem::new(pkid, msg, tweak_data)
+                                },
+                                deadline,
+                                selection,
+                            ) {
+                            Ok(journaled) => journaled,
+                            Err(e) => {
+                                if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                                    channel,
+                                    NodeToCoorResponse::Failure(e.to_string()),
+                                ) {
+                                    tracing::error!(
+                                        "Error sending failure response to node: {:?}",
+                                        e
+                                    );
+                                }
+                                return Ok(());
+                            }
+                        };
+                        if let Some(key) = idempotency_key {
+                            handle_err!(
+                                self.idempotency.start(
+                                    requester.clone(),
+                                    key,
+                                    &session_request,
+                                    journal_id
+                                ),
+                                "Error recording idempotency key: {}"
+                            );
+                        }
+                        self.instruction_sender.send(instruction).unwrap();
                         let (node_response_sender, node_response_receiver) = oneshot::channel();
                         self.signing_response_futures_for_node
                             .push(node_response_receiver);
-                        let instruction = Instruction::Sign {
-                            pkid,
-                            msg,
-                            tweak_data,
-                            signature_response_oneshot: instruction_sender,
-                        };
-                        self.instruction_sender.send(instruction).unwrap();
                         tokio::spawn(async move {
                             let result = instruction_receiver.await;
                             match result {
                                 Ok(signature_suite_info) => {
-                                    if let Err(e) =
-                                        node_response_sender.send((signature_suite_info, channel))
-                                    {
+                                    if let Err(e) = node_response_sender.send((
+                                        journal_id,
+                                        signature_format,
+                                        signature_suite_info,
+                                        channel,
+                                    )) {
                                         tracing::error!("Error sending response to node: {:?}", e);
                                     }
                                 }
                                 Err(e) => {
                                     if let Err(e) = node_response_sender.send((
+                                        journal_id,
+                                        signature_format,
                                         Err(SessionError::InstructionResponseError(e.to_string())),
                                         channel,
                                     )) {
@@ -843,6 +1414,89 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         });
                         return Ok(());
                     }
+                    NodeToCoorRequest::SigningBatchRequest {
+                        items,
+                        deadline,
+                        selection,
+                        ..
+                    } => {
+                        if items.len() > MAX_SIGN_BATCH {
+                            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                                channel,
+                                NodeToCoorResponse::Failure(format!(
+                                    "Too many items in batch, at most {} are allowed",
+                                    MAX_SIGN_BATCH
+                                )),
+                            ) {
+                                tracing::error!("Error sending failure response to node: {:?}", e);
+                            }
+                            return Ok(());
+                        }
+                        // an item that cannot be started fails alone; an item attached to a
+                        // session of its own batch would never get its result
+                        let mut idempotency_keys = HashSet::new();
+                        let items = items
+                            .into_iter()
+                            .map(|item| {
+                                let signature_format = item.signature_format;
+                                let item = match &item.idempotency_key {
+                                    Some(key) if !idempotency_keys.insert(key.clone()) => {
+                                        BatchItem::Done(Err(format!(
+                                            "idempotency key {} is used twice in the batch",
+                                            key
+                                        )))
+                                    }
+                                    _ => self
+                                        .start_batch_item(&requester, item, deadline, &selection),
+                                };
+                                (signature_format, item)
+                            })
+                            .collect::<Vec<_>>();
+                        let (node_response_sender, node_response_receiver) = oneshot::channel();
+                        self.signing_batch_response_futures_for_node
+                            .push(node_response_receiver);
+                        let batch_sessions = self.batch_sessions.clone();
+                        let instruction_sender = self.instruction_sender.clone();
+                        tokio::spawn(async move {
+                            let results = futures::future::join_all(items.into_iter().map(
+                                |(signature_format, item)| {
+                                    let batch_sessions = batch_sessions.clone();
+                                    let instruction_sender = instruction_sender.clone();
+                                    async move {
+                                        match item {
+                                            BatchItem::Started(journal_id, instruction, receiver) => {
+                                                // the slot is held until the session finished
+                                                let _permit = batch_sessions.acquire().await;
+                                                let result = match instruction_sender.send(instruction) {
+                                                    Ok(()) => receiver
+                                                        .await
+                                                        .unwrap_or_else(|e| {
+                                                            Err(SessionError::InstructionResponseError(
+                                                                e.to_string(),
+                                                            ))
+                                                        })
+                                                        .map_err(|e| e.to_string()),
+                                                    Err(e) => Err(e.to_string()),
+                                                };
+                                                (Some(journal_id), signature_format, result)
+                                            }
+                                            BatchItem::Attached(receiver) => (
+                                                None,
+                                                signature_format,
+                                                receiver.await.unwrap_or_else(|e| Err(e.to_string())),
+                                            ),
+                                            BatchItem::Done(result) => (None, signature_format, result),
+                                        }
+                                    }
+                                },
+                            ))
+                            .await;
+                            if let Err(e) = node_response_sender.send((results, channel)) {
+                                tracing::error!("Error sending response to node: {:?}", e);
+                            }
+                        });
+                        return Ok(());
+                    }
                     NodeToCoorRequest::LsPkRequest { .. } => {
                         let (session_response_sender, session_response_receiver) =
                             oneshot::channel();
@@ -850,6 +1504,7 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         self.lspk_response_futures_for_node
                             .push(node_response_receiver);
                         let instruction = Instruction::ListPkIds {
+                            scope: self.namespaces.scope_of(&requester),
                             list_pkids_response_oneshot: session_response_sender,
                         };
                         self.instruction_sender.send(instruction).unwrap();
@@ -876,28 +1531,218 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                             }
                         });
                     }
-                    NodeToCoorRequest::AutoDKGRequest { .. } => {
-                        if let Some(auto_dkg) = &self.auto_dkg {
-                            let auto_dkg_result = auto_dkg.read().await.clone();
+                    NodeToCoorRequest::AutoDKGRequest { app, .. } => {
+                        // the AutoDKG keys belong to the default namespace
+                        let auto_dkg = self
+                            .auto_dkg
+                            .clone()
+                            .filter(|_| self.namespaces.namespace_of(&requester).is_none());
+                        let Some(auto_dkg) = auto_dkg else {
                             if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
                                 channel,
                                 NodeToCoorResponse::AutoDKGResponse {
-                                    auto_dkg_result: Some(auto_dkg_result),
+                                    auto_dkg_result: None,
                                 },
                             ) {
                                 tracing::error!("Error sending response to node: {:?}", e);
                             }
-                        } else {
+                            return Ok(());
+                        };
+                        let app = app.unwrap_or(DEFAULT_APP.to_string());
+                        if let Err(e) = AutoDKG::<VI::Identity>::check_app(&app) {
+                            if let Err(e) = self
+                                .swarm
+                                .behaviour_mut()
+                                .node2coor
+                                .send_response(channel, NodeToCoorResponse::Failure(e))
+                            {
+                                tracing::error!("Error sending failure response to node: {:?}", e);
+                            }
+                            return Ok(());
+                        }
+                        let auto_dkg_result = auto_dkg.read().await.clone();
+                        // generated slots, and every slot while the default one is not
+                        // done, are answered with the current state
+                        if app == DEFAULT_APP
+                            || !auto_dkg_result.is_done()
+                            || auto_dkg_result.has_app(&app)
+                        {
                             if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
                                 channel,
                                 NodeToCoorResponse::AutoDKGResponse {
-                                    auto_dkg_result: None,
+                                    auto_dkg_result: Some(auto_dkg_result),
                                 },
                             ) {
                                 tracing::error!("Error sending response to node: {:?}", e);
                             }
+                            return Ok(());
+                        }
+                        let participants = match auto_dkg_result.slot_participants() {
+                            Ok(participants) => participants,
+                            Err(e) => {
+                                if let Err(e) = self
+                                    .swarm
+                                    .behaviour_mut()
+                                    .node2coor
+                                    .send_response(channel, NodeToCoorResponse::Failure(e))
+                                {
+                                    tracing::error!(
+                                        "Error sending failure response to node: {:?}",
+                                        e
+                                    );
+                                }
+                                return Ok(());
+                            }
+                        };
+                        let first = !self.auto_dkg_waiters.contains_key(&app);
+                        self.auto_dkg_waiters
+                            .entry(app.clone())
+                            .or_default()
+                            .push(channel);
+                        if first {
+                            tracing::info!("Coordinator starts DKGs of AutoDKG slot {}", app);
+                            let crypto_types = auto_dkg_result.missing_crypto_types(&app);
+                            self.start_auto_dkg_slot(&auto_dkg, app, crypto_types, &participants);
+                        }
+                    }
+                    NodeToCoorRequest::SessionOutcomeRequest { .. } => {
+                        let (journal_ids, outcomes): (Vec<_>, Vec<_>) =
+                            self.journal.undelivered(&requester).into_iter().unzip();
+                        if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                            channel,
+                            NodeToCoorResponse::SessionOutcomeResponse { outcomes },
+                        ) {
+                            tracing::error!("Error sending response to node: {:?}", e);
+                        } else {
+                            handle_err!(
+                                self.journal.delivered(&journal_ids),
+                                "Error journaling delivered outcomes: {}"
+                            );
+                        }
+                    }
+                    NodeToCoorRequest::SessionStatusRequest { .. } => {
+                        let report = self
+                            .session_monitor
+                            .report(&self.namespaces.scope_of(&requester));
+                        if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                            channel,
+                            NodeToCoorResponse::SessionStatusResponse { report },
+                        ) {
+                            tracing::error!("Error sending response to node: {:?}", e);
                         }
                     }
+                    NodeToCoorRequest::KeyMetadataRequest { pkid, update, .. } => {
+                        let (session_response_sender, session_response_receiver) =
+                            oneshot::channel();
+                        let (node_response_sender, node_response_receiver) = oneshot::channel();
+                        self.key_metadata_response_futures_for_node
+                            .push(node_response_receiver);
+                        let instruction = Instruction::UpdateKeyMetadata {
+                            pkid,
+                            update,
+                            scope: self.namespaces.scope_of(&requester),
+                            requester: Some(requester),
+                            metadata_response_oneshot: session_response_sender,
+                        };
+                        self.instruction_sender.send(instruction).unwrap();
+                        tokio::spawn(async move {
+                            let result = session_response_receiver.await.unwrap_or_else(|e| {
+                                Err(SessionError::InstructionResponseError(e.to_string()))
+                            });
+                            if let Err(e) = node_response_sender.send((result, channel)) {
+                                tracing::error!("Error sending response to node: {:?}", e);
+                            }
+                        });
+                        return Ok(());
+                    }
+                    NodeToCoorRequest::PublicKeyPackageRequest { pkid, .. } => {
+                        let (session_response_sender, session_response_receiver) =
+                            oneshot::channel();
+                        let (node_response_sender, node_response_receiver) = oneshot::channel();
+                        self.package_response_futures_for_node
+                            .push(node_response_receiver);
+                        let instruction = Instruction::PublicKeyPackageRequest {
+                            pkid,
+                            scope: self.namespaces.scope_of(&requester),
+                            package_response_oneshot: session_response_sender,
+                        };
+                        self.instruction_sender.send(instruction).unwrap();
+                        tokio::spawn(async move {
+                            let result = session_response_receiver.await.unwrap_or_else(|e| {
+                                Err(SessionError::InstructionResponseError(e.to_string()))
+                            });
+                            if let Err(e) = node_response_sender.send((result, channel)) {
+                                tracing::error!("Error sending response to node: {:?}", e);
+                            }
+                        });
+                        return Ok(());
+                    }
+                    NodeToCoorRequest::KeyStateRequest { pkid, state, .. } => {
+                        let (responder, response_receiver) = oneshot::channel();
+                        let (node_response_sender, node_response_receiver) = oneshot::channel();
+                        self.key_metadata_response_futures_for_node
+                            .push(node_response_receiver);
+                        let scope = self.namespaces.scope_of(&requester);
+                        self.change_key_state(pkid, state, Some(requester), scope, responder)
+                            .await;
+                        tokio::spawn(async move {
+                            let result = response_receiver.await.unwrap_or_else(|e| {
+                                Err(SessionError::InstructionResponseError(e.to_string()))
+                            });
+                            if let Err(e) = node_response_sender.send((result, channel)) {
+                                tracing::error!("Error sending response to node: {:?}", e);
+                            }
+                        });
+                        return Ok(());
+                    }
+                    NodeToCoorRequest::SignatureHistoryRequest { query, .. } => {
+                        // nodes only see the signatures made with the keys of their namespace
+                        let page = self
+                            .signature_store
+                            .query(&query, &self.namespaces.scope_of(&requester));
+                        if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                            channel,
+                            NodeToCoorResponse::SignatureHistoryResponse { page },
+                        ) {
+                            tracing::error!("Error sending response to node: {:?}", e);
+                        }
+                    }
+                    NodeToCoorRequest::CancelRequest { session_id, .. } => {
+                        let target = match CancelTarget::from_string(&session_id) {
+                            Ok(target) => target,
+                            Err(e) => {
+                                if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                                    channel,
+                                    NodeToCoorResponse::Failure(e.to_string()),
+                                ) {
+                                    tracing::error!(
+                                        "Error sending failure response to node: {:?}",
+                                        e
+                                    );
+                                }
+                                return Ok(());
+                            }
+                        };
+                        let (session_response_sender, session_response_receiver) =
+                            oneshot::channel();
+                        let (node_response_sender, node_response_receiver) = oneshot::channel();
+                        self.cancel_response_futures_for_node
+                            .push(node_response_receiver);
+                        let instruction = Instruction::Cancel {
+                            target,
+                            requester: Some(requester),
+                            cancel_response_oneshot: session_response_sender,
+                        };
+                        self.instruction_sender.send(instruction).unwrap();
+                        tokio::spawn(async move {
+                            let result = session_response_receiver.await.unwrap_or_else(|e| {
+                                Err(SessionError::InstructionResponseError(e.to_string()))
+                            });
+                            if let Err(e) = node_response_sender.send((target, result, channel)) {
+                                tracing::error!("Error sending response to node: {:?}", e);
+                            }
+                        });
+                    }
                     NodeToCoorRequest::PkTweakRequest {
                         pkid, tweak_data, ..
                     } => {
@@ -909,6 +1754,7 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         let instruction = Instruction::PkTweakRequest {
                             pkid,
                             tweak_data,
+                            scope: self.namespaces.scope_of(&requester),
                             pk_response_oneshot: session_response_sender,
                         };
                         self.instruction_sender.send(instruction).unwrap();
@@ -1235,26 +2081,701 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                     }
                 }
             },
+            SwarmEvent::Behaviour(CoorBehaviourEvent::Ping(ping::Event {
+                peer, result, ..
+            })) => {
+                if let Some(identity) = self.identity_of(&peer) {
+                    match result {
+                        Ok(rtt) => {
+                            tracing::debug!("Ping to {} is {}ms", peer, rtt.as_millis());
+                            self.signer_scheduler.ping(&identity, Some(rtt));
+                        }
+                        Err(e) => {
+                            tracing::debug!("Ping to {} failed: {}", peer, e);
+                            self.signer_scheduler.ping(&identity, None);
+                        }
+                    }
+                }
+            }
+            SwarmEvent::Behaviour(CoorBehaviourEvent::Coor2sig(
+                request_response::Event::OutboundFailure {
+                    peer,
+                    request_id,
+                    error,
+                    ..
+                },
+            )) => {
+                tracing::warn!(
+                    "Request {} to signer {} failed: {}",
+                    request_id,
+                    peer,
+                    error
+                );
+                // dropping the sender tells the waiting session the signer is gone
+                let signing_request = self.signing_request_mapping.remove(&request_id).is_some()
+                    || self
+                        .signing_request_mapping_ex
+                        .remove(&request_id)
+                        .is_some();
+                if signing_request {
+                    if let Some(identity) = self.identity_of(&peer) {
+                        self.signer_scheduler.request_finished(&identity, false);
+                    }
+                }
+                if let Some(pkid) = self.key_destroy_requests.remove(&request_id) {
+                    self.destroy_answered(&pkid, None, false).await;
+                }
+            }
             other => {
                 tracing::debug!("Unhandled {:?}", other);
             }
         }
         Ok(())
     }
+    // forgets a registered signer and closes its connection
+    fn disconnect_signer(&mut self, identity: &VI::Identity) -> bool {
+        let Some(validator) = self.valid_validators.remove(identity) else {
+            return false;
+        };
+        self.signer_scheduler.connection_changed(identity, false);
+        if self
+            .swarm
+            .disconnect_peer_id(validator.p2p_peer_id)
+            .is_err()
+        {
+            tracing::debug!("Signer {} was not connected", validator.p2p_peer_id);
+        }
+        tracing::info!(
+            "Coordinator drops the validator with identity: {}, total validators: {}",
+            identity.to_fmt_string(),
+            self.valid_validators.len()
+        );
+        true
+    }
+    // adds `signer` to the whitelist, started with the registered signers if none is
+    // enforced yet, and returns the reply to the operator
+    fn add_whitelisted(
+        &mut self,
+        signer: VI::Identity,
+        identity: &str,
+        label: Option<String>,
+    ) -> String {
+        if let Err(e) = SignerWhitelist::<VI::Identity>::check_label(&label) {
+            return format!("Error adding signer {}: {}\n", identity, e);
+        }
+        let mut msg = String::new();
+        if !self.signer_whitelist.is_started() {
+            let registered = self
+                .valid_validators
+                .keys()
+                .cloned()
+                .collect::<HashSet<_>>();
+            if let Err(e) = self.signer_whitelist.start(registered.clone()) {
+                return format!("Error starting the signer whitelist: {}\n", e);
+            }
+            for signer in registered.iter() {
+                self.audit_whitelist(signer.clone(), None, true);
+            }
+            msg = format!(
+                "Signer whitelist started with the {} registered signers, other signers may no longer register\n",
+                registered.len()
+            );
+        }
+        match self.signer_whitelist.add(signer.clone(), label.clone()) {
+            Ok(new) => {
+                self.audit_whitelist(signer, label, true);
+                if new {
+                    msg + &format!("Signer {} added to the whitelist\n", identity)
+                } else {
+                    msg + &format!("Signer {} relabeled\n", identity)
+                }
+            }
+            Err(e) => msg + &format!("Error adding signer {}: {}\n", identity, e),
+        }
+    }
+    // removes `signer` from the whitelist and disconnects it, returns the reply to the
+    // operator
+    async fn remove_whitelisted(&mut self, signer: VI::Identity, identity: &str) -> String {
+        if !self.signer_whitelist.is_started() {
+            return "No signer whitelist, every signer may register\n".to_string();
+        }
+        // the AutoDKG keys could neither sign nor get new slots without it
+        if let Some(auto_dkg) = &self.auto_dkg {
+            if auto_dkg.read().await.is_participant(&signer) {
+                return format!(
+                    "Signer {} holds the AutoDKG keys and cannot be removed\n",
+                    identity
+                );
+            }
+        }
+        match self.signer_whitelist.remove(&signer) {
+            Ok(true) => {
+                self.audit_whitelist(signer.clone(), None, false);
+                if self.disconnect_signer(&signer) {
+                    format!(
+                        "Signer {} removed from the whitelist and disconnected\n",
+                        identity
+                    )
+                } else {
+                    format!("Signer {} removed from the whitelist\n", identity)
+                }
+            }
+            Ok(false) => format!("Signer {} is not whitelisted\n", identity),
+            Err(e) => format!("Error removing signer {}: {}\n", identity, e),
+        }
+    }
+    fn audit_whitelist(&mut self, signer: VI::Identity, label: Option<String>, added: bool) {
+        handle_err!(
+            self.audit_log.append(&AuditEntry::Whitelist {
+                signer,
+                label,
+                added,
+                changed_at: utils::now(),
+            }),
+            "Error writing audit record: {}"
+        );
+    }
+    fn identity_of(&self, peer_id: &PeerId) -> Option<VI::Identity> {
+        self.valid_validators
+            .iter()
+            .find(|(_, validator)| validator.p2p_peer_id == *peer_id)
+            .map(|(identity, _)| identity.clone())
+    }
+    // must run before the journal forgets the session
+    fn audit_session(&mut self, journal_id: u64, result: &SessionResult<VI::Identity>) {
+        let Some((requester, request, requested_at)) = self.journal.started(journal_id) else {
+            return;
+        };
+        let dkg_session_id = match result {
+            SessionResult::Dkg(Ok(pkid)) => self.session_monitor.dkg_session_id(pkid),
+            _ => None,
+        };
+        handle_err!(
+            self.audit_log.append(&AuditEntry::Session {
+                requester,
+                request,
+                dkg_session_id,
+                requested_at,
+                finished_at: utils::now(),
+                result: result.clone(),
+            }),
+            "Error writing audit record: {}"
+        );
+    }
+    // the dkgs generating the keys of `crypto_types` for the AutoDKG slot `app`
+    fn auto_dkg_dkgs(
+        &self,
+        auto_dkg: &Arc<RwLock<AutoDKG<VI::Identity>>>,
+        app: &AppId,
+        crypto_types: Vec<CryptoType>,
+        participants: &[(u16, VI::Identity)],
+    ) -> impl std::future::Future<Output = ()> {
+        let dkgs = crypto_types
+            .into_iter()
+            .map(|crypto_type| {
+                run_auto_dkg(
+                    self.instruction_sender.clone(),
+                    auto_dkg.clone(),
+                    self.auto_dkg_store.clone(),
+                    app.clone(),
+                    crypto_type,
+                    participants.to_vec(),
+                )
+            })
+            .collect::<Vec<_>>();
+        async move {
+            futures::future::join_all(dkgs).await;
+        }
+    }
+    // generates the keys of `crypto_types` for the AutoDKG slot `app` of a done AutoDKG,
+    // the nodes waiting for the slot are answered once all of them are generated
+    fn start_auto_dkg_slot(
+        &mut self,
+        auto_dkg: &Arc<RwLock<AutoDKG<VI::Identity>>>,
+        app: AppId,
+        crypto_types: Vec<CryptoType>,
+        participants: &[(u16, VI::Identity)],
+    ) {
+        self.auto_dkg_waiters.entry(app.clone()).or_default();
+        let (slot_sender, slot_receiver) = oneshot::channel();
+        self.auto_dkg_slot_futures.push(slot_receiver);
+        let dkgs = self.auto_dkg_dkgs(auto_dkg, &app, crypto_types, participants);
+        tokio::spawn(async move {
+            dkgs.await;
+            if let Err(e) = slot_sender.send(app) {
+                tracing::error!("Error sending AutoDKG slot: {:?}", e);
+            }
+        });
+    }
+    // restarts the dkgs of the AutoDKG slots the coordinator stopped in the middle of
+    async fn resume_auto_dkg(&mut self) {
+        let Some(auto_dkg) = self.auto_dkg.clone() else {
+            return;
+        };
+        let auto_dkg_value = auto_dkg.read().await.clone();
+        let participants = auto_dkg_value.participants().to_vec();
+        for (app, crypto_types) in auto_dkg_value.pending_slots() {
+            if participants.is_empty() {
+                tracing::warn!(
+                    "Cannot resume AutoDKG slot {}, its participants are unknown",
+                    app
+                );
+                continue;
+            }
+            tracing::info!(
+                "Coordinator resumes DKGs of AutoDKG slot {}: {:?}",
+                app,
+                crypto_types
+            );
+            if app == DEFAULT_APP {
+                tokio::spawn(self.auto_dkg_dkgs(&auto_dkg, &app, crypto_types, &participants));
+            } else {
+                self.start_auto_dkg_slot(&auto_dkg, app, crypto_types, &participants);
+            }
+        }
+    }
+    // answers the nodes waiting for the keys of the AutoDKG slot `app`
+    async fn answer_auto_dkg_waiters(&mut self, app: &AppId) {
+        let Some(auto_dkg) = &self.auto_dkg else {
+            return;
+        };
+        let auto_dkg_result = auto_dkg.read().await.clone();
+        for channel in self.auto_dkg_waiters.remove(app).unwrap_or_default() {
+            if let Err(e) = self.swarm.behaviour_mut().node2coor.send_response(
+                channel,
+                NodeToCoorResponse::AutoDKGResponse {
+                    auto_dkg_result: Some(auto_dkg_result.clone()),
+                },
+            ) {
+                tracing::error!("Error sending response to node: {:?}", e);
+            }
+        }
+    }
+    // checks the signing policy and namespace quotas and journals the request, the caller
+    // hands the instruction to the session manager
+    fn journal_signing(
+        &mut self,
+        requester: &VI::Identity,
+        item: SignItem,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VI::Identity>>,
+    ) -> Result<
+        (
+            u64,
+            Instruction<VI::Identity>,
+            SignatureReceiver<VI::Identity>,
+        ),
+        SessionError,
+    > {
+        let SignItem {
+            pkid,
+            msg,
+            tweak_data,
+            signature_format,
+            ..
+        } = item;
+        if self.key_destroyer.is_pending(&pkid) {
+            return Err(SessionError::InvalidRequest(format!(
+                "key {} is being destroyed",
+                pkid
+            )));
+        }
+        if let Err(e) = self
+            .signing_policy
+            .check_sign(requester, &pkid, &msg, &tweak_data)
+        {
+            tracing::warn!("Signing request denied: {}", e);
+            return Err(e);
+        }
+        if let Err(e) = self.namespaces.check_sign(requester) {
+            tracing::warn!("Signing request denied: {}", e);
+            return Err(e);
+        }
+        // a request denied by either check counts against neither
+        handle_err!(
+            self.signing_policy.record_sign(requester, &pkid),
+            "Error recording signing usage: {}"
+        );
+        self.namespaces.record_sign(requester);
+        let journal_id = self
+            .journal
+            .start(
+                requester.clone(),
+                SessionRequest::Signing {
+                    pkid: pkid.clone(),
+                    msg: msg.clone(),
+                    tweak_data: tweak_data.clone(),
+                    deadline,
+                    selection: selection.clone(),
+                },
+                signature_format,
+            )
+            .map_err(|e| {
+                tracing::error!("Error journaling signing request: {}", e);
+                e
+            })?;
+        self.signing_namespaces
+            .insert(journal_id, self.namespaces.namespace_of(requester));
+        let (instruction_sender, instruction_receiver) = oneshot::channel();
+        let instruction = Instruction::Sign {
+            pkid,
+            msg,
+            tweak_data,
+            requester: Some(requester.clone()),
+            deadline,
+            selection,
+            scope: self.namespaces.scope_of(requester),
+            signature_response_oneshot: instruction_sender,
+        };
+        Ok((journal_id, instruction, instruction_receiver))
+    }
+    // an item of a batch goes through the same checks and idempotency keys as a single
+    // signing request
+    fn start_batch_item(
+        &mut self,
+        requester: &VI::Identity,
+        item: SignItem,
+        deadline: Option<Deadline>,
+        selection: &Option<SignerSelection<VI::Identity>>,
+    ) -> BatchItem<VI::Identity> {
+        if let Err(e) = check_signature_format(&item.pkid, item.signature_format) {
+            return BatchItem::Done(Err(e));
+        }
+        let session_request = SessionRequest::Signing {
+            pkid: item.pkid.clone(),
+            msg: item.msg.clone(),
+            tweak_data: item.tweak_data.clone(),
+            deadline,
+            selection: selection.clone(),
+        };
+        if let Some(key) = &item.idempotency_key {
+            match self.idempotency.lookup(requester, key, &session_request) {
+                Ok(Replay::New) => {}
+                Ok(Replay::InFlight(journal_id)) => {
+                    tracing::info!(
+                        "Attaching batch item with idempotency key {} to session {}",
+                        key,
+                        journal_id
+                    );
+                    let (sender, receiver) = oneshot::channel();
+                    self.idempotent_waiters
+                        .entry(journal_id)
+                        .or_default()
+                        .push(IdempotentWaiter::BatchItem(sender));
+                    return BatchItem::Attached(receiver);
+                }
+                Ok(Replay::Finished(SessionResult::Signing(result))) => {
+                    tracing::info!("Replaying result for idempotency key {}", key);
+                    return BatchItem::Done(result);
+                }
+                Ok(Replay::Finished(SessionResult::Dkg(_))) | Ok(Replay::Mismatch) => {
+                    return BatchItem::Done(Err(format!(
+                        "idempotency key {} was used for a different request",
+                        key
+                    )));
+                }
+                Err(e) => return BatchItem::Done(Err(e.to_string())),
+            }
+        }
+        let idempotency_key = item.idempotency_key.clone();
+        match self.journal_signing(requester, item, deadline, selection.clone()) {
+            Ok((journal_id, instruction, receiver)) => {
+                if let Some(key) = idempotency_key {
+                    handle_err!(
+                        self.idempotency.start(
+                            requester.clone(),
+                            key,
+                            &session_request,
+                            journal_id
+                        ),
+                        "Error recording idempotency key: {}"
+                    );
+                }
+                BatchItem::Started(journal_id, instruction, receiver)
+            }
+            Err(e) => BatchItem::Done(Err(e.to_string())),
+        }
+    }
+    // answers a request whose idempotency key is known from an earlier one, gives the
+    // channel back if a session has to be started for it
+    fn replay_idempotent(
+        &mut self,
+        requester: &VI::Identity,
+        key: &Option<String>,
+        request: &SessionRequest<VI::Identity>,
+        signature_format: SignatureFormat,
+        channel: ResponseChannel<NodeToCoorResponse<VI::Identity>>,
+    ) -> Option<ResponseChannel<NodeToCoorResponse<VI::Identity>>> {
+        let Some(key) = key else {
+            return Some(channel);
+        };
+        let response = match self.idempotency.lookup(requester, key, request) {
+            Ok(Replay::New) => return Some(channel),
+            Ok(Replay::InFlight(journal_id)) => {
+                tracing::info!(
+                    "Attaching request with idempotency key {} to session {}",
+                    key,
+                    journal_id
+                );
+                self.idempotent_waiters
+                    .entry(journal_id)
+                    .or_default()
+                    .push(IdempotentWaiter::Request(channel, signature_format));
+                return None;
+            }
+            Ok(Replay::Finished(result)) => {
+                tracing::info!("Replaying result for idempotency key {}", key);
+                session_result_response(result, signature_format)
+            }
+            Ok(Replay::Mismatch) => NodeToCoorResponse::Failure(format!(
+                "idempotency key {} was used for a different request",
+                key
+            )),
+            Err(e) => NodeToCoorResponse::Failure(e.to_string()),
+        };
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_response(channel, response)
+        {
+            tracing::error!("Error sending replayed response to node: {:?}", e);
+        }
+        None
+    }
+    // returns whether one of the retries received the result
+    fn answer_idempotent_waiters(
+        &mut self,
+        journal_id: u64,
+        result: &SessionResult<VI::Identity>,
+    ) -> bool {
+        let mut delivered = false;
+        for waiter in self
+            .idempotent_waiters
+            .remove(&journal_id)
+            .unwrap_or_default()
+        {
+            match waiter {
+                IdempotentWaiter::Request(channel, signature_format) => {
+                    match self.swarm.behaviour_mut().node2coor.send_response(
+                        channel,
+                        session_result_response(result.clone(), signature_format),
+                    ) {
+                        Ok(()) => delivered = true,
+                        Err(e) => {
+                            tracing::error!("Error sending response to retried request: {:?}", e)
+                        }
+                    }
+                }
+                // the batch response is sent later and may still be lost
+                IdempotentWaiter::BatchItem(sender) => {
+                    if let SessionResult::Signing(result) = result {
+                        let _ = sender.send(result.clone());
+                    }
+                }
+            }
+        }
+        delivered
+    }
+    fn store_signature(&mut self, journal_id: u64, result: &SessionResult<VI::Identity>) {
+        let namespace = self.signing_namespaces.remove(&journal_id);
+        let SessionResult::Signing(Ok(signature_suite_info)) = result else {
+            return;
+        };
+        let Some((requester, _, _)) = self.journal.started(journal_id) else {
+            return;
+        };
+        let namespace = namespace.unwrap_or_else(|| self.namespaces.namespace_of(&requester));
+        // kept as the node asked for it, the native signature is part of the record anyway
+        let signature_suite_info = signature_suite_info
+            .clone()
+            .with_format(self.journal.signature_format(journal_id))
+            .unwrap_or_else(|_| signature_suite_info.clone());
+        handle_err!(
+            self.signature_store
+                .insert(requester, namespace, signature_suite_info),
+            "Error storing signature: {}"
+        );
+    }
     pub(crate) fn send_request_to_signer(
         &mut self,
         peer_id: &VI::Identity,
         request: CoorToSigRequest<VI::Identity>,
-    ) -> Result<(), String> {
+    ) -> Result<OutboundRequestId, String> {
         let validator_peer = self
             .valid_validators
             .get(&peer_id)
             .ok_or(format!("Invalid participant: {:?}", peer_id))?;
-        self.swarm
+        Ok(self
+            .swarm
             .behaviour_mut()
             .coor2sig
-            .send_request(&validator_peer.p2p_peer_id, request);
-        Ok(())
+            .send_request(&validator_peer.p2p_peer_id, request))
+    }
+    // moves `pkid` to `state` and tells its signers; a destroy is answered on `responder`
+    // once enough signers confirmed erasing their share
+    async fn change_key_state(
+        &mut self,
+        pkid: PkId,
+        state: KeyState,
+        requester: Option<VI::Identity>,
+        scope: KeyScope,
+        responder: KeyStateResponder<VI::Identity>,
+    ) {
+        if self.key_destroyer.is_pending(&pkid) {
+            let error = SessionError::InvalidRequest(format!("key {} is being destroyed", pkid));
+            if let Err(e) = responder.send(Err(error)) {
+                tracing::error!("Error sending key state response: {:?}", e);
+            }
+            return;
+        }
+        let (sender, receiver) = oneshot::channel();
+        self.instruction_sender
+            .send(Instruction::SetKeyState {
+                pkid: pkid.clone(),
+                state,
+                requester: requester.clone(),
+                scope,
+                state_response_oneshot: sender,
+            })
+            .unwrap();
+        let (from, metadata) = match receiver.await {
+            Ok(Ok(change)) => change,
+            Ok(Err(e)) => {
+                if let Err(e) = responder.send(Err(e)) {
+                    tracing::error!("Error sending key state response: {:?}", e);
+                }
+                return;
+            }
+            Err(e) => {
+                let error = SessionError::InstructionResponseError(e.to_string());
+                if let Err(e) = responder.send(Err(error)) {
+                    tracing::error!("Error sending key state response: {:?}", e);
+                }
+                return;
+            }
+        };
+        tracing::info!("Key {} goes from {} to {}", pkid, from, state);
+        if state == KeyState::Destroyed {
+            self.key_destroyer
+                .start(pkid.clone(), from, &metadata, requester, responder);
+        } else {
+            self.audit_key_state(&pkid, from, state, requester, Vec::new());
+            if let Err(e) = responder.send(Ok(metadata.clone())) {
+                tracing::error!("Error sending key state response: {:?}", e);
+            }
+        }
+        for participant in metadata.participants.iter() {
+            let request = CoorToSigRequest::KeyState {
+                pkid: pkid.clone(),
+                state,
+            };
+            match self.send_request_to_signer(participant, request) {
+                Ok(request_id) if state == KeyState::Destroyed => {
+                    self.key_destroy_requests.insert(request_id, pkid.clone());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Error sending state of key {} to signer: {}", pkid, e);
+                    if state == KeyState::Destroyed {
+                        self.destroy_answered(&pkid, None, false).await;
+                    }
+                }
+            }
+        }
+    }
+    async fn destroy_answered(&mut self, pkid: &PkId, signer: Option<VI::Identity>, erased: bool) {
+        let Some(outcome) = self.key_destroyer.answered(pkid, signer, erased) else {
+            return;
+        };
+        self.key_destroy_requests.retain(|_, p| p != pkid);
+        let (responder, result) = match outcome {
+            DestroyOutcome::Confirmed(pending) => {
+                let (sender, receiver) = oneshot::channel();
+                self.instruction_sender
+                    .send(Instruction::DestroyKey {
+                        pkid: pkid.clone(),
+                        destroy_response_oneshot: sender,
+                    })
+                    .unwrap();
+                let result = receiver
+                    .await
+                    .unwrap_or_else(|e| Err(SessionError::InstructionResponseError(e.to_string())));
+                if result.is_ok() {
+                    self.audit_key_state(
+                        pkid,
+                        pending.from,
+                        KeyState::Destroyed,
+                        pending.requester,
+                        pending.confirmed_by,
+                    );
+                }
+                (pending.responder, result)
+            }
+            DestroyOutcome::Failed(pending) => {
+                tracing::warn!(
+                    "Destroying key {} failed, {} signers confirmed erasing their share",
+                    pkid,
+                    pending.confirmed_by.len()
+                );
+                // the signers that erased their share cannot take it back, the key stays
+                // disabled, or retired, for the operator to destroy again
+                let left = match pending.from {
+                    KeyState::Active => KeyState::Disabled,
+                    from => from,
+                };
+                self.audit_key_state(
+                    pkid,
+                    pending.from,
+                    left,
+                    pending.requester.clone(),
+                    pending.confirmed_by.clone(),
+                );
+                let error = SessionError::CoordinatorSessionError(format!(
+                    "only {} signers confirmed erasing their share of {}",
+                    pending.confirmed_by.len(),
+                    pkid
+                ));
+                (pending.responder, Err(error))
+            }
+        };
+        if let Err(e) = responder.send(result) {
+            tracing::error!("Error sending destroy response: {:?}", e);
+        }
+    }
+    fn audit_key_state(
+        &mut self,
+        pkid: &PkId,
+        from: KeyState,
+        to: KeyState,
+        requester: Option<VI::Identity>,
+        confirmed_by: Vec<VI::Identity>,
+    ) {
+        handle_err!(
+            self.audit_log.append(&AuditEntry::KeyLifecycle {
+                pkid: pkid.clone(),
+                from,
+                to,
+                requester,
+                confirmed_by,
+                changed_at: utils::now(),
+            }),
+            "Error writing audit record: {}"
+        );
+    }
+    // tell the participants of a cancelled session to drop their state
+    pub(crate) fn notify_cancel(&mut self, target: CancelTarget, participants: Vec<VI::Identity>) {
+        for participant in participants.iter() {
+            if let Err(e) =
+                self.send_request_to_signer(participant, CoorToSigRequest::Cancel(target))
+            {
+                tracing::warn!(
+                    "Error sending cancel of {} to signer: {}",
+                    target.to_string(),
+                    e
+                );
+            }
+        }
     }
     // handle validator identity request
     pub(crate) async fn handle_vi_request(
@@ -1278,17 +2799,15 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
         let public_key = VI::PublicKey::from_bytes(public_key)
             .map_err(|e| format!("Invalid public key: {}", e))?;
         let validator_peer = public_key.to_identity();
-        if let Some(whitelist) = &self.signer_whitelist {
-            if !whitelist.contains(&validator_peer) && verify_whitelist {
-                tracing::warn!(
-                    "Validator peerid {} is not in whitelist",
-                    validator_peer.to_fmt_string()
-                );
-                return Err(format!(
-                    "Validator peerid {} is not in whitelist",
-                    validator_peer.to_fmt_string()
-                ));
-            }
+        if !self.signer_whitelist.allows(&validator_peer) && verify_whitelist {
+            tracing::warn!(
+                "Validator peerid {} is not in whitelist",
+                validator_peer.to_fmt_string()
+            );
+            return Err(format!(
+                "Validator peerid {} is not in whitelist",
+                validator_peer.to_fmt_string()
+            ));
         }
         let hash = list_hash(&[
             "register".as_bytes(),
@@ -1345,66 +2864,27 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
             }
             // update auto dkg
             if let Some(auto_dkg) = &self.auto_dkg {
-                let min_signers = auto_dkg.read().await.min_signers;
-                if let Some(participants) = auto_dkg
-                    .write()
-                    .await
-                    .register_signer(validator_peer.clone())
-                {
-                    // note you cannot use read() here, because it will block the thread
+                let mut auto_dkg_value = auto_dkg.write().await;
+                if let Some(participants) = auto_dkg_value.register_signer(validator_peer.clone()) {
+                    handle_err!(
+                        self.auto_dkg_store.lock().unwrap().save(&*auto_dkg_value),
+                        "Error saving AutoDKG state: {}"
+                    );
+                    let crypto_types = auto_dkg_value.missing_crypto_types(DEFAULT_APP);
                     tracing::info!(
-                        "Coordinator starts auto DKG with {} participants and min signers {}",
+                        "Coordinator starts auto DKG of {:?} with {} participants and min signers {}",
+                        crypto_types,
                         participants.len(),
-                        min_signers
+                        auto_dkg_value.min_signers
                     );
-                    for crypto_type in <CryptoType as strum::IntoEnumIterator>::iter() {
-                        let manger_instruction_sender = self.instruction_sender.clone();
-                        let participants = participants.clone();
-                        let auto_dkg = auto_dkg.clone();
-                        let base_path = self.base_path.clone();
-                        tokio::spawn(async move {
-                            loop {
-                                let (instruction_sender, instruction_receiver) = oneshot::channel();
-                                let instruction = Instruction::NewKey {
-                                    crypto_type,
-                                    participants: participants.clone(),
-                                    min_signers: auto_dkg.read().await.min_signers,
-                                    pkid_response_oneshot: instruction_sender,
-                                };
-                                manger_instruction_sender.send(instruction).unwrap();
-                                let result = instruction_receiver.await;
-                                match result {
-                                    Ok(Ok(pkid)) => {
-                                        if let (true, auto_dkg_value) = auto_dkg
-                                            .write()
-                                            .await
-                                            .update_new_dkg_result(crypto_type, pkid)
-                                        {
-                                            tracing::info!("All DKGs are done");
-                                            // save auto_dkg to base_path
-                                            let auto_dkg_path = base_path.join("auto_dkg.json");
-                                            let auto_dkg_file =
-                                                File::create(auto_dkg_path).unwrap();
-                                            let auto_dkg_writer = BufWriter::new(auto_dkg_file);
-                                            serde_json::to_writer(auto_dkg_writer, &auto_dkg_value)
-                                                .unwrap();
-                                        }
-                                        break;
-                                    }
-                                    Ok(Err(e)) => {
-                                        tracing::error!("Error starting DKG: {}", e);
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Error starting DKG: {}", e);
-                                    }
-                                }
-                                tokio::time::sleep(Duration::from_secs(
-                                    Settings::global().session.state_channel_retry_interval,
-                                ))
-                                .await;
-                            }
-                        });
-                    }
+                    // the dkgs take the lock to record their keys
+                    drop(auto_dkg_value);
+                    tokio::spawn(self.auto_dkg_dkgs(
+                        auto_dkg,
+                        &DEFAULT_APP.to_string(),
+                        crypto_types,
+                        &participants,
+                    ));
                 }
             }
             return Ok(());
@@ -1465,6 +2945,123 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                             reader.get_mut().write_all(b"\n").await?;
                         }
                     }
+                    Command::ReloadPolicy => {
+                        let msg = match self.signing_policy.reload() {
+                            Ok(Some(rules)) => {
+                                format!("Signing policy reloaded, {} rules\n", rules)
+                            }
+                            Ok(None) => {
+                                "No signing policy file, all requests allowed\n".to_string()
+                            }
+                            Err(e) => format!("Error reloading signing policy: {}\n", e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::ReloadNamespaces => {
+                        let msg = match self.namespaces.reload() {
+                            Ok(namespaces) => {
+                                format!("Namespaces reloaded, {} namespaces\n", namespaces)
+                            }
+                            Err(e) => format!("Error reloading namespaces: {}\n", e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::WhitelistList => {
+                        let msg = if self.signer_whitelist.is_started() {
+                            self.signer_whitelist
+                                .list()
+                                .into_iter()
+                                .map(|(identity, label)| {
+                                    format!(
+                                        "{}\t{}\t{}\n",
+                                        identity.to_fmt_string(),
+                                        label.as_deref().unwrap_or("-"),
+                                        if self.valid_validators.contains_key(&identity) {
+                                            "registered"
+                                        } else {
+                                            "not registered"
+                                        }
+                                    )
+                                })
+                                .collect::<String>()
+                        } else {
+                            "No signer whitelist, every signer may register\n".to_string()
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::WhitelistAdd(identity, label) => {
+                        let msg = match VI::Identity::from_fmt_str(&identity) {
+                            Ok(signer) => self.add_whitelisted(signer, &identity, label),
+                            Err(e) => format!("Invalid signer {}: {}\n", identity, e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::WhitelistRemove(identity) => {
+                        let msg = match VI::Identity::from_fmt_str(&identity) {
+                            Ok(signer) => self.remove_whitelisted(signer, &identity).await,
+                            Err(e) => format!("Invalid signer {}: {}\n", identity, e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::SessionStatus => {
+                        let report = serde_json::to_string_pretty(
+                            &self.session_monitor.report(&KeyScope::All),
+                        )?;
+                        writer.write_all(report.as_bytes()).await?;
+                        writer.write_all(b"\n").await?;
+                    }
+                    Command::Cancel(session_id) => {
+                        let result = match CancelTarget::from_string(&session_id) {
+                            Ok(target) => {
+                                let (sender, receiver) = oneshot::channel();
+                                self.instruction_sender
+                                    .send(Instruction::Cancel {
+                                        target,
+                                        requester: None,
+                                        cancel_response_oneshot: sender,
+                                    })
+                                    .unwrap();
+                                match receiver.await {
+                                    Ok(Ok(participants)) => {
+                                        self.notify_cancel(target, participants);
+                                        Ok(())
+                                    }
+                                    Ok(Err(e)) => Err(e.to_string()),
+                                    Err(e) => Err(e.to_string()),
+                                }
+                            }
+                            Err(e) => Err(e.to_string()),
+                        };
+                        let msg = match result {
+                            Ok(()) => format!("Cancelled {}\n", session_id),
+                            Err(e) => format!("Error cancelling {}: {}\n", session_id, e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::KeyState(pkid, state) => {
+                        let pkid = match hex::decode(&pkid) {
+                            Ok(pkid) => PkId::new(pkid),
+                            Err(e) => {
+                                let msg = format!("Invalid pkid {}: {}\n", pkid, e);
+                                reader.get_mut().write_all(msg.as_bytes()).await?;
+                                return Ok(());
+                            }
+                        };
+                        let (responder, receiver) = oneshot::channel();
+                        self.change_key_state(pkid.clone(), state, None, KeyScope::All, responder)
+                            .await;
+                        // a destroy waits for the signers, answer from outside the event loop
+                        tokio::spawn(async move {
+                            let msg = match receiver.await {
+                                Ok(Ok(metadata)) => format!("Key {} is {}\n", pkid, metadata.state),
+                                Ok(Err(e)) => format!("Error changing state of {}: {}\n", pkid, e),
+                                Err(e) => format!("Error changing state of {}: {}\n", pkid, e),
+                            };
+                            if let Err(e) = reader.get_mut().write_all(msg.as_bytes()).await {
+                                tracing::error!("Error answering key state command: {}", e);
+                            }
+                        });
+                    }
                     Command::Unknown(cmd) => {
                         let msg = format!("Unknown command: {}\n", cmd);
                         reader.get_mut().write_all(msg.as_bytes()).await?;
@@ -1497,6 +3094,10 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                                 pkid,
                                 msg: msg.as_bytes().to_vec(),
                                 tweak_data: tweak_data.map(|s| s.as_bytes().to_vec()),
+                                requester: None,
+                                deadline: None,
+                                selection: None,
+                                scope: KeyScope::All,
                                 signature_response_oneshot: sender,
                             })
                             .unwrap();
@@ -1538,6 +3139,10 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                                     pkid: pkid.clone(),
                                     msg: msg.clone(),
                                     tweak_data: tweak_data.clone(),
+                                    requester: None,
+                                    deadline: None,
+                                    selection: None,
+                                    scope: KeyScope::All,
                                     signature_response_oneshot: sender,
                                 })
                                 .unwrap();
@@ -1586,15 +3191,29 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                         let (sender, receiver) = oneshot::channel();
                         self.instruction_sender
                             .send(Instruction::ListPkIds {
+                                scope: KeyScope::All,
                                 list_pkids_response_oneshot: sender,
                             })
                             .unwrap();
-                        let pkids = receiver.await.unwrap();
-                        for (crypto_type, pkids) in pkids {
-                            reader
-                                .get_mut()
-                                .write_all(format!("{:?}: {:?}\n", crypto_type, pkids).as_bytes())
-                                .await?;
+                        let keys = receiver.await.unwrap();
+                        for (crypto_type, keys) in keys {
+                            for key in keys {
+                                reader
+                                    .get_mut()
+                                    .write_all(
+                                        format!(
+                                            "{:?}: {} label: {} tags: {:?} state: {} namespace: {}\n",
+                                            crypto_type,
+                                            key.pkid,
+                                            key.metadata.label.as_deref().unwrap_or("-"),
+                                            key.metadata.tags,
+                                            key.metadata.state,
+                                            key.metadata.namespace.as_deref().unwrap_or("-")
+                                        )
+                                        .as_bytes(),
+                                    )
+                                    .await?;
+                            }
                         }
                     }
                     Command::StartDkg(min_signers, crypto_type) => {
@@ -1614,6 +3233,10 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
                                 min_signers,
                                 crypto_type,
                                 participants,
+                                requester: None,
+                                deadline: None,
+                                metadata: KeyMetadataUpdate::default(),
+                                namespace: None,
                                 pkid_response_oneshot: sender,
                             })
                             .unwrap();
@@ -1663,3 +3286,60 @@ impl<VI: ValidatorIdentity> Coordinator<VI> {
         return Ok(listener);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_resumed_signing_keeps_requester() {
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let dir = std::env::temp_dir().join(format!("resume_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("namespaces.json");
+        let file = serde_json::json!({
+            "namespaces": [{ "name": "wallet", "nodes": [alice] }]
+        });
+        std::fs::write(&path, file.to_string()).unwrap();
+        let namespaces = Namespaces::<PeerId>::load(path).unwrap();
+
+        let keystore = Arc::new(Keystore::new(b"resume", None).unwrap());
+        let signing = SessionRequest::<PeerId>::Signing {
+            pkid: PkId::new(vec![1; 33]),
+            msg: b"msg".to_vec(),
+            tweak_data: None,
+            deadline: None,
+            selection: None,
+        };
+        let mut journal = SessionJournal::open(keystore.clone(), &dir, 3600).unwrap();
+        let id = journal
+            .start(alice, signing.clone(), SignatureFormat::Compact)
+            .unwrap();
+        drop(journal);
+
+        // after a restart the session is resumed as alice, within her namespace
+        let journal = SessionJournal::<PeerId>::open(keystore, &dir, 3600).unwrap();
+        let (pending_id, requester, request) = journal.pending().remove(0);
+        assert_eq!((pending_id, requester), (id, alice));
+        let (sender, _receiver) = oneshot::channel();
+        match resumed_signing(&namespaces, requester, request, sender) {
+            Some(Instruction::Sign {
+                requester, scope, ..
+            }) => {
+                assert_eq!(requester, Some(alice));
+                assert_eq!(scope, KeyScope::Namespace(Some("wallet".to_string())));
+                assert!(!scope.covers(&None));
+            }
+            _ => panic!("expected a signing instruction"),
+        }
+        let (sender, _receiver) = oneshot::channel();
+        match resumed_signing(&namespaces, bob, signing, sender) {
+            Some(Instruction::Sign { scope, .. }) => {
+                assert_eq!(scope, KeyScope::Namespace(None))
+            }
+            _ => panic!("expected a signing instruction"),
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+//! The AutoDKG state, kept encrypted in the coordinator keystore so that a restarted
+//! coordinator picks up where it stopped instead of waiting for the signers to register
+//! again. A plaintext `auto_dkg.json` left by an older coordinator is imported once; the
+//! file itself is left in place.
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::ValidatorIdentityIdentity;
+use crate::keystore::{Keystore, KeystoreError, KeystoreManagement};
+use crate::types::error::SessionError;
+use crate::types::AutoDKG;
+
+pub(crate) struct AutoDKGStore {
+    keystore_management: KeystoreManagement,
+}
+impl AutoDKGStore {
+    /// Opens the store under `dir`, returning the saved state, or the one of
+    /// `legacy_path` if nothing was saved yet.
+    pub(crate) fn open<VII>(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+        legacy_path: impl AsRef<Path>,
+    ) -> Result<(Self, Option<AutoDKG<VII>>), SessionError>
+    where
+        VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>,
+    {
+        let (keystore_management, data) =
+            KeystoreManagement::new(keystore, dir.as_ref().join("auto_dkg"))?;
+        let mut store = Self {
+            keystore_management,
+        };
+        let auto_dkg = match data {
+            Some(data) => Some(
+                serde_json::from_slice(&data)
+                    .map_err(|e| SessionError::DeserializationError(e.to_string()))?,
+            ),
+            None if legacy_path.as_ref().exists() => {
+                let data = std::fs::read(legacy_path.as_ref()).map_err(KeystoreError::from)?;
+                let auto_dkg: Option<AutoDKG<VII>> = serde_json::from_slice(&data)
+                    .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+                if let Some(auto_dkg) = &auto_dkg {
+                    store.save(auto_dkg)?;
+                }
+                auto_dkg
+            }
+            None => None,
+        };
+        Ok((store, auto_dkg))
+    }
+    pub(crate) fn save<VII>(&mut self, auto_dkg: &AutoDKG<VII>) -> Result<(), SessionError>
+    where
+        VII: ValidatorIdentityIdentity + Serialize,
+    {
+        let data = serde_json::to_vec(auto_dkg)
+            .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+        Ok(self.keystore_management.write(&data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::CryptoType;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_auto_dkg_store() {
+        let keystore = Arc::new(Keystore::new(b"auto_dkg", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("auto_dkg_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let legacy_path = dir.join("auto_dkg.json");
+        let signer = PeerId::random();
+
+        let (mut store, auto_dkg) =
+            AutoDKGStore::open::<PeerId>(keystore.clone(), &dir, &legacy_path).unwrap();
+        assert!(auto_dkg.is_none());
+        let mut auto_dkg = AutoDKG::new(1, [signer].into(), vec![CryptoType::Ed25519]);
+        auto_dkg.register_signer(signer).unwrap();
+        store.save(&auto_dkg).unwrap();
+        let (_, saved) =
+            AutoDKGStore::open::<PeerId>(keystore.clone(), &dir, &legacy_path).unwrap();
+        assert_eq!(saved.unwrap().participants(), auto_dkg.participants());
+
+        // a legacy file is only read while the store is empty
+        let other = AutoDKG::new(1, [PeerId::random()].into(), vec![]);
+        std::fs::write(&legacy_path, serde_json::to_vec(&Some(&other)).unwrap()).unwrap();
+        let (_, saved) =
+            AutoDKGStore::open::<PeerId>(keystore.clone(), &dir, &legacy_path).unwrap();
+        assert_eq!(saved.unwrap().participants(), auto_dkg.participants());
+        let legacy_dir = dir.join("legacy");
+        let (_, imported) =
+            AutoDKGStore::open::<PeerId>(keystore.clone(), &legacy_dir, &legacy_path).unwrap();
+        assert_eq!(imported.unwrap().crypto_types(), other.crypto_types());
+        let (_, imported) = AutoDKGStore::open::<PeerId>(keystore, &legacy_dir, "").unwrap();
+        assert!(imported.is_some());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,10 +1,21 @@
+use std::str::FromStr;
+
 use crate::crypto::CryptoType;
+use crate::types::KeyState;
 
 pub(crate) enum Command {
     PeerId,
     Help,
     ListSignerAddr,
     ListPkId,
+    SessionStatus,
+    ReloadPolicy,
+    ReloadNamespaces,
+    WhitelistList,
+    WhitelistAdd(String, Option<String>),
+    WhitelistRemove(String),
+    Cancel(String),
+    KeyState(String, KeyState),
     StartDkg(u16, CryptoType),
     Unknown(String),
     Dial(String),
@@ -42,6 +53,25 @@ impl Command {
                 Command::LoopSign(origin[1].to_string(), times.parse::<usize>().unwrap())
             }
             ["lspk"] => Command::ListPkId,
+            ["session", "status"] | ["sessions"] => Command::SessionStatus,
+            ["reload", "policy"] | ["policy", "reload"] => Command::ReloadPolicy,
+            ["reload", "namespaces"] | ["namespaces", "reload"] => Command::ReloadNamespaces,
+            ["whitelist"] | ["whitelist", "list"] | ["whitelist", "ls"] => Command::WhitelistList,
+            // the label may be several words, take it from the original input
+            ["whitelist", "add", ..] if origin.len() >= 3 => {
+                let label = origin[3..].join(" ");
+                Command::WhitelistAdd(origin[2].to_string(), (!label.is_empty()).then_some(label))
+            }
+            ["whitelist", "remove" | "rm", _] if origin.len() == 3 => {
+                Command::WhitelistRemove(origin[2].to_string())
+            }
+            // session ids contain '-', so take the id from the original input
+            ["cancel", ..] if origin.len() == 2 => Command::Cancel(origin[1].to_string()),
+            // accepts both `key-state` and `key state`
+            ["key", "state", _pkid, state] => match KeyState::from_str(state) {
+                Ok(state) => Command::KeyState(origin[origin.len() - 2].to_string(), state),
+                Err(_) => Command::Unknown(parts.join(" ")),
+            },
             ["start", "dkg", num, crypto_type] | ["dkg", num, crypto_type] => {
                 if let Ok(n) = num.parse::<u16>() {
                     if let Ok(c) = crypto_type.parse::<u8>() {
@@ -68,6 +98,14 @@ impl Command {
         - help | h: Show this help message
         - list signer info | ls: List signer info
         - lspk: List pkid
+        - session status | sessions: Show active and recently finished sessions
+        - reload policy: Reload the signing policy file
+        - reload namespaces: Reload the namespaces file
+        - whitelist [list]: List the whitelisted signers
+        - whitelist add <signer> [label]: Allow a signer to register, or change its label; starts a whitelist of the registered signers if there is none
+        - whitelist remove <signer>: Disallow a signer and disconnect it, unless it holds the AutoDKG keys
+        - cancel <session_id|subsession_id>: Cancel a running DKG session or signing subsession
+        - key-state <pkid> <active|disabled|retired|destroyed>: Change the lifecycle state of a key
         - sign <public_key> <message>: Sign a message with the given public key
         - start dkg <n> <crypto_type> | dkg <n> <crypto_type>: Start DKG with min n signers and crypto type:
           0: Ed25519
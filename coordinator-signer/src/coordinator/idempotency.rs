@@ -0,0 +1,302 @@
+//! Idempotency keys of node DKG and signing requests.
+//!
+//! A node may attach a key of its choice to a request so that retrying it after a lost
+//! response does not start a second session. The coordinator remembers each key per
+//! requester for a configurable window: a retry attaches to the session still running
+//! for the key or gets its result again. Only successful results are kept, a failed
+//! session releases the key so the request can be retried.
+//!
+//! Records are appended encrypted to `<dir>/idempotency.store` and compacted to the keys
+//! still in the window on startup.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::ValidatorIdentityIdentity;
+use crate::keystore::{AppendStore, Keystore};
+use crate::types::error::SessionError;
+use crate::types::{SessionRequest, SessionResult};
+use crate::utils::list_hash;
+use crate::utils::now;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum IdempotencyRecord<VII: ValidatorIdentityIdentity> {
+    Started {
+        requester: VII,
+        key: String,
+        fingerprint: Vec<u8>,
+        journal_id: u64,
+        created_at: u64,
+    },
+    Finished {
+        journal_id: u64,
+        result: SessionResult<VII>,
+    },
+    Released {
+        journal_id: u64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct IdempotencyEntry<VII: ValidatorIdentityIdentity> {
+    fingerprint: Vec<u8>,
+    journal_id: u64,
+    created_at: u64,
+    result: Option<SessionResult<VII>>,
+}
+
+/// What to do with a request carrying an idempotency key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Replay<VII: ValidatorIdentityIdentity> {
+    /// The key is unknown, start a session.
+    New,
+    /// The session of journal id is still running.
+    InFlight(u64),
+    Finished(SessionResult<VII>),
+    /// The key was used for a different request.
+    Mismatch,
+}
+
+// the deadline is left out since a retry usually comes with a later one
+fn fingerprint<VII: ValidatorIdentityIdentity + Serialize>(
+    request: &SessionRequest<VII>,
+) -> Result<Vec<u8>, SessionError> {
+    let mut request = request.clone();
+    match &mut request {
+        SessionRequest::Dkg { deadline, .. } | SessionRequest::Signing { deadline, .. } => {
+            *deadline = None
+        }
+    }
+    let data = serde_json::to_vec(&request)
+        .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+    Ok(list_hash(&[data]))
+}
+
+pub(crate) struct IdempotencyStore<VII: ValidatorIdentityIdentity> {
+    store: AppendStore,
+    // seconds a key is remembered for
+    window: u64,
+    entries: HashMap<(VII, String), IdempotencyEntry<VII>>,
+    by_journal_id: HashMap<u64, (VII, String)>,
+}
+
+impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>> IdempotencyStore<VII> {
+    /// Replays `<dir>/idempotency.store` and compacts it to the keys of the last
+    /// `window` seconds.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+        window: u64,
+    ) -> Result<Self, SessionError> {
+        let (store, records) = AppendStore::open(keystore, dir.as_ref().join("idempotency.store"))?;
+        let mut store = Self {
+            store,
+            window,
+            entries: HashMap::new(),
+            by_journal_id: HashMap::new(),
+        };
+        for record in records {
+            let record = serde_json::from_slice(&record)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+            store.apply(record);
+        }
+        store.prune();
+        store.compact()?;
+        Ok(store)
+    }
+    fn apply(&mut self, record: IdempotencyRecord<VII>) {
+        match record {
+            IdempotencyRecord::Started {
+                requester,
+                key,
+                fingerprint,
+                journal_id,
+                created_at,
+            } => {
+                self.by_journal_id
+                    .insert(journal_id, (requester.clone(), key.clone()));
+                self.entries.insert(
+                    (requester, key),
+                    IdempotencyEntry {
+                        fingerprint,
+                        journal_id,
+                        created_at,
+                        result: None,
+                    },
+                );
+            }
+            IdempotencyRecord::Finished { journal_id, result } => {
+                if let Some(entry) = self
+                    .by_journal_id
+                    .get(&journal_id)
+                    .and_then(|id| self.entries.get_mut(id))
+                {
+                    entry.result = Some(result);
+                }
+            }
+            IdempotencyRecord::Released { journal_id } => {
+                if let Some(id) = self.by_journal_id.remove(&journal_id) {
+                    self.entries.remove(&id);
+                }
+            }
+        }
+    }
+    fn compact(&mut self) -> Result<(), SessionError> {
+        let mut records = Vec::new();
+        for ((requester, key), entry) in &self.entries {
+            records.push(Self::encode(&IdempotencyRecord::Started {
+                requester: requester.clone(),
+                key: key.clone(),
+                fingerprint: entry.fingerprint.clone(),
+                journal_id: entry.journal_id,
+                created_at: entry.created_at,
+            })?);
+            if let Some(result) = &entry.result {
+                records.push(Self::encode(&IdempotencyRecord::Finished {
+                    journal_id: entry.journal_id,
+                    result: result.clone(),
+                })?);
+            }
+        }
+        Ok(self.store.rewrite(records)?)
+    }
+    fn encode(record: &IdempotencyRecord<VII>) -> Result<Vec<u8>, SessionError> {
+        serde_json::to_vec(record).map_err(|e| SessionError::SerializationError(e.to_string()))
+    }
+    fn append(&mut self, record: IdempotencyRecord<VII>) -> Result<(), SessionError> {
+        self.store.append(&Self::encode(&record)?)?;
+        self.apply(record);
+        Ok(())
+    }
+    // forgets the keys older than the window
+    fn prune(&mut self) {
+        let oldest = now().saturating_sub(self.window);
+        let by_journal_id = &mut self.by_journal_id;
+        self.entries.retain(|_, entry| {
+            let keep = entry.created_at >= oldest;
+            if !keep {
+                by_journal_id.remove(&entry.journal_id);
+            }
+            keep
+        });
+    }
+    pub(crate) fn lookup(
+        &mut self,
+        requester: &VII,
+        key: &str,
+        request: &SessionRequest<VII>,
+    ) -> Result<Replay<VII>, SessionError> {
+        self.prune();
+        let Some(entry) = self.entries.get(&(requester.clone(), key.to_string())) else {
+            return Ok(Replay::New);
+        };
+        if entry.fingerprint != fingerprint(request)? {
+            return Ok(Replay::Mismatch);
+        }
+        Ok(match &entry.result {
+            Some(result) => Replay::Finished(result.clone()),
+            None => Replay::InFlight(entry.journal_id),
+        })
+    }
+    /// Remembers `key` for the session journaled as `journal_id`.
+    pub(crate) fn start(
+        &mut self,
+        requester: VII,
+        key: String,
+        request: &SessionRequest<VII>,
+        journal_id: u64,
+    ) -> Result<(), SessionError> {
+        let fingerprint = fingerprint(request)?;
+        self.append(IdempotencyRecord::Started {
+            requester,
+            key,
+            fingerprint,
+            journal_id,
+            created_at: now(),
+        })
+    }
+    /// Keeps the result of session `journal_id` if it succeeded and releases its key
+    /// otherwise. Sessions without a key are ignored.
+    pub(crate) fn finish(
+        &mut self,
+        journal_id: u64,
+        result: &SessionResult<VII>,
+    ) -> Result<(), SessionError> {
+        if !self.by_journal_id.contains_key(&journal_id) {
+            return Ok(());
+        }
+        let succeeded = match result {
+            SessionResult::Dkg(result) => result.is_ok(),
+            SessionResult::Signing(result) => result.is_ok(),
+        };
+        if succeeded {
+            self.append(IdempotencyRecord::Finished {
+                journal_id,
+                result: result.clone(),
+            })
+        } else {
+            self.append(IdempotencyRecord::Released { journal_id })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::PkId;
+    use crate::types::Deadline;
+    use libp2p::PeerId;
+    use std::time::Duration;
+
+    #[test]
+    fn test_idempotency_store() {
+        let keystore = Arc::new(Keystore::new(b"idempotency", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("idempotency_{}", uuid::Uuid::new_v4()));
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let request = |msg: &[u8], deadline| SessionRequest::<PeerId>::Signing {
+            pkid: PkId::new(vec![1; 33]),
+            msg: msg.to_vec(),
+            tweak_data: None,
+            deadline,
+            selection: None,
+        };
+        let first = request(b"msg", None);
+        let retry = request(b"msg", Some(Deadline::after(Duration::from_secs(10))));
+
+        let mut store = IdempotencyStore::open(keystore.clone(), &dir, 3600).unwrap();
+        assert_eq!(store.lookup(&alice, "a", &first).unwrap(), Replay::New);
+        store.start(alice, "a".to_string(), &first, 1).unwrap();
+        store.start(alice, "b".to_string(), &first, 2).unwrap();
+        assert_eq!(
+            store.lookup(&alice, "a", &retry).unwrap(),
+            Replay::InFlight(1)
+        );
+        assert_eq!(
+            store.lookup(&alice, "a", &request(b"other", None)).unwrap(),
+            Replay::Mismatch
+        );
+        assert_eq!(store.lookup(&bob, "a", &first).unwrap(), Replay::New);
+        store
+            .finish(1, &SessionResult::Dkg(Ok(PkId::new(vec![2; 33]))))
+            .unwrap();
+        store
+            .finish(2, &SessionResult::Signing(Err("failed".to_string())))
+            .unwrap();
+        drop(store);
+
+        let mut store = IdempotencyStore::open(keystore.clone(), &dir, 3600).unwrap();
+        assert_eq!(
+            store.lookup(&alice, "a", &retry).unwrap(),
+            Replay::Finished(SessionResult::Dkg(Ok(PkId::new(vec![2; 33]))))
+        );
+        assert_eq!(store.lookup(&alice, "b", &first).unwrap(), Replay::New);
+        drop(store);
+
+        let mut store = IdempotencyStore::open(keystore, &dir, 0).unwrap();
+        std::thread::sleep(Duration::from_millis(1100));
+        assert_eq!(store.lookup(&alice, "a", &first).unwrap(), Replay::New);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
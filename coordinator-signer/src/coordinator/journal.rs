@@ -0,0 +1,362 @@
+//! Write-ahead journal of the DKG and signing sessions requested by nodes.
+//!
+//! A request is appended before it is handed to the session manager and its result
+//! once the session finishes. Round state and node response channels are lost on a
+//! restart, so at startup pending signing sessions are started again from the
+//! recorded request and pending DKG sessions are aborted. Outcomes that never reached
+//! the requesting node are kept until that node polls for them or for
+//! `outcome_retention` seconds after the request.
+//!
+//! The journal is compacted to the entries still needed on startup and every
+//! `COMPACT_AFTER` appended records. Session ids are never reused, a compaction keeps
+//! the next id.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::ValidatorIdentityIdentity;
+use crate::keystore::{AppendStore, Keystore};
+use crate::types::error::SessionError;
+use crate::types::{SessionOutcome, SessionRequest, SessionResult, SignatureFormat};
+use crate::utils::now;
+
+// records appended between two compactions
+const COMPACT_AFTER: usize = 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum JournalRecord<VII: ValidatorIdentityIdentity> {
+    Started {
+        id: u64,
+        requester: VII,
+        request: SessionRequest<VII>,
+        #[serde(default)]
+        requested_at: u64,
+        /// Encoding of the signature the node asked for.
+        #[serde(default)]
+        signature_format: SignatureFormat,
+    },
+    Finished {
+        id: u64,
+        result: SessionResult<VII>,
+    },
+    Delivered {
+        id: u64,
+    },
+    /// Written first by a compaction, which may drop the session of the highest id.
+    NextId {
+        id: u64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry<VII: ValidatorIdentityIdentity> {
+    requester: VII,
+    request: SessionRequest<VII>,
+    requested_at: u64,
+    signature_format: SignatureFormat,
+    result: Option<SessionResult<VII>>,
+}
+
+pub(crate) struct SessionJournal<VII: ValidatorIdentityIdentity> {
+    store: AppendStore,
+    // records appended since the last compaction
+    appended: usize,
+    next_id: u64,
+    // seconds an undelivered outcome is kept after the request
+    outcome_retention: u64,
+    // sessions that are still running or whose outcome has not been delivered
+    entries: BTreeMap<u64, JournalEntry<VII>>,
+}
+
+impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>> SessionJournal<VII> {
+    /// Replays `<dir>/session.journal` and compacts it to the entries still needed.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+        outcome_retention: u64,
+    ) -> Result<Self, SessionError> {
+        let (store, records) = AppendStore::open(keystore, dir.as_ref().join("session.journal"))?;
+        let mut entries = BTreeMap::new();
+        let mut next_id = 0;
+        for record in records {
+            let record: JournalRecord<VII> = serde_json::from_slice(&record)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+            match record {
+                JournalRecord::Started {
+                    id,
+                    requester,
+                    request,
+                    requested_at,
+                    signature_format,
+                } => {
+                    next_id = next_id.max(id + 1);
+                    entries.insert(
+                        id,
+                        JournalEntry {
+                            requester,
+                            request,
+                            requested_at,
+                            signature_format,
+                            result: None,
+                        },
+                    );
+                }
+                JournalRecord::Finished { id, result } => {
+                    if let Some(entry) = entries.get_mut(&id) {
+                        entry.result = Some(result);
+                    }
+                }
+                JournalRecord::Delivered { id } => {
+                    entries.remove(&id);
+                }
+                JournalRecord::NextId { id } => {
+                    next_id = next_id.max(id);
+                }
+            }
+        }
+        let mut journal = Self {
+            store,
+            appended: 0,
+            next_id,
+            outcome_retention,
+            entries,
+        };
+        journal.compact()?;
+        Ok(journal)
+    }
+    fn encode(record: &JournalRecord<VII>) -> Result<Vec<u8>, SessionError> {
+        serde_json::to_vec(record).map_err(|e| SessionError::SerializationError(e.to_string()))
+    }
+    // rewrites the journal with the entries still needed, dropping the outcomes past
+    // the retention
+    fn compact(&mut self) -> Result<(), SessionError> {
+        let now = now();
+        let retention = self.outcome_retention;
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| {
+            entry.result.is_none() || entry.requested_at.saturating_add(retention) > now
+        });
+        if self.entries.len() != before {
+            tracing::warn!(
+                "Dropping {} undelivered session outcomes past the retention",
+                before - self.entries.len()
+            );
+        }
+        let mut records = vec![Self::encode(&JournalRecord::NextId { id: self.next_id })?];
+        for (id, entry) in &self.entries {
+            records.push(Self::encode(&JournalRecord::Started {
+                id: *id,
+                requester: entry.requester.clone(),
+                request: entry.request.clone(),
+                requested_at: entry.requested_at,
+                signature_format: entry.signature_format,
+            })?);
+            if let Some(result) = &entry.result {
+                records.push(Self::encode(&JournalRecord::Finished {
+                    id: *id,
+                    result: result.clone(),
+                })?);
+            }
+        }
+        self.store.rewrite(records)?;
+        self.appended = 0;
+        Ok(())
+    }
+    fn append(&mut self, record: JournalRecord<VII>) -> Result<(), SessionError> {
+        self.store.append(&Self::encode(&record)?)?;
+        self.appended += 1;
+        Ok(())
+    }
+    // called once the entries reflect every appended record
+    fn compact_if_due(&mut self) -> Result<(), SessionError> {
+        if self.appended < COMPACT_AFTER {
+            return Ok(());
+        }
+        self.compact()
+    }
+    /// Sessions that were still running when the journal was opened, with their
+    /// requester.
+    pub(crate) fn pending(&self) -> Vec<(u64, VII, SessionRequest<VII>)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.result.is_none())
+            .map(|(id, entry)| (*id, entry.requester.clone(), entry.request.clone()))
+            .collect()
+    }
+    /// `signature_format` is the encoding of the signature a signing session returns to
+    /// the node.
+    pub(crate) fn start(
+        &mut self,
+        requester: VII,
+        request: SessionRequest<VII>,
+        signature_format: SignatureFormat,
+    ) -> Result<u64, SessionError> {
+        let id = self.next_id;
+        let requested_at = now();
+        self.append(JournalRecord::Started {
+            id,
+            requester: requester.clone(),
+            request: request.clone(),
+            requested_at,
+            signature_format,
+        })?;
+        self.next_id += 1;
+        self.entries.insert(
+            id,
+            JournalEntry {
+                requester,
+                request,
+                requested_at,
+                signature_format,
+                result: None,
+            },
+        );
+        self.compact_if_due()?;
+        Ok(id)
+    }
+    /// The requester, request and unix time of session `id` while it is journaled.
+    pub(crate) fn started(&self, id: u64) -> Option<(VII, SessionRequest<VII>, u64)> {
+        self.entries.get(&id).map(|entry| {
+            (
+                entry.requester.clone(),
+                entry.request.clone(),
+                entry.requested_at,
+            )
+        })
+    }
+    /// The signature format the node asked for in session `id`.
+    pub(crate) fn signature_format(&self, id: u64) -> SignatureFormat {
+        self.entries
+            .get(&id)
+            .map_or(SignatureFormat::Compact, |entry| entry.signature_format)
+    }
+    /// Records the result of session `id`, `delivered` tells whether the requesting
+    /// node already received it.
+    pub(crate) fn finish(
+        &mut self,
+        id: u64,
+        result: SessionResult<VII>,
+        delivered: bool,
+    ) -> Result<(), SessionError> {
+        self.append(JournalRecord::Finished {
+            id,
+            result: result.clone(),
+        })?;
+        if delivered {
+            return self.delivered(&[id]);
+        }
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.result = Some(result);
+        }
+        self.compact_if_due()
+    }
+    pub(crate) fn delivered(&mut self, ids: &[u64]) -> Result<(), SessionError> {
+        for id in ids {
+            self.append(JournalRecord::Delivered { id: *id })?;
+            self.entries.remove(id);
+        }
+        self.compact_if_due()
+    }
+    /// Finished sessions of `requester` whose outcome has not been delivered yet, with
+    /// the signatures encoded as the node asked.
+    pub(crate) fn undelivered(&self, requester: &VII) -> Vec<(u64, SessionOutcome<VII>)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| &entry.requester == requester)
+            .filter_map(|(id, entry)| {
+                entry.result.clone().map(|result| {
+                    (
+                        *id,
+                        SessionOutcome {
+                            request: entry.request.clone(),
+                            result: result.with_signature_format(entry.signature_format),
+                        },
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{CryptoType, PkId};
+    use libp2p::PeerId;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_session_journal_replay() {
+        let keystore = Arc::new(Keystore::new(b"journal", None).unwrap());
+        let dir = PathBuf::from(".test_journal");
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        let signing = SessionRequest::<PeerId>::Signing {
+            pkid: PkId::new(vec![1; 33]),
+            msg: b"msg".to_vec(),
+            tweak_data: None,
+            deadline: None,
+            selection: None,
+        };
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let dkg = SessionRequest::Dkg {
+            crypto_type: CryptoType::Ed25519,
+            participants: vec![(1, alice), (2, bob)],
+            min_signers: 2,
+            deadline: None,
+        };
+        let mut journal = SessionJournal::open(keystore.clone(), &dir, 3600).unwrap();
+        let delivered = journal
+            .start(alice, dkg.clone(), SignatureFormat::Compact)
+            .unwrap();
+        let undelivered = journal
+            .start(alice, dkg.clone(), SignatureFormat::Compact)
+            .unwrap();
+        let pending = journal
+            .start(bob, signing.clone(), SignatureFormat::Der)
+            .unwrap();
+        let pkid = PkId::new(vec![1; 33]);
+        journal
+            .finish(delivered, SessionResult::Dkg(Ok(pkid.clone())), true)
+            .unwrap();
+        journal
+            .finish(undelivered, SessionResult::Dkg(Ok(pkid.clone())), false)
+            .unwrap();
+        drop(journal);
+
+        let mut journal = SessionJournal::<PeerId>::open(keystore.clone(), &dir, 3600).unwrap();
+        assert_eq!(journal.pending(), vec![(pending, bob, signing.clone())]);
+        assert_eq!(journal.signature_format(pending), SignatureFormat::Der);
+        let outcomes = journal.undelivered(&alice);
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].0, undelivered);
+        assert_eq!(outcomes[0].1.result, SessionResult::Dkg(Ok(pkid.clone())));
+        journal.delivered(&[undelivered]).unwrap();
+        assert!(journal.undelivered(&alice).is_empty());
+        let last = journal.start(alice, dkg, SignatureFormat::Compact).unwrap();
+        assert!(last > pending);
+        journal
+            .finish(pending, SessionResult::Dkg(Ok(pkid.clone())), true)
+            .unwrap();
+        journal
+            .finish(last, SessionResult::Dkg(Ok(pkid)), false)
+            .unwrap();
+        drop(journal);
+
+        // outcomes past the retention are dropped, the ids of forgotten sessions are
+        // not handed out again
+        let mut journal = SessionJournal::<PeerId>::open(keystore, &dir, 0).unwrap();
+        assert!(journal.undelivered(&alice).is_empty());
+        assert!(journal.pending().is_empty());
+        assert!(
+            journal
+                .start(bob, signing, SignatureFormat::Compact)
+                .unwrap()
+                > last
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+//! Destroy requests waiting for signers to confirm that they erased their shares.
+//!
+//! A key counts as destroyed once at most `min_signers - 1` shares may be left, so
+//! `participants - min_signers + 1` signers have to confirm. The key is disabled while
+//! they are asked and stays so if too few of them confirm.
+use std::collections::HashMap;
+
+use tokio::sync::oneshot;
+
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+use crate::types::error::SessionError;
+use crate::types::{KeyMetadata, KeyState};
+
+pub(crate) type KeyStateResponder<VII> = oneshot::Sender<Result<KeyMetadata<VII>, SessionError>>;
+
+pub(crate) struct PendingDestroy<VII: ValidatorIdentityIdentity> {
+    pub(crate) from: KeyState,
+    pub(crate) requester: Option<VII>,
+    pub(crate) confirmed_by: Vec<VII>,
+    pub(crate) responder: KeyStateResponder<VII>,
+    needed: usize,
+    waiting: usize,
+}
+
+pub(crate) enum DestroyOutcome<VII: ValidatorIdentityIdentity> {
+    Confirmed(PendingDestroy<VII>),
+    /// Too many signers failed or could not be reached.
+    Failed(PendingDestroy<VII>),
+}
+
+pub(crate) struct KeyDestroyer<VII: ValidatorIdentityIdentity> {
+    pending: HashMap<PkId, PendingDestroy<VII>>,
+}
+impl<VII: ValidatorIdentityIdentity> KeyDestroyer<VII> {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+        }
+    }
+    pub(crate) fn is_pending(&self, pkid: &PkId) -> bool {
+        self.pending.contains_key(pkid)
+    }
+    /// `from` is the state of the key before it was disabled for the destroy.
+    pub(crate) fn start(
+        &mut self,
+        pkid: PkId,
+        from: KeyState,
+        metadata: &KeyMetadata<VII>,
+        requester: Option<VII>,
+        responder: KeyStateResponder<VII>,
+    ) {
+        let participants = metadata.participants.len();
+        self.pending.insert(
+            pkid,
+            PendingDestroy {
+                from,
+                requester,
+                confirmed_by: Vec::new(),
+                responder,
+                needed: participants.saturating_sub(metadata.min_signers as usize) + 1,
+                waiting: participants,
+            },
+        );
+    }
+    /// Records the answer of one participant, `signer` is `None` if it could not be
+    /// reached. Returns the outcome once it is decided.
+    pub(crate) fn answered(
+        &mut self,
+        pkid: &PkId,
+        signer: Option<VII>,
+        erased: bool,
+    ) -> Option<DestroyOutcome<VII>> {
+        let pending = self.pending.get_mut(pkid)?;
+        pending.waiting = pending.waiting.saturating_sub(1);
+        if let (Some(signer), true) = (signer, erased) {
+            if !pending.confirmed_by.contains(&signer) {
+                pending.confirmed_by.push(signer);
+            }
+        }
+        if pending.confirmed_by.len() >= pending.needed {
+            Some(DestroyOutcome::Confirmed(self.pending.remove(pkid)?))
+        } else if pending.confirmed_by.len() + pending.waiting < pending.needed {
+            Some(DestroyOutcome::Failed(self.pending.remove(pkid)?))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_destroy_quorum() {
+        let signers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let metadata = KeyMetadata::new(None, None, signers.clone(), 3);
+        let pkid = PkId::new(vec![1; 33]);
+        let mut destroyer = KeyDestroyer::new();
+
+        // 4 participants with threshold 3 need 2 confirmations
+        let (tx, _rx) = oneshot::channel();
+        destroyer.start(pkid.clone(), KeyState::Active, &metadata, None, tx);
+        assert!(destroyer.answered(&pkid, Some(signers[0]), true).is_none());
+        match destroyer.answered(&pkid, Some(signers[1]), true) {
+            Some(DestroyOutcome::Confirmed(pending)) => {
+                assert_eq!(pending.confirmed_by, vec![signers[0], signers[1]]);
+            }
+            _ => panic!("destroy should be confirmed"),
+        }
+        assert!(!destroyer.is_pending(&pkid));
+        assert!(destroyer.answered(&pkid, Some(signers[2]), true).is_none());
+
+        let (tx, _rx) = oneshot::channel();
+        destroyer.start(pkid.clone(), KeyState::Active, &metadata, None, tx);
+        assert!(destroyer.answered(&pkid, None, false).is_none());
+        assert!(destroyer.answered(&pkid, Some(signers[1]), false).is_none());
+        assert!(matches!(
+            destroyer.answered(&pkid, Some(signers[2]), false),
+            Some(DestroyOutcome::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+//! Labels, tags and creation info of the keys of one crypto type, kept encrypted next to
+//! their signing sessions.
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::namespace::KeyScope;
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+use crate::keystore::{Keystore, KeystoreManagement};
+use crate::types::error::SessionError;
+use crate::types::{KeyMetadata, KeyMetadataUpdate, KeyState};
+
+// identities are kept as bytes like in `Participants`, `VII` is not `Serialize`
+#[derive(Serialize, Deserialize)]
+struct StoredKeyMetadata {
+    label: Option<String>,
+    description: Option<String>,
+    tags: BTreeSet<String>,
+    created_at: Option<u64>,
+    requester: Option<Vec<u8>>,
+    participants: Vec<Vec<u8>>,
+    min_signers: u16,
+    state: KeyState,
+    namespace: Option<String>,
+}
+impl StoredKeyMetadata {
+    fn from_metadata<VII: ValidatorIdentityIdentity>(metadata: &KeyMetadata<VII>) -> Self {
+        Self {
+            label: metadata.label.clone(),
+            description: metadata.description.clone(),
+            tags: metadata.tags.clone(),
+            created_at: metadata.created_at,
+            requester: metadata.requester.as_ref().map(|r| r.to_bytes()),
+            participants: metadata.participants.iter().map(|p| p.to_bytes()).collect(),
+            min_signers: metadata.min_signers,
+            state: metadata.state,
+            namespace: metadata.namespace.clone(),
+        }
+    }
+    fn into_metadata<VII: ValidatorIdentityIdentity>(
+        self,
+    ) -> Result<KeyMetadata<VII>, SessionError> {
+        let identity = |bytes: Vec<u8>| {
+            VII::from_bytes(bytes).map_err(|e| SessionError::DeserializationError(e.to_string()))
+        };
+        Ok(KeyMetadata {
+            label: self.label,
+            description: self.description,
+            tags: self.tags,
+            created_at: self.created_at,
+            requester: self.requester.map(identity).transpose()?,
+            participants: self
+                .participants
+                .into_iter()
+                .map(identity)
+                .collect::<Result<_, _>>()?,
+            min_signers: self.min_signers,
+            state: self.state,
+            namespace: self.namespace,
+        })
+    }
+}
+
+pub(crate) struct KeyMetadataBook<VII: ValidatorIdentityIdentity> {
+    keystore_management: KeystoreManagement,
+    metadata: HashMap<PkId, KeyMetadata<VII>>,
+}
+impl<VII: ValidatorIdentityIdentity> KeyMetadataBook<VII> {
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self, SessionError> {
+        let (keystore_management, data) = KeystoreManagement::new(keystore, dir)?;
+        let metadata = match data {
+            Some(data) => bincode::deserialize::<HashMap<PkId, StoredKeyMetadata>>(&data)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?
+                .into_iter()
+                .map(|(pkid, stored)| Ok((pkid, stored.into_metadata()?)))
+                .collect::<Result<_, SessionError>>()?,
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            keystore_management,
+            metadata,
+        })
+    }
+    fn save(&mut self) -> Result<(), SessionError> {
+        let stored = self
+            .metadata
+            .iter()
+            .map(|(pkid, metadata)| (pkid.clone(), StoredKeyMetadata::from_metadata(metadata)))
+            .collect::<HashMap<_, _>>();
+        let data = bincode::serialize(&stored)
+            .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+        Ok(self.keystore_management.write(&data)?)
+    }
+    pub(crate) fn contains(&self, pkid: &PkId) -> bool {
+        self.metadata.contains_key(pkid)
+    }
+    pub(crate) fn get(&self, pkid: &PkId) -> Result<KeyMetadata<VII>, SessionError> {
+        self.metadata
+            .get(pkid)
+            .cloned()
+            .ok_or(SessionError::CoordinatorSessionError(format!(
+                "No metadata for pkid {}",
+                pkid
+            )))
+    }
+    pub(crate) fn insert(
+        &mut self,
+        pkid: PkId,
+        metadata: KeyMetadata<VII>,
+    ) -> Result<(), SessionError> {
+        self.metadata.insert(pkid, metadata);
+        self.save()
+    }
+    // only the node that asked for a key may change it, `requester` is `None` for the operator;
+    // keys no node asked for, like the AutoDKG ones, are left to the operator
+    fn owned_mut(
+        &mut self,
+        pkid: &PkId,
+        requester: &Option<VII>,
+    ) -> Result<&mut KeyMetadata<VII>, SessionError> {
+        let metadata = self
+            .metadata
+            .get_mut(pkid)
+            .ok_or(SessionError::CoordinatorSessionError(format!(
+                "No metadata for pkid {}",
+                pkid
+            )))?;
+        if let Some(requester) = requester {
+            if metadata.requester.as_ref() != Some(requester) {
+                return Err(SessionError::InvalidRequest(format!(
+                    "key {} was not requested by {}",
+                    pkid,
+                    requester.to_fmt_string()
+                )));
+            }
+        }
+        Ok(metadata)
+    }
+    /// Edits the metadata of `pkid`.
+    pub(crate) fn update(
+        &mut self,
+        pkid: &PkId,
+        update: KeyMetadataUpdate,
+        requester: &Option<VII>,
+    ) -> Result<KeyMetadata<VII>, SessionError> {
+        let metadata = self.owned_mut(pkid, requester)?;
+        metadata.apply(update);
+        let metadata = metadata.clone();
+        self.save()?;
+        Ok(metadata)
+    }
+    /// Checks that `requester` may move `pkid` to `state` and returns its metadata.
+    pub(crate) fn check_transition(
+        &mut self,
+        pkid: &PkId,
+        state: KeyState,
+        requester: &Option<VII>,
+    ) -> Result<KeyMetadata<VII>, SessionError> {
+        let metadata = self.owned_mut(pkid, requester)?;
+        if !metadata.state.can_become(state) {
+            return Err(SessionError::InvalidRequest(format!(
+                "key {} is {} and cannot become {}",
+                pkid, metadata.state, state
+            )));
+        }
+        Ok(metadata.clone())
+    }
+    pub(crate) fn set_state(
+        &mut self,
+        pkid: &PkId,
+        state: KeyState,
+    ) -> Result<KeyMetadata<VII>, SessionError> {
+        let metadata = self.owned_mut(pkid, &None)?;
+        metadata.state = state;
+        let metadata = metadata.clone();
+        self.save()?;
+        Ok(metadata)
+    }
+    /// The namespace of `pkid`, keys without metadata are in the default one.
+    pub(crate) fn namespace_of(&self, pkid: &PkId) -> Option<String> {
+        self.metadata
+            .get(pkid)
+            .and_then(|metadata| metadata.namespace.clone())
+    }
+    /// Errors as if `pkid` did not exist unless `scope` covers its namespace.
+    pub(crate) fn check_scope(&self, pkid: &PkId, scope: &KeyScope) -> Result<(), SessionError> {
+        if scope.covers(&self.namespace_of(pkid)) {
+            Ok(())
+        } else {
+            Err(SessionError::InvalidRequest(format!(
+                "key {} not found",
+                pkid
+            )))
+        }
+    }
+    /// Errors unless `pkid` may sign.
+    pub(crate) fn check_active(&self, pkid: &PkId) -> Result<(), SessionError> {
+        match self.metadata.get(pkid).map(|metadata| metadata.state) {
+            Some(KeyState::Active) | None => Ok(()),
+            Some(state) => Err(SessionError::InvalidRequest(format!(
+                "key {} is {}",
+                pkid, state
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_key_metadata_book() {
+        let keystore = Arc::new(Keystore::new(b"key metadata", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("key_metadata_{}", uuid::Uuid::new_v4()));
+        let pkid = PkId::new(vec![1; 33]);
+        let (owner, other) = (PeerId::random(), PeerId::random());
+
+        let mut book = KeyMetadataBook::open(keystore.clone(), &dir).unwrap();
+        let mut metadata = KeyMetadata::new(Some(1), Some(owner), vec![owner, other], 2);
+        metadata.apply(KeyMetadataUpdate {
+            label: Some("treasury".to_string()),
+            description: None,
+            tags: Some(BTreeSet::from(["prod".to_string()])),
+        });
+        book.insert(pkid.clone(), metadata).unwrap();
+
+        let update = KeyMetadataUpdate {
+            label: Some("".to_string()),
+            description: Some("cold wallet".to_string()),
+            tags: None,
+        };
+        assert!(book.update(&pkid, update.clone(), &Some(other)).is_err());
+        book.update(&pkid, update, &Some(owner)).unwrap();
+        drop(book);
+
+        let book = KeyMetadataBook::<PeerId>::open(keystore.clone(), &dir).unwrap();
+        let metadata = book.get(&pkid).unwrap();
+        assert_eq!(metadata.label, None);
+        assert_eq!(metadata.description.as_deref(), Some("cold wallet"));
+        assert!(metadata.tags.contains("prod"));
+        assert_eq!(metadata.requester, Some(owner));
+        assert_eq!(metadata.participants, vec![owner, other]);
+        assert!(book.get(&PkId::new(vec![2; 33])).is_err());
+
+        let mut book = book;
+        book.check_transition(&pkid, KeyState::Disabled, &Some(owner))
+            .unwrap();
+        assert!(book
+            .check_transition(&pkid, KeyState::Disabled, &Some(other))
+            .is_err());
+        book.set_state(&pkid, KeyState::Retired).unwrap();
+        assert!(book.check_active(&pkid).is_err());
+        assert!(book
+            .check_transition(&pkid, KeyState::Active, &None)
+            .is_err());
+        drop(book);
+        let mut book = KeyMetadataBook::<PeerId>::open(keystore, &dir).unwrap();
+        assert_eq!(book.get(&pkid).unwrap().state, KeyState::Retired);
+
+        // a key no node asked for, like an AutoDKG one, is left to the operator
+        let auto_pkid = PkId::new(vec![3; 33]);
+        book.insert(
+            auto_pkid.clone(),
+            KeyMetadata::new(Some(1), None, vec![owner, other], 2),
+        )
+        .unwrap();
+        assert!(book
+            .check_transition(&auto_pkid, KeyState::Destroyed, &Some(other))
+            .is_err());
+        assert!(book
+            .update(&auto_pkid, KeyMetadataUpdate::default(), &Some(other))
+            .is_err());
+        book.check_transition(&auto_pkid, KeyState::Destroyed, &None)
+            .unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -5,9 +5,11 @@ use tokio::sync::{
     mpsc::{UnboundedReceiver, UnboundedSender},
     oneshot,
 };
+use tokio::task::AbortHandle;
 
 use crate::{
     coordinator::session_ex::SessionWrapEx,
+    coordinator::{monitor::SessionMonitor, scheduler::SignerScheduler},
     keystore::Keystore,
     types::{
         error::SessionError,
@@ -15,12 +17,14 @@ use crate::{
             DKGRequestWrap, DKGRequestWrapEx, DKGResponseWrap, DKGResponseWrapEx,
             SigningRequestWrap, SigningRequestWrapEx, SigningResponseWrap, SigningResponseWrapEx,
         },
-        GroupPublicKeyInfo, SignatureSuiteInfo,
+        CancelTarget, Deadline, KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState, PublicKeyInfo,
+        PublicKeyPackageInfo, SignatureSuiteInfo, SignerSelection,
     },
 };
 
 use crate::crypto::*;
 
+use super::namespace::KeyScope;
 use super::SessionWrap;
 
 #[derive(Debug)]
@@ -28,21 +32,59 @@ pub(crate) enum InstructionCipher<VII: ValidatorIdentityIdentity> {
     NewKey {
         participants: Vec<(u16, VII)>,
         min_signers: u16,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        metadata: KeyMetadataUpdate,
+        namespace: Option<String>,
         pkid_response_oneshot: oneshot::Sender<Result<PkId, SessionError>>,
     },
     Sign {
         pkid: PkId,
         msg: Vec<u8>,
         tweak_data: Option<Vec<u8>>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
+        scope: KeyScope,
         signature_response_oneshot: oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>,
     },
+    Cancel {
+        target: CancelTarget,
+        requester: Option<VII>,
+        cancel_response_oneshot: oneshot::Sender<Result<Vec<VII>, SessionError>>,
+    },
     ListPkIds {
-        list_pkids_response_oneshot: oneshot::Sender<Vec<PkId>>,
+        scope: KeyScope,
+        list_pkids_response_oneshot: oneshot::Sender<Vec<KeyInfo<VII>>>,
     },
     PkTweakRequest {
         pkid: PkId,
         tweak_data: Option<Vec<u8>>,
-        pk_response_oneshot: oneshot::Sender<Result<GroupPublicKeyInfo, SessionError>>,
+        scope: KeyScope,
+        pk_response_oneshot: oneshot::Sender<Result<PublicKeyInfo<VII>, SessionError>>,
+    },
+    PublicKeyPackageRequest {
+        pkid: PkId,
+        scope: KeyScope,
+        package_response_oneshot: oneshot::Sender<Result<PublicKeyPackageInfo<VII>, SessionError>>,
+    },
+    UpdateKeyMetadata {
+        pkid: PkId,
+        update: KeyMetadataUpdate,
+        requester: Option<VII>,
+        scope: KeyScope,
+        metadata_response_oneshot: oneshot::Sender<Result<KeyMetadata<VII>, SessionError>>,
+    },
+    SetKeyState {
+        pkid: PkId,
+        state: KeyState,
+        requester: Option<VII>,
+        scope: KeyScope,
+        state_response_oneshot: oneshot::Sender<Result<(KeyState, KeyMetadata<VII>), SessionError>>,
+    },
+    DestroyKey {
+        pkid: PkId,
+        destroy_response_oneshot: oneshot::Sender<Result<KeyMetadata<VII>, SessionError>>,
     },
 }
 #[derive(Clone, Debug)]
@@ -58,25 +100,101 @@ pub(crate) enum Instruction<VII: ValidatorIdentityIdentity> {
         crypto_type: CryptoType,
         participants: Vec<(u16, VII)>,
         min_signers: u16,
+        // node that asked for the session, `None` for the operator and AutoDKG
+        requester: Option<VII>,
+        // every stage gives up once it has passed, `None` keeps the configured timeouts
+        deadline: Option<Deadline>,
+        // label, description and tags of the new key
+        metadata: KeyMetadataUpdate,
+        // namespace of the requester, the key is only visible there
+        namespace: Option<String>,
         pkid_response_oneshot: oneshot::Sender<Result<PkId, SessionError>>,
     },
     Sign {
         pkid: PkId,
         msg: Vec<u8>,
         tweak_data: Option<Vec<u8>>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
+        scope: KeyScope,
         signature_response_oneshot: oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>,
     },
+    Cancel {
+        target: CancelTarget,
+        // only the node that requested the session may cancel it, `None` for the operator
+        requester: Option<VII>,
+        // resolves to the participants that have to drop their session state
+        cancel_response_oneshot: oneshot::Sender<Result<Vec<VII>, SessionError>>,
+    },
     ListPkIds {
-        list_pkids_response_oneshot: oneshot::Sender<HashMap<CryptoType, Vec<PkId>>>,
+        scope: KeyScope,
+        list_pkids_response_oneshot: oneshot::Sender<HashMap<CryptoType, Vec<KeyInfo<VII>>>>,
     },
     PkTweakRequest {
         pkid: PkId,
         tweak_data: Option<Vec<u8>>,
-        pk_response_oneshot: oneshot::Sender<Result<GroupPublicKeyInfo, SessionError>>,
+        scope: KeyScope,
+        pk_response_oneshot: oneshot::Sender<Result<PublicKeyInfo<VII>, SessionError>>,
+    },
+    PublicKeyPackageRequest {
+        pkid: PkId,
+        scope: KeyScope,
+        package_response_oneshot: oneshot::Sender<Result<PublicKeyPackageInfo<VII>, SessionError>>,
+    },
+    UpdateKeyMetadata {
+        pkid: PkId,
+        update: KeyMetadataUpdate,
+        // only the node that asked for the key may edit it, `None` for the operator
+        requester: Option<VII>,
+        scope: KeyScope,
+        metadata_response_oneshot: oneshot::Sender<Result<KeyMetadata<VII>, SessionError>>,
+    },
+    // resolves to the previous state and the metadata after the change; `Destroyed` is
+    // only checked here, `DestroyKey` erases the key once a quorum of signers erased
+    // their shares
+    SetKeyState {
+        pkid: PkId,
+        state: KeyState,
+        requester: Option<VII>,
+        scope: KeyScope,
+        state_response_oneshot: oneshot::Sender<Result<(KeyState, KeyMetadata<VII>), SessionError>>,
     },
+    DestroyKey {
+        pkid: PkId,
+        destroy_response_oneshot: oneshot::Sender<Result<KeyMetadata<VII>, SessionError>>,
+    },
+}
+/// A DKG session or signing subsession that can still be cancelled.
+pub(crate) struct ActiveSession<VII: ValidatorIdentityIdentity> {
+    pub(crate) requester: Option<VII>,
+    pub(crate) participants: Vec<VII>,
+    // FROST sessions run in their own task; ECDSA sessions stop once their final
+    // channel is dropped
+    pub(crate) abort_handle: Option<AbortHandle>,
+    // kept so that retried ECDSA subsessions give up at the same time
+    pub(crate) deadline: Option<Deadline>,
+}
+impl<VII: ValidatorIdentityIdentity> ActiveSession<VII> {
+    pub(crate) fn check_requester(
+        &self,
+        target: &CancelTarget,
+        requester: &Option<VII>,
+    ) -> Result<(), SessionError> {
+        if let Some(requester) = requester {
+            if self.requester.as_ref() != Some(requester) {
+                return Err(SessionError::InvalidRequest(format!(
+                    "{} was not requested by {}",
+                    target.to_string(),
+                    requester.to_fmt_string()
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 macro_rules! new_session_wrap {
-    ($generic_type:ty, $crypto_variant:ident, $dkg_session_sender:expr, $signing_session_sender:expr, $session_inst_channels:expr, $keystore:expr, $base_path:expr) => {{
+    ($generic_type:ty, $crypto_variant:ident, $dkg_session_sender:expr, $signing_session_sender:expr, $session_inst_channels:expr, $keystore:expr, $base_path:expr, $monitor:expr, $scheduler:expr) => {{
         let (instruction_sender_cipher, instruction_receiver_cipher) =
             tokio::sync::mpsc::unbounded_channel();
 
@@ -86,6 +204,8 @@ macro_rules! new_session_wrap {
             instruction_receiver_cipher,
             $keystore.clone(),
             $base_path,
+            $monitor.clone(),
+            $scheduler.clone(),
         )?;
         assert!(session_wrap.check_serialize_deserialize().is_ok());
 
@@ -146,6 +266,8 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
 
         keystore: Arc<Keystore>,
         base_path: &PathBuf,
+        monitor: SessionMonitor<VII>,
+        scheduler: SignerScheduler<VII>,
     ) -> Result<Self, SessionError> {
         let mut session_inst_channels = HashMap::new();
         let mut dkg_in_final_channel_mapping = HashMap::new();
@@ -157,7 +279,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         new_session_wrap!(
             Secp256K1Sha256,
@@ -166,7 +290,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         new_session_wrap!(
             Secp256K1Sha256TR,
@@ -175,7 +301,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         new_session_wrap!(
             Ed448Shake256,
@@ -184,7 +312,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         new_session_wrap!(
             Ristretto255Sha512,
@@ -193,7 +323,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         new_session_wrap!(
             P256Sha256,
@@ -202,7 +334,9 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             signing_session_sender,
             session_inst_channels,
             keystore,
-            base_path
+            base_path,
+            monitor,
+            scheduler
         );
         let (instruction_sender_cipher, instruction_receiver_cipher) =
             tokio::sync::mpsc::unbounded_channel();
@@ -223,6 +357,8 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             instruction_receiver_cipher,
             keystore.clone(),
             base_path,
+            monitor,
+            scheduler,
         )?;
         assert!(session_wrap.check_serialize_deserialize().is_ok());
 
@@ -238,6 +374,17 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
             dkg_in_final_channel_receiver: dkg_in_final_channel_receiver_wrap,
         })
     }
+    fn session_inst_channel(
+        &self,
+        pkid: &PkId,
+    ) -> Result<&UnboundedSender<InstructionCipher<VII>>, SessionError> {
+        let crypto_type = pkid
+            .crypto_type()
+            .map_err(SessionError::CryptoTypeErrorNative)?;
+        self.session_inst_channels
+            .get(&crypto_type)
+            .ok_or(SessionError::CryptoTypeError(crypto_type))
+    }
     pub(crate) fn listening(mut self) {
         tokio::spawn(async move {
             loop {
@@ -272,6 +419,10 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                             crypto_type,
                             participants,
                             min_signers,
+                            requester,
+                            deadline,
+                            metadata,
+                            namespace,
                             pkid_response_oneshot,
                         } => {
                             let session_inst_channel =
@@ -280,6 +431,10 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                 .send(InstructionCipher::NewKey {
                                     participants,
                                     min_signers,
+                                    requester,
+                                    deadline,
+                                    metadata,
+                                    namespace,
                                     pkid_response_oneshot,
                                 })
                                 .unwrap();
@@ -288,6 +443,10 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                             pkid,
                             msg,
                             tweak_data,
+                            requester,
+                            deadline,
+                            selection,
+                            scope,
                             signature_response_oneshot,
                         } => {
                             let crypto_type = pkid.crypto_type();
@@ -307,6 +466,10 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                             pkid: pkid.clone(),
                                             msg: msg.clone(),
                                             tweak_data,
+                                            requester,
+                                            deadline,
+                                            selection,
+                                            scope,
                                             signature_response_oneshot,
                                         })
                                         .unwrap();
@@ -322,7 +485,38 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                 }
                             }
                         }
+                        Instruction::Cancel {
+                            target,
+                            requester,
+                            cancel_response_oneshot,
+                        } => {
+                            let session_inst_channel = target
+                                .crypto_type()
+                                .map_err(SessionError::CryptoTypeErrorNative)
+                                .and_then(|crypto_type| {
+                                    self.session_inst_channels
+                                        .get(&crypto_type)
+                                        .ok_or(SessionError::CryptoTypeError(crypto_type))
+                                });
+                            match session_inst_channel {
+                                Ok(session_inst_channel) => {
+                                    session_inst_channel
+                                        .send(InstructionCipher::Cancel {
+                                            target,
+                                            requester,
+                                            cancel_response_oneshot,
+                                        })
+                                        .unwrap();
+                                }
+                                Err(e) => {
+                                    if let Err(e) = cancel_response_oneshot.send(Err(e)) {
+                                        tracing::error!("Error sending cancel response: {:?}", e);
+                                    }
+                                }
+                            }
+                        }
                         Instruction::ListPkIds {
+                            scope,
                             list_pkids_response_oneshot,
                         } => {
                             let mut pkids = HashMap::new();
@@ -330,6 +524,7 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                 let (tx, rx) = oneshot::channel();
                                 inst_chan
                                     .send(InstructionCipher::ListPkIds {
+                                        scope: scope.clone(),
                                         list_pkids_response_oneshot: tx,
                                     })
                                     .unwrap();
@@ -343,6 +538,7 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                         Instruction::PkTweakRequest {
                             pkid,
                             tweak_data,
+                            scope,
                             pk_response_oneshot,
                         } => {
                             let crypto_type = pkid.crypto_type();
@@ -363,6 +559,7 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                         .send(InstructionCipher::PkTweakRequest {
                                             pkid: pkid.clone(),
                                             tweak_data,
+                                            scope,
                                             pk_response_oneshot,
                                         })
                                         .unwrap();
@@ -380,6 +577,92 @@ impl<VII: ValidatorIdentityIdentity> CoordiantorSessionManager<VII> {
                                 }
                             }
                         }
+                        Instruction::PublicKeyPackageRequest {
+                            pkid,
+                            scope,
+                            package_response_oneshot,
+                        } => match self.session_inst_channel(&pkid) {
+                            Ok(session_inst_channel) => {
+                                session_inst_channel
+                                    .send(InstructionCipher::PublicKeyPackageRequest {
+                                        pkid,
+                                        scope,
+                                        package_response_oneshot,
+                                    })
+                                    .unwrap();
+                            }
+                            Err(e) => {
+                                if let Err(e) = package_response_oneshot.send(Err(e)) {
+                                    tracing::error!("Error sending package response: {:?}", e);
+                                }
+                            }
+                        },
+                        Instruction::UpdateKeyMetadata {
+                            pkid,
+                            update,
+                            requester,
+                            scope,
+                            metadata_response_oneshot,
+                        } => match self.session_inst_channel(&pkid) {
+                            Ok(session_inst_channel) => {
+                                session_inst_channel
+                                    .send(InstructionCipher::UpdateKeyMetadata {
+                                        pkid,
+                                        update,
+                                        requester,
+                                        scope,
+                                        metadata_response_oneshot,
+                                    })
+                                    .unwrap();
+                            }
+                            Err(e) => {
+                                if let Err(e) = metadata_response_oneshot.send(Err(e)) {
+                                    tracing::error!("Error sending metadata response: {:?}", e);
+                                }
+                            }
+                        },
+                        Instruction::SetKeyState {
+                            pkid,
+                            state,
+                            requester,
+                            scope,
+                            state_response_oneshot,
+                        } => match self.session_inst_channel(&pkid) {
+                            Ok(session_inst_channel) => {
+                                session_inst_channel
+                                    .send(InstructionCipher::SetKeyState {
+                                        pkid,
+                                        state,
+                                        requester,
+                                        scope,
+                                        state_response_oneshot,
+                                    })
+                                    .unwrap();
+                            }
+                            Err(e) => {
+                                if let Err(e) = state_response_oneshot.send(Err(e)) {
+                                    tracing::error!("Error sending key state response: {:?}", e);
+                                }
+                            }
+                        },
+                        Instruction::DestroyKey {
+                            pkid,
+                            destroy_response_oneshot,
+                        } => match self.session_inst_channel(&pkid) {
+                            Ok(session_inst_channel) => {
+                                session_inst_channel
+                                    .send(InstructionCipher::DestroyKey {
+                                        pkid,
+                                        destroy_response_oneshot,
+                                    })
+                                    .unwrap();
+                            }
+                            Err(e) => {
+                                if let Err(e) = destroy_response_oneshot.send(Err(e)) {
+                                    tracing::error!("Error sending destroy response: {:?}", e);
+                                }
+                            }
+                        },
                     }
                 }
             }
@@ -0,0 +1,273 @@
+//! Live view of the DKG and signing sessions run by the coordinator, shared by the
+//! session tasks that update it and the request handlers that report it. Nodes only see
+//! the sessions of their namespace.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::namespace::KeyScope;
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+use crate::types::{
+    DkgSessionStatus, FinishedSessionStatus, SessionId, SessionKind, SessionStatusReport,
+    SigningSessionStatus, SubsessionId,
+};
+use crate::utils::now;
+
+// number of finished sessions kept for the history
+const FINISHED_SESSIONS_CAPACITY: usize = 100;
+
+struct SessionMonitorState<VII: ValidatorIdentityIdentity> {
+    dkg_sessions: BTreeMap<String, DkgSessionStatus<VII>>,
+    signing_sessions: BTreeMap<String, SigningSessionStatus<VII>>,
+    finished_sessions: VecDeque<(Option<String>, FinishedSessionStatus)>,
+    // namespace of the running sessions outside the default one, by session id
+    namespaces: HashMap<String, String>,
+}
+
+#[derive(Clone)]
+pub(crate) struct SessionMonitor<VII: ValidatorIdentityIdentity>(
+    Arc<Mutex<SessionMonitorState<VII>>>,
+);
+
+impl<VII: ValidatorIdentityIdentity> SessionMonitor<VII> {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(SessionMonitorState {
+            dkg_sessions: BTreeMap::new(),
+            signing_sessions: BTreeMap::new(),
+            finished_sessions: VecDeque::new(),
+            namespaces: HashMap::new(),
+        })))
+    }
+    fn update(&self, f: impl FnOnce(&mut SessionMonitorState<VII>)) {
+        match self.0.lock() {
+            Ok(mut state) => f(&mut state),
+            Err(e) => tracing::error!("Session monitor lock poisoned: {}", e),
+        }
+    }
+    fn finish(&self, finished: FinishedSessionStatus) {
+        self.update(|state| {
+            if state.finished_sessions.len() == FINISHED_SESSIONS_CAPACITY {
+                state.finished_sessions.pop_back();
+            }
+            let namespace = state.namespaces.remove(&finished.id);
+            state.finished_sessions.push_front((namespace, finished));
+        });
+    }
+    /// Puts the session or subsession `id` in `namespace`, sessions are in the default
+    /// namespace otherwise.
+    pub(crate) fn set_namespace(&self, id: String, namespace: Option<String>) {
+        if let Some(namespace) = namespace {
+            self.update(|state| {
+                state.namespaces.insert(id, namespace);
+            });
+        }
+    }
+    pub(crate) fn dkg_started(
+        &self,
+        session_id: &SessionId,
+        crypto_type: CryptoType,
+        min_signers: u16,
+        participants: Vec<VII>,
+        round: &str,
+    ) {
+        self.update(|state| {
+            state.dkg_sessions.insert(
+                session_id.to_string(),
+                DkgSessionStatus {
+                    session_id: session_id.to_string(),
+                    crypto_type,
+                    min_signers,
+                    participants,
+                    round: round.to_string(),
+                    responded: Vec::new(),
+                    started_at: now(),
+                },
+            );
+        });
+    }
+    pub(crate) fn dkg_round(&self, session_id: &SessionId, round: &str) {
+        self.update(|state| {
+            if let Some(status) = state.dkg_sessions.get_mut(&session_id.to_string()) {
+                status.round = round.to_string();
+                status.responded.clear();
+            }
+        });
+    }
+    pub(crate) fn dkg_responded(&self, session_id: &SessionId, identity: &VII) {
+        self.update(|state| {
+            if let Some(status) = state.dkg_sessions.get_mut(&session_id.to_string()) {
+                if !status.responded.contains(identity) {
+                    status.responded.push(identity.clone());
+                }
+            }
+        });
+    }
+    pub(crate) fn dkg_finished(&self, session_id: &SessionId, result: Result<PkId, String>) {
+        let mut status = None;
+        self.update(|state| status = state.dkg_sessions.remove(&session_id.to_string()));
+        if let Some(status) = status {
+            let (pkid, error) = match result {
+                Ok(pkid) => (Some(pkid), None),
+                Err(e) => (None, Some(e)),
+            };
+            self.finish(FinishedSessionStatus {
+                kind: SessionKind::Dkg,
+                id: status.session_id,
+                crypto_type: status.crypto_type,
+                pkid,
+                error,
+                started_at: status.started_at,
+                finished_at: now(),
+            });
+        }
+    }
+    /// The recently finished DKG session that generated `pkid`.
+    pub(crate) fn dkg_session_id(&self, pkid: &PkId) -> Option<String> {
+        let mut session_id = None;
+        self.update(|state| {
+            session_id = state
+                .finished_sessions
+                .iter()
+                .map(|(_, finished)| finished)
+                .find(|finished| {
+                    finished.kind == SessionKind::Dkg && finished.pkid.as_ref() == Some(pkid)
+                })
+                .map(|finished| finished.id.clone())
+        });
+        session_id
+    }
+    pub(crate) fn signing_started(
+        &self,
+        subsession_id: &SubsessionId,
+        pkid: &PkId,
+        crypto_type: CryptoType,
+        min_signers: u16,
+        round: &str,
+    ) {
+        self.update(|state| {
+            state.signing_sessions.insert(
+                subsession_id.to_string(),
+                SigningSessionStatus {
+                    subsession_id: subsession_id.to_string(),
+                    pkid: pkid.clone(),
+                    crypto_type,
+                    min_signers,
+                    round: round.to_string(),
+                    joined_participants: Vec::new(),
+                    responded: Vec::new(),
+                    started_at: now(),
+                },
+            );
+        });
+    }
+    pub(crate) fn signing_round(
+        &self,
+        subsession_id: &SubsessionId,
+        round: &str,
+        joined_participants: Vec<VII>,
+    ) {
+        self.update(|state| {
+            if let Some(status) = state.signing_sessions.get_mut(&subsession_id.to_string()) {
+                status.round = round.to_string();
+                status.joined_participants = joined_participants;
+                status.responded.clear();
+            }
+        });
+    }
+    pub(crate) fn signing_responded(&self, subsession_id: &SubsessionId, identity: &VII) {
+        self.update(|state| {
+            if let Some(status) = state.signing_sessions.get_mut(&subsession_id.to_string()) {
+                if !status.responded.contains(identity) {
+                    status.responded.push(identity.clone());
+                }
+            }
+        });
+    }
+    pub(crate) fn signing_finished(&self, subsession_id: &SubsessionId, error: Option<String>) {
+        let mut status = None;
+        self.update(|state| status = state.signing_sessions.remove(&subsession_id.to_string()));
+        if let Some(status) = status {
+            self.finish(FinishedSessionStatus {
+                kind: SessionKind::Signing,
+                id: status.subsession_id,
+                crypto_type: status.crypto_type,
+                pkid: Some(status.pkid),
+                error,
+                started_at: status.started_at,
+                finished_at: now(),
+            });
+        }
+    }
+    /// The sessions of the namespaces `scope` covers.
+    pub(crate) fn report(&self, scope: &KeyScope) -> SessionStatusReport<VII> {
+        let mut report = SessionStatusReport {
+            dkg_sessions: Vec::new(),
+            signing_sessions: Vec::new(),
+            finished_sessions: Vec::new(),
+        };
+        self.update(|state| {
+            let visible = |id: &String| scope.covers(&state.namespaces.get(id).cloned());
+            report.dkg_sessions = state
+                .dkg_sessions
+                .iter()
+                .filter(|(id, _)| visible(id))
+                .map(|(_, status)| status.clone())
+                .collect();
+            report.signing_sessions = state
+                .signing_sessions
+                .iter()
+                .filter(|(id, _)| visible(id))
+                .map(|(_, status)| status.clone())
+                .collect();
+            report.finished_sessions = state
+                .finished_sessions
+                .iter()
+                .filter(|(namespace, _)| scope.covers(namespace))
+                .map(|(_, finished)| finished.clone())
+                .collect();
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Participants;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_report_scope() {
+        let monitor = SessionMonitor::<PeerId>::new();
+        let participants =
+            Participants::<PeerId, u16>::new((1..=2u16).map(|i| (i, PeerId::random()))).unwrap();
+        let subsession_id = |msg: &[u8]| {
+            SubsessionId::new(
+                CryptoType::Ed25519,
+                2,
+                &participants,
+                msg.to_vec(),
+                None,
+                PkId::new(vec![0, 1]),
+            )
+            .unwrap()
+        };
+        let (default, wallet) = (subsession_id(b"default"), subsession_id(b"wallet"));
+        for id in [&default, &wallet] {
+            monitor.signing_started(id, &PkId::new(vec![0, 1]), CryptoType::Ed25519, 2, "round1");
+        }
+        monitor.set_namespace(wallet.to_string(), Some("wallet".to_string()));
+        monitor.signing_finished(&wallet, None);
+
+        let report = monitor.report(&KeyScope::Namespace(None));
+        assert_eq!(report.signing_sessions.len(), 1);
+        assert!(report.finished_sessions.is_empty());
+        let report = monitor.report(&KeyScope::Namespace(Some("wallet".to_string())));
+        assert!(report.signing_sessions.is_empty());
+        assert_eq!(report.finished_sessions[0].id, wallet.to_string());
+        let report = monitor.report(&KeyScope::All);
+        assert_eq!(
+            report.signing_sessions.len() + report.finished_sessions.len(),
+            2
+        );
+    }
+}
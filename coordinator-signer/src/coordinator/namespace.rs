@@ -0,0 +1,313 @@
+//! Namespaces separating the applications that share one coordinator.
+//!
+//! The namespaces are read from `namespaces.json` in the coordinator base path and can be
+//! reloaded through IPC. Each namespace lists the nodes of one application; those nodes
+//! only see and use the keys generated for their namespace, and their signing requests
+//! count against the namespace quotas. Nodes listed in no namespace, and every node while
+//! the file does not exist, share the default namespace. The key packages of a namespace
+//! are kept in their own keystore subdirectory.
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use super::policy::{check_rate, expire};
+use crate::crypto::{CryptoType, ValidatorIdentityIdentity};
+use crate::keystore::{Keystore, KeystoreManagement};
+use crate::types::error::SessionError;
+
+const MAX_NAME_LENGTH: usize = 64;
+
+// names become directory names
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Namespace<VII: ValidatorIdentityIdentity> {
+    pub(crate) name: String,
+    pub(crate) nodes: BTreeSet<VII>,
+    /// Signatures all nodes of the namespace together may ask for.
+    #[serde(default)]
+    pub(crate) max_per_minute: Option<usize>,
+    #[serde(default)]
+    pub(crate) max_per_day: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct NamespaceFile<VII: ValidatorIdentityIdentity> {
+    namespaces: Vec<Namespace<VII>>,
+}
+
+/// The keys a request may see and use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum KeyScope {
+    /// The operator and the coordinator itself.
+    All,
+    /// A node, `None` for the default namespace.
+    Namespace(Option<String>),
+}
+impl KeyScope {
+    pub(crate) fn covers(&self, namespace: &Option<String>) -> bool {
+        match self {
+            KeyScope::All => true,
+            KeyScope::Namespace(own) => own == namespace,
+        }
+    }
+}
+
+pub(crate) struct Namespaces<VII: ValidatorIdentityIdentity> {
+    path: PathBuf,
+    namespaces: HashMap<String, Namespace<VII>>,
+    // the namespace of every listed node
+    nodes: HashMap<VII, String>,
+    // times of the signatures each namespace asked for, kept for a day
+    usage: HashMap<String, VecDeque<u64>>,
+}
+
+impl<VII: ValidatorIdentityIdentity + for<'de> Deserialize<'de>> Namespaces<VII> {
+    /// Loads the namespaces from `path`, putting every node in the default namespace if
+    /// the file does not exist.
+    pub(crate) fn load(path: PathBuf) -> Result<Self, SessionError> {
+        let (namespaces, nodes) = Self::read(&path)?;
+        Ok(Self {
+            path,
+            namespaces,
+            nodes,
+            usage: HashMap::new(),
+        })
+    }
+    fn read(
+        path: &PathBuf,
+    ) -> Result<(HashMap<String, Namespace<VII>>, HashMap<VII, String>), SessionError> {
+        if !path.exists() {
+            return Ok((HashMap::new(), HashMap::new()));
+        }
+        let invalid = |reason: String| {
+            SessionError::DeserializationError(format!(
+                "invalid namespaces {}: {}",
+                path.display(),
+                reason
+            ))
+        };
+        let data = std::fs::read(path).map_err(crate::keystore::KeystoreError::from)?;
+        let file: NamespaceFile<VII> =
+            serde_json::from_slice(&data).map_err(|e| invalid(e.to_string()))?;
+        let mut namespaces = HashMap::new();
+        let mut nodes = HashMap::new();
+        for namespace in file.namespaces {
+            if !is_valid_name(&namespace.name) {
+                return Err(invalid(format!(
+                    "namespace name {:?} must be 1 to {} letters, digits, '-' or '_'",
+                    namespace.name, MAX_NAME_LENGTH
+                )));
+            }
+            for node in namespace.nodes.iter() {
+                if let Some(other) = nodes.insert(node.clone(), namespace.name.clone()) {
+                    return Err(invalid(format!(
+                        "node {} is in namespaces {} and {}",
+                        node.to_fmt_string(),
+                        other,
+                        namespace.name
+                    )));
+                }
+            }
+            let name = namespace.name.clone();
+            if namespaces.insert(name.clone(), namespace).is_some() {
+                return Err(invalid(format!("namespace {} is listed twice", name)));
+            }
+        }
+        Ok((namespaces, nodes))
+    }
+    /// Reads the namespaces again, keeping the current ones if the file is invalid.
+    /// Returns the number of namespaces besides the default one.
+    pub(crate) fn reload(&mut self) -> Result<usize, SessionError> {
+        let (namespaces, nodes) = Self::read(&self.path)?;
+        self.namespaces = namespaces;
+        self.nodes = nodes;
+        self.usage
+            .retain(|name, _| self.namespaces.contains_key(name));
+        Ok(self.namespaces.len())
+    }
+    pub(crate) fn namespace_of(&self, node: &VII) -> Option<String> {
+        self.nodes.get(node).cloned()
+    }
+    pub(crate) fn scope_of(&self, node: &VII) -> KeyScope {
+        KeyScope::Namespace(self.namespace_of(node))
+    }
+    /// Decides whether a signing request of `node` stays within the quotas of its
+    /// namespace. The request only counts once it is recorded with `record_sign`.
+    pub(crate) fn check_sign(&mut self, node: &VII) -> Result<(), SessionError> {
+        let Some(name) = self.nodes.get(node) else {
+            return Ok(());
+        };
+        let namespace = &self.namespaces[name];
+        let usage = self.usage.entry(name.clone()).or_default();
+        expire(usage, |time| *time);
+        check_rate(
+            usage.iter().copied(),
+            namespace.max_per_minute,
+            namespace.max_per_day,
+        )
+        .map_err(|reason| SessionError::PolicyDenied(format!("namespace {}: {}", name, reason)))?;
+        Ok(())
+    }
+    /// Counts a signing request of `node` that passed `check_sign`.
+    pub(crate) fn record_sign(&mut self, node: &VII) {
+        if let Some(name) = self.nodes.get(node) {
+            self.usage
+                .entry(name.clone())
+                .or_default()
+                .push_back(crate::utils::now());
+        }
+    }
+}
+
+/// The signing sessions of one crypto type, in one keystore file per namespace: the
+/// default namespace in `root/<crypto type>`, namespace `n` in
+/// `root/namespaces/n/<crypto type>`.
+pub(crate) struct NamespaceKeystores {
+    keystore: Arc<Keystore>,
+    root: PathBuf,
+    crypto_type: CryptoType,
+    files: HashMap<Option<String>, KeystoreManagement>,
+}
+impl NamespaceKeystores {
+    /// Opens the files of every namespace found under `root`, returning their contents.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        root: impl AsRef<Path>,
+        crypto_type: CryptoType,
+    ) -> Result<(Self, Vec<(Option<String>, Vec<u8>)>), SessionError> {
+        let mut keystores = Self {
+            keystore,
+            root: root.as_ref().to_path_buf(),
+            crypto_type,
+            files: HashMap::new(),
+        };
+        let mut namespaces = vec![None];
+        let namespaces_dir = keystores.root.join("namespaces");
+        if namespaces_dir.exists() {
+            for entry in
+                std::fs::read_dir(&namespaces_dir).map_err(crate::keystore::KeystoreError::from)?
+            {
+                let entry = entry.map_err(crate::keystore::KeystoreError::from)?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if is_valid_name(&name) && keystores.dir(&Some(name.clone())).exists() {
+                    namespaces.push(Some(name));
+                }
+            }
+        }
+        let mut contents = Vec::new();
+        for namespace in namespaces {
+            let (file, data) =
+                KeystoreManagement::new(keystores.keystore.clone(), keystores.dir(&namespace))?;
+            keystores.files.insert(namespace.clone(), file);
+            if let Some(data) = data {
+                contents.push((namespace, data));
+            }
+        }
+        Ok((keystores, contents))
+    }
+    fn dir(&self, namespace: &Option<String>) -> PathBuf {
+        match namespace {
+            Some(name) => self.root.join("namespaces").join(name),
+            None => self.root.clone(),
+        }
+        .join(self.crypto_type.to_string())
+    }
+    pub(crate) fn write(
+        &mut self,
+        namespace: &Option<String>,
+        data: &[u8],
+    ) -> Result<(), SessionError> {
+        if !self.files.contains_key(namespace) {
+            let (file, _) = KeystoreManagement::new(self.keystore.clone(), self.dir(namespace))?;
+            self.files.insert(namespace.clone(), file);
+        }
+        Ok(self.files.get_mut(namespace).unwrap().write(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_namespaces() {
+        let (alice, bob, carol) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let dir = std::env::temp_dir().join(format!("namespaces_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("namespaces.json");
+
+        let mut namespaces = Namespaces::<PeerId>::load(path.clone()).unwrap();
+        assert_eq!(namespaces.scope_of(&alice), KeyScope::Namespace(None));
+
+        let file = serde_json::json!({
+            "namespaces": [
+                { "name": "wallet", "nodes": [alice, bob], "max_per_minute": 1 },
+                { "name": "bridge", "nodes": [carol] },
+            ]
+        });
+        std::fs::write(&path, file.to_string()).unwrap();
+        assert_eq!(namespaces.reload().unwrap(), 2);
+        let wallet = namespaces.scope_of(&alice);
+        assert!(wallet.covers(&Some("wallet".to_string())));
+        assert!(!wallet.covers(&None));
+        assert!(!namespaces
+            .scope_of(&carol)
+            .covers(&Some("wallet".to_string())));
+        assert!(KeyScope::All.covers(&Some("bridge".to_string())));
+        // the quota is shared by the nodes of the namespace
+        namespaces.check_sign(&alice).unwrap();
+        namespaces.check_sign(&bob).unwrap();
+        namespaces.record_sign(&alice);
+        assert!(namespaces.check_sign(&bob).is_err());
+        namespaces.check_sign(&carol).unwrap();
+
+        let file = serde_json::json!({
+            "namespaces": [
+                { "name": "wallet", "nodes": [alice] },
+                { "name": "../bridge", "nodes": [carol] },
+            ]
+        });
+        std::fs::write(&path, file.to_string()).unwrap();
+        assert!(namespaces.reload().is_err());
+        let file = serde_json::json!({
+            "namespaces": [
+                { "name": "wallet", "nodes": [alice] },
+                { "name": "bridge", "nodes": [alice] },
+            ]
+        });
+        std::fs::write(&path, file.to_string()).unwrap();
+        assert!(namespaces.reload().is_err());
+        assert_eq!(namespaces.namespace_of(&carol).as_deref(), Some("bridge"));
+
+        let keystore = Arc::new(Keystore::new(b"namespaces", None).unwrap());
+        let root = dir.join("keystore");
+        let (mut keystores, contents) =
+            NamespaceKeystores::open(keystore.clone(), &root, CryptoType::Ed25519).unwrap();
+        assert!(contents.is_empty());
+        keystores.write(&None, b"default").unwrap();
+        keystores
+            .write(&Some("wallet".to_string()), b"wallet")
+            .unwrap();
+        let (_, mut contents) =
+            NamespaceKeystores::open(keystore, &root, CryptoType::Ed25519).unwrap();
+        contents.sort();
+        assert_eq!(
+            contents,
+            vec![
+                (None, b"default".to_vec()),
+                (Some("wallet".to_string()), b"wallet".to_vec())
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
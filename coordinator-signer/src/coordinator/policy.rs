@@ -0,0 +1,401 @@
+//! Declarative rules deciding which node may ask the coordinator for which signature.
+//!
+//! The rules are read from `signing_policy.json` in the coordinator base path and can
+//! be reloaded through IPC. Without the file every whitelisted node may sign with any
+//! key. With it, a sign request is allowed only if the first rule naming the node, the
+//! key and its crypto type also accepts the message, the tweak and the request rate.
+//!
+//! The requests counted against the rate limits are appended encrypted to
+//! `<dir>/signing_usage.store`, so that restarting the coordinator does not reset them.
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+use crate::keystore::{AppendStore, Keystore};
+use crate::types::error::SessionError;
+use crate::utils::now;
+
+const MINUTE: u64 = 60;
+const DAY: u64 = 24 * 60 * 60;
+// the usage store is rewritten with the last day of requests after this many appends
+const COMPACT_AFTER: usize = 1024;
+
+fn deserialize_hex_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<Vec<u8>>>, D::Error> {
+    let list = Option::<Vec<String>>::deserialize(deserializer)?;
+    list.map(|list| {
+        list.iter()
+            .map(|s| hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom))
+            .collect()
+    })
+    .transpose()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PolicyRule<VII: ValidatorIdentityIdentity> {
+    /// Nodes the rule applies to, every node if empty.
+    #[serde(default)]
+    pub(crate) nodes: BTreeSet<VII>,
+    /// Keys the rule applies to, every key if not set.
+    #[serde(default)]
+    pub(crate) pkids: Option<BTreeSet<PkId>>,
+    /// Crypto types the rule applies to, every type if not set.
+    #[serde(default)]
+    pub(crate) crypto_types: Option<Vec<CryptoType>>,
+    /// Hex tweaks a request must use, any tweak or none if not set.
+    #[serde(default, deserialize_with = "deserialize_hex_list")]
+    pub(crate) tweaks: Option<Vec<Vec<u8>>>,
+    /// Whether untweaked requests, signing with the base key, pass `tweaks`.
+    #[serde(default)]
+    pub(crate) allow_untweaked: bool,
+    #[serde(default)]
+    pub(crate) max_message_size: Option<usize>,
+    /// Hex prefixes one of which the message must start with, any message if not set.
+    #[serde(default, deserialize_with = "deserialize_hex_list")]
+    pub(crate) message_prefixes: Option<Vec<Vec<u8>>>,
+    #[serde(default)]
+    pub(crate) max_per_minute: Option<usize>,
+    #[serde(default)]
+    pub(crate) max_per_day: Option<usize>,
+}
+
+impl<VII: ValidatorIdentityIdentity> PolicyRule<VII> {
+    fn applies_to(&self, node: &VII, pkid: &PkId, crypto_type: CryptoType) -> bool {
+        (self.nodes.is_empty() || self.nodes.contains(node))
+            && self
+                .pkids
+                .as_ref()
+                .map_or(true, |pkids| pkids.contains(pkid))
+            && self
+                .crypto_types
+                .as_ref()
+                .map_or(true, |types| types.contains(&crypto_type))
+    }
+    fn check_request(&self, msg: &[u8], tweak_data: &Option<Vec<u8>>) -> Result<(), String> {
+        if let Some(max) = self.max_message_size {
+            if msg.len() > max {
+                return Err(format!(
+                    "message is {} bytes, at most {} are allowed",
+                    msg.len(),
+                    max
+                ));
+            }
+        }
+        if let Some(prefixes) = &self.message_prefixes {
+            if !prefixes.iter().any(|prefix| msg.starts_with(prefix)) {
+                return Err("message does not match any allowed prefix".to_string());
+            }
+        }
+        match (&self.tweaks, tweak_data) {
+            (Some(tweaks), Some(tweak)) if !tweaks.contains(tweak) => {
+                return Err(format!("tweak {} is not allowed", hex::encode(tweak)));
+            }
+            (Some(_), None) if !self.allow_untweaked => {
+                return Err("untweaked requests are not allowed".to_string());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PolicyFile<VII: ValidatorIdentityIdentity> {
+    rules: Vec<PolicyRule<VII>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UsageRecord<VII> {
+    node: VII,
+    time: u64,
+    pkid: PkId,
+    crypto_type: CryptoType,
+}
+
+pub(crate) struct SigningPolicy<VII: ValidatorIdentityIdentity> {
+    path: PathBuf,
+    rules: Option<Vec<PolicyRule<VII>>>,
+    // time and key of the requests allowed per node, kept for a day; they count against
+    // the rule that covers them now, so the limits hold across reloads
+    usage: HashMap<VII, VecDeque<(u64, PkId, CryptoType)>>,
+    store: AppendStore,
+    // records appended since the store was last rewritten
+    appended: usize,
+}
+
+// the rule deciding the requests of `node` for `pkid`
+fn first_rule<'a, VII: ValidatorIdentityIdentity>(
+    rules: &'a [PolicyRule<VII>],
+    node: &VII,
+    pkid: &PkId,
+    crypto_type: CryptoType,
+) -> Option<(usize, &'a PolicyRule<VII>)> {
+    rules
+        .iter()
+        .enumerate()
+        .find(|(_, rule)| rule.applies_to(node, pkid, crypto_type))
+}
+
+impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>> SigningPolicy<VII> {
+    /// Loads the rules from `path`, allowing everything if the file does not exist, and
+    /// the requests of the last day from `<dir>/signing_usage.store`.
+    pub(crate) fn load(
+        path: PathBuf,
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self, SessionError> {
+        let rules = Self::read(&path)?;
+        let (store, records) =
+            AppendStore::open(keystore, dir.as_ref().join("signing_usage.store"))?;
+        let mut usage: HashMap<VII, VecDeque<_>> = HashMap::new();
+        for record in records {
+            let record: UsageRecord<VII> = serde_json::from_slice(&record)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+            usage.entry(record.node).or_default().push_back((
+                record.time,
+                record.pkid,
+                record.crypto_type,
+            ));
+        }
+        let mut policy = Self {
+            path,
+            rules,
+            usage,
+            store,
+            appended: 0,
+        };
+        policy.compact()?;
+        Ok(policy)
+    }
+    fn encode(record: &UsageRecord<VII>) -> Result<Vec<u8>, SessionError> {
+        serde_json::to_vec(record).map_err(|e| SessionError::SerializationError(e.to_string()))
+    }
+    // drops the requests older than a day and rewrites the store with the others
+    fn compact(&mut self) -> Result<(), SessionError> {
+        let mut records = Vec::new();
+        for (node, usage) in self.usage.iter_mut() {
+            expire(usage, |(time, ..)| *time);
+            for (time, pkid, crypto_type) in usage.iter() {
+                records.push(Self::encode(&UsageRecord {
+                    node: node.clone(),
+                    time: *time,
+                    pkid: pkid.clone(),
+                    crypto_type: *crypto_type,
+                })?);
+            }
+        }
+        self.usage.retain(|_, usage| !usage.is_empty());
+        self.store.rewrite(records)?;
+        self.appended = 0;
+        Ok(())
+    }
+    fn read(path: &PathBuf) -> Result<Option<Vec<PolicyRule<VII>>>, SessionError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read(path).map_err(crate::keystore::KeystoreError::from)?;
+        let file: PolicyFile<VII> = serde_json::from_slice(&data).map_err(|e| {
+            SessionError::DeserializationError(format!(
+                "invalid signing policy {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Some(file.rules))
+    }
+    /// Reads the rules again, keeping the current ones if the file is invalid.
+    /// Returns the number of rules now in force, `None` if everything is allowed.
+    pub(crate) fn reload(&mut self) -> Result<Option<usize>, SessionError> {
+        self.rules = Self::read(&self.path)?;
+        if let Some(rules) = &self.rules {
+            // nodes no rule names any more cannot be limited
+            self.usage.retain(|node, _| {
+                rules
+                    .iter()
+                    .any(|rule| rule.nodes.is_empty() || rule.nodes.contains(node))
+            });
+            self.compact()?;
+        }
+        Ok(self.rules.as_ref().map(|rules| rules.len()))
+    }
+    /// Decides whether `node` may have `msg` signed with `pkid`. The request only
+    /// counts against the rate limits once it is recorded with `record_sign`.
+    pub(crate) fn check_sign(
+        &mut self,
+        node: &VII,
+        pkid: &PkId,
+        msg: &[u8],
+        tweak_data: &Option<Vec<u8>>,
+    ) -> Result<(), SessionError> {
+        let Some(rules) = &self.rules else {
+            return Ok(());
+        };
+        let crypto_type = pkid.crypto_type()?;
+        let denied = |reason: String| {
+            SessionError::PolicyDenied(format!(
+                "{} may not sign with {}: {}",
+                node.to_fmt_string(),
+                pkid,
+                reason
+            ))
+        };
+        let (index, rule) = first_rule(rules, node, pkid, crypto_type)
+            .ok_or_else(|| denied(format!("no rule covers the node for {} keys", crypto_type)))?;
+        rule.check_request(msg, tweak_data).map_err(denied)?;
+        let usage = self.usage.entry(node.clone()).or_default();
+        expire(usage, |(time, ..)| *time);
+        let times = usage
+            .iter()
+            .filter(|(_, pkid, crypto_type)| {
+                first_rule(rules, node, pkid, *crypto_type).map(|(i, _)| i) == Some(index)
+            })
+            .map(|(time, ..)| *time);
+        check_rate(times, rule.max_per_minute, rule.max_per_day).map_err(denied)?;
+        Ok(())
+    }
+    /// Counts a signing request of `node` with `pkid` that passed `check_sign`.
+    pub(crate) fn record_sign(&mut self, node: &VII, pkid: &PkId) -> Result<(), SessionError> {
+        if self.rules.is_none() {
+            return Ok(());
+        }
+        let crypto_type = pkid.crypto_type()?;
+        let time = now();
+        // counted in memory even if it cannot be stored
+        self.usage
+            .entry(node.clone())
+            .or_default()
+            .push_back((time, pkid.clone(), crypto_type));
+        self.store.append(&Self::encode(&UsageRecord {
+            node: node.clone(),
+            time,
+            pkid: pkid.clone(),
+            crypto_type,
+        })?)?;
+        self.appended += 1;
+        if self.appended >= COMPACT_AFTER {
+            self.compact()?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops the entries of `usage` older than a day, `time` gives the time of an entry.
+pub(crate) fn expire<T>(usage: &mut VecDeque<T>, time: impl Fn(&T) -> u64) {
+    let now = now();
+    while usage.front().is_some_and(|entry| time(entry) + DAY <= now) {
+        usage.pop_front();
+    }
+}
+
+/// Errors if one more request would exceed the limits, `times` are the times of the
+/// requests of the last day counted so far.
+pub(crate) fn check_rate(
+    times: impl Iterator<Item = u64>,
+    max_per_minute: Option<usize>,
+    max_per_day: Option<usize>,
+) -> Result<(), String> {
+    let now = now();
+    let (mut day, mut minute) = (0, 0);
+    for time in times {
+        day += 1;
+        if time + MINUTE > now {
+            minute += 1;
+        }
+    }
+    if let Some(max) = max_per_day.filter(|max| day >= *max) {
+        return Err(format!("limit of {} signatures per day reached", max));
+    }
+    if let Some(max) = max_per_minute.filter(|max| minute >= *max) {
+        return Err(format!("limit of {} signatures per minute reached", max));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_signing_policy() {
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let pkid = PkId::new(vec![CryptoType::Ed25519.into(), 1, 2, 3]);
+        let other_pkid = PkId::new(vec![CryptoType::Secp256k1.into(), 1, 2, 3]);
+        let dir = std::env::temp_dir().join(format!("signing_policy_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("signing_policy.json");
+
+        let keystore = Arc::new(Keystore::new(b"signing_policy", None).unwrap());
+        let mut policy =
+            SigningPolicy::<PeerId>::load(path.clone(), keystore.clone(), &dir).unwrap();
+        assert!(policy.check_sign(&bob, &other_pkid, b"msg", &None).is_ok());
+
+        let rules = serde_json::json!({
+            "rules": [{
+                "nodes": [alice],
+                "crypto_types": ["Ed25519"],
+                "tweaks": ["0x01"],
+                "allow_untweaked": true,
+                "max_message_size": 8,
+                "message_prefixes": ["ab"],
+                "max_per_minute": 2,
+            }]
+        });
+        std::fs::write(&path, rules.to_string()).unwrap();
+        assert_eq!(policy.reload().unwrap(), Some(1));
+
+        assert!(policy.check_sign(&alice, &pkid, &[0xab, 1], &None).is_ok());
+        // only recorded requests count
+        assert!(policy.check_sign(&alice, &pkid, &[0xab, 1], &None).is_ok());
+        policy.record_sign(&alice, &pkid).unwrap();
+        assert!(policy
+            .check_sign(&alice, &pkid, &[0xab, 2], &Some(vec![1]))
+            .is_ok());
+        policy.record_sign(&alice, &pkid).unwrap();
+        // over the rate limit
+        assert!(matches!(
+            policy.check_sign(&alice, &pkid, &[0xab, 3], &None),
+            Err(SessionError::PolicyDenied(_))
+        ));
+        // reloading and restarting keep the usage
+        policy.reload().unwrap();
+        assert!(policy.check_sign(&alice, &pkid, &[0xab, 3], &None).is_err());
+        drop(policy);
+        let mut policy =
+            SigningPolicy::<PeerId>::load(path.clone(), keystore.clone(), &dir).unwrap();
+        assert_eq!(policy.usage[&alice].len(), 2);
+        assert!(policy.check_sign(&alice, &pkid, &[0xab, 3], &None).is_err());
+        assert!(policy.check_sign(&alice, &pkid, &[0xcd], &None).is_err());
+        assert!(policy.check_sign(&alice, &pkid, &[0xab; 9], &None).is_err());
+        assert!(policy
+            .check_sign(&alice, &pkid, &[0xab], &Some(vec![2]))
+            .is_err());
+        assert!(policy
+            .check_sign(&alice, &other_pkid, &[0xab], &None)
+            .is_err());
+        assert!(policy.check_sign(&bob, &pkid, &[0xab], &None).is_err());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(policy.reload().is_err());
+        assert!(policy.check_sign(&bob, &pkid, &[0xab], &None).is_err());
+
+        // only the usage of nodes named by no rule is dropped
+        let rules = serde_json::json!({ "rules": [{ "nodes": [bob], "tweaks": ["0x01"] }] });
+        std::fs::write(&path, rules.to_string()).unwrap();
+        policy.reload().unwrap();
+        assert!(!policy.usage.contains_key(&alice));
+        drop(policy);
+        let mut policy = SigningPolicy::<PeerId>::load(path, keystore, &dir).unwrap();
+        assert!(!policy.usage.contains_key(&alice));
+        // a tweak list without allow_untweaked keeps nodes off the base key
+        assert!(policy.check_sign(&bob, &pkid, b"msg", &None).is_err());
+        assert!(policy
+            .check_sign(&bob, &pkid, b"msg", &Some(vec![1]))
+            .is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
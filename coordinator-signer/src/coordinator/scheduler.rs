@@ -0,0 +1,160 @@
+//! Liveness and latency of the signers as seen by the coordinator, fed by the swarm
+//! events and used by the signing sessions to ask the best signers first.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::crypto::ValidatorIdentityIdentity;
+
+// weight of the latest sample in the moving averages
+const SMOOTHING: f64 = 0.3;
+// assumed round trip for signers that have not answered a ping yet
+const UNKNOWN_RTT_MILLIS: f64 = 500.0;
+// added to the cost for every request a signer has not answered yet
+const IN_FLIGHT_PENALTY_MILLIS: f64 = 200.0;
+// lowest success rate used when scaling the cost, keeps it finite
+const MIN_SUCCESS_RATE: f64 = 0.05;
+
+fn smooth(average: f64, success: bool) -> f64 {
+    let sample = if success { 1.0 } else { 0.0 };
+    SMOOTHING * sample + (1.0 - SMOOTHING) * average
+}
+
+struct SignerStats {
+    connected: bool,
+    rtt: Option<f64>,
+    // share of the signing requests answered successfully
+    success_rate: f64,
+    // share of the pings answered, kept apart so that pings do not dilute failed signings
+    ping_rate: f64,
+    in_flight: usize,
+}
+impl Default for SignerStats {
+    fn default() -> Self {
+        Self {
+            connected: true,
+            rtt: None,
+            success_rate: 1.0,
+            ping_rate: 1.0,
+            in_flight: 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct SignerScheduler<VII: ValidatorIdentityIdentity>(
+    Arc<Mutex<HashMap<VII, SignerStats>>>,
+);
+
+impl<VII: ValidatorIdentityIdentity> SignerScheduler<VII> {
+    pub(crate) fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+    fn update(&self, identity: &VII, f: impl FnOnce(&mut SignerStats)) {
+        match self.0.lock() {
+            Ok(mut stats) => f(stats.entry(identity.clone()).or_default()),
+            Err(e) => tracing::error!("Signer scheduler lock poisoned: {}", e),
+        }
+    }
+    pub(crate) fn connection_changed(&self, identity: &VII, connected: bool) {
+        self.update(identity, |stats| {
+            stats.connected = connected;
+            if !connected {
+                stats.rtt = None;
+            }
+        });
+    }
+    pub(crate) fn ping(&self, identity: &VII, rtt: Option<Duration>) {
+        self.update(identity, |stats| {
+            stats.ping_rate = smooth(stats.ping_rate, rtt.is_some());
+            if let Some(rtt) = rtt {
+                let sample = rtt.as_secs_f64() * 1000.0;
+                stats.rtt = Some(match stats.rtt {
+                    Some(rtt) => SMOOTHING * sample + (1.0 - SMOOTHING) * rtt,
+                    None => sample,
+                });
+            }
+        });
+    }
+    pub(crate) fn request_sent(&self, identity: &VII) {
+        self.update(identity, |stats| stats.in_flight += 1);
+    }
+    pub(crate) fn request_finished(&self, identity: &VII, success: bool) {
+        self.update(identity, |stats| {
+            stats.in_flight = stats.in_flight.saturating_sub(1);
+            stats.success_rate = smooth(stats.success_rate, success);
+        });
+    }
+    /// How expensive it is to ask the signer right now, lower is better. Disconnected
+    /// signers cost the most, signers never seen are assumed connected but slow.
+    pub(crate) fn cost(&self, identity: &VII) -> u64 {
+        let stats = match self.0.lock() {
+            Ok(stats) => stats,
+            Err(e) => {
+                tracing::error!("Signer scheduler lock poisoned: {}", e);
+                return 0;
+            }
+        };
+        match stats.get(identity) {
+            Some(stats) if !stats.connected => u64::MAX,
+            Some(stats) => {
+                let millis = stats.rtt.unwrap_or(UNKNOWN_RTT_MILLIS)
+                    + stats.in_flight as f64 * IN_FLIGHT_PENALTY_MILLIS;
+                let rate = stats.success_rate * stats.ping_rate;
+                (millis / rate.max(MIN_SUCCESS_RATE)) as u64
+            }
+            None => UNKNOWN_RTT_MILLIS as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_signer_cost() {
+        let scheduler = SignerScheduler::new();
+        let (fast, slow, busy, flaky, offline) = (
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+        );
+        for peer in [&fast, &busy, &flaky] {
+            scheduler.ping(peer, Some(Duration::from_millis(10)));
+        }
+        scheduler.ping(&slow, Some(Duration::from_millis(300)));
+        scheduler.request_sent(&busy);
+        scheduler.request_sent(&busy);
+        for _ in 0..3 {
+            scheduler.request_sent(&flaky);
+            scheduler.request_finished(&flaky, false);
+        }
+        scheduler.connection_changed(&offline, false);
+
+        assert!(scheduler.cost(&fast) < scheduler.cost(&slow));
+        assert!(scheduler.cost(&fast) < scheduler.cost(&busy));
+        assert!(scheduler.cost(&fast) < scheduler.cost(&flaky));
+        assert!(scheduler.cost(&slow) < scheduler.cost(&PeerId::random()));
+        assert_eq!(scheduler.cost(&offline), u64::MAX);
+
+        scheduler.request_finished(&busy, true);
+        scheduler.request_finished(&busy, true);
+        assert_eq!(scheduler.cost(&busy), scheduler.cost(&fast));
+
+        // missed pings count until the signer answers again, without touching the
+        // signing success rate
+        let unreachable = PeerId::random();
+        scheduler.ping(&unreachable, Some(Duration::from_millis(10)));
+        scheduler.ping(&unreachable, None);
+        assert!(scheduler.cost(&fast) < scheduler.cost(&unreachable));
+        for _ in 0..50 {
+            scheduler.ping(&unreachable, Some(Duration::from_millis(10)));
+        }
+        assert!(scheduler.cost(&unreachable) < scheduler.cost(&flaky));
+        assert_eq!(scheduler.0.lock().unwrap()[&unreachable].success_rate, 1.0);
+    }
+}
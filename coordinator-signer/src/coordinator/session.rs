@@ -1,15 +1,21 @@
 mod dkg;
 mod signing;
-use super::manager::InstructionCipher;
+use super::key_metadata::KeyMetadataBook;
+use super::manager::{ActiveSession, InstructionCipher};
+use super::monitor::SessionMonitor;
+use super::namespace::{KeyScope, NamespaceKeystores};
+use super::scheduler::SignerScheduler;
 use super::{Cipher, PkId, PublicKeyPackage, ValidatorIdentityIdentity};
 use crate::crypto::{Identifier, Tweak, VerifyingKey};
-use crate::keystore::KeystoreManagement;
 use crate::types::{
     error::SessionError,
     message::{DKGRequestWrap, DKGResponseWrap, SigningRequestWrap, SigningResponseWrap},
-    Participants, SessionId, SignatureSuite,
+    CancelTarget, Deadline, Participants, SessionId, SignatureSuite, SignerSelection,
+};
+use crate::types::{
+    GroupPublicKeyInfo, KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState, PublicKeyInfo,
+    PublicKeyPackageInfo, SignatureSuiteInfo, SubsessionId,
 };
-use crate::types::{GroupPublicKeyInfo, SignatureSuiteInfo, SubsessionId};
 use common::Settings;
 use dkg::{CoordinatorDKGSession as DkgSession, DKGInfo};
 use futures::stream::FuturesUnordered;
@@ -33,6 +39,8 @@ pub(crate) struct SessionWrap<VII: ValidatorIdentityIdentity, C: Cipher> {
     )>,
 
     session_id_key_map: HashMap<SessionId, oneshot::Sender<Result<PkId, SessionError>>>,
+    // namespace and metadata requested for the keys of running DKG sessions
+    dkg_key_metadata: HashMap<SessionId, (Option<String>, KeyMetadataUpdate)>,
     subsession_id_signaturesuite_map:
         HashMap<SubsessionId, oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>>,
 
@@ -42,8 +50,13 @@ pub(crate) struct SessionWrap<VII: ValidatorIdentityIdentity, C: Cipher> {
         oneshot::Receiver<Result<SignatureSuite<VII, C>, (Option<SubsessionId>, SessionError)>>,
     >,
 
+    active_sessions: HashMap<CancelTarget, ActiveSession<VII>>,
+
     instruction_receiver: UnboundedReceiver<InstructionCipher<VII>>,
-    keystore_management: KeystoreManagement,
+    keystores: NamespaceKeystores,
+    key_metadata: KeyMetadataBook<VII>,
+    monitor: SessionMonitor<VII>,
+    scheduler: SignerScheduler<VII>,
 }
 impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
     pub(crate) fn new(
@@ -58,35 +71,53 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         instruction_receiver: UnboundedReceiver<InstructionCipher<VII>>,
         keystore: Arc<crate::keystore::Keystore>,
         base_path: &PathBuf,
+        monitor: SessionMonitor<VII>,
+        scheduler: SignerScheduler<VII>,
     ) -> Result<Self, SessionError> {
-        let path = base_path
-            .join(Settings::global().coordinator.keystore_path)
-            .join(C::crypto_type().to_string());
-        let (keystore_management, data) =
-            crate::keystore::KeystoreManagement::new(keystore, path).unwrap();
-        let signing_sessions = match data {
-            Some(data) => {
-                Self::deserialize_sessions(data.as_slice(), signing_session_sender.clone())?
+        let root = base_path.join(Settings::global().coordinator.keystore_path);
+        let mut key_metadata = KeyMetadataBook::open(
+            keystore.clone(),
+            root.join(C::crypto_type().to_string()).join("metadata"),
+        )?;
+        let (keystores, contents) = NamespaceKeystores::open(keystore, &root, C::crypto_type())?;
+        let mut signing_sessions = HashMap::new();
+        for (namespace, data) in contents {
+            let sessions =
+                Self::deserialize_sessions(data.as_slice(), signing_session_sender.clone())?;
+            for (pkid, session) in sessions {
+                tracing::info!("Coordinator restored pkid: {} from local keystore", pkid);
+                if !key_metadata.contains(&pkid) {
+                    let mut metadata = KeyMetadata::new(
+                        None,
+                        None,
+                        session.participants.values().cloned().collect(),
+                        session.min_signers,
+                    );
+                    metadata.namespace = namespace.clone();
+                    key_metadata.insert(pkid.clone(), metadata)?;
+                }
+                signing_sessions.insert(pkid, session);
             }
-            None => HashMap::new(),
-        };
-        for (pkid, _) in signing_sessions.iter() {
-            tracing::info!("Coordinator restored pkid: {} from local keystore", pkid);
         }
         Ok(Self {
             signing_sessions,
             dkg_session_sender,
             signing_session_sender,
             session_id_key_map: HashMap::new(),
+            dkg_key_metadata: HashMap::new(),
             dkg_futures: FuturesUnordered::new(),
             instruction_receiver,
             signing_futures: FuturesUnordered::new(),
             subsession_id_signaturesuite_map: HashMap::new(),
-            keystore_management,
+            active_sessions: HashMap::new(),
+            keystores,
+            key_metadata,
+            monitor,
+            scheduler,
         })
     }
     pub(crate) fn check_serialize_deserialize(&self) -> Result<(), SessionError> {
-        let serialized = self.serialize_sessions()?;
+        let serialized = self.serialize_sessions(|_| true)?;
         let deserialized =
             Self::deserialize_sessions(serialized.as_slice(), self.signing_session_sender.clone())?;
         assert_eq!(self.signing_sessions.len(), deserialized.len());
@@ -124,10 +155,11 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         }
         Ok(signing_sessions)
     }
-    fn serialize_sessions(&self) -> Result<Vec<u8>, SessionError> {
+    fn serialize_sessions(&self, filter: impl Fn(&PkId) -> bool) -> Result<Vec<u8>, SessionError> {
         let sessions = self
             .signing_sessions
             .iter()
+            .filter(|(pkid, _)| filter(pkid))
             .map(|(pkid, session)| match session.serialize() {
                 Ok(data) => Ok((pkid.clone(), data)),
                 Err(e) => Err(e),
@@ -135,10 +167,18 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
             .collect::<Result<HashMap<PkId, Vec<u8>>, SessionError>>()?;
         Ok(bincode::serialize(&sessions).unwrap())
     }
+    // every namespace keeps its keys in its own file
+    fn save_namespace(&mut self, namespace: &Option<String>) -> Result<(), SessionError> {
+        let sessions =
+            self.serialize_sessions(|pkid| &self.key_metadata.namespace_of(pkid) == namespace)?;
+        self.keystores.write(namespace, sessions.as_slice())
+    }
     async fn new_key<IT>(
         &mut self,
         participants: Vec<(IT, VII)>,
         min_signers: u16,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
         identifier_transform: impl Fn(IT) -> Result<C::Identifier, C::CryptoError> + 'static,
     ) -> Result<SessionId, SessionError> {
         //TODO: remove the following participants judgement
@@ -152,15 +192,27 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
             .map_err(|e| SessionError::CryptoError(e.to_string()))?;
         let participants = Participants::new(participants)?;
         participants.check_min_signers(min_signers)?;
+        Deadline::check(&deadline, "DKG request")?;
         let session = DkgSession::<VII, C>::new(
             participants.clone(),
             min_signers,
             self.dkg_session_sender.clone(),
+            self.monitor.clone(),
+            deadline,
         )?;
         let session_id = session.session_id().clone();
         let (tx, rx) = oneshot::channel();
         self.dkg_futures.push(rx);
-        session.start_dkg(tx).await;
+        let abort_handle = session.start_dkg(tx).await;
+        self.active_sessions.insert(
+            CancelTarget::Dkg(session_id),
+            ActiveSession {
+                requester,
+                participants: participants.values().cloned().collect(),
+                abort_handle: Some(abort_handle),
+                deadline,
+            },
+        );
         return Ok(session_id);
     }
     async fn sign<T: AsRef<[u8]>>(
@@ -168,15 +220,30 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         pkid_raw: T,
         msg: T,
         tweak_data: Option<T>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
+        scope: KeyScope,
         signature_response_oneshot: oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>,
     ) {
+        if let Err(e) = Deadline::check(&deadline, "signing request") {
+            if let Err(e) = signature_response_oneshot.send(Err(e)) {
+                tracing::error!("Error sending signature response: {:?}", e);
+            }
+            return;
+        }
         let pkid = PkId::new(pkid_raw.as_ref().to_vec());
-        let signing_session =
-            self.signing_sessions
-                .get_mut(&pkid)
-                .ok_or(SessionError::SignerSessionError(
-                    "Signing session not found".to_string(),
-                ));
+        let signing_session = self
+            .key_metadata
+            .check_scope(&pkid, &scope)
+            .and_then(|_| self.key_metadata.check_active(&pkid))
+            .and_then(|_| {
+                self.signing_sessions
+                    .get_mut(&pkid)
+                    .ok_or(SessionError::SignerSessionError(
+                        "Signing session not found".to_string(),
+                    ))
+            });
         let signing_session = match signing_session {
             Ok(signing_session) => signing_session,
             Err(e) => {
@@ -186,14 +253,112 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
                 return;
             }
         };
+        let participants = signing_session.participants.values().cloned().collect();
+        let namespace = self.key_metadata.namespace_of(&pkid);
         let (tx, rx) = oneshot::channel();
         self.signing_futures.push(rx);
-        signing_session
-            .start_new_signing(msg, tweak_data, tx, |subsession_id| {
-                self.subsession_id_signaturesuite_map
-                    .insert(subsession_id, signature_response_oneshot);
-            })
+        let mut new_subsession_id = None;
+        let abort_handle = signing_session
+            .start_new_signing(
+                msg,
+                tweak_data,
+                self.monitor.clone(),
+                self.scheduler.clone(),
+                requester.clone(),
+                deadline,
+                selection,
+                tx,
+                |subsession_id| {
+                    new_subsession_id = Some(subsession_id);
+                    self.monitor
+                        .set_namespace(subsession_id.to_string(), namespace);
+                    self.subsession_id_signaturesuite_map
+                        .insert(subsession_id, signature_response_oneshot);
+                },
+            )
             .await;
+        if let Some(subsession_id) = new_subsession_id {
+            self.active_sessions.insert(
+                CancelTarget::Signing(subsession_id),
+                ActiveSession {
+                    requester,
+                    participants,
+                    abort_handle,
+                    deadline,
+                },
+            );
+        }
+    }
+    fn cancel(
+        &mut self,
+        target: CancelTarget,
+        requester: Option<VII>,
+    ) -> Result<Vec<VII>, SessionError> {
+        let active_session =
+            self.active_sessions
+                .get(&target)
+                .ok_or(SessionError::CoordinatorSessionError(format!(
+                    "Session {} not found",
+                    target.to_string()
+                )))?;
+        active_session.check_requester(&target, &requester)?;
+        let active_session = self.active_sessions.remove(&target).unwrap();
+        if let Some(abort_handle) = active_session.abort_handle {
+            abort_handle.abort();
+        }
+        let error = SessionError::Cancelled(target.to_string());
+        match target {
+            CancelTarget::Dkg(session_id) => {
+                self.dkg_key_metadata.remove(&session_id);
+                self.monitor
+                    .dkg_finished(&session_id, Err(error.to_string()));
+                if let Some(oneshot) = self.session_id_key_map.remove(&session_id) {
+                    if let Err(e) = oneshot.send(Err(error)) {
+                        tracing::error!("Error sending pkid response: {:?}", e);
+                    }
+                }
+            }
+            CancelTarget::Signing(subsession_id) => {
+                self.monitor
+                    .signing_finished(&subsession_id, Some(error.to_string()));
+                if let Some(oneshot) = self.subsession_id_signaturesuite_map.remove(&subsession_id)
+                {
+                    if let Err(e) = oneshot.send(Err(error)) {
+                        tracing::error!("Error sending signature response: {:?}", e);
+                    }
+                }
+            }
+        }
+        Ok(active_session.participants)
+    }
+    fn set_key_state(
+        &mut self,
+        pkid: &PkId,
+        state: KeyState,
+        requester: &Option<VII>,
+        scope: &KeyScope,
+    ) -> Result<(KeyState, KeyMetadata<VII>), SessionError> {
+        self.key_metadata.check_scope(pkid, scope)?;
+        let metadata = self.key_metadata.check_transition(pkid, state, requester)?;
+        if state == KeyState::Destroyed {
+            // the key stops signing while its signers erase their shares
+            if metadata.state != KeyState::Active {
+                return Ok((metadata.state, metadata));
+            }
+            return Ok((
+                metadata.state,
+                self.key_metadata.set_state(pkid, KeyState::Disabled)?,
+            ));
+        }
+        Ok((metadata.state, self.key_metadata.set_state(pkid, state)?))
+    }
+    // the metadata stays behind with the key's state for the audit trail
+    fn destroy_key(&mut self, pkid: &PkId) -> Result<KeyMetadata<VII>, SessionError> {
+        if self.signing_sessions.remove(pkid).is_some() {
+            let namespace = self.key_metadata.namespace_of(pkid);
+            self.save_namespace(&namespace)?;
+        }
+        self.key_metadata.set_state(pkid, KeyState::Destroyed)
     }
     pub(crate) fn listening(mut self) {
         tokio::spawn(async move {
@@ -225,15 +390,25 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
             InstructionCipher::NewKey {
                 participants,
                 min_signers,
+                requester,
+                deadline,
+                metadata,
+                namespace,
                 pkid_response_oneshot,
             } => {
                 let session_id = self
-                    .new_key(participants, min_signers, |id| C::Identifier::from_u16(id))
+                    .new_key(participants, min_signers, requester, deadline, |id| {
+                        C::Identifier::from_u16(id)
+                    })
                     .await;
                 match session_id {
                     Ok(session_id) => {
+                        self.monitor
+                            .set_namespace(session_id.to_string(), namespace.clone());
                         self.session_id_key_map
                             .insert(session_id, pkid_response_oneshot);
+                        self.dkg_key_metadata
+                            .insert(session_id, (namespace, metadata));
                     }
                     Err(e) => {
                         if let Err(e) = pkid_response_oneshot.send(Err(e)) {
@@ -246,30 +421,69 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
                 pkid,
                 msg,
                 tweak_data,
+                requester,
+                deadline,
+                selection,
+                scope,
                 signature_response_oneshot,
             } => {
-                self.sign(pkid.to_bytes(), msg, tweak_data, signature_response_oneshot)
-                    .await;
+                self.sign(
+                    pkid.to_bytes(),
+                    msg,
+                    tweak_data,
+                    requester,
+                    deadline,
+                    selection,
+                    scope,
+                    signature_response_oneshot,
+                )
+                .await;
+            }
+            InstructionCipher::Cancel {
+                target,
+                requester,
+                cancel_response_oneshot,
+            } => {
+                let r = self.cancel(target, requester);
+                if let Err(e) = cancel_response_oneshot.send(r) {
+                    tracing::error!("Error sending cancel response: {:?}", e);
+                }
             }
             InstructionCipher::ListPkIds {
+                scope,
                 list_pkids_response_oneshot,
             } => {
-                let pkids = self.signing_sessions.keys().cloned().collect::<Vec<_>>();
-                if let Err(e) = list_pkids_response_oneshot.send(pkids) {
+                let keys = self
+                    .signing_sessions
+                    .keys()
+                    .filter_map(|pkid| {
+                        Some(KeyInfo {
+                            pkid: pkid.clone(),
+                            metadata: self.key_metadata.get(pkid).ok()?,
+                        })
+                    })
+                    .filter(|key| scope.covers(&key.metadata.namespace))
+                    .collect::<Vec<_>>();
+                if let Err(e) = list_pkids_response_oneshot.send(keys) {
                     tracing::error!("Error sending pkids response: {:?}", e);
                 }
             }
             InstructionCipher::PkTweakRequest {
                 pkid,
                 tweak_data,
+                scope,
                 pk_response_oneshot,
             } => {
                 let r = self
-                    .signing_sessions
-                    .get(&pkid)
-                    .ok_or(SessionError::SignerSessionError(
-                        "Signing session not found".to_string(),
-                    ))
+                    .key_metadata
+                    .check_scope(&pkid, &scope)
+                    .and_then(|_| {
+                        self.signing_sessions
+                            .get(&pkid)
+                            .ok_or(SessionError::SignerSessionError(
+                                "Signing session not found".to_string(),
+                            ))
+                    })
                     .and_then(|session| {
                         let group_public_key_tweak = session
                             .public_key_package
@@ -278,12 +492,89 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
                             .verifying_key()
                             .serialize_frost()
                             .map_err(|e| SessionError::CryptoError(e.to_string()))?;
-                        Ok(GroupPublicKeyInfo::new(group_public_key_tweak, tweak_data))
+                        Ok(PublicKeyInfo {
+                            group_public_key_info: GroupPublicKeyInfo::new(
+                                group_public_key_tweak,
+                                tweak_data,
+                            ),
+                            metadata: self.key_metadata.get(&pkid)?,
+                        })
                     });
                 if let Err(e) = pk_response_oneshot.send(r) {
                     tracing::error!("Error sending pk response: {:?}", e);
                 }
             }
+            InstructionCipher::PublicKeyPackageRequest {
+                pkid,
+                scope,
+                package_response_oneshot,
+            } => {
+                let r = self
+                    .key_metadata
+                    .check_scope(&pkid, &scope)
+                    .and_then(|_| {
+                        self.signing_sessions
+                            .get(&pkid)
+                            .ok_or(SessionError::SignerSessionError(
+                                "Signing session not found".to_string(),
+                            ))
+                    })
+                    .and_then(|session| {
+                        Ok(PublicKeyPackageInfo {
+                            pkid: pkid.clone(),
+                            crypto_type: C::crypto_type(),
+                            public_key_package: PublicKeyPackage::serialize_binary(
+                                &session.public_key_package,
+                            )
+                            .map_err(|e| SessionError::CryptoError(e.to_string()))?,
+                            min_signers: session.min_signers,
+                            participants: session
+                                .participants
+                                .iter()
+                                .map(|(k, v)| (k.to_bytes(), v.clone()))
+                                .collect(),
+                        })
+                    });
+                if let Err(e) = package_response_oneshot.send(r) {
+                    tracing::error!("Error sending package response: {:?}", e);
+                }
+            }
+            InstructionCipher::UpdateKeyMetadata {
+                pkid,
+                update,
+                requester,
+                scope,
+                metadata_response_oneshot,
+            } => {
+                let r = self
+                    .key_metadata
+                    .check_scope(&pkid, &scope)
+                    .and_then(|_| self.key_metadata.update(&pkid, update, &requester));
+                if let Err(e) = metadata_response_oneshot.send(r) {
+                    tracing::error!("Error sending metadata response: {:?}", e);
+                }
+            }
+            InstructionCipher::SetKeyState {
+                pkid,
+                state,
+                requester,
+                scope,
+                state_response_oneshot,
+            } => {
+                let r = self.set_key_state(&pkid, state, &requester, &scope);
+                if let Err(e) = state_response_oneshot.send(r) {
+                    tracing::error!("Error sending key state response: {:?}", e);
+                }
+            }
+            InstructionCipher::DestroyKey {
+                pkid,
+                destroy_response_oneshot,
+            } => {
+                let r = self.destroy_key(&pkid);
+                if let Err(e) = destroy_response_oneshot.send(r) {
+                    tracing::error!("Error sending destroy response: {:?}", e);
+                }
+            }
         }
     }
     async fn handle_dkg_future(
@@ -292,11 +583,32 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
     ) -> Result<(), SessionError> {
         match dkg_info {
             Ok(dkg_info) => {
+                let requester = self
+                    .active_sessions
+                    .remove(&CancelTarget::Dkg(dkg_info.session_id))
+                    .and_then(|session| session.requester);
+                let pkid = dkg_info
+                    .public_key_package
+                    .pkid()
+                    .map_err(|e| SessionError::CryptoError(e.to_string()))?;
+                let mut metadata = KeyMetadata::new(
+                    Some(crate::utils::now()),
+                    requester,
+                    dkg_info.participants.values().cloned().collect(),
+                    dkg_info.min_signers,
+                );
+                if let Some((namespace, update)) =
+                    self.dkg_key_metadata.remove(&dkg_info.session_id)
+                {
+                    metadata.namespace = namespace;
+                    metadata.apply(update);
+                }
+                let namespace = metadata.namespace.clone();
+                if let Err(e) = self.key_metadata.insert(pkid.clone(), metadata) {
+                    tracing::error!("Error storing metadata of key {}: {:?}", pkid, e);
+                }
                 self.signing_sessions.insert(
-                    dkg_info
-                        .public_key_package
-                        .pkid()
-                        .map_err(|e| SessionError::CryptoError(e.to_string()))?,
+                    pkid,
                     SigningSession::new(
                         dkg_info.public_key_package.clone(),
                         dkg_info.min_signers,
@@ -304,8 +616,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
                         self.signing_session_sender.clone(),
                     )?,
                 );
-                let sessions = self.serialize_sessions()?;
-                self.keystore_management.write(sessions.as_slice())?;
+                self.save_namespace(&namespace)?;
                 let oneshot = self.session_id_key_map.remove(&dkg_info.session_id);
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(
@@ -324,6 +635,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
             }
             Err((session_id, e)) => {
                 tracing::error!("Error in DKG future: {:?}", e);
+                self.active_sessions.remove(&CancelTarget::Dkg(session_id));
+                self.dkg_key_metadata.remove(&session_id);
                 let oneshot = self.session_id_key_map.remove(&session_id);
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(Err(e)) {
@@ -346,6 +659,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         match signing_session {
             Ok(signature_suite) => {
                 let subsession_id = signature_suite.subsession_id;
+                self.active_sessions
+                    .remove(&CancelTarget::Signing(subsession_id));
                 let oneshot = self.subsession_id_signaturesuite_map.remove(&subsession_id);
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(
@@ -369,6 +684,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
             }
             Err((Some(subsession_id), e)) => {
                 tracing::error!("Error in signing future: {:?}", e);
+                self.active_sessions
+                    .remove(&CancelTarget::Signing(subsession_id));
                 let oneshot = self.subsession_id_signaturesuite_map.remove(&subsession_id);
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(Err(e)) {
@@ -1,9 +1,13 @@
 use std::collections::BTreeMap;
 
 use futures::stream::{FuturesUnordered, StreamExt};
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::{
+    sync::{mpsc::UnboundedSender, oneshot},
+    task::AbortHandle,
+};
 
 use super::SessionId;
+use super::SessionMonitor;
 use super::{DKGRequestWrap, DKGResponseWrap};
 use crate::crypto::*;
 use crate::{
@@ -11,7 +15,7 @@ use crate::{
     types::{
         error::SessionError,
         message::{DKGBaseMessage, DKGRequest, DKGRequestStage, DKGResponse, DKGResponseStage},
-        Participants,
+        Deadline, Participants,
     },
 };
 
@@ -37,6 +41,8 @@ pub(crate) struct CoordinatorDKGSession<VII: ValidatorIdentityIdentity, C: Ciphe
     dkg_state: CoordinatorDKGState<C>,
     participants: Participants<VII, C::Identifier>,
     dkg_sender: UnboundedSender<(DKGRequestWrap<VII>, oneshot::Sender<DKGResponseWrap<VII>>)>,
+    monitor: SessionMonitor<VII>,
+    deadline: Option<Deadline>,
 }
 #[derive(Debug, Clone)]
 pub(crate) struct DKGInfo<VII: ValidatorIdentityIdentity, C: Cipher> {
@@ -50,6 +56,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
         participants: Participants<VII, C::Identifier>,
         min_signers: u16,
         dkg_sender: UnboundedSender<(DKGRequestWrap<VII>, oneshot::Sender<DKGResponseWrap<VII>>)>,
+        monitor: SessionMonitor<VII>,
+        deadline: Option<Deadline>,
     ) -> Result<Self, SessionError> {
         participants.check_min_signers(min_signers)?;
         let session_id = SessionId::new(C::crypto_type(), min_signers, &participants)?;
@@ -61,6 +69,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
             dkg_state,
             participants: participants,
             dkg_sender,
+            monitor,
+            deadline,
         })
     }
     fn match_base_info(
@@ -94,9 +104,16 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
     pub(crate) async fn start_dkg(
         mut self,
         response_sender: oneshot::Sender<Result<DKGInfo<VII, C>, (SessionId, SessionError)>>,
-    ) {
+    ) -> AbortHandle {
         tokio::spawn(async move {
             tracing::debug!("Starting DKG session with id: {:?}", self.session_id);
+            self.monitor.dkg_started(
+                &self.session_id,
+                C::crypto_type(),
+                self.min_signers,
+                self.participants.values().cloned().collect(),
+                self.dkg_state.round(),
+            );
             let result = 'out: loop {
                 if let Some(public_key_package) = self.dkg_state.completed() {
                     break 'out Ok(DKGInfo {
@@ -106,6 +123,11 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                         public_key_package,
                     });
                 }
+                if let Err(e) = Deadline::check(&self.deadline, "DKG session") {
+                    break 'out Err(e);
+                }
+                self.monitor
+                    .dkg_round(&self.session_id, self.dkg_state.round());
                 tracing::debug!("Starting new DKG round");
                 let mut futures = FuturesUnordered::new();
                 match self.split_into_single_requests() {
@@ -141,12 +163,23 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                 tracing::debug!("Waiting for {} responses", self.participants.len());
                 for i in 0..self.participants.len() {
                     tracing::debug!("Waiting for response {}/{}", i + 1, self.participants.len());
-                    let response = futures.next().await;
+                    let response =
+                        match Deadline::wait(&self.deadline, "DKG session", futures.next()).await {
+                            Ok(response) => response,
+                            Err(e) => {
+                                tracing::error!("{}", e);
+                                break 'out Err(e);
+                            }
+                        };
                     match response {
                         Some(Ok(response)) => {
                             tracing::debug!("Received valid response: {:?}", response.clone());
                             match DKGResponse::<VII, C>::from(response) {
                                 Ok(response) => {
+                                    self.monitor.dkg_responded(
+                                        &self.session_id,
+                                        &response.base_info.identity,
+                                    );
                                     responses
                                         .insert(response.base_info.identifier.clone(), response);
                                 }
@@ -200,10 +233,18 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                     )));
                 }
             };
+            self.monitor.dkg_finished(
+                &self.session_id,
+                match &result {
+                    Ok(info) => info.public_key_package.pkid().map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                },
+            );
             if let Err(e) = response_sender.send(result.map_err(|e| (self.session_id.clone(), e))) {
                 tracing::error!("Failed to send response: {:?}", e);
             }
-        });
+        })
+        .abort_handle()
     }
     fn split_into_single_requests(&self) -> Result<Vec<DKGRequest<VII, C>>, SessionError> {
         match self.dkg_state.clone() {
@@ -219,6 +260,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                             identifier: id.clone(),
                             identity: identity.clone(),
                             session_id: self.session_id.clone(),
+                            deadline: self.deadline,
                         },
                         stage: DKGRequestStage::Part1,
                     })
@@ -238,6 +280,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                             identifier: id.clone(),
                             identity: identity.clone(),
                             session_id: self.session_id.clone(),
+                            deadline: self.deadline,
                         },
                         stage: DKGRequestStage::Part2 {
                             round1_package_map: round1_package_map,
@@ -280,6 +323,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher + PartialEq + Eq> CoordinatorDKGS
                             identifier: id.clone(),
                             identity: identity.clone(),
                             session_id: self.session_id.clone(),
+                            deadline: self.deadline,
                         },
                         stage: DKGRequestStage::GenPublicKey {
                             round1_package_map: round1_package_map.clone(),
@@ -408,6 +452,14 @@ impl<C: Cipher> CoordinatorDKGState<C> {
     pub(crate) fn new() -> Self {
         Self::Part1 {}
     }
+    fn round(&self) -> &'static str {
+        match self {
+            CoordinatorDKGState::Part1 => "part1",
+            CoordinatorDKGState::Part2 { .. } => "part2",
+            CoordinatorDKGState::GenPublicKey { .. } => "gen_public_key",
+            CoordinatorDKGState::Completed { .. } => "completed",
+        }
+    }
     fn completed(&self) -> Option<C::PublicKeyPackage> {
         match self {
             CoordinatorDKGState::Completed { public_key } => Some(public_key.clone()),
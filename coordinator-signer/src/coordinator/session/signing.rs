@@ -1,13 +1,16 @@
 use serde::{Deserialize, Serialize};
 use subsession::CoordinatorSubsession;
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::{
+    sync::{mpsc::UnboundedSender, oneshot},
+    task::AbortHandle,
+};
 
 use crate::{
     crypto::{PkId, PublicKeyPackage, ValidatorIdentityIdentity},
     types::{
         error::SessionError,
         message::{SigningRequest, SigningResponse},
-        Participants, SignatureSuite, SubsessionId,
+        Deadline, Participants, SignatureSuite, SignerSelection, SubsessionId,
     },
 };
 #[derive(Serialize, Deserialize)]
@@ -18,7 +21,7 @@ struct CoordinatorSigningSessionInfo {
     pub(crate) participants: Vec<u8>,
 }
 
-use super::{Cipher, SigningRequestWrap, SigningResponseWrap};
+use super::{Cipher, SessionMonitor, SignerScheduler, SigningRequestWrap, SigningResponseWrap};
 
 mod subsession;
 pub(crate) struct CoordinatorSigningSession<VII: ValidatorIdentityIdentity, C: Cipher> {
@@ -87,11 +90,16 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSigningSession<VII, C
         &mut self,
         msg: T,
         tweak_data: Option<T>,
+        monitor: SessionMonitor<VII>,
+        scheduler: SignerScheduler<VII>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
         response: oneshot::Sender<
             Result<SignatureSuite<VII, C>, (Option<SubsessionId>, SessionError)>,
         >,
         callback: impl FnOnce(SubsessionId),
-    ) {
+    ) -> Option<AbortHandle> {
         let msg = msg.as_ref().to_vec();
         let subsession_result = CoordinatorSubsession::<VII, C>::new(
             self.pkid.clone(),
@@ -101,18 +109,24 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSigningSession<VII, C
             msg.clone(),
             tweak_data.map(|s| s.as_ref().to_vec()),
             self.signing_sender.clone(),
+            monitor,
+            scheduler,
+            requester,
+            deadline,
+            selection,
         );
         match subsession_result {
             Ok(subsession) => {
                 let subsession_id = subsession.subsession_id();
                 callback(subsession_id);
-                subsession.start_signing(response).await
+                Some(subsession.start_signing(response).await)
             }
             Err(e) => {
                 if let Err(e) = response.send(Err((None, e))) {
                     tracing::error!("Failed to send error response: {:?}", e);
                 }
+                None
             }
-        };
+        }
     }
 }
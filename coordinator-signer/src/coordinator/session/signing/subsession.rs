@@ -1,24 +1,28 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, VecDeque},
     time::Duration,
 };
 
 use common::Settings;
+use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
-use futures::StreamExt;
-use tokio::sync::{
-    mpsc::{unbounded_channel, UnboundedSender},
-    oneshot,
+use futures::{FutureExt, StreamExt};
+use tokio::{
+    sync::{mpsc::UnboundedSender, oneshot},
+    task::AbortHandle,
 };
 
 use crate::{
     crypto::*,
-    types::message::{SigningBaseMessage, SigningRequestStage, SigningResponseStage},
+    types::{
+        message::{SigningBaseMessage, SigningRequestStage, SigningResponseStage},
+        Deadline, SelectedIdentifiers, SignerSelection,
+    },
 };
 
 use super::{
-    Cipher, Participants, PkId, SessionError, SignatureSuite, SigningRequest, SigningRequestWrap,
-    SigningResponse, SigningResponseWrap, SubsessionId,
+    Cipher, Participants, PkId, SessionError, SessionMonitor, SignatureSuite, SignerScheduler,
+    SigningRequest, SigningRequestWrap, SigningResponse, SigningResponseWrap, SubsessionId,
 };
 
 #[derive(Debug, Clone)]
@@ -47,6 +51,11 @@ pub(crate) struct CoordinatorSubsession<VII: ValidatorIdentityIdentity, C: Ciphe
         SigningRequestWrap<VII>,
         oneshot::Sender<SigningResponseWrap<VII>>,
     )>,
+    monitor: SessionMonitor<VII>,
+    scheduler: SignerScheduler<VII>,
+    requester: Option<VII>,
+    deadline: Option<Deadline>,
+    selection: SelectedIdentifiers<C::Identifier>,
 }
 impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
     pub(crate) fn new(
@@ -60,7 +69,16 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
             SigningRequestWrap<VII>,
             oneshot::Sender<SigningResponseWrap<VII>>,
         )>,
+        monitor: SessionMonitor<VII>,
+        scheduler: SignerScheduler<VII>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
     ) -> Result<Self, SessionError> {
+        let selection = match selection {
+            Some(selection) => selection.resolve(&participants, min_signers)?,
+            None => SelectedIdentifiers::default(),
+        };
         let subsession_id = SubsessionId::new(
             C::crypto_type(),
             min_signers,
@@ -79,6 +97,11 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
             state: CoordinatorSigningState::Round1,
             signing_sender: sender,
             message: sign_message,
+            monitor,
+            scheduler,
+            requester,
+            deadline,
+            selection,
         })
     }
     pub(crate) async fn start_signing(
@@ -86,105 +109,116 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
         response_sender: oneshot::Sender<
             Result<SignatureSuite<VII, C>, (Option<SubsessionId>, SessionError)>,
         >,
-    ) {
+    ) -> AbortHandle {
         tokio::spawn(async move {
             tracing::debug!("Starting Signing session with id: {:?}", self.subsession_id);
+            self.monitor.signing_started(
+                &self.subsession_id,
+                &self.pkid,
+                C::crypto_type(),
+                self.min_signers,
+                "round1",
+            );
 
-            let mut futures = FuturesUnordered::new();
-            let mut round1_sent = 0;
-            for request in self.split_into_single_requests() {
-                tracing::debug!("Sending Signing request: {:?}", request);
-                let (tx, rx) = oneshot::channel();
-                futures.push(rx);
-                let request_wrap = SigningRequestWrap::from(request);
-                match request_wrap {
-                    Ok(request_wrap) => {
-                        if let Err(e) = self.signing_sender.send((request_wrap, tx)) {
-                            tracing::error!(
-                                "Failed to send signing request: {:?}, but continue",
-                                e
-                            );
-                        } else {
-                            round1_sent += 1;
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to get signing request: {:?}", e);
-                    }
-                }
-            }
-            tracing::debug!("Sent {} round 1 requests", round1_sent);
-            let (event_channel_tx, mut event_channel_rx) = unbounded_channel();
-            // round1 thread
-            let handle = tokio::spawn(async move {
-                for _ in 0..round1_sent {
-                    let response = tokio::select! {
-                        response = futures.next() => response.unwrap(),
-                        _ = tokio::time::sleep(Duration::from_secs(Settings::global().session.signing_round1_timeout)) => {
-                            tracing::warn!("Signing round 1 timeout,retry");
-                            break;
-                        }
-                    };
-                    match response {
-                        Ok(response) => {
-                            let response = SigningResponse::<VII, C>::from(response);
-                            match response {
-                                Ok(response) => {
-                                    tracing::debug!(
-                                        "Received valid round 1 response: {:?}",
-                                        response
-                                    );
-                                    event_channel_tx.send(Some(response)).unwrap();
-                                }
-                                Err(e) => {
-                                    tracing::error!("Error receiving Signing state: {}", e);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Error receiving Signing state: {}", e);
-                        }
-                    }
-                }
-                tracing::debug!("Round 1 thread completed, sending None");
-                event_channel_tx.send(None).unwrap();
-                event_channel_tx.closed().await;
-            });
+            // signers are asked best first, the rest stand by in case one fails or is slow
+            let mut standby = self.round1_requests_by_rank();
+            let mut round1_futures = FuturesUnordered::new();
+            let mut asked = BTreeSet::new();
             let mut round1_responses_pool = BTreeMap::new();
             let original_state = self.state.clone();
             let selected_responses: Result<SignatureSuite<VII, C>, SessionError> = 'out: loop {
                 self.state = original_state.clone();
+                if let Err(e) = Deadline::check(&self.deadline, "signing subsession") {
+                    break 'out Err(e);
+                }
                 // if round1 response is None and no enough participants, break
-                if round1_responses_pool.len() >= self.min_signers as usize {
+                let picked = self
+                    .selection
+                    .pick(round1_responses_pool.keys(), self.min_signers as usize);
+                let picked = if let Some(picked) = picked {
                     tracing::debug!(
                         "Have enough round 1 responses: {}",
                         round1_responses_pool.len()
                     );
+                    picked
                 } else {
-                    let response = event_channel_rx.recv().await.unwrap();
+                    while round1_responses_pool.len() + asked.len() < self.min_signers as usize
+                        && self.wake_next(&mut standby, &mut round1_futures, &mut asked)
+                    {}
+                    if let Some(id) = self.selection.required().iter().find(|id| {
+                        !round1_responses_pool.contains_key(*id) && !asked.contains(*id)
+                    }) {
+                        break 'out Err(SessionError::CoordinatorSessionError(format!(
+                            "required signer {} is not available for round 1",
+                            id.to_string()
+                        )));
+                    }
+                    if asked.is_empty() {
+                        if let Err(e) = Deadline::check(&self.deadline, "signing subsession") {
+                            break 'out Err(e);
+                        }
+                        tracing::error!("Not enough responses for round 1, breaking");
+                        break 'out Err(SessionError::CoordinatorSessionError(
+                            "not enough responses for round 1".to_string(),
+                        ));
+                    }
+                    let (id, response) = tokio::select! {
+                        response = round1_futures.next() => response.unwrap(),
+                        _ = tokio::time::sleep(Deadline::clamp(&self.deadline, Duration::from_secs(Settings::global().session.signing_round1_timeout))) => {
+                            // keep waiting for the slow signers but ask as many standby ones
+                            let missing = (self.min_signers as usize)
+                                .saturating_sub(round1_responses_pool.len());
+                            let mut woken = 0;
+                            while woken < missing
+                                && self.wake_next(&mut standby, &mut round1_futures, &mut asked)
+                            {
+                                woken += 1;
+                            }
+                            if woken == 0 {
+                                if let Err(e) = Deadline::check(&self.deadline, "signing subsession") {
+                                    break 'out Err(e);
+                                }
+                                tracing::error!("Signing round 1 timeout, no standby signers left");
+                                break 'out Err(SessionError::CoordinatorSessionError(
+                                    "not enough responses for round 1".to_string(),
+                                ));
+                            }
+                            tracing::warn!("Signing round 1 timeout, asked {} standby signers", woken);
+                            continue 'out;
+                        }
+                    };
+                    asked.remove(&id);
+                    let response = response
+                        .map_err(|e| SessionError::CoordinatorSessionError(e.to_string()))
+                        .and_then(SigningResponse::<VII, C>::from);
                     match response {
-                        Some(response) => {
+                        Ok(response) => {
                             tracing::debug!(
                                 "Adding response to pool from: {:?}",
                                 response.base_info.identifier
                             );
+                            self.monitor.signing_responded(
+                                &self.subsession_id,
+                                &response.base_info.identity,
+                            );
                             round1_responses_pool
                                 .insert(response.base_info.identifier.clone(), response);
-                            continue 'out;
                         }
-                        None => {
-                            tracing::error!("Not enough responses for round 1, breaking");
-                            break 'out Err(SessionError::CoordinatorSessionError(
-                                "not enough responses for round 1".to_string(),
-                            ));
+                        Err(e) => {
+                            tracing::warn!(
+                                "Error receiving Signing state from {}: {}, falling back",
+                                id.to_string(),
+                                e
+                            );
                         }
                     }
-                }
-                // select first min_signers responses
+                    continue 'out;
+                };
+                // select min_signers responses honouring the requested signers
                 let round1_responses = round1_responses_pool
                     .clone()
                     .into_iter()
-                    .take(self.min_signers as usize)
+                    .filter(|(id, _)| picked.contains(id))
                     .collect::<BTreeMap<_, _>>();
                 tracing::debug!("Selected {} responses for round 1", round1_responses.len());
                 let mut error_ids: BTreeSet<C::Identifier> =
@@ -221,6 +255,10 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                         round2_requests.len(),
                         self.min_signers
                     );
+                    self.monitor.signing_finished(
+                        &self.subsession_id,
+                        Some("not enough responses for round 1".to_string()),
+                    );
                     response_sender
                         .send(Err((
                             Some(self.subsession_id),
@@ -229,9 +267,16 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                             ),
                         )))
                         .unwrap();
-                    handle.abort();
                     return;
                 }
+                self.monitor.signing_round(
+                    &self.subsession_id,
+                    "round2",
+                    round2_requests
+                        .iter()
+                        .map(|request| request.base_info.identity.clone())
+                        .collect(),
+                );
                 //check round2 request is valid, if valid send to signer
                 for request in round2_requests {
                     tracing::debug!("Sending round 2 request: {:?}", request);
@@ -259,7 +304,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                     );
                     let response = tokio::select! {
                         response = futures.next() => response.unwrap(),
-                        _ = tokio::time::sleep(Duration::from_secs(Settings::global().session.signing_round2_timeout)) => {
+                        _ = tokio::time::sleep(Deadline::clamp(&self.deadline, Duration::from_secs(Settings::global().session.signing_round2_timeout))) => {
                             tracing::warn!("Signing round 2 timeout,retry");
                             break;
                         }
@@ -274,6 +319,10 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                                         "Received valid round 2 response from: {:?}",
                                         id
                                     );
+                                    self.monitor.signing_responded(
+                                        &self.subsession_id,
+                                        &response.base_info.identity,
+                                    );
                                     round2_responses.insert(id.clone(), response);
                                     error_ids.remove(&id);
                                 }
@@ -333,24 +382,86 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                         }
                         None => {
                             tracing::error!("Error handling Signing state: {}", e);
+                            self.monitor
+                                .signing_finished(&self.subsession_id, Some(e.to_string()));
                             response_sender
                                 .send(Err((Some(self.subsession_id), e)))
                                 .unwrap();
-                            handle.abort();
                             return;
                         }
                     },
                 }
             };
-            handle.abort();
+            self.monitor.signing_finished(
+                &self.subsession_id,
+                selected_responses.as_ref().err().map(|e| e.to_string()),
+            );
             if let Err(e) =
                 response_sender.send(selected_responses.map_err(|e| (Some(self.subsession_id), e)))
             {
                 tracing::error!("Failed to send response: {:?}", e);
             }
-        });
+        })
+        .abort_handle()
     }
 
+    /// Round 1 requests ordered by the selection priority, then by how cheap the signer
+    /// currently is to ask.
+    fn round1_requests_by_rank(&self) -> VecDeque<(C::Identifier, SigningRequestWrap<VII>)> {
+        let mut ranked = self
+            .split_into_single_requests()
+            .into_iter()
+            .map(|request| {
+                let rank = (
+                    self.selection.priority(&request.base_info.identifier),
+                    self.scheduler.cost(&request.base_info.identity),
+                );
+                (rank, request)
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by_key(|(rank, _)| *rank);
+        ranked
+            .into_iter()
+            .filter_map(|(_, request)| {
+                let id = request.base_info.identifier.clone();
+                match SigningRequestWrap::from(request) {
+                    Ok(request_wrap) => Some((id, request_wrap)),
+                    Err(e) => {
+                        tracing::error!("Failed to get signing request: {:?}", e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+    /// Sends the round 1 request of the best signer on standby, `false` once none is left.
+    fn wake_next(
+        &self,
+        standby: &mut VecDeque<(C::Identifier, SigningRequestWrap<VII>)>,
+        futures: &mut FuturesUnordered<
+            BoxFuture<
+                'static,
+                (
+                    C::Identifier,
+                    Result<SigningResponseWrap<VII>, oneshot::error::RecvError>,
+                ),
+            >,
+        >,
+        asked: &mut BTreeSet<C::Identifier>,
+    ) -> bool {
+        while let Some((id, request_wrap)) = standby.pop_front() {
+            tracing::debug!("Sending round 1 request to: {:?}", id);
+            let (tx, rx) = oneshot::channel();
+            if let Err(e) = self.signing_sender.send((request_wrap, tx)) {
+                tracing::error!("Failed to send signing request: {:?}, but continue", e);
+                continue;
+            }
+            asked.insert(id.clone());
+            futures.push(rx.map(move |response| (id, response)).boxed());
+            return true;
+        }
+        false
+    }
     pub(crate) fn subsession_id(&self) -> SubsessionId {
         self.subsession_id.clone()
     }
@@ -359,6 +470,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
             CoordinatorSigningState::Round1 => self
                 .participants
                 .iter()
+                .filter(|(id, _)| !self.selection.is_excluded(id))
                 .map(|(id, identity)| SigningRequest {
                     base_info: SigningBaseMessage {
                         crypto_type: C::crypto_type(),
@@ -369,6 +481,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                         identifier: id.clone(),
                         identity: identity.clone(),
                         public_key: self.public_key.clone(),
+                        deadline: self.deadline,
+                        requester: self.requester.clone(),
                     },
                     stage: SigningRequestStage::Round1 {},
                 })
@@ -389,6 +503,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> CoordinatorSubsession<VII, C> {
                         identifier: id.clone(),
                         identity: identity.clone(),
                         public_key: self.public_key.clone(),
+                        deadline: self.deadline,
+                        requester: self.requester.clone(),
                     },
                     stage: SigningRequestStage::Round2 {
                         tweak_data: self.tweak_data.clone(),
@@ -1,15 +1,23 @@
 mod combinations;
 mod dkg_ex;
 mod signing_ex;
-use super::manager::InstructionCipher;
+use super::key_metadata::KeyMetadataBook;
+use super::manager::{ActiveSession, InstructionCipher};
+use super::monitor::SessionMonitor;
+use super::namespace::{KeyScope, NamespaceKeystores};
+use super::scheduler::SignerScheduler;
 use super::{PkId, ValidatorIdentityIdentity};
-use crate::crypto::{pk_to_pkid, CryptoType};
-use crate::keystore::KeystoreManagement;
+use crate::crypto::{pk_to_pkid, CryptoType, Identifier};
 use crate::types::message::{
     DKGRequestWrapEx, DKGResponseWrapEx, SigningRequestWrapEx, SigningResponseWrapEx,
 };
-use crate::types::{error::SessionError, Participants, SessionId};
-use crate::types::{GroupPublicKeyInfo, SignatureSuiteInfo, SubsessionId};
+use crate::types::{
+    error::SessionError, CancelTarget, Deadline, Participants, SelectedIdentifiers, SessionId,
+};
+use crate::types::{
+    GroupPublicKeyInfo, KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState, PublicKeyInfo,
+    PublicKeyPackageInfo, SignatureSuiteInfo, SubsessionId,
+};
 use crate::utils;
 use combinations::Combinations;
 use common::Settings;
@@ -38,6 +46,8 @@ pub(crate) struct SessionWrapEx<VII: ValidatorIdentityIdentity> {
     )>,
 
     session_id_key_map: HashMap<SessionId, oneshot::Sender<Result<PkId, SessionError>>>,
+    // namespace and metadata requested for the keys of running DKG sessions
+    dkg_key_metadata: HashMap<SessionId, (Option<String>, KeyMetadataUpdate)>,
     subsession_id_signaturesuite_map:
         HashMap<SubsessionId, oneshot::Sender<Result<SignatureSuiteInfo<VII>, SessionError>>>,
 
@@ -75,9 +85,13 @@ pub(crate) struct SessionWrapEx<VII: ValidatorIdentityIdentity> {
         )>,
     >,
     combinations_cache: Option<Combinations>,
+    active_sessions: HashMap<CancelTarget, ActiveSession<VII>>,
 
     instruction_receiver: UnboundedReceiver<InstructionCipher<VII>>,
-    keystore_management: KeystoreManagement,
+    keystores: NamespaceKeystores,
+    key_metadata: KeyMetadataBook<VII>,
+    monitor: SessionMonitor<VII>,
+    scheduler: SignerScheduler<VII>,
 }
 impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
     pub(crate) fn new(
@@ -101,20 +115,33 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
         instruction_receiver: UnboundedReceiver<InstructionCipher<VII>>,
         keystore: Arc<crate::keystore::Keystore>,
         base_path: &PathBuf,
+        monitor: SessionMonitor<VII>,
+        scheduler: SignerScheduler<VII>,
     ) -> Result<Self, SessionError> {
-        let path = base_path
-            .join(Settings::global().coordinator.keystore_path)
-            .join(crypto_type.to_string());
-        let (keystore_management, data) =
-            crate::keystore::KeystoreManagement::new(keystore, path).unwrap();
-        let signing_sessions = match data {
-            Some(data) => {
-                Self::deserialize_sessions(data.as_slice(), out_init_signing_sender.clone())?
+        let root = base_path.join(Settings::global().coordinator.keystore_path);
+        let mut key_metadata = KeyMetadataBook::open(
+            keystore.clone(),
+            root.join(crypto_type.to_string()).join("metadata"),
+        )?;
+        let (keystores, contents) = NamespaceKeystores::open(keystore, &root, crypto_type)?;
+        let mut signing_sessions = HashMap::new();
+        for (namespace, data) in contents {
+            let sessions =
+                Self::deserialize_sessions(data.as_slice(), out_init_signing_sender.clone())?;
+            for (pkid, session) in sessions {
+                tracing::info!("Coordinator restored pkid: {} from local keystore", pkid);
+                if !key_metadata.contains(&pkid) {
+                    let mut metadata = KeyMetadata::new(
+                        None,
+                        None,
+                        session.base_info.participants.values().cloned().collect(),
+                        session.base_info.min_signers,
+                    );
+                    metadata.namespace = namespace.clone();
+                    key_metadata.insert(pkid.clone(), metadata)?;
+                }
+                signing_sessions.insert(pkid, session);
             }
-            None => HashMap::new(),
-        };
-        for (pkid, _) in signing_sessions.iter() {
-            tracing::info!("Coordinator restored pkid: {} from local keystore", pkid);
         }
         Ok(Self {
             crypto_type,
@@ -122,6 +149,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             out_init_dkg_sender,
             out_init_signing_sender,
             session_id_key_map: HashMap::new(),
+            dkg_key_metadata: HashMap::new(),
             dkg_futures: FuturesUnordered::new(),
             instruction_receiver,
             combinations_cache: None,
@@ -129,13 +157,17 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             subsession_id_signaturesuite_map: HashMap::new(),
             dkg_in_final_channel_mapping: HashMap::new(),
             signing_in_final_channel_mapping: HashMap::new(),
+            active_sessions: HashMap::new(),
             dkg_in_final_channel_receiver,
             signing_in_final_channel_receiver,
-            keystore_management,
+            keystores,
+            key_metadata,
+            monitor,
+            scheduler,
         })
     }
     pub(crate) fn check_serialize_deserialize(&self) -> Result<(), SessionError> {
-        let serialized = self.serialize_sessions()?;
+        let serialized = self.serialize_sessions(|_| true)?;
         let deserialized = Self::deserialize_sessions(
             serialized.as_slice(),
             self.out_init_signing_sender.clone(),
@@ -167,10 +199,11 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
         }
         Ok(signing_sessions)
     }
-    fn serialize_sessions(&self) -> Result<Vec<u8>, SessionError> {
+    fn serialize_sessions(&self, filter: impl Fn(&PkId) -> bool) -> Result<Vec<u8>, SessionError> {
         let sessions = self
             .signing_sessions
             .iter()
+            .filter(|(pkid, _)| filter(pkid))
             .map(|(pkid, session)| match session.serialize() {
                 Ok(data) => Ok((pkid.clone(), data)),
                 Err(e) => Err(e),
@@ -178,10 +211,18 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             .collect::<Result<HashMap<PkId, Vec<u8>>, SessionError>>()?;
         Ok(bincode::serialize(&sessions).unwrap())
     }
+    // every namespace keeps its keys in its own file
+    fn save_namespace(&mut self, namespace: &Option<String>) -> Result<(), SessionError> {
+        let sessions =
+            self.serialize_sessions(|pkid| &self.key_metadata.namespace_of(pkid) == namespace)?;
+        self.keystores.write(namespace, sessions.as_slice())
+    }
     async fn new_key(
         &mut self,
         participants: Vec<(u16, VII)>,
         min_signers: u16,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
         in_final_rx: UnboundedReceiver<(DKGRequestWrapEx<VII>, oneshot::Sender<DKGResponseWrapEx>)>,
     ) -> Result<SessionId, SessionError> {
         //TODO: remove the following participants judgement
@@ -192,16 +233,35 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             .map_err(|e| SessionError::CryptoError(e.to_string()))?;
         let participants = Participants::new(participants)?;
         participants.check_min_signers(min_signers)?;
+        Deadline::check(&deadline, "DKG request")?;
         let session = CoordinatorDKGSessionEx::<VII>::new(
             self.crypto_type,
             participants.clone(),
             min_signers,
             self.out_init_dkg_sender.clone(),
+            deadline,
         )?;
         let session_id = session.session_id().clone();
+        // ECDSA rounds run between the signers, only the final reports are seen here
+        self.monitor.dkg_started(
+            &session_id,
+            self.crypto_type,
+            min_signers,
+            participants.values().cloned().collect(),
+            "running",
+        );
         let (tx, rx) = oneshot::channel();
         self.dkg_futures.push(rx);
         session.start_dkg(in_final_rx, tx).await;
+        self.active_sessions.insert(
+            CancelTarget::Dkg(session_id),
+            ActiveSession {
+                requester,
+                participants: participants.values().cloned().collect(),
+                abort_handle: None,
+                deadline,
+            },
+        );
         return Ok(session_id);
     }
     async fn sign<T: AsRef<[u8]>>(
@@ -214,13 +274,17 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             oneshot::Sender<SigningResponseWrapEx>,
         )>,
         mut combinations: Combinations,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
     ) -> Result<SubsessionId, SessionError> {
+        Deadline::check(&deadline, "signing request")?;
         if msg.as_ref().len() != 32 {
             return Err(SessionError::SignerSessionError(
                 "Message length must be 32".to_string(),
             ));
         }
         let pkid = PkId::new(pkid_raw.as_ref().to_vec());
+        self.key_metadata.check_active(&pkid)?;
         let signing_session =
             self.signing_sessions
                 .get_mut(&pkid)
@@ -241,9 +305,16 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             .iter()
             .map(|id| *id)
             .collect::<Vec<_>>();
+        let min_signers = signing_session.base_info.min_signers;
+        let participants = signing_session
+            .base_info
+            .participants
+            .values()
+            .cloned()
+            .collect();
         let (tx, rx) = oneshot::channel();
         self.signing_futures.push(rx);
-        return signing_session
+        let subsession_id = signing_session
             .start_new_signing(
                 msg,
                 tweak_data,
@@ -251,8 +322,31 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                 participants_candidates,
                 combinations,
                 in_final_rx,
+                requester.clone(),
+                deadline,
             )
-            .await;
+            .await?;
+        self.monitor.signing_started(
+            &subsession_id,
+            &pkid,
+            self.crypto_type,
+            min_signers,
+            "running",
+        );
+        self.monitor.set_namespace(
+            subsession_id.to_string(),
+            self.key_metadata.namespace_of(&pkid),
+        );
+        self.active_sessions.insert(
+            CancelTarget::Signing(subsession_id),
+            ActiveSession {
+                requester,
+                participants,
+                abort_handle: None,
+                deadline,
+            },
+        );
+        Ok(subsession_id)
     }
     pub(crate) fn handle_dkg_final_channel_request(
         &mut self,
@@ -264,6 +358,8 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             dkg_request
         );
         let session_id = dkg_request.dkg_request_ex()?.base_info.session_id;
+        self.monitor
+            .dkg_responded(&session_id, dkg_request.identity());
         let sender = self.dkg_in_final_channel_mapping.get_mut(&session_id);
         if let Some(sender) = sender {
             sender
@@ -286,6 +382,8 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             .signing_request_ex()?
             .base_info
             .subsession_id;
+        self.monitor
+            .signing_responded(&subsession_id, signing_request.identity());
         let sender = self
             .signing_in_final_channel_mapping
             .get_mut(&subsession_id);
@@ -301,6 +399,35 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
         }
         Ok(())
     }
+    fn set_key_state(
+        &mut self,
+        pkid: &PkId,
+        state: KeyState,
+        requester: &Option<VII>,
+        scope: &KeyScope,
+    ) -> Result<(KeyState, KeyMetadata<VII>), SessionError> {
+        self.key_metadata.check_scope(pkid, scope)?;
+        let metadata = self.key_metadata.check_transition(pkid, state, requester)?;
+        if state == KeyState::Destroyed {
+            // the key stops signing while its signers erase their shares
+            if metadata.state != KeyState::Active {
+                return Ok((metadata.state, metadata));
+            }
+            return Ok((
+                metadata.state,
+                self.key_metadata.set_state(pkid, KeyState::Disabled)?,
+            ));
+        }
+        Ok((metadata.state, self.key_metadata.set_state(pkid, state)?))
+    }
+    // the metadata stays behind with the key's state for the audit trail
+    fn destroy_key(&mut self, pkid: &PkId) -> Result<KeyMetadata<VII>, SessionError> {
+        if self.signing_sessions.remove(pkid).is_some() {
+            let namespace = self.key_metadata.namespace_of(pkid);
+            self.save_namespace(&namespace)?;
+        }
+        self.key_metadata.set_state(pkid, KeyState::Destroyed)
+    }
     pub(crate) fn listening(mut self) {
         tokio::spawn(async move {
             loop {
@@ -342,14 +469,24 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             InstructionCipher::NewKey {
                 participants,
                 min_signers,
+                requester,
+                deadline,
+                metadata,
+                namespace,
                 pkid_response_oneshot,
             } => {
                 let (in_final_tx, in_final_rx) = tokio::sync::mpsc::unbounded_channel();
-                let session_id = self.new_key(participants, min_signers, in_final_rx).await;
+                let session_id = self
+                    .new_key(participants, min_signers, requester, deadline, in_final_rx)
+                    .await;
                 match session_id {
                     Ok(session_id) => {
+                        self.monitor
+                            .set_namespace(session_id.to_string(), namespace.clone());
                         self.session_id_key_map
                             .insert(session_id, pkid_response_oneshot);
+                        self.dkg_key_metadata
+                            .insert(session_id, (namespace, metadata));
                         self.dkg_in_final_channel_mapping
                             .insert(session_id, in_final_tx);
                     }
@@ -364,16 +501,51 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                 pkid,
                 msg,
                 tweak_data,
+                requester,
+                deadline,
+                selection,
+                scope,
                 signature_response_oneshot,
             } => {
+                if let Err(e) = self.key_metadata.check_scope(&pkid, &scope) {
+                    if let Err(e) = signature_response_oneshot.send(Err(e)) {
+                        tracing::error!("Error sending signature response: {:?}", e);
+                    }
+                    return;
+                }
                 let (in_final_tx, in_final_rx) = tokio::sync::mpsc::unbounded_channel();
                 let sessions = self.signing_sessions.get(&pkid);
                 let combinations = match sessions {
-                    Some(sessions) => Combinations::new(
-                        sessions.base_info.participants.keys().cloned().collect(),
-                        sessions.base_info.min_signers,
-                        &self.combinations_cache,
-                    ),
+                    Some(sessions) => {
+                        let mut combinations = Combinations::new(
+                            sessions.base_info.participants.keys().cloned().collect(),
+                            sessions.base_info.min_signers,
+                            &self.combinations_cache,
+                        );
+                        let selection = match selection.map(|selection| {
+                            selection.resolve(
+                                &sessions.base_info.participants,
+                                sessions.base_info.min_signers,
+                            )
+                        }) {
+                            Some(Ok(selection)) => selection,
+                            Some(Err(e)) => {
+                                if let Err(e) = signature_response_oneshot.send(Err(e)) {
+                                    tracing::error!("Error sending signature response: {:?}", e);
+                                }
+                                return;
+                            }
+                            None => SelectedIdentifiers::default(),
+                        };
+                        let participants = &sessions.base_info.participants;
+                        combinations.prioritize(&selection, |id| {
+                            participants
+                                .get(id)
+                                .map(|identity| self.scheduler.cost(identity))
+                                .unwrap_or(u64::MAX)
+                        });
+                        combinations
+                    }
                     None => {
                         signature_response_oneshot
                             .send(Err(SessionError::SignerSessionError(format!(
@@ -385,7 +557,15 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     }
                 };
                 let subsession_id = self
-                    .sign(pkid.to_bytes(), msg, tweak_data, in_final_rx, combinations)
+                    .sign(
+                        pkid.to_bytes(),
+                        msg,
+                        tweak_data,
+                        in_final_rx,
+                        combinations,
+                        requester,
+                        deadline,
+                    )
                     .await;
                 match subsession_id {
                     Ok(subsession_id) => {
@@ -401,25 +581,48 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     }
                 }
             }
+            InstructionCipher::Cancel {
+                target,
+                requester,
+                cancel_response_oneshot,
+            } => {
+                let r = self.cancel(target, requester);
+                if let Err(e) = cancel_response_oneshot.send(r) {
+                    tracing::error!("Error sending cancel response: {:?}", e);
+                }
+            }
             InstructionCipher::ListPkIds {
+                scope,
                 list_pkids_response_oneshot,
             } => {
-                let pkids = self.signing_sessions.keys().cloned().collect::<Vec<_>>();
-                if let Err(e) = list_pkids_response_oneshot.send(pkids) {
+                let keys = self
+                    .signing_sessions
+                    .keys()
+                    .filter_map(|pkid| {
+                        Some(KeyInfo {
+                            pkid: pkid.clone(),
+                            metadata: self.key_metadata.get(pkid).ok()?,
+                        })
+                    })
+                    .filter(|key| scope.covers(&key.metadata.namespace))
+                    .collect::<Vec<_>>();
+                if let Err(e) = list_pkids_response_oneshot.send(keys) {
                     tracing::error!("Error sending pkids response: {:?}", e);
                 }
             }
             InstructionCipher::PkTweakRequest {
                 pkid,
                 tweak_data,
+                scope,
                 pk_response_oneshot,
             } => {
-                let r = self
-                    .signing_sessions
-                    .get(&pkid)
-                    .ok_or(SessionError::SignerSessionError(
-                        "Signing session not found".to_string(),
-                    ));
+                let r = self.key_metadata.check_scope(&pkid, &scope).and_then(|_| {
+                    self.signing_sessions
+                        .get(&pkid)
+                        .ok_or(SessionError::SignerSessionError(
+                            "Signing session not found".to_string(),
+                        ))
+                });
                 let response = match r {
                     Ok(session) => {
                         let client = ecdsa_tss::EcdsaTssSignerClient::new(
@@ -444,6 +647,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                                     let base_info = session.base_info.clone();
                                     let derived_pk_c = derived_pk.clone();
                                     let tweak_data_c = tweak_data.clone();
+                                    let pkid_c = pkid.clone();
                                     tokio::spawn(async move {
                                         let client = ecdsa_tss::EcdsaTssSignerClient::new(
                                             common::Settings::global().signer.ecdsa_port,
@@ -454,7 +658,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                                         let result = client
                                             .check_pk(CheckPkRequest {
                                                 crypto_type: curve_id as u32,
-                                                pkid: pkid.to_bytes(),
+                                                pkid: pkid_c.to_bytes(),
                                                 public_key: pk,
                                                 public_key_derived: derived_pk_c,
                                                 delta: utils::derived_data(tweak_data_c),
@@ -473,7 +677,12 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                                         tracing::info!("check_pk result: {:?}", result);
                                     });
 
-                                    Ok(GroupPublicKeyInfo::new(derived_pk, tweak_data))
+                                    self.key_metadata.get(&pkid).map(|metadata| PublicKeyInfo {
+                                        group_public_key_info: GroupPublicKeyInfo::new(
+                                            derived_pk, tweak_data,
+                                        ),
+                                        metadata,
+                                    })
                                 }
                                 Err(e) => Err(e),
                             }
@@ -489,8 +698,114 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     tracing::error!("Error sending pk response: {:?}", e);
                 }
             }
+            InstructionCipher::PublicKeyPackageRequest {
+                pkid,
+                scope,
+                package_response_oneshot,
+            } => {
+                let r = self.key_metadata.check_scope(&pkid, &scope).and_then(|_| {
+                    self.signing_sessions
+                        .get(&pkid)
+                        .map(|session| PublicKeyPackageInfo {
+                            pkid: pkid.clone(),
+                            crypto_type: session.base_info.crypto_type,
+                            public_key_package: session.base_info.public_key_package.clone(),
+                            min_signers: session.base_info.min_signers,
+                            participants: session
+                                .base_info
+                                .participants
+                                .iter()
+                                .map(|(k, v)| (k.to_bytes(), v.clone()))
+                                .collect(),
+                        })
+                        .ok_or(SessionError::SignerSessionError(
+                            "Signing session not found".to_string(),
+                        ))
+                });
+                if let Err(e) = package_response_oneshot.send(r) {
+                    tracing::error!("Error sending package response: {:?}", e);
+                }
+            }
+            InstructionCipher::UpdateKeyMetadata {
+                pkid,
+                update,
+                requester,
+                scope,
+                metadata_response_oneshot,
+            } => {
+                let r = self
+                    .key_metadata
+                    .check_scope(&pkid, &scope)
+                    .and_then(|_| self.key_metadata.update(&pkid, update, &requester));
+                if let Err(e) = metadata_response_oneshot.send(r) {
+                    tracing::error!("Error sending metadata response: {:?}", e);
+                }
+            }
+            InstructionCipher::SetKeyState {
+                pkid,
+                state,
+                requester,
+                scope,
+                state_response_oneshot,
+            } => {
+                let r = self.set_key_state(&pkid, state, &requester, &scope);
+                if let Err(e) = state_response_oneshot.send(r) {
+                    tracing::error!("Error sending key state response: {:?}", e);
+                }
+            }
+            InstructionCipher::DestroyKey {
+                pkid,
+                destroy_response_oneshot,
+            } => {
+                let r = self.destroy_key(&pkid);
+                if let Err(e) = destroy_response_oneshot.send(r) {
+                    tracing::error!("Error sending destroy response: {:?}", e);
+                }
+            }
         }
     }
+    fn cancel(
+        &mut self,
+        target: CancelTarget,
+        requester: Option<VII>,
+    ) -> Result<Vec<VII>, SessionError> {
+        let active_session =
+            self.active_sessions
+                .get(&target)
+                .ok_or(SessionError::CoordinatorSessionError(format!(
+                    "Session {} not found",
+                    target.to_string()
+                )))?;
+        active_session.check_requester(&target, &requester)?;
+        let active_session = self.active_sessions.remove(&target).unwrap();
+        let error = SessionError::Cancelled(target.to_string());
+        // dropping the final channel sender stops the waiting coordinator task
+        match target {
+            CancelTarget::Dkg(session_id) => {
+                self.dkg_in_final_channel_mapping.remove(&session_id);
+                self.dkg_key_metadata.remove(&session_id);
+                self.monitor
+                    .dkg_finished(&session_id, Err(error.to_string()));
+                if let Some(oneshot) = self.session_id_key_map.remove(&session_id) {
+                    if let Err(e) = oneshot.send(Err(error)) {
+                        tracing::error!("Error sending pkid response: {:?}", e);
+                    }
+                }
+            }
+            CancelTarget::Signing(subsession_id) => {
+                self.signing_in_final_channel_mapping.remove(&subsession_id);
+                self.monitor
+                    .signing_finished(&subsession_id, Some(error.to_string()));
+                if let Some(oneshot) = self.subsession_id_signaturesuite_map.remove(&subsession_id)
+                {
+                    if let Err(e) = oneshot.send(Err(error)) {
+                        tracing::error!("Error sending signature response: {:?}", e);
+                    }
+                }
+            }
+        }
+        Ok(active_session.participants)
+    }
     async fn handle_dkg_future(
         &mut self,
         dkg_info: Result<DKGInfo<VII>, (SessionId, SessionError)>,
@@ -499,6 +814,28 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
         match dkg_info {
             Ok(dkg_info) => {
                 let pkid = pk_to_pkid(dkg_info.crypto_type, &dkg_info.public_key_package)?;
+                self.monitor
+                    .dkg_finished(&dkg_info.session_id, Ok(pkid.clone()));
+                let requester = self
+                    .active_sessions
+                    .get(&CancelTarget::Dkg(dkg_info.session_id))
+                    .and_then(|session| session.requester.clone());
+                let mut metadata = KeyMetadata::new(
+                    Some(crate::utils::now()),
+                    requester,
+                    dkg_info.participants.values().cloned().collect(),
+                    dkg_info.min_signers,
+                );
+                if let Some((namespace, update)) =
+                    self.dkg_key_metadata.remove(&dkg_info.session_id)
+                {
+                    metadata.namespace = namespace;
+                    metadata.apply(update);
+                }
+                let namespace = metadata.namespace.clone();
+                if let Err(e) = self.key_metadata.insert(pkid.clone(), metadata) {
+                    tracing::error!("Error storing metadata of key {}: {:?}", pkid, e);
+                }
                 self.signing_sessions.insert(
                     pkid,
                     CoordinatorSigningSessionEx::new(
@@ -510,11 +847,12 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                         self.out_init_signing_sender.clone(),
                     )?,
                 );
-                let sessions = self.serialize_sessions()?;
-                self.keystore_management.write(sessions.as_slice())?;
+                self.save_namespace(&namespace)?;
                 let oneshot = self.session_id_key_map.remove(&dkg_info.session_id);
                 self.dkg_in_final_channel_mapping
                     .remove(&dkg_info.session_id);
+                self.active_sessions
+                    .remove(&CancelTarget::Dkg(dkg_info.session_id));
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(pk_to_pkid(
                         dkg_info.crypto_type,
@@ -530,8 +868,11 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             }
             Err((session_id, e)) => {
                 tracing::error!("Error in DKG future: {:?}", e);
+                self.monitor.dkg_finished(&session_id, Err(e.to_string()));
                 let oneshot = self.session_id_key_map.remove(&session_id);
                 self.dkg_in_final_channel_mapping.remove(&session_id);
+                self.dkg_key_metadata.remove(&session_id);
+                self.active_sessions.remove(&CancelTarget::Dkg(session_id));
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(Err(e)) {
                         tracing::error!("Error sending pkid response: {:?}", e);
@@ -592,8 +933,11 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     tracing::info!("check_pk result: {:?}", result);
                 });
                 let subsession_id = signature_suite.subsession_id;
+                self.monitor.signing_finished(&subsession_id, None);
                 let oneshot = self.subsession_id_signaturesuite_map.remove(&subsession_id);
                 self.signing_in_final_channel_mapping.remove(&subsession_id);
+                self.active_sessions
+                    .remove(&CancelTarget::Signing(subsession_id));
                 if let Some(oneshot) = oneshot {
                     if let Err(e) = oneshot.send(Ok(signature_suite)) {
                         tracing::error!("Error sending signature response: {:?}", e);
@@ -612,8 +956,15 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             }
             Err((Some(subsession_id), (pkid, msg, tweak_data, combinations), e)) => {
                 tracing::error!("Error in signing future: {:?}", e);
+                self.monitor
+                    .signing_finished(&subsession_id, Some(e.to_string()));
                 let oneshot = self.subsession_id_signaturesuite_map.remove(&subsession_id);
                 self.signing_in_final_channel_mapping.remove(&subsession_id);
+                let (requester, deadline) = self
+                    .active_sessions
+                    .remove(&CancelTarget::Signing(subsession_id))
+                    .map(|active_session| (active_session.requester, active_session.deadline))
+                    .unwrap_or_default();
                 if let Some(oneshot) = oneshot {
                     if combinations.is_empty() {
                         self.combinations_cache = None;
@@ -629,7 +980,15 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                         self.combinations_cache = Some(combinations.clone());
                         let (in_final_tx, in_final_rx) = tokio::sync::mpsc::unbounded_channel();
                         let subsession_id = self
-                            .sign(pkid.to_bytes(), msg, tweak_data, in_final_rx, combinations)
+                            .sign(
+                                pkid.to_bytes(),
+                                msg,
+                                tweak_data,
+                                in_final_rx,
+                                combinations,
+                                requester,
+                                deadline,
+                            )
                             .await;
                         match subsession_id {
                             Ok(subsession_id) => {
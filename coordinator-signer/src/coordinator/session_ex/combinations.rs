@@ -1,5 +1,8 @@
 use itertools::Itertools;
+
+use crate::types::SelectedIdentifiers;
 use std::{
+    cmp::Reverse,
     collections::{BTreeSet, HashSet},
     ops::{Deref, DerefMut},
 };
@@ -43,6 +46,23 @@ impl Combinations {
     pub(crate) fn filter_error_ids(&mut self, error_ids: &BTreeSet<u16>) {
         self.0.retain(|set| set.is_disjoint(error_ids));
     }
+
+    /// Keep only combinations the selection allows. The ones with most preferred
+    /// signers are tried first, then the ones whose slowest signer is cheapest to ask.
+    /// Ties keep their previous order, so cached combinations still come first.
+    pub(crate) fn prioritize(
+        &mut self,
+        selection: &SelectedIdentifiers<u16>,
+        cost: impl Fn(&u16) -> u64,
+    ) {
+        self.0.retain(|set| selection.allows(set));
+        // combinations are popped from the back
+        self.0.sort_by_cached_key(|set| {
+            let worst = set.iter().map(&cost).max().unwrap_or_default();
+            let total = set.iter().map(&cost).fold(0u64, u64::saturating_add);
+            (selection.preferred_count(set), Reverse((worst, total)))
+        });
+    }
 }
 
 impl Deref for Combinations {
@@ -117,4 +137,17 @@ mod tests {
         ];
         assert_eq!(&*prioritized, &expected_final);
     }
+
+    #[test]
+    fn test_combinations_prioritized_by_cost() {
+        let mut combinations = Combinations::new(vec![1, 2, 3, 4], 2, &None);
+        // signer 4 is slow, signer 1 is the fastest
+        combinations.prioritize(&SelectedIdentifiers::default(), |id| match id {
+            4 => 1000,
+            id => *id as u64 * 10,
+        });
+        assert_eq!(combinations.pop(), Some(bset(&[1, 2])));
+        assert_eq!(combinations.pop(), Some(bset(&[1, 3])));
+        assert_eq!(combinations.first(), Some(&bset(&[3, 4])));
+    }
 }
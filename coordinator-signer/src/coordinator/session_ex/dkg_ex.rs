@@ -11,7 +11,7 @@ use crate::types::message::DKGRequestWrapEx;
 use crate::types::message::DKGResponseWrapEx;
 use crate::types::message::DKGStageEx;
 use crate::types::message::{DKGRequestEx, DKGResult};
-use crate::types::{error::SessionError, message::DKGBaseMessage, Participants};
+use crate::types::{error::SessionError, message::DKGBaseMessage, Deadline, Participants};
 pub(crate) struct CoordinatorDKGSessionEx<VII: ValidatorIdentityIdentity> {
     crypto_type: CryptoType,
     min_signers: u16,
@@ -20,6 +20,7 @@ pub(crate) struct CoordinatorDKGSessionEx<VII: ValidatorIdentityIdentity> {
     dkg_state: CoordinatorStateEx<Vec<u8>>,
     out_init_dkg_sender:
         UnboundedSender<(DKGRequestWrapEx<VII>, oneshot::Sender<DKGResponseWrapEx>)>,
+    deadline: Option<Deadline>,
 }
 #[derive(Debug, Clone)]
 pub(crate) struct DKGInfo<VII: ValidatorIdentityIdentity> {
@@ -39,6 +40,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorDKGSessionEx<VII> {
             DKGRequestWrapEx<VII>,
             oneshot::Sender<DKGResponseWrapEx>,
         )>,
+        deadline: Option<Deadline>,
     ) -> Result<Self, SessionError> {
         participants.check_min_signers(min_signers)?;
         let session_id = SessionId::new(crypto_type, min_signers, &participants)?;
@@ -51,6 +53,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorDKGSessionEx<VII> {
             participants,
             dkg_state,
             out_init_dkg_sender,
+            deadline,
         })
     }
     fn match_base_info(&self, base_info: &DKGBaseMessage<VII, u16>) -> Result<(), SessionError> {
@@ -138,7 +141,14 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorDKGSessionEx<VII> {
                 tracing::debug!("Waiting for {} responses", self.participants.len());
                 for i in 0..self.participants.len() {
                     tracing::debug!("Waiting for response {}/{}", i + 1, self.participants.len());
-                    let response = futures.next().await;
+                    let response =
+                        match Deadline::wait(&self.deadline, "DKG session", futures.next()).await {
+                            Ok(response) => response,
+                            Err(e) => {
+                                tracing::error!("{}", e);
+                                break 'out Err(e);
+                            }
+                        };
                     match response {
                         Some(Ok(response)) => {
                             tracing::debug!("Received valid response: {:?}", response.clone());
@@ -171,7 +181,16 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorDKGSessionEx<VII> {
                 let mut results = BTreeMap::new();
                 for i in 0..self.participants.len() {
                     tracing::debug!("Waiting for response {}/{}", i + 1, self.participants.len());
-                    let response = in_final_rx.recv().await;
+                    let response =
+                        match Deadline::wait(&self.deadline, "DKG session", in_final_rx.recv())
+                            .await
+                        {
+                            Ok(response) => response,
+                            Err(e) => {
+                                tracing::error!("{}", e);
+                                break 'out Err(e);
+                            }
+                        };
                     match response {
                         Some((request, response_chan)) => {
                             let request_ex = request.dkg_request_ex();
@@ -223,6 +242,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorDKGSessionEx<VII> {
                             identifier: id.clone(),
                             identity: identity.clone(),
                             session_id: self.session_id.clone(),
+                            deadline: self.deadline,
                         },
                         stage: DKGStageEx::Init,
                     })
@@ -12,7 +12,7 @@ use crate::{
     types::{
         error::SessionError,
         message::{DKGResult, SigningRequestWrapEx, SigningResponseWrapEx},
-        Participants, SubsessionId,
+        Deadline, Participants, SubsessionId,
     },
     SignatureSuiteInfo,
 };
@@ -136,6 +136,8 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSigningSessionEx<VII> {
             SigningRequestWrapEx<VII>,
             oneshot::Sender<SigningResponseWrapEx>,
         )>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
     ) -> Result<SubsessionId, SessionError> {
         let mut base_info = self.base_info.clone();
         // check all participants candidates are in the participants
@@ -156,6 +158,8 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSigningSessionEx<VII> {
             msg.clone(),
             tweak_data.clone(),
             self.out_init_signing_sender.clone(),
+            requester,
+            deadline,
         );
         match subssesion {
             Ok(subsession) => {
@@ -6,9 +6,12 @@ use std::{
 use crate::{
     coordinator::{session_ex::combinations::Combinations, CoordinatorStateEx},
     crypto::*,
-    types::message::{
-        SigningBaseMessage, SigningRequestEx, SigningRequestWrapEx, SigningResponseWrapEx,
-        SigningStageEx,
+    types::{
+        message::{
+            SigningBaseMessage, SigningRequestEx, SigningRequestWrapEx, SigningResponseWrapEx,
+            SigningStageEx,
+        },
+        Deadline,
     },
     SignatureSuiteInfo,
 };
@@ -35,6 +38,8 @@ pub(crate) struct CoordinatorSubsessionEx<VII: ValidatorIdentityIdentity> {
         SigningRequestWrapEx<VII>,
         oneshot::Sender<SigningResponseWrapEx>,
     )>,
+    requester: Option<VII>,
+    deadline: Option<Deadline>,
 }
 impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
     pub(crate) fn new(
@@ -45,6 +50,8 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
             SigningRequestWrapEx<VII>,
             oneshot::Sender<SigningResponseWrapEx>,
         )>,
+        requester: Option<VII>,
+        deadline: Option<Deadline>,
     ) -> Result<Self, SessionError> {
         let subsession_id = SubsessionId::new(
             base_info.crypto_type,
@@ -61,6 +68,8 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
             state: CoordinatorStateEx::Init,
             out_init_signing_sender: out_init_signing_sender,
             message: sign_message,
+            requester,
+            deadline,
         })
     }
     pub(crate) async fn start_signing(
@@ -113,6 +122,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
                 let (tx, rx) = oneshot::channel();
                 let (tx_with_id, rx_with_id) = oneshot::channel();
                 let base_info = request.base_info.clone();
+                let deadline = self.deadline;
                 let request_wrap = SigningRequestWrapEx::from(request);
                 match request_wrap {
                     Ok(request_wrap) => {
@@ -124,7 +134,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
                             // if timeout or recevive rx, send tx_with_id
                             let response = tokio::select! {
                                 Ok(response) = rx => response,
-                                _ = tokio::time::sleep(Duration::from_secs(Settings::global().session.signing_round1_timeout)) => {
+                                _ = tokio::time::sleep(Deadline::clamp(&deadline, Duration::from_secs(Settings::global().session.signing_round1_timeout))) => {
                                         tracing::warn!("Signing round 1 timeout,retry");
                                         SigningResponseWrapEx::Failure(format!("Signing init timeout,retry"))
                                 }
@@ -173,7 +183,28 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
                     i + 1,
                     self.base_info.participants.len()
                 );
-                let response = in_final_rx.recv().await;
+                let response =
+                    match Deadline::wait(&self.deadline, "signing subsession", in_final_rx.recv())
+                        .await
+                    {
+                        Ok(response) => response,
+                        Err(e) => {
+                            tracing::error!("{}", e);
+                            response_sender
+                                .send(Err((
+                                    Some(self.subsession_id),
+                                    (
+                                        self.base_info.pkid.clone(),
+                                        self.message.clone(),
+                                        self.tweak_data.clone(),
+                                        combinations,
+                                    ),
+                                    e,
+                                )))
+                                .unwrap();
+                            return;
+                        }
+                    };
                 match response {
                     Some((request, response_chan)) => {
                         let request_ex = request.signing_request_ex();
@@ -464,6 +495,8 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
                             identifier: id.clone(),
                             identity: identity.clone(),
                             public_key: self.base_info.public_key_package.clone(),
+                            deadline: self.deadline,
+                            requester: self.requester.clone(),
                         },
                         stage: SigningStageEx::Init(self.message.clone(), self.tweak_data.clone()),
                     })
@@ -600,6 +633,7 @@ impl<VII: ValidatorIdentityIdentity> CoordinatorSubsessionEx<VII> {
                     pk_verifying_key_tweak: first_package.public_key_derived.clone(),
                     crypto_type: self.base_info.crypto_type,
                     original_serialized: "".to_string(),
+                    formatted_signature: None,
                 })
             }
             CoordinatorStateEx::Final { .. } => {
@@ -0,0 +1,259 @@
+//! Every signature the coordinator made for a node, kept so nodes can fetch a signature
+//! again after losing the response and browse the history.
+//!
+//! Records are appended encrypted to `<dir>/signatures.store` and indexed in memory by
+//! subsession id and by key, message and tweak.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::namespace::KeyScope;
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+use crate::keystore::{AppendStore, Keystore};
+use crate::types::error::SessionError;
+use crate::types::{SignaturePage, SignatureQuery, SignatureRecord, SignatureSuiteInfo};
+use crate::utils::list_hash;
+use crate::utils::now;
+
+// largest page returned by a list query
+const MAX_PAGE_SIZE: usize = 100;
+
+// bucket of the signatures of `message` with `pkid` and `tweak_data`
+fn message_key(pkid: &PkId, message: &[u8], tweak_data: &Option<Vec<u8>>) -> Vec<u8> {
+    let tweak_data = match tweak_data {
+        Some(tweak_data) => [&[1][..], tweak_data].concat(),
+        None => vec![0],
+    };
+    list_hash(&[
+        pkid.to_bytes(),
+        list_hash(&[message]),
+        list_hash(&[tweak_data]),
+    ])
+}
+
+pub(crate) struct SignatureStore<VII: ValidatorIdentityIdentity> {
+    store: AppendStore,
+    // oldest first
+    records: Vec<SignatureRecord<VII>>,
+    by_subsession: HashMap<String, usize>,
+    by_message: HashMap<Vec<u8>, Vec<usize>>,
+}
+
+impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>> SignatureStore<VII> {
+    /// Loads `<dir>/signatures.store`, dropping a record cut short by a crash.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self, SessionError> {
+        let (store, records) = AppendStore::open(keystore, dir.as_ref().join("signatures.store"))?;
+        let mut store = Self {
+            store,
+            records: Vec::new(),
+            by_subsession: HashMap::new(),
+            by_message: HashMap::new(),
+        };
+        for record in records {
+            let record = serde_json::from_slice(&record)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+            store.index(record);
+        }
+        Ok(store)
+    }
+    fn index(&mut self, record: SignatureRecord<VII>) {
+        let info = &record.signature_suite_info;
+        let position = self.records.len();
+        self.by_subsession
+            .insert(info.subsession_id.to_string(), position);
+        self.by_message
+            .entry(message_key(&info.pkid, &info.message, &info.tweak_data))
+            .or_default()
+            .push(position);
+        self.records.push(record);
+    }
+    /// Records a signature made with a key of `namespace`.
+    pub(crate) fn insert(
+        &mut self,
+        requester: VII,
+        namespace: Option<String>,
+        signature_suite_info: SignatureSuiteInfo<VII>,
+    ) -> Result<(), SessionError> {
+        let record = SignatureRecord {
+            requester,
+            signed_at: now(),
+            namespace,
+            signature_suite_info,
+        };
+        let data = serde_json::to_vec(&record)
+            .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+        self.store.append(&data)?;
+        self.index(record);
+        Ok(())
+    }
+    /// Answers `query` from the signatures made with keys `scope` covers.
+    pub(crate) fn query(&self, query: &SignatureQuery, scope: &KeyScope) -> SignaturePage<VII> {
+        let positions: Vec<usize> = match query {
+            SignatureQuery::Subsession(subsession_id) => self
+                .by_subsession
+                .get(subsession_id)
+                .into_iter()
+                .copied()
+                .collect(),
+            SignatureQuery::Message {
+                pkid,
+                message,
+                tweak_data,
+            } => self
+                .by_message
+                .get(&message_key(pkid, message, tweak_data))
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|position| {
+                    let info = &self.records[*position].signature_suite_info;
+                    &info.pkid == pkid && &info.message == message && &info.tweak_data == tweak_data
+                })
+                .rev()
+                .collect(),
+            SignatureQuery::List { pkid, .. } => (0..self.records.len())
+                .rev()
+                .filter(|position| {
+                    pkid.as_ref().map_or(true, |pkid| {
+                        &self.records[*position].signature_suite_info.pkid == pkid
+                    })
+                })
+                .collect(),
+        };
+        let positions: Vec<usize> = positions
+            .into_iter()
+            .filter(|position| scope.covers(&self.records[*position].namespace))
+            .collect();
+        let total = positions.len();
+        let (offset, limit) = match query {
+            SignatureQuery::List { offset, limit, .. } => (*offset, (*limit).min(MAX_PAGE_SIZE)),
+            _ => (0, total),
+        };
+        SignaturePage {
+            records: positions
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|position| self.records[position].clone())
+                .collect(),
+            total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::CryptoType;
+    use crate::types::{Participants, SubsessionId};
+    use libp2p::PeerId;
+    use std::collections::BTreeMap;
+
+    fn signature(
+        pkid: &PkId,
+        message: &[u8],
+        tweak_data: Option<Vec<u8>>,
+    ) -> SignatureSuiteInfo<PeerId> {
+        SignatureSuiteInfo {
+            signature: vec![1; 64],
+            pk: vec![],
+            pk_tweak: vec![],
+            pk_verifying_key: vec![],
+            pk_verifying_key_tweak: vec![],
+            tweak_data: tweak_data.clone(),
+            subsession_id: SubsessionId::new(
+                CryptoType::Ed25519,
+                1,
+                &Participants::<PeerId, u16>::new(vec![(1, PeerId::random())]).unwrap(),
+                message.to_vec(),
+                tweak_data,
+                pkid.clone(),
+            )
+            .unwrap(),
+            participants: BTreeMap::new(),
+            joined_participants: BTreeMap::new(),
+            pkid: pkid.clone(),
+            message: message.to_vec(),
+            crypto_type: CryptoType::Ed25519,
+            original_serialized: "".to_string(),
+            formatted_signature: None,
+        }
+    }
+
+    #[test]
+    fn test_signature_store() {
+        let keystore = Arc::new(Keystore::new(b"signatures", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("signature_store_{}", uuid::Uuid::new_v4()));
+        let (pkid, other_pkid) = (PkId::new(vec![1; 33]), PkId::new(vec![2; 33]));
+        let requester = PeerId::random();
+        let first = signature(&pkid, b"message", None);
+        let tweaked = signature(&pkid, b"message", Some(b"tweak".to_vec()));
+        let other = signature(&other_pkid, b"message", None);
+
+        let mut store = SignatureStore::open(keystore.clone(), &dir).unwrap();
+        let wallet = Some("wallet".to_string());
+        for info in [&first, &tweaked] {
+            store.insert(requester, None, info.clone()).unwrap();
+        }
+        store
+            .insert(requester, wallet.clone(), other.clone())
+            .unwrap();
+        drop(store);
+        let store = SignatureStore::<PeerId>::open(keystore, &dir).unwrap();
+
+        let page = store.query(
+            &SignatureQuery::Subsession(first.subsession_id.to_string()),
+            &KeyScope::All,
+        );
+        assert_eq!(page.total, 1);
+        assert_eq!(page.records[0].signature_suite_info, first);
+        assert_eq!(page.records[0].requester, requester);
+        let page = store.query(
+            &SignatureQuery::Message {
+                pkid: pkid.clone(),
+                message: b"message".to_vec(),
+                tweak_data: Some(b"tweak".to_vec()),
+            },
+            &KeyScope::All,
+        );
+        assert_eq!(page.total, 1);
+        assert_eq!(page.records[0].signature_suite_info, tweaked);
+        let page = store.query(
+            &SignatureQuery::List {
+                pkid: None,
+                offset: 1,
+                limit: 1,
+            },
+            &KeyScope::All,
+        );
+        assert_eq!(page.total, 3);
+        assert_eq!(page.records[0].signature_suite_info, tweaked);
+        let page = store.query(
+            &SignatureQuery::List {
+                pkid: Some(other_pkid),
+                offset: 0,
+                limit: 10,
+            },
+            &KeyScope::All,
+        );
+        assert_eq!(page.total, 1);
+        assert_eq!(page.records[0].signature_suite_info, other);
+        let page = store.query(
+            &SignatureQuery::List {
+                pkid: None,
+                offset: 0,
+                limit: 10,
+            },
+            &KeyScope::Namespace(wallet),
+        );
+        // only the signatures of the namespace's keys, whoever asked for them
+        assert_eq!(page.total, 1);
+        assert_eq!(page.records[0].signature_suite_info, other);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
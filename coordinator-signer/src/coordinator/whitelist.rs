@@ -0,0 +1,199 @@
+//! The signers allowed to register with the coordinator.
+//!
+//! The whitelist starts out as `peer_id_whitelist` of the config and is then changed
+//! through IPC. It is kept encrypted in the coordinator keystore, so once saved it wins
+//! over the config on later starts. A coordinator started without a whitelist lets
+//! every signer register until a whitelist is started through IPC.
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::ValidatorIdentityIdentity;
+use crate::keystore::{Keystore, KeystoreManagement};
+use crate::types::error::SessionError;
+
+const MAX_LABEL_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WhitelistEntry<VII: ValidatorIdentityIdentity> {
+    identity: VII,
+    #[serde(default)]
+    label: Option<String>,
+}
+
+pub(crate) struct SignerWhitelist<VII: ValidatorIdentityIdentity> {
+    keystore_management: KeystoreManagement,
+    // none until a whitelist is started, every signer may register
+    entries: Option<HashMap<VII, Option<String>>>,
+}
+
+impl<VII> SignerWhitelist<VII>
+where
+    VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>,
+{
+    /// Opens the whitelist saved under `dir`, starting it with `initial` if none was
+    /// saved yet. A saved whitelist is kept even if `initial` differs from it.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+        initial: Option<HashSet<VII>>,
+    ) -> Result<Self, SessionError> {
+        let (keystore_management, data) =
+            KeystoreManagement::new(keystore, dir.as_ref().join("whitelist"))?;
+        let mut whitelist = Self {
+            keystore_management,
+            entries: None,
+        };
+        match (data, initial) {
+            (Some(data), initial) => {
+                let entries: Vec<WhitelistEntry<VII>> = serde_json::from_slice(&data)
+                    .map_err(|e| SessionError::DeserializationError(e.to_string()))?;
+                whitelist.entries = Some(
+                    entries
+                        .into_iter()
+                        .map(|entry| (entry.identity, entry.label))
+                        .collect(),
+                );
+                if initial.is_some_and(|initial| initial != whitelist.identities()) {
+                    tracing::warn!(
+                        "The configured signer whitelist differs from the saved one, which is used; change it through IPC"
+                    );
+                }
+            }
+            (None, Some(initial)) => whitelist.start(initial)?,
+            (None, None) => {}
+        }
+        Ok(whitelist)
+    }
+    /// Starts enforcing a whitelist of `identities` if none is enforced yet.
+    pub(crate) fn start(&mut self, identities: HashSet<VII>) -> Result<(), SessionError> {
+        if self.entries.is_some() {
+            return Ok(());
+        }
+        let entries = identities
+            .into_iter()
+            .map(|identity| (identity, None))
+            .collect();
+        self.save(entries)
+    }
+    fn save(&mut self, entries: HashMap<VII, Option<String>>) -> Result<(), SessionError> {
+        let list = entries
+            .iter()
+            .map(|(identity, label)| WhitelistEntry {
+                identity: identity.clone(),
+                label: label.clone(),
+            })
+            .collect::<Vec<_>>();
+        let data = serde_json::to_vec(&list)
+            .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+        self.keystore_management.write(&data)?;
+        self.entries = Some(entries);
+        Ok(())
+    }
+    pub(crate) fn is_started(&self) -> bool {
+        self.entries.is_some()
+    }
+    /// Whether `identity` may register, every signer may without a whitelist.
+    pub(crate) fn allows(&self, identity: &VII) -> bool {
+        self.entries
+            .as_ref()
+            .map_or(true, |entries| entries.contains_key(identity))
+    }
+    pub(crate) fn identities(&self) -> HashSet<VII> {
+        self.entries
+            .iter()
+            .flat_map(|entries| entries.keys().cloned())
+            .collect()
+    }
+    pub(crate) fn check_label(label: &Option<String>) -> Result<(), SessionError> {
+        match label {
+            Some(label) if label.len() > MAX_LABEL_LENGTH => Err(SessionError::InvalidRequest(
+                format!("signer label is longer than {} bytes", MAX_LABEL_LENGTH),
+            )),
+            _ => Ok(()),
+        }
+    }
+    /// Adds `identity`, or changes its label if it is listed already, starting a
+    /// whitelist of only `identity` if none is enforced yet. Returns whether it is new.
+    pub(crate) fn add(
+        &mut self,
+        identity: VII,
+        label: Option<String>,
+    ) -> Result<bool, SessionError> {
+        Self::check_label(&label)?;
+        let mut entries = self.entries.clone().unwrap_or_default();
+        let new = entries.insert(identity, label).is_none();
+        self.save(entries)?;
+        Ok(new)
+    }
+    /// Removes `identity`, returning whether it was listed.
+    pub(crate) fn remove(&mut self, identity: &VII) -> Result<bool, SessionError> {
+        let Some(mut entries) = self.entries.clone() else {
+            return Ok(false);
+        };
+        if entries.remove(identity).is_none() {
+            return Ok(false);
+        }
+        self.save(entries)?;
+        Ok(true)
+    }
+    /// The listed signers and their labels, sorted by identity.
+    pub(crate) fn list(&self) -> Vec<(VII, Option<String>)> {
+        let mut list = self
+            .entries
+            .iter()
+            .flatten()
+            .map(|(identity, label)| (identity.clone(), label.clone()))
+            .collect::<Vec<_>>();
+        list.sort_by_key(|(identity, _)| identity.to_fmt_string());
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_signer_whitelist() {
+        let keystore = Arc::new(Keystore::new(b"whitelist", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("whitelist_{}", uuid::Uuid::new_v4()));
+        let (alice, bob, carol) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+        let mut whitelist =
+            SignerWhitelist::open(keystore.clone(), &dir, Some([alice, bob].into())).unwrap();
+        assert!(whitelist.allows(&alice));
+        assert!(!whitelist.allows(&carol));
+        assert!(whitelist.add(carol, Some("eu-west".to_string())).unwrap());
+        assert!(!whitelist.add(carol, Some("us-east".to_string())).unwrap());
+        assert!(whitelist.add(carol, Some("x".repeat(65))).is_err());
+        assert!(whitelist.remove(&alice).unwrap());
+        assert!(!whitelist.remove(&alice).unwrap());
+
+        // the saved whitelist wins over the initial one
+        let whitelist =
+            SignerWhitelist::open(keystore.clone(), &dir, Some([alice].into())).unwrap();
+        assert_eq!(whitelist.identities(), HashSet::from([bob, carol]));
+        assert!(whitelist
+            .list()
+            .contains(&(carol, Some("us-east".to_string()))));
+        let whitelist = SignerWhitelist::<PeerId>::open(keystore.clone(), &dir, None).unwrap();
+        assert!(!whitelist.allows(&alice));
+
+        // without a whitelist every signer may register until one is started
+        let other_dir = dir.join("other");
+        let mut whitelist = SignerWhitelist::open(keystore.clone(), &other_dir, None).unwrap();
+        assert!(!whitelist.is_started());
+        assert!(whitelist.allows(&alice));
+        assert!(!whitelist.remove(&alice).unwrap());
+        whitelist.start([alice].into()).unwrap();
+        assert!(whitelist.add(bob, None).unwrap());
+        assert!(!whitelist.allows(&carol));
+        let whitelist = SignerWhitelist::<PeerId>::open(keystore, &other_dir, None).unwrap();
+        assert_eq!(whitelist.identities(), HashSet::from([alice, bob]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
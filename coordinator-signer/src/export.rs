@@ -0,0 +1,250 @@
+//! Export of group verifying keys in standard formats.
+//!
+//! | crypto type                                    | SPKI DER/PEM | JWK                | OpenSSH               |
+//! |------------------------------------------------|--------------|--------------------|-----------------------|
+//! | `ed25519`                                      | `id-Ed25519` | `OKP`, `Ed25519`   | `ssh-ed25519`         |
+//! | `ed448`                                        | `id-Ed448`   | `OKP`, `Ed448`     | -                     |
+//! | `p256`                                         | `prime256v1` | `EC`, `P-256`      | `ecdsa-sha2-nistp256` |
+//! | `secp256k1`, `secp256k1-tr`, `ecdsa-secp256k1` | `secp256k1`  | `EC`, `secp256k1`  | -                     |
+//! | `ristretto255`                                 | -            | -                  | -                     |
+//!
+//! Formats only describe the key. Apart from `ed25519` and `ecdsa-secp256k1`, FROST
+//! signatures are Schnorr signatures that verifiers of the matching standard algorithm
+//! (e.g. ECDSA for an `ecdsa-sha2-nistp256` key) will not accept.
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::ca::der;
+use crate::ca::{
+    SshPublicKey, OID_EC_PUBLIC_KEY, OID_ED25519, OID_ED448, OID_PRIME256V1, OID_SECP256K1,
+};
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+use crate::jws::{Jwk, JwsError};
+use crate::types::{GroupPublicKeyInfo, SignatureSuiteInfo};
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, EnumString, Display,
+)]
+pub enum PublicKeyFormat {
+    /// The frost serialized verifying key, hex encoded
+    #[default]
+    #[strum(serialize = "hex")]
+    Hex,
+    /// SubjectPublicKeyInfo DER, hex encoded in text output
+    #[strum(serialize = "der")]
+    Der,
+    /// SubjectPublicKeyInfo PEM (`PUBLIC KEY`)
+    #[strum(serialize = "pem")]
+    Pem,
+    #[strum(serialize = "jwk")]
+    Jwk,
+    /// `authorized_keys` line
+    #[strum(serialize = "openssh")]
+    OpenSsh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExportError {
+    #[error("format {1} is not defined for crypto type {0}")]
+    UnsupportedFormat(CryptoType, PublicKeyFormat),
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+}
+
+// SEC1 uncompressed point of a short Weierstrass curve key, from either point encoding
+pub(crate) fn uncompressed_point(
+    crypto_type: CryptoType,
+    public_key: &[u8],
+) -> Result<Vec<u8>, ExportError> {
+    match crypto_type {
+        CryptoType::P256 => p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+            .map(|key| key.to_encoded_point(false).as_bytes().to_vec())
+            .map_err(|e| ExportError::InvalidPublicKey(e.to_string())),
+        CryptoType::Secp256k1 | CryptoType::Secp256k1Tr | CryptoType::EcdsaSecp256k1 => {
+            k256::PublicKey::from_sec1_bytes(public_key)
+                .map(|key| key.to_encoded_point(false).as_bytes().to_vec())
+                .map_err(|e| ExportError::InvalidPublicKey(e.to_string()))
+        }
+        _ => Err(ExportError::InvalidPublicKey(format!(
+            "{} keys are not SEC1 points",
+            crypto_type
+        ))),
+    }
+}
+fn edwards_key(crypto_type: CryptoType, public_key: &[u8]) -> Result<&[u8], ExportError> {
+    let len = match crypto_type {
+        CryptoType::Ed25519 => 32,
+        _ => 57,
+    };
+    if public_key.len() != len {
+        return Err(ExportError::InvalidPublicKey(format!(
+            "{} public key must be {} bytes",
+            crypto_type, len
+        )));
+    }
+    Ok(public_key)
+}
+
+/// SubjectPublicKeyInfo (RFC 5280, RFC 5480, RFC 8410) of a frost serialized verifying key.
+pub fn spki_der(crypto_type: CryptoType, public_key: &[u8]) -> Result<Vec<u8>, ExportError> {
+    let (algorithm, key) = match crypto_type {
+        CryptoType::Ed25519 => (
+            der::sequence(&[der::oid(OID_ED25519)]),
+            edwards_key(crypto_type, public_key)?.to_vec(),
+        ),
+        CryptoType::Ed448 => (
+            der::sequence(&[der::oid(OID_ED448)]),
+            edwards_key(crypto_type, public_key)?.to_vec(),
+        ),
+        CryptoType::P256 => (
+            der::sequence(&[der::oid(OID_EC_PUBLIC_KEY), der::oid(OID_PRIME256V1)]),
+            uncompressed_point(crypto_type, public_key)?,
+        ),
+        CryptoType::Secp256k1 | CryptoType::Secp256k1Tr | CryptoType::EcdsaSecp256k1 => (
+            der::sequence(&[der::oid(OID_EC_PUBLIC_KEY), der::oid(OID_SECP256K1)]),
+            uncompressed_point(crypto_type, public_key)?,
+        ),
+        CryptoType::Ristretto255 => {
+            return Err(ExportError::UnsupportedFormat(
+                crypto_type,
+                PublicKeyFormat::Der,
+            ))
+        }
+    };
+    Ok(der::sequence(&[algorithm, der::bit_string(&key)]))
+}
+pub fn spki_pem(crypto_type: CryptoType, public_key: &[u8]) -> Result<String, ExportError> {
+    let der = spki_der(crypto_type, public_key).map_err(|e| match e {
+        ExportError::UnsupportedFormat(crypto_type, _) => {
+            ExportError::UnsupportedFormat(crypto_type, PublicKeyFormat::Pem)
+        }
+        e => e,
+    })?;
+    Ok(pem::encode(&pem::Pem::new("PUBLIC KEY", der)))
+}
+pub fn jwk(crypto_type: CryptoType, public_key: &[u8], kid: &str) -> Result<Jwk, ExportError> {
+    Jwk::from_public_key(crypto_type, public_key, kid.to_string()).map_err(|e| match e {
+        JwsError::UnsupportedCryptoType(crypto_type) => {
+            ExportError::UnsupportedFormat(crypto_type, PublicKeyFormat::Jwk)
+        }
+        e => ExportError::InvalidPublicKey(e.to_string()),
+    })
+}
+pub fn openssh(crypto_type: CryptoType, public_key: &[u8]) -> Result<String, ExportError> {
+    match crypto_type {
+        CryptoType::Ed25519 => {
+            let key = <[u8; 32]>::try_from(edwards_key(crypto_type, public_key)?).unwrap();
+            Ok(SshPublicKey::ed25519(&key).to_openssh())
+        }
+        CryptoType::P256 => Ok(SshPublicKey::ecdsa_nistp256(&uncompressed_point(
+            crypto_type,
+            public_key,
+        )?)
+        .to_openssh()),
+        _ => Err(ExportError::UnsupportedFormat(
+            crypto_type,
+            PublicKeyFormat::OpenSsh,
+        )),
+    }
+}
+/// Text form of a verifying key, `kid` is only used by [`PublicKeyFormat::Jwk`].
+pub fn export_public_key(
+    crypto_type: CryptoType,
+    public_key: &[u8],
+    kid: &str,
+    format: PublicKeyFormat,
+) -> Result<String, ExportError> {
+    match format {
+        PublicKeyFormat::Hex => Ok(hex::encode(public_key)),
+        PublicKeyFormat::Der => spki_der(crypto_type, public_key).map(hex::encode),
+        PublicKeyFormat::Pem => spki_pem(crypto_type, public_key),
+        PublicKeyFormat::Jwk => Ok(serde_json::to_string(&jwk(crypto_type, public_key, kid)?)
+            .expect("jwk serializes to json")),
+        PublicKeyFormat::OpenSsh => openssh(crypto_type, public_key),
+    }
+}
+
+impl GroupPublicKeyInfo {
+    /// Exports the (tweaked) group key of `pkid`.
+    pub fn export(&self, pkid: &PkId, format: PublicKeyFormat) -> Result<String, ExportError> {
+        let crypto_type = pkid
+            .crypto_type()
+            .map_err(|e| ExportError::InvalidPublicKey(e.to_string()))?;
+        export_public_key(
+            crypto_type,
+            &self.group_public_key_tweak,
+            &pkid.to_string(),
+            format,
+        )
+    }
+}
+impl<VII: ValidatorIdentityIdentity> SignatureSuiteInfo<VII> {
+    /// Exports the (tweaked) verifying key the signature was made with.
+    pub fn export_public_key(&self, format: PublicKeyFormat) -> Result<String, ExportError> {
+        export_public_key(
+            self.crypto_type,
+            &self.pk_verifying_key_tweak,
+            &self.pkid.to_string(),
+            format,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spki_export() {
+        let ed25519 = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let der = spki_der(CryptoType::Ed25519, ed25519.as_bytes()).unwrap();
+        // fixed RFC 8410 prefix followed by the key
+        assert_eq!(
+            &der[..12],
+            &[0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00]
+        );
+        assert_eq!(&der[12..], ed25519.as_bytes());
+
+        let p256 = p256::ecdsa::SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let compressed = p256.verifying_key().to_encoded_point(true);
+        let pem = spki_pem(CryptoType::P256, compressed.as_bytes()).unwrap();
+        let der = pem::parse(pem).unwrap().into_contents();
+        assert_eq!(
+            hex::encode(&der[..26]),
+            "3059301306072a8648ce3d020106082a8648ce3d030107034200"
+        );
+        assert_eq!(
+            &der[26..],
+            p256.verifying_key().to_encoded_point(false).as_bytes()
+        );
+
+        assert_eq!(
+            spki_der(CryptoType::Ristretto255, &[0u8; 32]),
+            Err(ExportError::UnsupportedFormat(
+                CryptoType::Ristretto255,
+                PublicKeyFormat::Der
+            ))
+        );
+    }
+    #[test]
+    fn test_openssh_and_jwk_export() {
+        let p256 = p256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let compressed = p256.verifying_key().to_encoded_point(true);
+        let line = openssh(CryptoType::P256, compressed.as_bytes()).unwrap();
+        let key = SshPublicKey::parse(&line).unwrap();
+        assert_eq!(key.key_type(), "ecdsa-sha2-nistp256");
+
+        let jwk = jwk(CryptoType::P256, compressed.as_bytes(), "kid").unwrap();
+        assert_eq!((jwk.kty.as_str(), jwk.crv.as_str()), ("EC", "P-256"));
+        assert_eq!(jwk.alg, None);
+        assert!(matches!(
+            openssh(CryptoType::Secp256k1, &[2u8; 33]),
+            Err(ExportError::UnsupportedFormat(..))
+        ));
+        assert_eq!(
+            "openssh".parse::<PublicKeyFormat>().unwrap(),
+            PublicKeyFormat::OpenSsh
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! JWS (RFC 7515) compact serialization signed by a group key, for minting JWTs.
+//!
+//! The algorithm follows from the key's [`CryptoType`]:
+//! - [`CryptoType::Ed25519`]: `EdDSA` (RFC 8037), the signing input is signed as is
+//! - [`CryptoType::EcdsaSecp256k1`]: `ES256K` (RFC 8812), the signers sign the SHA-256
+//!   digest of the signing input and the signature is `r || s` with a low `s`
+//!
+//! Keys are published as JWKs whose `kid` is the hex encoded [`PkId`]. JWKs of the other
+//! crypto types, for export only, carry no `alg`.
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL;
+use base64::Engine as _;
+use common::Settings;
+use ed25519_dalek::Verifier as _;
+use k256::ecdsa::signature::hazmat::PrehashVerifier as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentity};
+use crate::export::uncompressed_point;
+use crate::node::Node;
+use crate::types::GroupPublicKeyInfo;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum JwsError {
+    #[error("crypto type {0} is not supported by JOSE")]
+    UnsupportedCryptoType(CryptoType),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("Invalid claims: {0}")]
+    InvalidClaims(String),
+    #[error("Invalid public key: {0}")]
+    InvalidPublicKey(String),
+    #[error("Signing error: {0}")]
+    SigningError(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JwsAlgorithm {
+    EdDSA,
+    ES256K,
+}
+impl JwsAlgorithm {
+    pub fn from_crypto_type(crypto_type: CryptoType) -> Result<Self, JwsError> {
+        match crypto_type {
+            CryptoType::Ed25519 => Ok(JwsAlgorithm::EdDSA),
+            CryptoType::EcdsaSecp256k1 => Ok(JwsAlgorithm::ES256K),
+            _ => Err(JwsError::UnsupportedCryptoType(crypto_type)),
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwsAlgorithm::EdDSA => "EdDSA",
+            JwsAlgorithm::ES256K => "ES256K",
+        }
+    }
+    // the bytes the signers are asked to sign for a given signing input
+    fn message(&self, signing_input: &[u8]) -> Vec<u8> {
+        match self {
+            JwsAlgorithm::EdDSA => signing_input.to_vec(),
+            JwsAlgorithm::ES256K => Sha256::digest(signing_input).to_vec(),
+        }
+    }
+}
+
+/// A public JWK (RFC 7517/8037) of a group key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    pub crv: String,
+    pub x: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    pub kid: String,
+    /// Only set for keys whose signatures are valid JWS signatures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<JwsAlgorithm>,
+    #[serde(rename = "use")]
+    pub key_use: String,
+}
+impl Jwk {
+    /// JWK of a key that can sign JWS, as published in the JWKS.
+    pub fn new(pkid: &PkId, public_key: &GroupPublicKeyInfo) -> Result<Self, JwsError> {
+        let crypto_type = pkid
+            .crypto_type()
+            .map_err(|e| JwsError::InvalidPublicKey(e.to_string()))?;
+        JwsAlgorithm::from_crypto_type(crypto_type)?;
+        Self::from_public_key(
+            crypto_type,
+            &public_key.group_public_key_tweak,
+            pkid.to_string(),
+        )
+    }
+    /// JWK of any key with a defined JWK representation, `public_key` being the frost
+    /// serialized verifying key.
+    pub fn from_public_key(
+        crypto_type: CryptoType,
+        public_key: &[u8],
+        kid: String,
+    ) -> Result<Self, JwsError> {
+        let (kty, crv, x, y) = match crypto_type {
+            CryptoType::Ed25519 | CryptoType::Ed448 => {
+                let (crv, len) = match crypto_type {
+                    CryptoType::Ed25519 => ("Ed25519", 32),
+                    _ => ("Ed448", 57),
+                };
+                if public_key.len() != len {
+                    return Err(JwsError::InvalidPublicKey(format!(
+                        "{} public key must be {} bytes",
+                        crypto_type, len
+                    )));
+                }
+                ("OKP", crv, BASE64URL.encode(public_key), None)
+            }
+            CryptoType::P256
+            | CryptoType::Secp256k1
+            | CryptoType::Secp256k1Tr
+            | CryptoType::EcdsaSecp256k1 => {
+                let uncompressed = uncompressed_point(crypto_type, public_key)
+                    .map_err(|e| JwsError::InvalidPublicKey(e.to_string()))?;
+                let crv = match crypto_type {
+                    CryptoType::P256 => "P-256",
+                    _ => "secp256k1",
+                };
+                (
+                    "EC",
+                    crv,
+                    BASE64URL.encode(&uncompressed[1..33]),
+                    Some(BASE64URL.encode(&uncompressed[33..65])),
+                )
+            }
+            CryptoType::Ristretto255 => return Err(JwsError::UnsupportedCryptoType(crypto_type)),
+        };
+        Ok(Self {
+            kty: kty.to_string(),
+            crv: crv.to_string(),
+            x,
+            y,
+            kid,
+            alg: JwsAlgorithm::from_crypto_type(crypto_type).ok(),
+            key_use: "sig".to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+/// Builds `BASE64URL(header) || '.' || BASE64URL(claims)`, with `alg` and `kid` set in the
+/// header. A header that already names a different `alg` or `kid` is rejected.
+pub(crate) fn signing_input(
+    header: serde_json::Value,
+    claims: &serde_json::Value,
+    alg: JwsAlgorithm,
+    kid: &str,
+) -> Result<String, JwsError> {
+    let serde_json::Value::Object(mut header) = header else {
+        return Err(JwsError::InvalidHeader(
+            "header must be a JSON object".to_string(),
+        ));
+    };
+    for (name, value) in [("alg", alg.as_str()), ("kid", kid)] {
+        match header.get(name) {
+            None => {
+                header.insert(name.to_string(), value.into());
+            }
+            Some(existing) if existing == value => {}
+            Some(existing) => {
+                return Err(JwsError::InvalidHeader(format!(
+                    "{} is {} but the key requires {}",
+                    name, existing, value
+                )));
+            }
+        }
+    }
+    if !claims.is_object() {
+        return Err(JwsError::InvalidClaims(
+            "claims must be a JSON object".to_string(),
+        ));
+    }
+    let header = serde_json::to_vec(&header).map_err(|e| JwsError::InvalidHeader(e.to_string()))?;
+    let claims = serde_json::to_vec(claims).map_err(|e| JwsError::InvalidClaims(e.to_string()))?;
+    Ok(format!(
+        "{}.{}",
+        BASE64URL.encode(header),
+        BASE64URL.encode(claims)
+    ))
+}
+
+/// Signs JWS/JWTs with the group key behind `pkid`.
+pub struct JwsSigner<'a, VI: ValidatorIdentity> {
+    node: &'a Node<VI>,
+    pkid: PkId,
+    alg: JwsAlgorithm,
+    jwk: Jwk,
+    public_key: Vec<u8>,
+}
+impl<'a, VI: ValidatorIdentity> JwsSigner<'a, VI> {
+    pub async fn new(node: &'a Node<VI>, pkid: PkId) -> Result<Self, JwsError> {
+        let crypto_type = pkid
+            .crypto_type()
+            .map_err(|e| JwsError::SigningError(e.to_string()))?;
+        let alg = JwsAlgorithm::from_crypto_type(crypto_type)?;
+        let public_key = node
+            .pk_async(
+                pkid.clone(),
+                None,
+                Some(Duration::from_secs(
+                    Settings::global().node.connection_timeout,
+                )),
+            )
+            .await
+            .map_err(|e| JwsError::SigningError(e.to_string()))?
+            .group_public_key_info;
+        let jwk = Jwk::new(&pkid, &public_key)?;
+        Ok(Self {
+            node,
+            pkid,
+            alg,
+            jwk,
+            public_key: public_key.group_public_key_tweak,
+        })
+    }
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        self.alg
+    }
+    pub fn jwk(&self) -> &Jwk {
+        &self.jwk
+    }
+    /// Signs `claims` and returns the compact serialization `header.payload.signature`.
+    pub async fn sign(
+        &self,
+        header: serde_json::Value,
+        claims: &serde_json::Value,
+    ) -> Result<String, JwsError> {
+        let signing_input = signing_input(header, claims, self.alg, &self.jwk.kid)?;
+        let signature_suite = self
+            .node
+            .sign_async(
+                self.pkid.clone(),
+                self.alg.message(signing_input.as_bytes()),
+                None,
+                Some(Duration::from_secs(
+                    Settings::global().node.connection_timeout,
+                )),
+            )
+            .await
+            .map_err(|e| JwsError::SigningError(e.to_string()))?;
+        let signature = jws_signature(
+            self.alg,
+            &self.public_key,
+            signing_input.as_bytes(),
+            &signature_suite.signature(),
+        )?;
+        Ok(format!("{}.{}", signing_input, BASE64URL.encode(signature)))
+    }
+}
+
+// Checks the group signature against the key and brings it into the JWS encoding.
+pub(crate) fn jws_signature(
+    alg: JwsAlgorithm,
+    public_key: &[u8],
+    signing_input: &[u8],
+    signature: &[u8],
+) -> Result<Vec<u8>, JwsError> {
+    match alg {
+        JwsAlgorithm::EdDSA => {
+            let public_key = <[u8; 32]>::try_from(public_key)
+                .map_err(|_| JwsError::InvalidPublicKey("ed25519 key must be 32 bytes".into()))?;
+            let public_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+                .map_err(|e| JwsError::InvalidPublicKey(e.to_string()))?;
+            let signature = ed25519_dalek::Signature::from_slice(signature)
+                .map_err(|e| JwsError::SigningError(e.to_string()))?;
+            public_key.verify(signing_input, &signature).map_err(|e| {
+                JwsError::SigningError(format!("group signature is invalid: {}", e))
+            })?;
+            Ok(signature.to_bytes().to_vec())
+        }
+        JwsAlgorithm::ES256K => {
+            let public_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|e| JwsError::InvalidPublicKey(e.to_string()))?;
+            let signature = k256::ecdsa::Signature::from_slice(signature)
+                .map_err(|e| JwsError::SigningError(e.to_string()))?;
+            // most ES256K verifiers are built on libsecp256k1, which rejects high s
+            let signature = signature.normalize_s().unwrap_or(signature);
+            public_key
+                .verify_prehash(&Sha256::digest(signing_input), &signature)
+                .map_err(|e| {
+                    JwsError::SigningError(format!("group signature is invalid: {}", e))
+                })?;
+            Ok(signature.to_bytes().to_vec())
+        }
+    }
+}
+
+/// JWKS of all group keys the coordinator holds that can sign JWS.
+pub async fn jwks<VI: ValidatorIdentity>(node: &Node<VI>) -> Result<JwkSet, anyhow::Error> {
+    let timeout = Duration::from_secs(Settings::global().node.connection_timeout);
+    let mut keys = Vec::new();
+    for (crypto_type, key_infos) in node.lspk_async().await? {
+        if JwsAlgorithm::from_crypto_type(crypto_type).is_err() {
+            continue;
+        }
+        for key_info in key_infos {
+            let public_key = node
+                .pk_async(key_info.pkid.clone(), None, Some(timeout))
+                .await?
+                .group_public_key_info;
+            keys.push(Jwk::new(&key_info.pkid, &public_key)?);
+        }
+    }
+    keys.sort_by(|a, b| a.kid.cmp(&b.kid));
+    Ok(JwkSet { keys })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer as _;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use serde_json::json;
+
+    #[test]
+    fn test_eddsa_jws() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let mut pkid = vec![CryptoType::Ed25519.into()];
+        pkid.extend([1u8; 32]);
+        let pkid = PkId::new(pkid);
+        let jwk = Jwk::new(
+            &pkid,
+            &GroupPublicKeyInfo::new(key.verifying_key().to_bytes().to_vec(), None),
+        )
+        .unwrap();
+        assert_eq!(jwk.kty, "OKP");
+        assert_eq!(jwk.kid, pkid.to_string());
+
+        let input = signing_input(
+            json!({"typ": "JWT"}),
+            &json!({"sub": "alice", "exp": 1700000000}),
+            JwsAlgorithm::EdDSA,
+            &jwk.kid,
+        )
+        .unwrap();
+        let (header, _) = input.split_once('.').unwrap();
+        let header: serde_json::Value =
+            serde_json::from_slice(&BASE64URL.decode(header).unwrap()).unwrap();
+        assert_eq!(header["alg"], "EdDSA");
+        assert_eq!(header["kid"], pkid.to_string());
+        assert_eq!(header["typ"], "JWT");
+
+        let signature = key.sign(input.as_bytes()).to_bytes();
+        let encoded = jws_signature(
+            JwsAlgorithm::EdDSA,
+            key.verifying_key().as_bytes(),
+            input.as_bytes(),
+            &signature,
+        )
+        .unwrap();
+        assert_eq!(encoded, signature.to_vec());
+        assert!(jws_signature(
+            JwsAlgorithm::EdDSA,
+            key.verifying_key().as_bytes(),
+            b"other input",
+            &signature,
+        )
+        .is_err());
+        assert!(signing_input(
+            json!({"alg": "none"}),
+            &json!({}),
+            JwsAlgorithm::EdDSA,
+            &jwk.kid
+        )
+        .is_err());
+    }
+    #[test]
+    fn test_es256k_jws() {
+        let key = k256::ecdsa::SigningKey::from_slice(&[9u8; 32]).unwrap();
+        let public_key = key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+        let pkid = PkId::new(vec![CryptoType::EcdsaSecp256k1.into(); 33]);
+        let jwk = Jwk::new(&pkid, &GroupPublicKeyInfo::new(public_key.clone(), None)).unwrap();
+        assert_eq!((jwk.kty.as_str(), jwk.crv.as_str()), ("EC", "secp256k1"));
+        assert_eq!(BASE64URL.decode(jwk.y.unwrap()).unwrap().len(), 32);
+
+        let input = signing_input(
+            json!({}),
+            &json!({"sub": "bob"}),
+            JwsAlgorithm::ES256K,
+            &jwk.kid,
+        )
+        .unwrap();
+        let message = JwsAlgorithm::ES256K.message(input.as_bytes());
+        let (signature, _) = key.sign_prehash_recoverable(&message).unwrap();
+        // a high s is normalized instead of producing a signature libsecp256k1 rejects
+        let high_s =
+            k256::ecdsa::Signature::from_scalars(signature.r(), -*signature.s().as_ref()).unwrap();
+        for signature in [signature, high_s] {
+            let encoded = jws_signature(
+                JwsAlgorithm::ES256K,
+                &public_key,
+                input.as_bytes(),
+                &signature.to_bytes(),
+            )
+            .unwrap();
+            let encoded = k256::ecdsa::Signature::from_slice(&encoded).unwrap();
+            assert!(encoded.normalize_s().is_none());
+        }
+    }
+}
@@ -1,5 +1,7 @@
+mod append_store;
 mod keystore;
 mod management;
+pub(crate) use append_store::AppendStore;
 pub(crate) use keystore::Keystore;
 pub(crate) use management::KeystoreManagement;
 use thiserror::Error;
@@ -0,0 +1,122 @@
+//! Encrypted append-only record file, for stores that change too often to be rewritten
+//! whole through [`super::KeystoreManagement`] on every change.
+//!
+//! Each record is encrypted on its own and prefixed with its big-endian `u32` length. A
+//! record cut short by a crash is dropped when the file is opened.
+use super::Keystore;
+use super::KeystoreError;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+pub(crate) struct AppendStore {
+    keystore: Arc<Keystore>,
+    path: PathBuf,
+    file: File,
+}
+impl AppendStore {
+    /// Opens the file at `path`, creating it if needed, and returns its records oldest
+    /// first.
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        path: impl AsRef<Path>,
+    ) -> Result<(Self, Vec<Vec<u8>>), KeystoreError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let data = std::fs::read(&path)?;
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while offset + 4 <= data.len() {
+            let len = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+            if offset + 4 + len > data.len() {
+                break;
+            }
+            records.push(keystore.decrypt(&data[offset + 4..offset + 4 + len])?);
+            offset += 4 + len;
+        }
+        if offset != data.len() {
+            // the process stopped in the middle of an append
+            tracing::warn!(
+                "Discarding {} bytes of truncated record in {}",
+                data.len() - offset,
+                path.display()
+            );
+            file.set_len(offset as u64)?;
+        }
+        Ok((
+            Self {
+                keystore,
+                path,
+                file,
+            },
+            records,
+        ))
+    }
+    fn encode(&self, record: &[u8]) -> Result<Vec<u8>, KeystoreError> {
+        let ciphertext = self.keystore.encrypt(record)?;
+        let mut data = (ciphertext.len() as u32).to_be_bytes().to_vec();
+        data.extend(ciphertext);
+        Ok(data)
+    }
+    pub(crate) fn append(&mut self, record: &[u8]) -> Result<(), KeystoreError> {
+        let data = self.encode(record)?;
+        self.file.write_all(&data)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+    /// Replaces the records with `records` through a temporary file, so that a crash
+    /// leaves either the old or the new ones.
+    pub(crate) fn rewrite<T: AsRef<[u8]>>(
+        &mut self,
+        records: impl IntoIterator<Item = T>,
+    ) -> Result<(), KeystoreError> {
+        let mut data = Vec::new();
+        for record in records {
+            data.extend(self.encode(record.as_ref())?);
+        }
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(&data)?;
+        tmp.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_store() {
+        let keystore = Arc::new(Keystore::new(b"append", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("append_store_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("records.store");
+
+        let (mut store, records) = AppendStore::open(keystore.clone(), &path).unwrap();
+        assert!(records.is_empty());
+        store.append(b"first").unwrap();
+        store.append(b"second").unwrap();
+        drop(store);
+        // a record cut short is dropped and cut off the file
+        let len = std::fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0, 0, 1, 0, 1, 2]).unwrap();
+        drop(file);
+        let (mut store, records) = AppendStore::open(keystore.clone(), &path).unwrap();
+        assert_eq!(records, vec![b"first".to_vec(), b"second".to_vec()]);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+
+        store.rewrite([b"second"]).unwrap();
+        store.append(b"third").unwrap();
+        drop(store);
+        let (_, records) = AppendStore::open(keystore, &path).unwrap();
+        assert_eq!(records, vec![b"second".to_vec(), b"third".to_vec()]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
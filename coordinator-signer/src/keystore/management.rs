@@ -3,7 +3,7 @@ use super::KeystoreError;
 use file_lock::FileLock;
 use file_lock::FileOptions;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 pub(crate) struct KeystoreManagement {
     keystore: Arc<Keystore>,
@@ -51,6 +51,15 @@ impl KeystoreManagement {
         ))
     }
     pub(crate) fn write(&mut self, data: &[u8]) -> Result<(), KeystoreError> {
+        self.write_inner(data, false)
+    }
+    /// Like [`Self::write`], but overwrites the previous contents and the backup with
+    /// zeros before letting go of them. Filesystems that do not write in place may still
+    /// keep the old blocks.
+    pub(crate) fn write_erasing(&mut self, data: &[u8]) -> Result<(), KeystoreError> {
+        self.write_inner(data, true)
+    }
+    fn write_inner(&mut self, data: &[u8], erase: bool) -> Result<(), KeystoreError> {
         tracing::debug!("write data");
         if self.tmp_path.exists() {
             return Err(KeystoreError::BackupFileExists(
@@ -59,6 +68,9 @@ impl KeystoreManagement {
         }
         if self.path.exists() {
             std::fs::copy(self.path.clone(), self.tmp_path.clone())?;
+            if erase {
+                overwrite_with_zeros(&self.path)?;
+            }
         }
         let ciphertext = self.keystore.as_ref().encrypt(data)?;
         let opts = FileOptions::new().write(true).create(true).truncate(true);
@@ -67,12 +79,21 @@ impl KeystoreManagement {
         lock.unlock()?;
         //remove the tmp_path
         if self.tmp_path.exists() {
+            if erase {
+                overwrite_with_zeros(&self.tmp_path)?;
+            }
             std::fs::remove_file(self.tmp_path.clone())?;
         }
         tracing::debug!("write data complete");
         Ok(())
     }
 }
+fn overwrite_with_zeros(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len() as usize;
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    file.write_all(&vec![0; len])?;
+    file.sync_all()
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,5 +118,10 @@ mod tests {
         assert_eq!(decrypted_data, Some(b"aaa".to_vec()));
         let data = b"test";
         keystore1.write(data).unwrap();
+        keystore1.write_erasing(b"erased").unwrap();
+        assert!(!keystore1.tmp_path.exists());
+        let keystore2 = Arc::new(Keystore::new(b"test", None).unwrap());
+        let (_, decrypted_data) = KeystoreManagement::new(keystore2, path.clone()).unwrap();
+        assert_eq!(decrypted_data, Some(b"erased".to_vec()));
     }
 }
@@ -1,8 +1,19 @@
+pub mod audit;
+pub mod ca;
 pub mod coordinator;
 pub mod crypto;
+pub mod export;
+pub mod jws;
 mod keystore;
 pub mod node;
 pub mod signer;
 mod types;
 mod utils;
-pub use types::SignatureSuiteInfo;
+pub use types::{
+    encode_ecdsa_signature, verify_ecdsa_signature, AutoDKGOptions, Deadline, DkgOptions,
+    DkgSessionStatus, FinishedSessionStatus, KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState,
+    PublicKeyInfo, PublicKeyPackageInfo, SessionKind, SessionOutcome, SessionRequest,
+    SessionResult, SessionStatusReport, SignItem, SignOptions, SignatureFormat, SignaturePage,
+    SignatureQuery, SignatureRecord, SignatureSuiteInfo, SignerSelection, SigningSessionStatus,
+    VOffset,
+};
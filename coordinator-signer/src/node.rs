@@ -8,7 +8,7 @@ use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 use tokio::sync::oneshot;
 
 use common::Settings;
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
 use libp2p::{
     identify::{self},
     noise,
@@ -25,7 +25,11 @@ use crate::types::message::{
     NodeBehaviour, NodeBehaviourEvent, NodeToCoorRequest, NodeToCoorResponse,
     ValidatorIdentityRequest,
 };
-use crate::types::{AutoDKG, ConnectionState, GroupPublicKeyInfo, SignatureSuiteInfo};
+use crate::types::{
+    AppId, AutoDKG, ConnectionState, Deadline, DkgOptions, KeyInfo, KeyMetadata, KeyMetadataUpdate,
+    KeyState, PublicKeyInfo, PublicKeyPackageInfo, SessionOutcome, SessionStatusReport, SignItem,
+    SignOptions, SignaturePage, SignatureQuery, SignatureSuiteInfo, SignerSelection,
+};
 use crate::utils::list_hash;
 
 pub(crate) struct NodeSwarm<VI: ValidatorIdentity> {
@@ -43,26 +47,77 @@ pub(crate) struct NodeSwarm<VI: ValidatorIdentity> {
     )>,
     auto_dkg_response_mapping:
         DashMap<OutboundRequestId, oneshot::Sender<Result<AutoDKG<VI::Identity>, String>>>,
-    lspk_response_mapping:
-        DashMap<OutboundRequestId, oneshot::Sender<Result<HashMap<CryptoType, Vec<PkId>>, String>>>,
+    lspk_response_mapping: DashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
+    >,
     pk_response_mapping:
-        DashMap<OutboundRequestId, oneshot::Sender<Result<GroupPublicKeyInfo, String>>>,
+        DashMap<OutboundRequestId, oneshot::Sender<Result<PublicKeyInfo<VI::Identity>, String>>>,
+    session_outcome_response_mapping: DashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<Vec<SessionOutcome<VI::Identity>>, String>>,
+    >,
+    session_status_response_mapping: DashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<SessionStatusReport<VI::Identity>, String>>,
+    >,
+    cancel_response_mapping: DashMap<OutboundRequestId, oneshot::Sender<Result<String, String>>>,
+    public_key_package_response_mapping: DashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<PublicKeyPackageInfo<VI::Identity>, String>>,
+    >,
+    sign_batch_response_mapping: DashMap<
+        OutboundRequestId,
+        oneshot::Sender<Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>>,
+    >,
+    key_metadata_response_mapping:
+        DashMap<OutboundRequestId, oneshot::Sender<Result<KeyMetadata<VI::Identity>, String>>>,
+    signature_history_response_mapping:
+        DashMap<OutboundRequestId, oneshot::Sender<Result<SignaturePage<VI::Identity>, String>>>,
     signing_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
         NodeToCoorRequest<VI::Identity>,
         oneshot::Sender<Result<SignatureSuiteInfo<VI::Identity>, String>>,
     )>,
     lspk_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
         NodeToCoorRequest<VI::Identity>,
-        oneshot::Sender<Result<HashMap<CryptoType, Vec<PkId>>, String>>,
+        oneshot::Sender<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
     )>,
     pk_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
         NodeToCoorRequest<VI::Identity>,
-        oneshot::Sender<Result<GroupPublicKeyInfo, String>>,
+        oneshot::Sender<Result<PublicKeyInfo<VI::Identity>, String>>,
     )>,
     auto_dkg_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
         NodeToCoorRequest<VI::Identity>,
         oneshot::Sender<Result<AutoDKG<VI::Identity>, String>>,
     )>,
+    session_outcome_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<Vec<SessionOutcome<VI::Identity>>, String>>,
+    )>,
+    session_status_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<SessionStatusReport<VI::Identity>, String>>,
+    )>,
+    cancel_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<String, String>>,
+    )>,
+    public_key_package_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<PublicKeyPackageInfo<VI::Identity>, String>>,
+    )>,
+    sign_batch_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>>,
+    )>,
+    key_metadata_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<KeyMetadata<VI::Identity>, String>>,
+    )>,
+    signature_history_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<SignaturePage<VI::Identity>, String>>,
+    )>,
     connection_state: ConnectionState,
 }
 impl<VI: ValidatorIdentity> NodeSwarm<VI> {
@@ -80,7 +135,7 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
         )>,
         lspk_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
             NodeToCoorRequest<VI::Identity>,
-            oneshot::Sender<Result<HashMap<CryptoType, Vec<PkId>>, String>>,
+            oneshot::Sender<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
         )>,
         auto_dkg_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
             NodeToCoorRequest<VI::Identity>,
@@ -88,7 +143,35 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
         )>,
         pk_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
             NodeToCoorRequest<VI::Identity>,
-            oneshot::Sender<Result<GroupPublicKeyInfo, String>>,
+            oneshot::Sender<Result<PublicKeyInfo<VI::Identity>, String>>,
+        )>,
+        session_outcome_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<Vec<SessionOutcome<VI::Identity>>, String>>,
+        )>,
+        session_status_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<SessionStatusReport<VI::Identity>, String>>,
+        )>,
+        cancel_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<String, String>>,
+        )>,
+        public_key_package_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<PublicKeyPackageInfo<VI::Identity>, String>>,
+        )>,
+        sign_batch_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>>,
+        )>,
+        key_metadata_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<KeyMetadata<VI::Identity>, String>>,
+        )>,
+        signature_history_request_receiver: tokio::sync::mpsc::UnboundedReceiver<(
+            NodeToCoorRequest<VI::Identity>,
+            oneshot::Sender<Result<SignaturePage<VI::Identity>, String>>,
         )>,
     ) -> Result<Self, anyhow::Error> {
         let mut swarm = libp2p::SwarmBuilder::with_existing_identity(p2p_keypair.clone())
@@ -125,11 +208,25 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
             signing_response_mapping: DashMap::new(),
             lspk_response_mapping: DashMap::new(),
             pk_response_mapping: DashMap::new(),
+            session_outcome_response_mapping: DashMap::new(),
+            session_status_response_mapping: DashMap::new(),
+            cancel_response_mapping: DashMap::new(),
+            public_key_package_response_mapping: DashMap::new(),
+            sign_batch_response_mapping: DashMap::new(),
+            key_metadata_response_mapping: DashMap::new(),
+            signature_history_response_mapping: DashMap::new(),
             dkg_request_receiver: dkg_request_receiver,
             auto_dkg_request_receiver: auto_dkg_request_receiver,
             signing_request_receiver: signing_request_receiver,
             lspk_request_receiver: lspk_request_receiver,
             pk_request_receiver: pk_request_receiver,
+            session_outcome_request_receiver: session_outcome_request_receiver,
+            session_status_request_receiver: session_status_request_receiver,
+            cancel_request_receiver: cancel_request_receiver,
+            public_key_package_request_receiver: public_key_package_request_receiver,
+            sign_batch_request_receiver: sign_batch_request_receiver,
+            key_metadata_request_receiver: key_metadata_request_receiver,
+            signature_history_request_receiver: signature_history_request_receiver,
             connection_state: ConnectionState::Disconnected(None),
         });
     }
@@ -210,6 +307,27 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
                         Some((request, sender)) = self.auto_dkg_request_receiver.recv()=>{
                             self.handle_auto_dkg_request(request, sender);
                         }
+                        Some((request, sender)) = self.session_outcome_request_receiver.recv()=>{
+                            self.handle_session_outcome_request(request, sender);
+                        }
+                        Some((request, sender)) = self.session_status_request_receiver.recv()=>{
+                            self.handle_session_status_request(request, sender);
+                        }
+                        Some((request, sender)) = self.cancel_request_receiver.recv()=>{
+                            self.handle_cancel_request(request, sender);
+                        }
+                        Some((request, sender)) = self.public_key_package_request_receiver.recv()=>{
+                            self.handle_public_key_package_request(request, sender);
+                        }
+                        Some((request, sender)) = self.sign_batch_request_receiver.recv()=>{
+                            self.handle_sign_batch_request(request, sender);
+                        }
+                        Some((request, sender)) = self.key_metadata_request_receiver.recv()=>{
+                            self.handle_key_metadata_request(request, sender);
+                        }
+                        Some((request, sender)) = self.signature_history_request_receiver.recv()=>{
+                            self.handle_signature_history_request(request, sender);
+                        }
                     }
                 } else {
                     let event = self.swarm.select_next_some().await;
@@ -252,7 +370,7 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
     pub(crate) fn handle_lspk_request(
         &mut self,
         request: NodeToCoorRequest<VI::Identity>,
-        sender: oneshot::Sender<Result<HashMap<CryptoType, Vec<PkId>>, String>>,
+        sender: oneshot::Sender<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
     ) {
         let request_id = self
             .swarm
@@ -276,7 +394,7 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
     pub(crate) fn handle_pk_request(
         &mut self,
         request: NodeToCoorRequest<VI::Identity>,
-        sender: oneshot::Sender<Result<GroupPublicKeyInfo, String>>,
+        sender: oneshot::Sender<Result<PublicKeyInfo<VI::Identity>, String>>,
     ) {
         let request_id = self
             .swarm
@@ -285,6 +403,97 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
             .send_request(&self.coordinator_peer_id, request);
         self.pk_response_mapping.insert(request_id, sender);
     }
+    pub(crate) fn handle_session_outcome_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<Vec<SessionOutcome<VI::Identity>>, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.session_outcome_response_mapping
+            .insert(request_id, sender);
+    }
+    pub(crate) fn handle_session_status_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<SessionStatusReport<VI::Identity>, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.session_status_response_mapping
+            .insert(request_id, sender);
+    }
+    pub(crate) fn handle_cancel_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<String, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.cancel_response_mapping.insert(request_id, sender);
+    }
+    pub(crate) fn handle_public_key_package_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<PublicKeyPackageInfo<VI::Identity>, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.public_key_package_response_mapping
+            .insert(request_id, sender);
+    }
+    pub(crate) fn handle_sign_batch_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<
+            Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>,
+        >,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.sign_batch_response_mapping.insert(request_id, sender);
+    }
+    pub(crate) fn handle_key_metadata_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<KeyMetadata<VI::Identity>, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.key_metadata_response_mapping
+            .insert(request_id, sender);
+    }
+    pub(crate) fn handle_signature_history_request(
+        &mut self,
+        request: NodeToCoorRequest<VI::Identity>,
+        sender: oneshot::Sender<Result<SignaturePage<VI::Identity>, String>>,
+    ) {
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .node2coor
+            .send_request(&self.coordinator_peer_id, request);
+        self.signature_history_response_mapping
+            .insert(request_id, sender);
+    }
     pub(crate) async fn handle_swarm_event(
         &mut self,
         event: SwarmEvent<NodeBehaviourEvent<VI::Identity>>,
@@ -390,11 +599,11 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
                             );
                         }
                     }
-                    NodeToCoorResponse::LsPkResponse { pkids } => {
+                    NodeToCoorResponse::LsPkResponse { keys } => {
                         if let Some((_, response_oneshot)) =
                             self.lspk_response_mapping.remove(&request_id)
                         {
-                            if let Err(e) = response_oneshot.send(Ok(pkids)) {
+                            if let Err(e) = response_oneshot.send(Ok(keys)) {
                                 tracing::error!("Failed to send response for lspk: {:?}", e);
                             }
                         }
@@ -425,17 +634,93 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
                             }
                         }
                     }
-                    NodeToCoorResponse::PkTweakResponse {
-                        group_public_key_info,
-                    } => {
+                    NodeToCoorResponse::PkTweakResponse { public_key_info } => {
                         if let Some((_, response_oneshot)) =
                             self.pk_response_mapping.remove(&request_id)
                         {
-                            if let Err(e) = response_oneshot.send(Ok(group_public_key_info)) {
+                            if let Err(e) = response_oneshot.send(Ok(public_key_info)) {
                                 tracing::error!("Failed to send response for pk: {:?}", e);
                             }
                         }
                     }
+                    NodeToCoorResponse::SessionOutcomeResponse { outcomes } => {
+                        if let Some((_, response_oneshot)) =
+                            self.session_outcome_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(outcomes)) {
+                                tracing::error!(
+                                    "Failed to send response for session outcomes: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::SessionStatusResponse { report } => {
+                        if let Some((_, response_oneshot)) =
+                            self.session_status_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(report)) {
+                                tracing::error!(
+                                    "Failed to send response for session status: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::CancelResponse { session_id } => {
+                        if let Some((_, response_oneshot)) =
+                            self.cancel_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(session_id)) {
+                                tracing::error!("Failed to send response for cancel: {:?}", e);
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::PublicKeyPackageResponse { package } => {
+                        if let Some((_, response_oneshot)) =
+                            self.public_key_package_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(package)) {
+                                tracing::error!(
+                                    "Failed to send response for public key package: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::SigningBatchResponse { results } => {
+                        if let Some((_, response_oneshot)) =
+                            self.sign_batch_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(results)) {
+                                tracing::error!("Failed to send response for sign batch: {:?}", e);
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::KeyMetadataResponse { metadata } => {
+                        if let Some((_, response_oneshot)) =
+                            self.key_metadata_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(metadata)) {
+                                tracing::error!(
+                                    "Failed to send response for key metadata: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    NodeToCoorResponse::SignatureHistoryResponse { page } => {
+                        if let Some((_, response_oneshot)) =
+                            self.signature_history_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Ok(page)) {
+                                tracing::error!(
+                                    "Failed to send response for signature history: {:?}",
+                                    e
+                                );
+                            }
+                        }
+                    }
                     NodeToCoorResponse::Failure(error) => {
                         if let Some((_, response_oneshot)) =
                             self.dkg_response_mapping.remove(&request_id)
@@ -449,6 +734,48 @@ impl<VI: ValidatorIdentity> NodeSwarm<VI> {
                             if let Err(e) = response_oneshot.send(Err(error)) {
                                 tracing::error!("Failed to send response for failure: {:?}", e);
                             }
+                        } else if let Some((_, response_oneshot)) =
+                            self.session_outcome_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.session_status_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.cancel_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.signature_history_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.key_metadata_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.sign_batch_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
+                        } else if let Some((_, response_oneshot)) =
+                            self.public_key_package_response_mapping.remove(&request_id)
+                        {
+                            if let Err(e) = response_oneshot.send(Err(error)) {
+                                tracing::error!("Failed to send response for failure: {:?}", e);
+                            }
                         } else {
                             tracing::error!(
                                 "No response mapping found for request id: {}",
@@ -480,7 +807,7 @@ pub struct Node<VI: ValidatorIdentity> {
     )>,
     lspk_request_sender: UnboundedSender<(
         NodeToCoorRequest<VI::Identity>,
-        oneshot::Sender<Result<HashMap<CryptoType, Vec<PkId>>, String>>,
+        oneshot::Sender<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
     )>,
     auto_dkg_request_sender: UnboundedSender<(
         NodeToCoorRequest<VI::Identity>,
@@ -488,7 +815,35 @@ pub struct Node<VI: ValidatorIdentity> {
     )>,
     pk_request_sender: UnboundedSender<(
         NodeToCoorRequest<VI::Identity>,
-        oneshot::Sender<Result<GroupPublicKeyInfo, String>>,
+        oneshot::Sender<Result<PublicKeyInfo<VI::Identity>, String>>,
+    )>,
+    session_outcome_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<Vec<SessionOutcome<VI::Identity>>, String>>,
+    )>,
+    session_status_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<SessionStatusReport<VI::Identity>, String>>,
+    )>,
+    cancel_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<String, String>>,
+    )>,
+    public_key_package_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<PublicKeyPackageInfo<VI::Identity>, String>>,
+    )>,
+    sign_batch_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>>,
+    )>,
+    key_metadata_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<KeyMetadata<VI::Identity>, String>>,
+    )>,
+    signature_history_request_sender: UnboundedSender<(
+        NodeToCoorRequest<VI::Identity>,
+        oneshot::Sender<Result<SignaturePage<VI::Identity>, String>>,
     )>,
 }
 
@@ -517,6 +872,16 @@ impl<VI: ValidatorIdentity> Node<VI> {
         let (lspk_request_sender, lspk_request_receiver) = unbounded_channel();
         let (pk_request_sender, pk_request_receiver) = unbounded_channel();
         let (auto_dkg_request_sender, auto_dkg_request_receiver) = unbounded_channel();
+        let (session_outcome_request_sender, session_outcome_request_receiver) =
+            unbounded_channel();
+        let (session_status_request_sender, session_status_request_receiver) = unbounded_channel();
+        let (cancel_request_sender, cancel_request_receiver) = unbounded_channel();
+        let (public_key_package_request_sender, public_key_package_request_receiver) =
+            unbounded_channel();
+        let (sign_batch_request_sender, sign_batch_request_receiver) = unbounded_channel();
+        let (key_metadata_request_sender, key_metadata_request_receiver) = unbounded_channel();
+        let (signature_history_request_sender, signature_history_request_receiver) =
+            unbounded_channel();
         let swarm_node = NodeSwarm::<VI>::new(
             p2p_keypair.clone(),
             coordinator_multiaddr.clone(),
@@ -526,6 +891,13 @@ impl<VI: ValidatorIdentity> Node<VI> {
             lspk_request_receiver,
             auto_dkg_request_receiver,
             pk_request_receiver,
+            session_outcome_request_receiver,
+            session_status_request_receiver,
+            cancel_request_receiver,
+            public_key_package_request_receiver,
+            sign_batch_request_receiver,
+            key_metadata_request_receiver,
+            signature_history_request_receiver,
         )?;
         tokio::spawn(async move {
             swarm_node.start_listening().await;
@@ -540,6 +912,13 @@ impl<VI: ValidatorIdentity> Node<VI> {
             lspk_request_sender: lspk_request_sender,
             auto_dkg_request_sender: auto_dkg_request_sender,
             pk_request_sender: pk_request_sender,
+            session_outcome_request_sender: session_outcome_request_sender,
+            session_status_request_sender: session_status_request_sender,
+            cancel_request_sender: cancel_request_sender,
+            public_key_package_request_sender: public_key_package_request_sender,
+            sign_batch_request_sender: sign_batch_request_sender,
+            key_metadata_request_sender: key_metadata_request_sender,
+            signature_history_request_sender: signature_history_request_sender,
         })
     }
 
@@ -570,6 +949,31 @@ impl<VI: ValidatorIdentity> Node<VI> {
         crypto_type: CryptoType,
         participants: Vec<VI::Identity>,
         min_signers: u16,
+    ) -> Result<oneshot::Receiver<Result<PkId, String>>, anyhow::Error> {
+        self.key_generate_with_deadline(crypto_type, participants, min_signers, None)
+    }
+    /// Like [`Self::key_generate`], but the coordinator and the signers give up once
+    /// `deadline` has passed.
+    pub fn key_generate_with_deadline(
+        &self,
+        crypto_type: CryptoType,
+        participants: Vec<VI::Identity>,
+        min_signers: u16,
+        deadline: Option<Deadline>,
+    ) -> Result<oneshot::Receiver<Result<PkId, String>>, anyhow::Error> {
+        let options = DkgOptions {
+            deadline,
+            ..Default::default()
+        };
+        self.key_generate_with_options(crypto_type, participants, min_signers, options)
+    }
+    /// Like [`Self::key_generate`], with a deadline and the metadata of the new key.
+    pub fn key_generate_with_options(
+        &self,
+        crypto_type: CryptoType,
+        participants: Vec<VI::Identity>,
+        min_signers: u16,
+        options: DkgOptions,
     ) -> Result<oneshot::Receiver<Result<PkId, String>>, anyhow::Error> {
         let request = self.generate_validator_identity();
         let (sender, receiver) = oneshot::channel();
@@ -579,6 +983,9 @@ impl<VI: ValidatorIdentity> Node<VI> {
                 crypto_type,
                 participants,
                 min_signers,
+                deadline: options.deadline,
+                metadata: options.metadata,
+                idempotency_key: options.idempotency_key,
             },
             sender,
         ))?;
@@ -590,20 +997,24 @@ impl<VI: ValidatorIdentity> Node<VI> {
         participants: Vec<VI::Identity>,
         min_signers: u16,
     ) -> Result<PkId, anyhow::Error> {
-        let r = self.key_generate(crypto_type, participants, min_signers)?;
-        let timeout = tokio::time::timeout(
-            Duration::from_secs(Settings::global().node.connection_timeout),
-            r,
-        )
-        .await?;
+        let timeout = Duration::from_secs(Settings::global().node.connection_timeout);
+        let r = self.key_generate_with_deadline(
+            crypto_type,
+            participants,
+            min_signers,
+            Some(Deadline::after(timeout)),
+        )?;
+        let timeout = tokio::time::timeout(timeout, r).await?;
         let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
         return timeout.map_err(|e| anyhow::anyhow!("kdg error: {:?}", e));
     }
 
     pub fn lspk(
         &self,
-    ) -> Result<oneshot::Receiver<Result<HashMap<CryptoType, Vec<PkId>>, String>>, anyhow::Error>
-    {
+    ) -> Result<
+        oneshot::Receiver<Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, String>>,
+        anyhow::Error,
+    > {
         let request = self.generate_validator_identity();
         let (sender, receiver) = oneshot::channel();
         self.lspk_request_sender.send((
@@ -614,7 +1025,9 @@ impl<VI: ValidatorIdentity> Node<VI> {
         ))?;
         return Ok(receiver);
     }
-    pub async fn lspk_async(&self) -> Result<HashMap<CryptoType, Vec<PkId>>, anyhow::Error> {
+    pub async fn lspk_async(
+        &self,
+    ) -> Result<HashMap<CryptoType, Vec<KeyInfo<VI::Identity>>>, anyhow::Error> {
         let r = self.lspk()?;
         let timeout = tokio::time::timeout(
             Duration::from_secs(Settings::global().node.connection_timeout),
@@ -626,12 +1039,21 @@ impl<VI: ValidatorIdentity> Node<VI> {
     }
     pub fn auto_dkg(
         &self,
+    ) -> Result<oneshot::Receiver<Result<AutoDKG<VI::Identity>, String>>, anyhow::Error> {
+        self.auto_dkg_for_app(None)
+    }
+    /// Asks for the AutoDKG state with the keys of the slot `app`, which the coordinator
+    /// generates on the first request; `None` for the default slot.
+    pub fn auto_dkg_for_app(
+        &self,
+        app: Option<AppId>,
     ) -> Result<oneshot::Receiver<Result<AutoDKG<VI::Identity>, String>>, anyhow::Error> {
         let request = self.generate_validator_identity();
         let (sender, receiver) = oneshot::channel();
         self.auto_dkg_request_sender.send((
             NodeToCoorRequest::AutoDKGRequest {
                 validator_identity: request,
+                app,
             },
             sender,
         ))?;
@@ -641,7 +1063,14 @@ impl<VI: ValidatorIdentity> Node<VI> {
         &self,
         timeout: Option<Duration>,
     ) -> Result<AutoDKG<VI::Identity>, anyhow::Error> {
-        let r = self.auto_dkg()?;
+        self.auto_dkg_for_app_async(None, timeout).await
+    }
+    pub async fn auto_dkg_for_app_async(
+        &self,
+        app: Option<AppId>,
+        timeout: Option<Duration>,
+    ) -> Result<AutoDKG<VI::Identity>, anyhow::Error> {
+        let r = self.auto_dkg_for_app(app)?;
         if timeout.is_none() {
             let r = r.await?;
             return r.map_err(|e| anyhow::anyhow!("auto_dkg error: {:?}", e));
@@ -654,7 +1083,7 @@ impl<VI: ValidatorIdentity> Node<VI> {
         &self,
         pkid: PkId,
         tweak_data: Option<Vec<u8>>,
-    ) -> Result<oneshot::Receiver<Result<GroupPublicKeyInfo, String>>, anyhow::Error> {
+    ) -> Result<oneshot::Receiver<Result<PublicKeyInfo<VI::Identity>, String>>, anyhow::Error> {
         let request = self.generate_validator_identity();
         let (sender, receiver) = oneshot::channel();
         self.pk_request_sender.send((
@@ -672,7 +1101,7 @@ impl<VI: ValidatorIdentity> Node<VI> {
         pkid: PkId,
         tweak_data: Option<Vec<u8>>,
         timeout: Option<Duration>,
-    ) -> Result<GroupPublicKeyInfo, anyhow::Error> {
+    ) -> Result<PublicKeyInfo<VI::Identity>, anyhow::Error> {
         let r = self.pk(pkid, tweak_data)?;
         if timeout.is_none() {
             let r = r.await?;
@@ -682,12 +1111,82 @@ impl<VI: ValidatorIdentity> Node<VI> {
         let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
         return timeout.map_err(|e| anyhow::anyhow!("pk error: {:?}", e));
     }
+    /// The full public key package of a key. The receiver resolves to what the
+    /// coordinator sent; `public_key_package_async` also checks it.
+    pub fn public_key_package(
+        &self,
+        pkid: PkId,
+    ) -> Result<oneshot::Receiver<Result<PublicKeyPackageInfo<VI::Identity>, String>>, anyhow::Error>
+    {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.public_key_package_request_sender.send((
+            NodeToCoorRequest::PublicKeyPackageRequest {
+                pkid,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    /// Fetches the public key package of `pkid` and checks that it hashes to `pkid`.
+    pub async fn public_key_package_async(
+        &self,
+        pkid: PkId,
+        timeout: Option<Duration>,
+    ) -> Result<PublicKeyPackageInfo<VI::Identity>, anyhow::Error> {
+        let r = self.public_key_package(pkid.clone())?;
+        let package = if timeout.is_none() {
+            r.await?
+        } else {
+            tokio::time::timeout(timeout.unwrap(), r)
+                .await?
+                .map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?
+        };
+        let package = package.map_err(|e| anyhow::anyhow!("public key package error: {:?}", e))?;
+        if package.pkid != pkid {
+            return Err(anyhow::anyhow!(
+                "public key package error: asked for {}, got {}",
+                pkid,
+                package.pkid
+            ));
+        }
+        package
+            .verify()
+            .map_err(|e| anyhow::anyhow!("public key package error: {}", e))?;
+        Ok(package)
+    }
+    /// Checks `suite` against the public key package of its key instead of the claims the
+    /// coordinator made in it.
+    pub async fn verify_signature_async(
+        &self,
+        suite: &SignatureSuiteInfo<VI::Identity>,
+        timeout: Option<Duration>,
+    ) -> Result<(), anyhow::Error> {
+        let package = self
+            .public_key_package_async(suite.pkid.clone(), timeout)
+            .await?;
+        package
+            .verify_signature(suite)
+            .map_err(|e| anyhow::anyhow!("signature verification error: {}", e))
+    }
     pub fn sign(
         &self,
         pkid: PkId,
         msg: Vec<u8>,
         tweak_data: Option<Vec<u8>>,
     ) -> Result<oneshot::Receiver<Result<SignatureSuiteInfo<VI::Identity>, String>>, anyhow::Error>
+    {
+        self.sign_with_options(pkid, msg, tweak_data, SignOptions::default())
+    }
+    /// Like [`Self::sign`], with a deadline and constraints on which participants sign.
+    pub fn sign_with_options(
+        &self,
+        pkid: PkId,
+        msg: Vec<u8>,
+        tweak_data: Option<Vec<u8>>,
+        options: SignOptions<VI::Identity>,
+    ) -> Result<oneshot::Receiver<Result<SignatureSuiteInfo<VI::Identity>, String>>, anyhow::Error>
     {
         let request = self.generate_validator_identity();
         let (sender, receiver) = oneshot::channel();
@@ -696,6 +1195,10 @@ impl<VI: ValidatorIdentity> Node<VI> {
                 pkid,
                 msg,
                 tweak_data,
+                deadline: options.deadline,
+                selection: options.selection,
+                idempotency_key: options.idempotency_key,
+                signature_format: options.signature_format,
                 validator_identity: request,
             },
             sender,
@@ -709,7 +1212,11 @@ impl<VI: ValidatorIdentity> Node<VI> {
         tweak_data: Option<Vec<u8>>,
         timeout: Option<Duration>,
     ) -> Result<SignatureSuiteInfo<VI::Identity>, anyhow::Error> {
-        let r = self.sign(pkid, msg, tweak_data)?;
+        let options = SignOptions {
+            deadline: timeout.map(Deadline::after),
+            ..Default::default()
+        };
+        let r = self.sign_with_options(pkid, msg, tweak_data, options)?;
         if timeout.is_none() {
             let r = r.await?;
             return r.map_err(|e| anyhow::anyhow!("sign error: {:?}", e));
@@ -718,6 +1225,269 @@ impl<VI: ValidatorIdentity> Node<VI> {
         let timeout = timeout.map_err(|e| anyhow::anyhow!("sign error: {:?}", e))?;
         return timeout.map_err(|e| anyhow::anyhow!("sign error: {:?}", e));
     }
+    /// Signs every item in one request, each in its own session. The results are in the
+    /// order of `items` and a failed item does not fail the others. `selection` applies to
+    /// every item.
+    pub fn sign_batch(
+        &self,
+        items: Vec<SignItem>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VI::Identity>>,
+    ) -> Result<
+        oneshot::Receiver<Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, String>>, String>>,
+        anyhow::Error,
+    > {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.sign_batch_request_sender.send((
+            NodeToCoorRequest::SigningBatchRequest {
+                items,
+                deadline,
+                selection,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn sign_batch_async(
+        &self,
+        items: Vec<SignItem>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Result<SignatureSuiteInfo<VI::Identity>, anyhow::Error>>, anyhow::Error> {
+        let r = self.sign_batch(items, timeout.map(Deadline::after), None)?;
+        let r = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, r).await?,
+            None => r.await,
+        };
+        let results = r
+            .map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?
+            .map_err(|e| anyhow::anyhow!("sign batch error: {:?}", e))?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.map_err(|e| anyhow::anyhow!("sign error: {:?}", e)))
+            .collect())
+    }
+    /// Signs `items` with at most `concurrency` requests in flight and yields the
+    /// signatures as they complete, which is not necessarily the order of `items`.
+    pub fn sign_stream(
+        &self,
+        items: Vec<SignItem>,
+        concurrency: usize,
+        timeout: Option<Duration>,
+    ) -> impl Stream<Item = Result<SignatureSuiteInfo<VI::Identity>, anyhow::Error>> + '_ {
+        futures::stream::iter(items)
+            .map(move |item| async move {
+                let options = SignOptions {
+                    deadline: timeout.map(Deadline::after),
+                    idempotency_key: item.idempotency_key,
+                    signature_format: item.signature_format,
+                    ..Default::default()
+                };
+                let r = self.sign_with_options(item.pkid, item.msg, item.tweak_data, options)?;
+                let r = match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, r).await?,
+                    None => r.await,
+                };
+                r.map_err(|e| anyhow::anyhow!("sign error: {:?}", e))?
+                    .map_err(|e| anyhow::anyhow!("sign error: {:?}", e))
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+    /// Outcomes of this node's DKG and signing requests whose response was lost, e.g.
+    /// because of a disconnect or a coordinator restart. Each outcome is returned once.
+    pub fn session_outcomes(
+        &self,
+    ) -> Result<oneshot::Receiver<Result<Vec<SessionOutcome<VI::Identity>>, String>>, anyhow::Error>
+    {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.session_outcome_request_sender.send((
+            NodeToCoorRequest::SessionOutcomeRequest {
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn session_outcomes_async(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<SessionOutcome<VI::Identity>>, anyhow::Error> {
+        let r = self.session_outcomes()?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r.map_err(|e| anyhow::anyhow!("session outcomes error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout.map_err(|e| anyhow::anyhow!("session outcomes error: {:?}", e));
+    }
+    /// Active DKG and signing sessions on the coordinator and the most recently
+    /// finished ones.
+    pub fn session_status(
+        &self,
+    ) -> Result<oneshot::Receiver<Result<SessionStatusReport<VI::Identity>, String>>, anyhow::Error>
+    {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.session_status_request_sender.send((
+            NodeToCoorRequest::SessionStatusRequest {
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn session_status_async(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<SessionStatusReport<VI::Identity>, anyhow::Error> {
+        let r = self.session_status()?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r.map_err(|e| anyhow::anyhow!("session status error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout.map_err(|e| anyhow::anyhow!("session status error: {:?}", e));
+    }
+    /// Cancel a DKG session (`session-...`) or signing subsession (`subsession-...`)
+    /// previously requested by this node. The pending request resolves with a
+    /// `Cancelled` error.
+    pub fn cancel(
+        &self,
+        session_id: String,
+    ) -> Result<oneshot::Receiver<Result<String, String>>, anyhow::Error> {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.cancel_request_sender.send((
+            NodeToCoorRequest::CancelRequest {
+                session_id,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn cancel_async(
+        &self,
+        session_id: String,
+        timeout: Option<Duration>,
+    ) -> Result<(), anyhow::Error> {
+        let r = self.cancel(session_id)?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r
+                .map(|_| ())
+                .map_err(|e| anyhow::anyhow!("cancel error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("cancel error: {:?}", e));
+    }
+    /// Signatures the coordinator made earlier, e.g. to fetch a signature again after
+    /// its signing response was lost.
+    pub fn signature_history(
+        &self,
+        query: SignatureQuery,
+    ) -> Result<oneshot::Receiver<Result<SignaturePage<VI::Identity>, String>>, anyhow::Error> {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.signature_history_request_sender.send((
+            NodeToCoorRequest::SignatureHistoryRequest {
+                query,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn signature_history_async(
+        &self,
+        query: SignatureQuery,
+        timeout: Option<Duration>,
+    ) -> Result<SignaturePage<VI::Identity>, anyhow::Error> {
+        let r = self.signature_history(query)?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r.map_err(|e| anyhow::anyhow!("signature history error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout.map_err(|e| anyhow::anyhow!("signature history error: {:?}", e));
+    }
+    /// Edits the label, description or tags of a key generated for this node, or of a
+    /// key generated by the operator.
+    pub fn update_key_metadata(
+        &self,
+        pkid: PkId,
+        update: KeyMetadataUpdate,
+    ) -> Result<oneshot::Receiver<Result<KeyMetadata<VI::Identity>, String>>, anyhow::Error> {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        self.key_metadata_request_sender.send((
+            NodeToCoorRequest::KeyMetadataRequest {
+                pkid,
+                update,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn update_key_metadata_async(
+        &self,
+        pkid: PkId,
+        update: KeyMetadataUpdate,
+        timeout: Option<Duration>,
+    ) -> Result<KeyMetadata<VI::Identity>, anyhow::Error> {
+        let r = self.update_key_metadata(pkid, update)?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r.map_err(|e| anyhow::anyhow!("key metadata error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout.map_err(|e| anyhow::anyhow!("key metadata error: {:?}", e));
+    }
+    /// Moves a key generated for this node to another lifecycle state. Destroying a key
+    /// resolves once enough signers confirmed erasing their share.
+    pub fn set_key_state(
+        &self,
+        pkid: PkId,
+        state: KeyState,
+    ) -> Result<oneshot::Receiver<Result<KeyMetadata<VI::Identity>, String>>, anyhow::Error> {
+        let request = self.generate_validator_identity();
+        let (sender, receiver) = oneshot::channel();
+        // answered with the metadata after the change, like metadata updates
+        self.key_metadata_request_sender.send((
+            NodeToCoorRequest::KeyStateRequest {
+                pkid,
+                state,
+                validator_identity: request,
+            },
+            sender,
+        ))?;
+        return Ok(receiver);
+    }
+    pub async fn set_key_state_async(
+        &self,
+        pkid: PkId,
+        state: KeyState,
+        timeout: Option<Duration>,
+    ) -> Result<KeyMetadata<VI::Identity>, anyhow::Error> {
+        let r = self.set_key_state(pkid, state)?;
+        if timeout.is_none() {
+            let r = r.await?;
+            return r.map_err(|e| anyhow::anyhow!("key state error: {:?}", e));
+        }
+        let timeout = tokio::time::timeout(timeout.unwrap(), r).await?;
+        let timeout = timeout.map_err(|e| anyhow::anyhow!("Timeout: {:?}", e))?;
+        return timeout.map_err(|e| anyhow::anyhow!("key state error: {:?}", e));
+    }
     pub fn print_info(&self) -> Result<(), anyhow::Error> {
         tracing::info!(
             "Node's identity: {}, p2p peer id: {}, coordinator peer id: {}",
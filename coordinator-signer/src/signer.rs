@@ -1,7 +1,13 @@
+mod approval;
 mod command;
+mod key_state;
 mod manager;
 mod session;
+mod wasm_policy;
+use approval::ApprovalQueue;
+use futures::future::BoxFuture;
 use futures::stream::FuturesUnordered;
+use key_state::KeyStates;
 use libp2p::request_response::{
     InboundRequestId, OutboundRequestId, ProtocolSupport, ResponseChannel,
 };
@@ -10,7 +16,7 @@ use manager::{
     ManagerRequestWithInboundRequestId, Request, RequestEx, RequestExWithInboundRequestId,
 };
 use session::SessionWrap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -21,7 +27,7 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::oneshot;
 
 use common::Settings;
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use libp2p::{
     identify::{self},
     noise,
@@ -30,6 +36,7 @@ use libp2p::{
 };
 use tokio::io::AsyncWriteExt;
 
+use crate::audit::{AuditEntry, AuditLog};
 use crate::crypto::PkId;
 use crate::crypto::{
     ValidatorIdentity, ValidatorIdentityIdentity, ValidatorIdentityKeypair,
@@ -39,12 +46,64 @@ use crate::keystore;
 use crate::types::error::SessionError;
 use crate::types::message::{
     CoorToSigRequest, CoorToSigResponse, DKGResponseWrap, DKGResponseWrapEx, DKGStageEx,
-    SigBehaviour, SigBehaviourEvent, SigToCoorRequest, SigToCoorResponse, SigningResponseWrap,
-    SigningResponseWrapEx, ValidatorIdentityRequest, ValidatorIdentityResponse,
+    SigBehaviour, SigBehaviourEvent, SigToCoorRequest, SigToCoorResponse, SigningRequestWrap,
+    SigningRequestWrapEx, SigningResponseWrap, SigningResponseWrapEx, SigningStageEx,
+    ValidatorIdentityRequest, ValidatorIdentityResponse,
 };
-use crate::types::ConnectionState;
-use crate::utils::list_hash;
+use crate::types::{ConnectionState, KeyState};
+use crate::utils::{list_hash, now};
 use command::Command;
+use wasm_policy::{PolicyContext, WasmPolicies};
+
+fn signing_audit_entry<VII: ValidatorIdentityIdentity>(
+    request: &SigningRequestWrap<VII>,
+    decision: Result<(), String>,
+) -> Option<AuditEntry<VII>> {
+    Some(AuditEntry::Signing {
+        message: hex::encode(request.message()?),
+        pkid: request.pkid().clone(),
+        crypto_type: request.crypto_type(),
+        subsession_id: request.subsession_id().to_string(),
+        tweak_data: request.tweak_data().map(hex::encode),
+        requester: request.requester().cloned(),
+        decided_at: now(),
+        decision,
+    })
+}
+
+fn signing_ex_audit_entry<VII: ValidatorIdentityIdentity>(
+    request: &SigningRequestWrapEx<VII>,
+    decision: Result<(), String>,
+) -> Option<AuditEntry<VII>> {
+    let request = request.signing_request_ex().ok()?;
+    let SigningStageEx::Init(message, tweak_data) = request.stage else {
+        return None;
+    };
+    Some(AuditEntry::Signing {
+        pkid: request.base_info.pkid,
+        crypto_type: request.base_info.crypto_type,
+        subsession_id: request.base_info.subsession_id.to_string(),
+        message: hex::encode(message),
+        tweak_data: tweak_data.map(hex::encode),
+        requester: request.base_info.requester,
+        decided_at: now(),
+        decision,
+    })
+}
+
+// a signing request held for the operator or turned down, with the channel to answer it on
+enum HeldSigning<VII: ValidatorIdentityIdentity> {
+    Signing(
+        InboundRequestId,
+        SigningRequestWrap<VII>,
+        ResponseChannel<CoorToSigResponse<VII>>,
+    ),
+    SigningEx(
+        InboundRequestId,
+        SigningRequestWrapEx<VII>,
+        ResponseChannel<CoorToSigResponse<VII>>,
+    ),
+}
 
 pub struct Signer<VI: ValidatorIdentity> {
     validator_keypair: VI::Keypair,
@@ -82,10 +141,17 @@ pub struct Signer<VI: ValidatorIdentity> {
         HashMap<OutboundRequestId, oneshot::Sender<Result<DKGResponseWrapEx, SessionError>>>,
     signing_out_response_channels_mapping:
         HashMap<OutboundRequestId, oneshot::Sender<Result<SigningResponseWrapEx, SessionError>>>,
+    erase_response_futures:
+        FuturesUnordered<oneshot::Receiver<(InboundRequestId, Result<(), SessionError>)>>,
 
     channel_mapping: HashMap<InboundRequestId, ResponseChannel<CoorToSigResponse<VI::Identity>>>,
     connection_state: ConnectionState,
     verify_message_fn: Box<dyn Fn(&VI::Identity, &[u8]) -> bool + Send + Sync>,
+    approval_queue: ApprovalQueue<HeldSigning<VI::Identity>>,
+    approval_timeouts: FuturesUnordered<BoxFuture<'static, u64>>,
+    wasm_policies: WasmPolicies,
+    audit_log: Option<AuditLog<VI::Keypair>>,
+    key_states: KeyStates,
 }
 
 impl<VI: ValidatorIdentity> Signer<VI> {
@@ -136,16 +202,28 @@ impl<VI: ValidatorIdentity> Signer<VI> {
             tokio::sync::mpsc::unbounded_channel();
         let (signer2coor_request_sender, signer2coor_request_receiver) =
             tokio::sync::mpsc::unbounded_channel();
+        let keystore = Arc::new(keystore::Keystore::new(
+            validator_keypair.derive_key(b"keystore"),
+            None,
+        )?);
         manager::SignerSessionManager::new(
             coor2signer_request_receiver,
             signer2coor_request_sender,
-            Arc::new(keystore::Keystore::new(
-                validator_keypair.derive_key(b"keystore"),
-                None,
-            )?),
+            keystore.clone(),
             &base_path,
         )?
         .listening();
+        let key_states = KeyStates::open(
+            keystore,
+            base_path
+                .join(Settings::global().signer.keystore_path)
+                .join("key_states"),
+        )?;
+        let wasm_policies = WasmPolicies::load(
+            base_path.join(Settings::global().signer.wasm_policy_path),
+            Settings::global().signer.wasm_policy_fuel,
+            Settings::global().signer.wasm_policy_max_memory,
+        )?;
         let fmt_string = validator_keypair
             .to_public_key()
             .to_identity()
@@ -169,14 +247,42 @@ impl<VI: ValidatorIdentity> Signer<VI> {
             signing_response_futures: FuturesUnordered::new(),
             dkg_response_futures_ex: FuturesUnordered::new(),
             signing_response_futures_ex: FuturesUnordered::new(),
+            erase_response_futures: FuturesUnordered::new(),
 
             channel_mapping: HashMap::new(),
             dkg_out_response_channels_mapping: HashMap::new(),
             signing_out_response_channels_mapping: HashMap::new(),
             connection_state: ConnectionState::Disconnected(None),
             verify_message_fn: Box::new(verify_message_fn),
+            approval_queue: ApprovalQueue::new(
+                HashSet::new(),
+                Duration::from_secs(Settings::global().signer.approval_timeout),
+            ),
+            approval_timeouts: FuturesUnordered::new(),
+            wasm_policies,
+            audit_log: None,
+            key_states,
         })
     }
+    /// Holds every signature with one of `pkids` until the operator approves it
+    /// through IPC.
+    pub fn with_approval(mut self, pkids: impl IntoIterator<Item = PkId>) -> Self {
+        self.approval_queue = ApprovalQueue::new(
+            pkids.into_iter().collect(),
+            Duration::from_secs(Settings::global().signer.approval_timeout),
+        );
+        self
+    }
+    /// Records every signing decision in a hash-chained audit log at `path`, see
+    /// [`crate::audit`].
+    pub fn with_audit_log(mut self, path: PathBuf) -> Result<Self, anyhow::Error> {
+        self.audit_log = Some(AuditLog::open(
+            path,
+            self.validator_keypair.clone(),
+            Settings::global().session.audit_checkpoint_interval,
+        )?);
+        Ok(self)
+    }
     pub async fn start_listening(mut self) -> Result<(), anyhow::Error> {
         tracing::info!(
             "Signer {} start listening",
@@ -299,12 +405,22 @@ impl<VI: ValidatorIdentity> Signer<VI> {
                             tracing::error!("Error handling signing response ex: {}", e);
                         }
                     }
+                    Some(Result::Ok(erase_response)) = self.erase_response_futures.next() => {
+                        tracing::debug!("Received erase response");
+                        self.erase_handle_response(erase_response);
+                    }
                     Some(request) = self.signer2coor_request_receiver.recv() => {
                         tracing::debug!("Received signer2coor request");
                         if let Err(e) = self.handle_signer2coor_request(request).await {
                             tracing::error!("Error handling signer2coor request: {}", e);
                         }
                     }
+                    Some(id) = self.approval_timeouts.next() => {
+                        if let Some(held) = self.approval_queue.take(id) {
+                            tracing::warn!("Signing request {} was not approved in time", id);
+                            self.reject_held(held, "approval timed out".to_string());
+                        }
+                    }
                 }
             } else {
                 loop {
@@ -516,27 +632,88 @@ impl<VI: ValidatorIdentity> Signer<VI> {
                                                             request.message().map(|m| hex::encode(m)),
                                                             request_id
                                                         );
+                        if let Err(reason) = self.key_states.check_active(request.pkid()) {
+                            tracing::warn!("Signing request rejected: {}", reason);
+                            self.reject_held(
+                                HeldSigning::Signing(request_id, request, channel),
+                                reason,
+                            );
+                            return Ok(());
+                        }
                         if let Some(message) = request.message() {
                             // TODO: verifying message may take a long time, we need to do it in a separate thread
                             // TODO: should response rejecting the request if the message is invalid instead of discarding the request
                             if !(*self.verify_message_fn)(request.identity(), message.as_ref()) {
                                 tracing::warn!("Invalid message for signing request, reject to sign the message");
+                                self.audit(signing_audit_entry(
+                                    &request,
+                                    Err("message rejected by the verify function".to_string()),
+                                ));
+                                return Ok(());
+                            }
+                            // owned, the request is moved into the approval queue
+                            let (pkid, requester) =
+                                (request.pkid().clone(), request.requester().cloned());
+                            let context = PolicyContext {
+                                pkid: &pkid,
+                                crypto_type: request.crypto_type(),
+                                tweak_data: request.tweak_data().map(hex::encode),
+                                message: hex::encode(&message),
+                                requester: requester.as_ref(),
+                                participants: request.joined_participants(),
+                            };
+                            let verdict = self.wasm_policies.check(&context);
+                            if let Err(reason) = verdict {
+                                tracing::warn!("Signing request rejected by policy: {}", reason);
+                                self.reject_held(
+                                    HeldSigning::Signing(request_id, request, channel),
+                                    reason,
+                                );
+                                return Ok(());
+                            }
+                            if self.approval_queue.requires_approval(&pkid) {
+                                let (id, timeout) = self.approval_queue.hold(
+                                    &context,
+                                    request.subsession_id().to_string(),
+                                    request.deadline(),
+                                    HeldSigning::Signing(request_id, request, channel),
+                                );
+                                self.wait_for_approval(id, timeout);
                                 return Ok(());
                             }
                         }
-                        let (tx, rx) = tokio::sync::oneshot::channel();
-                        self.signing_response_futures.push(rx);
-                        self.channel_mapping.insert(request_id, channel);
-                        // send request to manager
-                        self.coor2signer_request_sender
-                            .send(ManagerRequestWithInboundRequestId::Request(
-                                Request::Signing((request_id, request), tx),
-                            ))
-                            .unwrap();
+                        self.forward_signing(request_id, request, channel);
                     }
                     CoorToSigRequest::Empty => {
                         tracing::info!("Signer received an empty request");
                     }
+                    CoorToSigRequest::Cancel(target) => {
+                        tracing::info!(
+                            "Signer received cancel request for {}, request_id: {}",
+                            target.to_string(),
+                            request_id
+                        );
+                        self.coor2signer_request_sender
+                            .send(ManagerRequestWithInboundRequestId::Cancel(target))
+                            .unwrap();
+                        if let Err(e) = self
+                            .swarm
+                            .behaviour_mut()
+                            .coor2sig
+                            .send_response(channel, CoorToSigResponse::Cancelled)
+                        {
+                            tracing::error!("Failed to send cancel response: {:?}", e);
+                        }
+                    }
+                    CoorToSigRequest::KeyState { pkid, state } => {
+                        tracing::info!(
+                            "Signer received key state {} for {}, request_id: {}",
+                            state,
+                            pkid,
+                            request_id
+                        );
+                        self.apply_key_state(request_id, pkid, state, channel);
+                    }
                     CoorToSigRequest::DKGRequestEx(dkgrequest_wrap_ex) => {
                         tracing::info!(
                             "Signer received dkg ex request: crypto_type: {}, from identity: {}, request_id: {}, stage: {}",
@@ -570,17 +747,71 @@ impl<VI: ValidatorIdentity> Signer<VI> {
                             signing_request_wrap_ex.identity().to_fmt_string(),
                             request_id
                         );
-                        let (tx, rx) = tokio::sync::oneshot::channel();
-                        self.signing_response_futures_ex.push(rx);
-                        self.channel_mapping.insert(request_id, channel);
-                        self.coor2signer_request_sender
-                            .send(ManagerRequestWithInboundRequestId::RequestEx(
-                                RequestExWithInboundRequestId::SigningEx(
-                                    (request_id, signing_request_wrap_ex),
-                                    tx,
-                                ),
-                            ))
-                            .unwrap();
+                        if let Ok(request) = signing_request_wrap_ex.signing_request_ex() {
+                            if let Err(reason) =
+                                self.key_states.check_active(&request.base_info.pkid)
+                            {
+                                tracing::warn!("Signing request rejected: {}", reason);
+                                self.reject_held(
+                                    HeldSigning::SigningEx(
+                                        request_id,
+                                        signing_request_wrap_ex,
+                                        channel,
+                                    ),
+                                    reason,
+                                );
+                                return Ok(());
+                            }
+                            if let SigningStageEx::Init(message, tweak_data) = &request.stage {
+                                let context = PolicyContext {
+                                    pkid: &request.base_info.pkid,
+                                    crypto_type: request.base_info.crypto_type,
+                                    tweak_data: tweak_data.as_ref().map(hex::encode),
+                                    message: hex::encode(message),
+                                    requester: request.base_info.requester.as_ref(),
+                                    participants: request
+                                        .base_info
+                                        .participants
+                                        .values()
+                                        .cloned()
+                                        .collect(),
+                                };
+                                let verdict = self.wasm_policies.check(&context);
+                                if let Err(reason) = verdict {
+                                    tracing::warn!(
+                                        "Signing request rejected by policy: {}",
+                                        reason
+                                    );
+                                    self.reject_held(
+                                        HeldSigning::SigningEx(
+                                            request_id,
+                                            signing_request_wrap_ex,
+                                            channel,
+                                        ),
+                                        reason,
+                                    );
+                                    return Ok(());
+                                }
+                                if self
+                                    .approval_queue
+                                    .requires_approval(&request.base_info.pkid)
+                                {
+                                    let (id, timeout) = self.approval_queue.hold(
+                                        &context,
+                                        request.base_info.subsession_id.to_string(),
+                                        request.base_info.deadline,
+                                        HeldSigning::SigningEx(
+                                            request_id,
+                                            signing_request_wrap_ex,
+                                            channel,
+                                        ),
+                                    );
+                                    self.wait_for_approval(id, timeout);
+                                    return Ok(());
+                                }
+                            }
+                        }
+                        self.forward_signing_ex(request_id, signing_request_wrap_ex, channel);
                     }
                 }
             }
@@ -681,6 +912,154 @@ impl<VI: ValidatorIdentity> Signer<VI> {
         }
         Ok(())
     }
+    fn forward_signing(
+        &mut self,
+        request_id: InboundRequestId,
+        request: SigningRequestWrap<VI::Identity>,
+        channel: ResponseChannel<CoorToSigResponse<VI::Identity>>,
+    ) {
+        self.audit(signing_audit_entry(&request, Ok(())));
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.signing_response_futures.push(rx);
+        self.channel_mapping.insert(request_id, channel);
+        // send request to manager
+        self.coor2signer_request_sender
+            .send(ManagerRequestWithInboundRequestId::Request(
+                Request::Signing((request_id, request), tx),
+            ))
+            .unwrap();
+    }
+    fn forward_signing_ex(
+        &mut self,
+        request_id: InboundRequestId,
+        request: SigningRequestWrapEx<VI::Identity>,
+        channel: ResponseChannel<CoorToSigResponse<VI::Identity>>,
+    ) {
+        self.audit(signing_ex_audit_entry(&request, Ok(())));
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.signing_response_futures_ex.push(rx);
+        self.channel_mapping.insert(request_id, channel);
+        self.coor2signer_request_sender
+            .send(ManagerRequestWithInboundRequestId::RequestEx(
+                RequestExWithInboundRequestId::SigningEx((request_id, request), tx),
+            ))
+            .unwrap();
+    }
+    fn wait_for_approval(&mut self, id: u64, timeout: Duration) {
+        tracing::info!(
+            "Signing request {} is waiting for approval for {} seconds",
+            id,
+            timeout.as_secs()
+        );
+        self.approval_timeouts
+            .push(tokio::time::sleep(timeout).map(move |_| id).boxed());
+    }
+    fn approve_held(&mut self, held: HeldSigning<VI::Identity>) {
+        match held {
+            HeldSigning::Signing(request_id, request, channel) => {
+                self.forward_signing(request_id, request, channel)
+            }
+            HeldSigning::SigningEx(request_id, request, channel) => {
+                self.forward_signing_ex(request_id, request, channel)
+            }
+        }
+    }
+    fn reject_held(&mut self, held: HeldSigning<VI::Identity>, reason: String) {
+        let (channel, response) = match held {
+            HeldSigning::Signing(_, request, channel) => {
+                self.audit(signing_audit_entry(&request, Err(reason.clone())));
+                (
+                    channel,
+                    CoorToSigResponse::SigningResponse(request.failure(reason)),
+                )
+            }
+            HeldSigning::SigningEx(_, request, channel) => {
+                self.audit(signing_ex_audit_entry(&request, Err(reason.clone())));
+                (
+                    channel,
+                    CoorToSigResponse::SigningResponseEx(request.failure(reason)),
+                )
+            }
+        };
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .coor2sig
+            .send_response(channel, response)
+        {
+            tracing::error!("Failed to send rejection to coordinator: {:?}", e);
+        }
+    }
+    // persists the state first so the key refuses signing even if erasing its share fails
+    fn apply_key_state(
+        &mut self,
+        request_id: InboundRequestId,
+        pkid: PkId,
+        state: KeyState,
+        channel: ResponseChannel<CoorToSigResponse<VI::Identity>>,
+    ) {
+        let previous = match self.key_states.set(pkid.clone(), state) {
+            Ok(previous) => previous,
+            Err(e) => {
+                tracing::error!("Failed to apply key state: {}", e);
+                self.send_key_state_applied(channel, Err(e.to_string()));
+                return;
+            }
+        };
+        if previous != state {
+            self.audit(Some(AuditEntry::KeyLifecycle {
+                pkid: pkid.clone(),
+                from: previous,
+                to: state,
+                requester: None,
+                confirmed_by: vec![],
+                changed_at: now(),
+            }));
+        }
+        if state.keeps_shares() {
+            self.send_key_state_applied(channel, Ok(()));
+            return;
+        }
+        let (tx, rx) = oneshot::channel();
+        self.erase_response_futures.push(rx);
+        self.channel_mapping.insert(request_id, channel);
+        self.coor2signer_request_sender
+            .send(ManagerRequestWithInboundRequestId::EraseKey(
+                (request_id, pkid),
+                tx,
+            ))
+            .unwrap();
+    }
+    fn erase_handle_response(&mut self, response: (InboundRequestId, Result<(), SessionError>)) {
+        let (request_id, result) = response;
+        if let Err(e) = &result {
+            tracing::error!("Failed to erase key share: {}", e);
+        }
+        if let Some(channel) = self.channel_mapping.remove(&request_id) {
+            self.send_key_state_applied(channel, result.map_err(|e| e.to_string()));
+        }
+    }
+    fn send_key_state_applied(
+        &mut self,
+        channel: ResponseChannel<CoorToSigResponse<VI::Identity>>,
+        result: Result<(), String>,
+    ) {
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .coor2sig
+            .send_response(channel, CoorToSigResponse::KeyStateApplied(result))
+        {
+            tracing::error!("Failed to send key state response: {:?}", e);
+        }
+    }
+    fn audit(&mut self, entry: Option<AuditEntry<VI::Identity>>) {
+        if let (Some(audit_log), Some(entry)) = (self.audit_log.as_mut(), entry) {
+            if let Err(e) = audit_log.append(&entry) {
+                tracing::error!("Error writing audit record: {}", e);
+            }
+        }
+    }
     pub(crate) async fn dkg_handle_response(
         &mut self,
         response: (
@@ -911,6 +1290,40 @@ impl<VI: ValidatorIdentity> Signer<VI> {
                             reader.get_mut().write_all(b"Coordinator pinged\n").await?;
                         }
                     }
+                    Command::Pending => {
+                        let pending = serde_json::to_string_pretty(&self.approval_queue.pending())?;
+                        reader.get_mut().write_all(pending.as_bytes()).await?;
+                        reader.get_mut().write_all(b"\n").await?;
+                    }
+                    Command::Approve(id) => {
+                        let msg = match self.approval_queue.take(id) {
+                            Some(held) => {
+                                tracing::info!("Operator approved signing request {}", id);
+                                self.approve_held(held);
+                                format!("Approved {}\n", id)
+                            }
+                            None => format!("No pending signing request {}\n", id),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::Reject(id) => {
+                        let msg = match self.approval_queue.take(id) {
+                            Some(held) => {
+                                tracing::info!("Operator rejected signing request {}", id);
+                                self.reject_held(held, "rejected by the operator".to_string());
+                                format!("Rejected {}\n", id)
+                            }
+                            None => format!("No pending signing request {}\n", id),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
+                    Command::ReloadPolicy => {
+                        let msg = match self.wasm_policies.reload() {
+                            Ok(modules) => format!("Policy reloaded, {} modules\n", modules),
+                            Err(e) => format!("Error reloading policy: {}\n", e),
+                        };
+                        reader.get_mut().write_all(msg.as_bytes()).await?;
+                    }
                     Command::Unknown(cmd) => {
                         tracing::debug!("Unknown command: {}", cmd);
                         let msg = format!("Unknown command: {}\n", cmd);
@@ -0,0 +1,158 @@
+//! Signing requests for keys that need an operator's approval.
+//!
+//! A request is held as soon as it carries the message to sign, which is round 2 for
+//! FROST and the init stage for ECDSA, and the coordinator gets no answer until the
+//! operator approves or rejects it through IPC or the approval times out. The
+//! coordinator's round and request timeouts have to leave the operator enough time.
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use super::wasm_policy::PolicyContext;
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+use crate::types::Deadline;
+
+/// What the operator sees of a held request.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct PendingSigning {
+    pub(crate) id: u64,
+    pub(crate) pkid: PkId,
+    pub(crate) crypto_type: CryptoType,
+    pub(crate) subsession_id: String,
+    /// Hex encoded.
+    pub(crate) message: String,
+    /// Hex encoded.
+    pub(crate) tweak_data: Option<String>,
+    /// The node that asked the coordinator for the signature.
+    pub(crate) requester: Option<String>,
+    /// The signers taking part in this signing.
+    pub(crate) participants: Vec<String>,
+    /// Unix millis.
+    pub(crate) deadline: Option<u64>,
+    /// Unix seconds.
+    pub(crate) received_at: u64,
+}
+
+pub(crate) struct ApprovalQueue<T> {
+    pkids: HashSet<PkId>,
+    timeout: Duration,
+    next_id: u64,
+    pending: BTreeMap<u64, (PendingSigning, T)>,
+}
+
+impl<T> ApprovalQueue<T> {
+    pub(crate) fn new(pkids: HashSet<PkId>, timeout: Duration) -> Self {
+        Self {
+            pkids,
+            timeout,
+            next_id: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+    pub(crate) fn requires_approval(&self, pkid: &PkId) -> bool {
+        self.pkids.contains(pkid)
+    }
+    /// Holds `held`, the signing described by `context`, until a decision. Returns its
+    /// id and how long the operator has to decide, which is never past the request
+    /// deadline.
+    pub(crate) fn hold<VII: ValidatorIdentityIdentity>(
+        &mut self,
+        context: &PolicyContext<VII>,
+        subsession_id: String,
+        deadline: Option<Deadline>,
+        held: T,
+    ) -> (u64, Duration) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let pending = PendingSigning {
+            id,
+            pkid: context.pkid.clone(),
+            crypto_type: context.crypto_type,
+            subsession_id,
+            message: context.message.clone(),
+            tweak_data: context.tweak_data.clone(),
+            requester: context.requester.map(|requester| requester.to_fmt_string()),
+            participants: context
+                .participants
+                .iter()
+                .map(|participant| participant.to_fmt_string())
+                .collect(),
+            deadline: deadline.map(|deadline| deadline.unix_millis()),
+            received_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        self.pending.insert(id, (pending, held));
+        (id, Deadline::clamp(&deadline, self.timeout))
+    }
+    pub(crate) fn pending(&self) -> Vec<PendingSigning> {
+        self.pending
+            .values()
+            .map(|(pending, _)| pending.clone())
+            .collect()
+    }
+    /// Removes a held request, `None` if it was already decided or timed out.
+    pub(crate) fn take(&mut self, id: u64) -> Option<T> {
+        self.pending.remove(&id).map(|(_, held)| held)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_approval_queue() {
+        let pkid = PkId::new(vec![CryptoType::Ed25519.into(), 1]);
+        let (requester, signer) = (PeerId::random(), PeerId::random());
+        let tweaked = PolicyContext {
+            pkid: &pkid,
+            crypto_type: CryptoType::Ed25519,
+            tweak_data: Some("01".to_string()),
+            message: hex::encode(b"message"),
+            requester: Some(&requester),
+            participants: vec![signer],
+        };
+        let untweaked = PolicyContext {
+            pkid: &pkid,
+            crypto_type: CryptoType::Ed25519,
+            tweak_data: None,
+            message: hex::encode(b"message"),
+            requester: None,
+            participants: vec![signer],
+        };
+        let mut queue = ApprovalQueue::new(HashSet::from([pkid.clone()]), Duration::from_secs(60));
+        assert!(queue.requires_approval(&pkid));
+        assert!(!queue.requires_approval(&PkId::new(vec![CryptoType::Ed25519.into(), 2])));
+
+        let (first, timeout) = queue.hold(
+            &tweaked,
+            "subsession".to_string(),
+            Some(Deadline::after(Duration::from_secs(5))),
+            "first",
+        );
+        assert!(timeout <= Duration::from_secs(5));
+        let (second, timeout) = queue.hold(&untweaked, "subsession".to_string(), None, "second");
+        assert_eq!(timeout, Duration::from_secs(60));
+        assert_eq!(queue.pending().len(), 2);
+        assert_eq!(queue.pending()[0].message, hex::encode(b"message"));
+        assert_eq!(queue.pending()[0].tweak_data, Some("01".to_string()));
+        assert_eq!(
+            queue.pending()[0].requester,
+            Some(requester.to_fmt_string())
+        );
+        assert_eq!(
+            queue.pending()[0].participants,
+            vec![signer.to_fmt_string()]
+        );
+        assert_eq!(queue.pending()[1].requester, None);
+
+        assert_eq!(queue.take(second), Some("second"));
+        assert_eq!(queue.take(second), None);
+        assert_eq!(queue.take(first), Some("first"));
+        assert!(queue.pending().is_empty());
+    }
+}
@@ -5,23 +5,41 @@ pub(crate) enum Command {
     P2pPeerId,
     Help,
     PingCoordinator,
+    Pending,
+    Approve(u64),
+    Reject(u64),
+    ReloadPolicy,
     Unknown(String),
 }
 impl Command {
     pub(crate) fn parse(input: &str) -> Self {
-        match input
+        let input = input
             .trim()
             .to_lowercase()
             .replace("-", " ")
-            .replace("_", " ")
-            .as_str()
-        {
+            .replace("_", " ");
+        match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["approve", id] => {
+                if let Ok(id) = id.parse() {
+                    return Command::Approve(id);
+                }
+            }
+            ["reject", id] => {
+                if let Ok(id) = id.parse() {
+                    return Command::Reject(id);
+                }
+            }
+            _ => {}
+        }
+        match input.as_str() {
             "peer id" | "id" | "pid" => Command::PeerId,
             "validator peer id" | "vpid" => Command::ValidatorPeerId,
             "coordinator peer id" | "cpid" => Command::CoordinatorPeerId,
             "p2p peer id" | "ppid" => Command::P2pPeerId,
             "help" | "h" => Command::Help,
             "ping coordinator" | "pc" => Command::PingCoordinator,
+            "pending" => Command::Pending,
+            "reload policy" | "policy reload" => Command::ReloadPolicy,
             other => Command::Unknown(other.to_string()),
         }
     }
@@ -32,6 +50,10 @@ impl Command {
             - `coordinator peer id`/`cpid`: Show the coordinator peer ID
             - `p2p peer id`/`ppid`: Show the p2p peer ID
         - `help`/`h`: Show this help message
-        - `ping coordinator`/`pc`: Ping the coordinator"
+        - `ping coordinator`/`pc`: Ping the coordinator
+        - `pending`: List signing requests waiting for approval
+        - `approve <id>`: Approve a pending signing request
+        - `reject <id>`: Reject a pending signing request
+        - `reload policy`/`policy reload`: Reload the WASM policy modules"
     }
 }
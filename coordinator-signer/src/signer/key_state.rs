@@ -0,0 +1,86 @@
+//! Lifecycle states the coordinator sent for this signer's keys, kept encrypted next to
+//! the key shares. Keys without an entry are active.
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::crypto::PkId;
+use crate::keystore::{Keystore, KeystoreManagement};
+use crate::types::error::SessionError;
+use crate::types::KeyState;
+
+pub(crate) struct KeyStates {
+    keystore_management: KeystoreManagement,
+    states: HashMap<PkId, KeyState>,
+}
+impl KeyStates {
+    pub(crate) fn open(
+        keystore: Arc<Keystore>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self, SessionError> {
+        let (keystore_management, data) = KeystoreManagement::new(keystore, dir)?;
+        let states = match data {
+            Some(data) => bincode::deserialize(&data)
+                .map_err(|e| SessionError::DeserializationError(e.to_string()))?,
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            keystore_management,
+            states,
+        })
+    }
+    pub(crate) fn get(&self, pkid: &PkId) -> KeyState {
+        self.states.get(pkid).copied().unwrap_or_default()
+    }
+    /// Moves `pkid` to `state` and returns the previous state. Setting the current state
+    /// again is allowed so that a failed destroy can be retried.
+    pub(crate) fn set(&mut self, pkid: PkId, state: KeyState) -> Result<KeyState, SessionError> {
+        let previous = self.get(&pkid);
+        if previous != state && !previous.can_become(state) {
+            return Err(SessionError::InvalidRequest(format!(
+                "key {} is {} and cannot become {}",
+                pkid, previous, state
+            )));
+        }
+        self.states.insert(pkid, state);
+        let data = bincode::serialize(&self.states)
+            .map_err(|e| SessionError::SerializationError(e.to_string()))?;
+        self.keystore_management.write(&data)?;
+        Ok(previous)
+    }
+    /// Errors with the reason unless `pkid` may sign.
+    pub(crate) fn check_active(&self, pkid: &PkId) -> Result<(), String> {
+        match self.get(pkid) {
+            KeyState::Active => Ok(()),
+            state => Err(format!("key {} is {}", pkid, state)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_states() {
+        let keystore = Arc::new(Keystore::new(b"key states", None).unwrap());
+        let dir = std::env::temp_dir().join(format!("key_states_{}", uuid::Uuid::new_v4()));
+        let pkid = PkId::new(vec![1; 33]);
+
+        let mut states = KeyStates::open(keystore.clone(), &dir).unwrap();
+        assert!(states.check_active(&pkid).is_ok());
+        assert_eq!(
+            states.set(pkid.clone(), KeyState::Disabled).unwrap(),
+            KeyState::Active
+        );
+        assert!(states.check_active(&pkid).is_err());
+        states.set(pkid.clone(), KeyState::Destroyed).unwrap();
+        states.set(pkid.clone(), KeyState::Destroyed).unwrap();
+        assert!(states.set(pkid.clone(), KeyState::Active).is_err());
+        drop(states);
+
+        let states = KeyStates::open(keystore, &dir).unwrap();
+        assert_eq!(states.get(&pkid), KeyState::Destroyed);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -9,6 +9,7 @@ use crate::types::message::{
     DKGRequestWrap, DKGRequestWrapEx, DKGResponseWrap, DKGResponseWrapEx, SigningRequestWrap,
     SigningRequestWrapEx, SigningResponseWrap, SigningResponseWrapEx,
 };
+use crate::types::CancelTarget;
 use libp2p::request_response::InboundRequestId;
 use strum::EnumCount;
 use tokio::sync::{
@@ -20,6 +21,8 @@ use crate::crypto::*;
 
 use super::SessionWrap;
 
+pub(crate) type EraseKeySender = oneshot::Sender<(InboundRequestId, Result<(), SessionError>)>;
+
 #[derive(Debug)]
 pub(crate) enum Request<VII: ValidatorIdentityIdentity> {
     DKG(
@@ -33,6 +36,9 @@ pub(crate) enum Request<VII: ValidatorIdentityIdentity> {
             Result<SigningResponseWrap<VII>, SessionError>,
         )>,
     ),
+    Cancel(CancelTarget),
+    // drops the share of a key from the keystore
+    EraseKey((InboundRequestId, PkId), EraseKeySender),
 }
 #[derive(Debug)]
 pub(crate) enum RequestExWithInboundRequestId<VII: ValidatorIdentityIdentity> {
@@ -47,6 +53,8 @@ pub(crate) enum RequestExWithInboundRequestId<VII: ValidatorIdentityIdentity> {
             Result<SigningResponseWrapEx, SessionError>,
         )>,
     ),
+    Cancel(CancelTarget),
+    EraseKey((InboundRequestId, PkId), EraseKeySender),
 }
 #[derive(Debug)]
 pub(crate) enum RequestEx<VII: ValidatorIdentityIdentity> {
@@ -63,6 +71,8 @@ pub(crate) enum RequestEx<VII: ValidatorIdentityIdentity> {
 pub(crate) enum ManagerRequestWithInboundRequestId<VII: ValidatorIdentityIdentity> {
     Request(Request<VII>),
     RequestEx(RequestExWithInboundRequestId<VII>),
+    Cancel(CancelTarget),
+    EraseKey((InboundRequestId, PkId), EraseKeySender),
 }
 pub(crate) enum ManagerRequest<VII: ValidatorIdentityIdentity> {
     #[allow(dead_code)]
@@ -158,6 +168,68 @@ impl<VII: ValidatorIdentityIdentity> SignerSessionManager<VII> {
                 tracing::debug!("Received request in manager {:?}", request);
                 if let Some(request) = request {
                     match request {
+                        ManagerRequestWithInboundRequestId::Cancel(target) => {
+                            let crypto_type = match target.crypto_type() {
+                                Ok(crypto_type) => crypto_type,
+                                Err(e) => {
+                                    tracing::error!("Invalid cancel target {:?}: {}", target, e);
+                                    continue;
+                                }
+                            };
+                            if let Some(session_inst_channel) =
+                                self.session_inst_channels.get(&crypto_type)
+                            {
+                                session_inst_channel.send(Request::Cancel(target)).unwrap();
+                            } else if let Some(session_inst_channel) =
+                                self.session_inst_channels_ex.get(&crypto_type)
+                            {
+                                session_inst_channel
+                                    .send(RequestExWithInboundRequestId::Cancel(target))
+                                    .unwrap();
+                            } else {
+                                tracing::error!("No session for crypto type {:?}", crypto_type);
+                            }
+                        }
+                        ManagerRequestWithInboundRequestId::EraseKey(
+                            (request_id, pkid),
+                            sender,
+                        ) => {
+                            let crypto_type = match pkid.crypto_type() {
+                                Ok(crypto_type) => crypto_type,
+                                Err(e) => {
+                                    sender
+                                        .send((
+                                            request_id,
+                                            Err(SessionError::CryptoTypeErrorNative(e)),
+                                        ))
+                                        .unwrap();
+                                    continue;
+                                }
+                            };
+                            if let Some(session_inst_channel) =
+                                self.session_inst_channels.get(&crypto_type)
+                            {
+                                session_inst_channel
+                                    .send(Request::EraseKey((request_id, pkid), sender))
+                                    .unwrap();
+                            } else if let Some(session_inst_channel) =
+                                self.session_inst_channels_ex.get(&crypto_type)
+                            {
+                                session_inst_channel
+                                    .send(RequestExWithInboundRequestId::EraseKey(
+                                        (request_id, pkid),
+                                        sender,
+                                    ))
+                                    .unwrap();
+                            } else {
+                                sender
+                                    .send((
+                                        request_id,
+                                        Err(SessionError::CryptoTypeError(crypto_type)),
+                                    ))
+                                    .unwrap();
+                            }
+                        }
                         ManagerRequestWithInboundRequestId::Request(Request::DKG(
                             (request_id, dkg_request_wrap),
                             sender,
@@ -11,7 +11,10 @@ use dkg_ex::DKGSessionEx;
 use rand::thread_rng;
 use signing::SigningSession;
 use signing_ex::SigningSessionEx;
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender},
+    task::AbortHandle,
+};
 
 use crate::{
     crypto::{Cipher, CryptoType},
@@ -24,7 +27,7 @@ use crate::{
             DKGResult, DKGStageEx, SigningRequest, SigningRequestEx, SigningRequestWrap,
             SigningRequestWrapEx, SigningResponseWrap, SigningResponseWrapEx, SigningStageEx,
         },
-        SessionId,
+        CancelTarget, Deadline, SessionId,
     },
     utils,
 };
@@ -54,6 +57,10 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         let rng = thread_rng();
         let request = DKGRequest::<VII, C>::from(request)?;
         let session_id = request.session_id();
+        if let Err(e) = Deadline::check(&request.base_info.deadline, "DKG request") {
+            self.dkg_sessions.remove(&session_id);
+            return Err(e);
+        }
         match self.dkg_sessions.get_mut(&session_id) {
             Some(session) => {
                 tracing::debug!(
@@ -88,12 +95,35 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
         let mut rng = thread_rng();
         let request = SigningRequest::<VII, C>::from(request)?;
         let pkid = request.base_info.pkid.clone();
+        if let Err(e) = Deadline::check(&request.base_info.deadline, "signing request") {
+            if let Some(session) = self.signing_sessions.get_mut(&pkid) {
+                session.cancel_subsession(&request.base_info.subsession_id);
+            }
+            return Err(e);
+        }
         let response = match self.signing_sessions.get_mut(&pkid) {
             Some(session) => Ok(session.apply_request(request, &mut rng)?),
             None => Err(SessionError::PkIdNotFound(pkid.to_string())),
         }?;
         Ok(SigningResponseWrap::from(response)?)
     }
+    pub(crate) fn cancel(&mut self, target: CancelTarget) -> bool {
+        match target {
+            CancelTarget::Dkg(session_id) => self.dkg_sessions.remove(&session_id).is_some(),
+            CancelTarget::Signing(subsession_id) => self
+                .signing_sessions
+                .get_mut(&subsession_id.pkid())
+                .map_or(false, |session| session.cancel_subsession(&subsession_id)),
+        }
+    }
+    // drops the share of `pkid` and overwrites the keystore that held it
+    pub(crate) fn erase_key(&mut self, pkid: &PkId) -> Result<(), SessionError> {
+        if self.signing_sessions.remove(pkid).is_some() {
+            self.keystore_management
+                .write_erasing(self.serialize_sessions()?.as_slice())?;
+        }
+        Ok(())
+    }
 }
 impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
     pub(crate) fn new(
@@ -180,6 +210,27 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SessionWrap<VII, C> {
 
                             response_oneshot.send((request_id, result)).unwrap();
                         }
+                        Request::Cancel(target) => {
+                            let cancelled = self.cancel(target);
+                            tracing::info!(
+                                "Cancel {} in {:?} session, found: {}",
+                                target.to_string(),
+                                C::crypto_type(),
+                                cancelled
+                            );
+                        }
+                        Request::EraseKey((request_id, pkid), response_oneshot) => {
+                            let result = self.erase_key(&pkid);
+                            tracing::info!(
+                                "Erase key {} in {:?} session: {:?}",
+                                pkid,
+                                C::crypto_type(),
+                                result
+                            );
+                            if let Err(e) = response_oneshot.send((request_id, result)) {
+                                tracing::error!("Error sending erase response: {:?}", e);
+                            }
+                        }
                     }
                 }
             }
@@ -191,6 +242,8 @@ pub(crate) struct SessionWrapEx<VII: ValidatorIdentityIdentity> {
     crypto_type: CryptoType,
     dkg_sessions_ex: HashMap<SessionId, UnboundedSender<DKGRequestEx<VII>>>,
     signing_sessions_ex: HashMap<PkId, SigningSessionEx<VII>>,
+    // ecdsa_tss client tasks, aborted on cancel
+    tasks_ex: HashMap<CancelTarget, AbortHandle>,
     in_rx: UnboundedReceiver<RequestExWithInboundRequestId<VII>>,
     out_tx: UnboundedSender<RequestEx<VII>>,
     keystore_management: KeystoreManagement,
@@ -237,6 +290,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
             crypto_type,
             dkg_sessions_ex: HashMap::new(),
             signing_sessions_ex: signing_sessions,
+            tasks_ex: HashMap::new(),
             in_rx,
             out_tx,
             keystore_management,
@@ -277,7 +331,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                 let dkg_results_tx = self.dkg_results_tx.clone();
                 let out_tx = self.out_tx.clone();
                 let dkg_final_tx = self.dkg_final_tx.clone();
-                tokio::spawn(async move {
+                let task = tokio::spawn(async move {
                     let result =
                         DKGSessionEx::new_from_request(request.clone(), in_rx_internal, out_tx)
                             .await;
@@ -301,6 +355,7 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     );
                     dkg_results_tx.send(result).unwrap();
                 });
+                self.track_task(CancelTarget::Dkg(session_id), task.abort_handle());
                 return Ok(());
             }
         }
@@ -337,12 +392,13 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     tracing::info!("create new subsession: {:?}", subsession_id.to_string());
                     if let Some(in_rx) = in_rx {
                         let base = session.base.clone();
-                        tokio::spawn(async move {
+                        let task = tokio::spawn(async move {
                             let result =
                                 SigningSessionEx::new_from_request(request, base, out_tx, in_rx)
                                     .await;
                             signing_results_tx.send(result).unwrap();
                         });
+                        self.track_task(CancelTarget::Signing(subsession_id), task.abort_handle());
                     } else {
                         return Err(SessionError::InvalidRequest(format!(
                             "subsession {:?} already exists",
@@ -356,6 +412,42 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
         }
         Ok(())
     }
+    fn track_task(&mut self, target: CancelTarget, abort_handle: AbortHandle) {
+        self.tasks_ex.retain(|_, handle| !handle.is_finished());
+        self.tasks_ex.insert(target, abort_handle);
+    }
+    pub(crate) fn cancel(&mut self, target: CancelTarget) -> bool {
+        let found = match target {
+            CancelTarget::Dkg(session_id) => self.dkg_sessions_ex.remove(&session_id).is_some(),
+            CancelTarget::Signing(subsession_id) => self
+                .signing_sessions_ex
+                .get_mut(&subsession_id.pkid())
+                .map_or(false, |session| {
+                    session.subsessions.remove(&subsession_id).is_some()
+                }),
+        };
+        // aborting the task drops its ecdsa_tss client
+        if let Some(abort_handle) = self.tasks_ex.remove(&target) {
+            abort_handle.abort();
+        }
+        found
+    }
+    // drops the share of `pkid` with its running subsessions and overwrites the keystore
+    // that held it
+    pub(crate) fn erase_key(&mut self, pkid: &PkId) -> Result<(), SessionError> {
+        self.tasks_ex.retain(|target, abort_handle| match target {
+            CancelTarget::Signing(subsession_id) if subsession_id.pkid() == *pkid => {
+                abort_handle.abort();
+                false
+            }
+            _ => true,
+        });
+        if self.signing_sessions_ex.remove(pkid).is_some() {
+            self.keystore_management
+                .write_erasing(self.serialize_sessions()?.as_slice())?;
+        }
+        Ok(())
+    }
     fn deserialize_sessions(
         bytes: &[u8],
     ) -> Result<HashMap<PkId, SigningSessionEx<VII>>, SessionError> {
@@ -481,6 +573,27 @@ impl<VII: ValidatorIdentityIdentity> SessionWrapEx<VII> {
                     }
                 }
             }
+            RequestExWithInboundRequestId::Cancel(target) => {
+                let cancelled = self.cancel(target);
+                tracing::info!(
+                    "Cancel {} in {:?} session, found: {}",
+                    target.to_string(),
+                    self.crypto_type,
+                    cancelled
+                );
+            }
+            RequestExWithInboundRequestId::EraseKey((request_id, pkid), response_oneshot) => {
+                let result = self.erase_key(&pkid);
+                tracing::info!(
+                    "Erase key {} in {:?} session: {:?}",
+                    pkid,
+                    self.crypto_type,
+                    result
+                );
+                if let Err(e) = response_oneshot.send((request_id, result)) {
+                    tracing::error!("Error sending erase response: {:?}", e);
+                }
+            }
         }
     }
     pub(crate) fn handle_dkg_final(
@@ -9,6 +9,7 @@ use crate::{
         message::{
             DKGBaseMessage, DKGFinal, DKGRequestEx, DKGStageEx, MessageEx, TargetOrBroadcast,
         },
+        Deadline,
     },
     utils,
 };
@@ -37,6 +38,7 @@ impl<VII: ValidatorIdentityIdentity> DKGSessionEx<VII> {
         } = request.base_info.clone();
         participants.check_identifier_identity_exists(&identifier, &identity)?;
         participants.check_min_signers(min_signers)?;
+        Deadline::check(&request.base_info.deadline, "DKG request")?;
         if let DKGStageEx::Init = request.stage.clone() {
             let client =
                 ecdsa_tss::EcdsaTssSignerClient::new(common::Settings::global().signer.ecdsa_port)
@@ -57,8 +59,10 @@ impl<VII: ValidatorIdentityIdentity> DKGSessionEx<VII> {
                 threshold: request.base_info.min_signers as u32,
                 ids: participants.iter().map(|p| (*p.0) as u32).collect(),
             };
-            let timeout =
-                time::Duration::from_secs(common::Settings::global().signer.ecdsa_dkg_timeout);
+            let timeout = Deadline::clamp(
+                &request.base_info.deadline,
+                time::Duration::from_secs(common::Settings::global().signer.ecdsa_dkg_timeout),
+            );
             let (in_tx_client, in_rx_client) =
                 tokio::sync::mpsc::unbounded_channel::<CoordinatorToSignerMsg>();
             let (out_tx_client, mut out_rx_client) =
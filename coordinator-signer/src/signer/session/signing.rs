@@ -92,4 +92,8 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SigningSession<VII, C> {
     pub(crate) fn pkid(&self) -> PkId {
         self.base.pkid.clone()
     }
+    // drops the subsession together with its nonces
+    pub(crate) fn cancel_subsession(&mut self, subsession_id: &SubsessionId) -> bool {
+        self.subsessions.remove(subsession_id).is_some()
+    }
 }
@@ -12,7 +12,7 @@ use crate::{
             DKGBaseMessage, MessageEx, SignatureEx, SigningBaseMessage, SigningRequestEx,
             SigningRequestWrapEx, SigningStageEx, TargetOrBroadcast,
         },
-        SubsessionId,
+        Deadline, SubsessionId,
     },
     utils,
 };
@@ -118,6 +118,7 @@ impl<VII: ValidatorIdentityIdentity> SigningSessionEx<VII> {
         } = request.base_info.clone();
         // todo: check pkid
         participants.check_identifier_identity_exists(&identifier, &identity)?;
+        Deadline::check(&request.base_info.deadline, "signing request")?;
         if let SigningStageEx::Init(msg, derive) = request.stage {
             let client =
                 ecdsa_tss::EcdsaTssSignerClient::new(common::Settings::global().signer.ecdsa_port)
@@ -146,8 +147,10 @@ impl<VII: ValidatorIdentityIdentity> SigningSessionEx<VII> {
                 message: msg,
                 derivation_delta: utils::derived_data(derive),
             };
-            let timeout =
-                time::Duration::from_secs(common::Settings::global().signer.ecdsa_sign_timeout);
+            let timeout = Deadline::clamp(
+                &request.base_info.deadline,
+                time::Duration::from_secs(common::Settings::global().signer.ecdsa_sign_timeout),
+            );
             let (out_tx_client, mut out_rx_client) =
                 tokio::sync::mpsc::unbounded_channel::<SignerToCoordinatorMsg>();
             let message_base_info = request.base_info.clone();
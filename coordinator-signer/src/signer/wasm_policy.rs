@@ -0,0 +1,214 @@
+//! Operator supplied WASM modules deciding whether this signer takes part in a signing.
+//!
+//! Every `*.wasm` file in the policy directory is loaded, in file name order, and all of
+//! them have to allow a request. A module exports its `memory`, an `alloc(len: i32) -> i32`
+//! returning where the host may write `len` bytes, and a `check(ptr: i32, len: i32) -> i64`
+//! that receives the [`PolicyContext`] as JSON. `check` returns 0 to allow the request,
+//! anything else denies it with the UTF-8 reason at `ret >> 32` of length `ret & 0xffffffff`.
+//! Modules get no imports, a fresh instance per request and bounded fuel and memory, so a
+//! module that traps, loops or grows too much denies the request instead of stalling the
+//! signer.
+use std::path::PathBuf;
+
+use serde::Serialize;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+
+// longest deny reason read back from a module
+const MAX_REASON_LEN: usize = 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum WasmPolicyError {
+    #[error("failed to read policy modules: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid policy module {0}: {1}")]
+    InvalidModule(String, wasmi::Error),
+}
+
+/// Everything a module knows about the signing it is asked about.
+#[derive(Debug, Serialize)]
+pub(crate) struct PolicyContext<'a, VII: ValidatorIdentityIdentity> {
+    pub(crate) pkid: &'a PkId,
+    pub(crate) crypto_type: CryptoType,
+    /// Hex encoded.
+    pub(crate) tweak_data: Option<String>,
+    /// Hex encoded.
+    pub(crate) message: String,
+    /// The node that asked the coordinator for the signature.
+    pub(crate) requester: Option<&'a VII>,
+    /// The signers taking part in this signing.
+    pub(crate) participants: Vec<VII>,
+}
+
+pub(crate) struct WasmPolicies {
+    dir: PathBuf,
+    engine: Engine,
+    modules: Vec<(String, Module)>,
+    fuel: u64,
+    max_memory: usize,
+}
+
+impl WasmPolicies {
+    /// Loads the modules in `dir`, allowing everything if it does not exist.
+    pub(crate) fn load(
+        dir: PathBuf,
+        fuel: u64,
+        max_memory: usize,
+    ) -> Result<Self, WasmPolicyError> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let mut policies = Self {
+            dir,
+            engine: Engine::new(&config),
+            modules: Vec::new(),
+            fuel,
+            max_memory,
+        };
+        policies.reload()?;
+        Ok(policies)
+    }
+    /// Loads the modules again, keeping the current ones if any module is invalid.
+    /// Returns the number of modules now in force.
+    pub(crate) fn reload(&mut self) -> Result<usize, WasmPolicyError> {
+        let mut modules = Vec::new();
+        if self.dir.exists() {
+            let mut paths = std::fs::read_dir(&self.dir)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            paths.retain(|path| path.extension().is_some_and(|ext| ext == "wasm"));
+            paths.sort();
+            for path in paths {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let bytes = std::fs::read(&path)?;
+                let module = Module::new(&self.engine, &bytes[..])
+                    .map_err(|e| WasmPolicyError::InvalidModule(name.clone(), e))?;
+                modules.push((name, module));
+            }
+        }
+        self.modules = modules;
+        Ok(self.modules.len())
+    }
+    /// Runs every module on `context`, returning the reason of the first denial.
+    pub(crate) fn check<VII: ValidatorIdentityIdentity>(
+        &self,
+        context: &PolicyContext<VII>,
+    ) -> Result<(), String> {
+        if self.modules.is_empty() {
+            return Ok(());
+        }
+        let input = serde_json::to_vec(context)
+            .map_err(|e| format!("failed to encode policy context: {}", e))?;
+        for (name, module) in self.modules.iter() {
+            match self.run(module, &input) {
+                Ok(None) => {}
+                Ok(Some(reason)) => return Err(format!("policy {} denied: {}", name, reason)),
+                Err(e) => return Err(format!("policy {} failed: {}", name, e)),
+            }
+        }
+        Ok(())
+    }
+    fn run(&self, module: &Module, input: &[u8]) -> Result<Option<String>, anyhow::Error> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(self.fuel)?;
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow::anyhow!("module does not export its memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+        let check = instance.get_typed_func::<(i32, i32), i64>(&store, "check")?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input)?;
+        let ret = check.call(&mut store, (ptr, len))? as u64;
+        if ret == 0 {
+            return Ok(None);
+        }
+        let (ptr, len) = ((ret >> 32) as usize, (ret & 0xffff_ffff) as usize);
+        let mut reason = vec![0; len.min(MAX_REASON_LEN)];
+        memory.read(&store, ptr, &mut reason)?;
+        Ok(Some(String::from_utf8_lossy(&reason).to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    const ALLOW: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "check") (param i32 i32) (result i64) (i64.const 0)))"#;
+    // denies requests whose JSON context is longer than 600 bytes
+    const SHORT_ONLY: &str = r#"(module
+        (memory (export "memory") 1)
+        (data (i32.const 16) "too long")
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "check") (param i32 i32) (result i64)
+            (if (result i64) (i32.gt_u (local.get 1) (i32.const 600))
+                (then (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 8)))
+                (else (i64.const 0)))))"#;
+    const SPIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "check") (param i32 i32) (result i64) (loop (br 0)) (i64.const 0)))"#;
+    const HUNGRY: &str = r#"(module
+        (memory (export "memory") 1024)
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "check") (param i32 i32) (result i64) (i64.const 0)))"#;
+
+    #[test]
+    fn test_wasm_policies() {
+        let dir = std::env::temp_dir().join(format!("wasm_policies_{}", uuid::Uuid::new_v4()));
+        let mut policies = WasmPolicies::load(dir.clone(), 1_000_000, 1 << 20).unwrap();
+        let pkid = PkId::new(vec![CryptoType::Ed25519.into(), 1]);
+        let requester = PeerId::random();
+        let context = |message: &[u8]| PolicyContext {
+            pkid: &pkid,
+            crypto_type: CryptoType::Ed25519,
+            tweak_data: None,
+            message: hex::encode(message),
+            requester: Some(&requester),
+            participants: vec![PeerId::random(), PeerId::random()],
+        };
+        assert!(policies.check(&context(&[0; 400])).is_ok());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a_allow.wasm"), wat::parse_str(ALLOW).unwrap()).unwrap();
+        std::fs::write(
+            dir.join("b_short.wasm"),
+            wat::parse_str(SHORT_ONLY).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("readme.txt"), "not a module").unwrap();
+        assert_eq!(policies.reload().unwrap(), 2);
+        assert!(policies.check(&context(b"short")).is_ok());
+        let denied = policies.check(&context(&[0; 400])).unwrap_err();
+        assert!(denied.contains("b_short.wasm") && denied.contains("too long"));
+
+        std::fs::write(dir.join("c_spin.wasm"), wat::parse_str(SPIN).unwrap()).unwrap();
+        policies.reload().unwrap();
+        assert!(policies.check(&context(b"short")).is_err());
+
+        std::fs::remove_file(dir.join("c_spin.wasm")).unwrap();
+        std::fs::write(dir.join("c_hungry.wasm"), wat::parse_str(HUNGRY).unwrap()).unwrap();
+        policies.reload().unwrap();
+        assert!(policies.check(&context(b"short")).is_err());
+
+        std::fs::write(dir.join("d_broken.wasm"), b"not wasm").unwrap();
+        assert!(policies.reload().is_err());
+        assert_eq!(policies.modules.len(), 3);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
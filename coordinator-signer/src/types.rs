@@ -1,15 +1,38 @@
 pub(crate) mod auto_dkg;
 pub use auto_dkg::*;
+mod deadline;
 pub(crate) mod error;
+mod key_metadata;
 pub(crate) mod message;
+mod public_key_package;
 mod session;
+mod session_outcome;
+mod session_status;
+mod sign_options;
+mod signature_format;
+mod signature_history;
 mod signature_suite;
+mod signer_selection;
 mod validator;
 
-pub(crate) use session::{Participants, SessionId, SubsessionId};
+pub use deadline::Deadline;
+pub use key_metadata::{KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState, PublicKeyInfo};
+pub use public_key_package::PublicKeyPackageInfo;
+pub(crate) use session::{CancelTarget, Participants, SessionId, SubsessionId};
+pub use session_outcome::{SessionOutcome, SessionRequest, SessionResult};
+pub use session_status::{
+    DkgSessionStatus, FinishedSessionStatus, SessionKind, SessionStatusReport, SigningSessionStatus,
+};
+pub use sign_options::{DkgOptions, SignItem, SignOptions};
+pub use signature_format::{
+    encode_ecdsa_signature, verify_ecdsa_signature, SignatureFormat, VOffset,
+};
+pub use signature_history::{SignaturePage, SignatureQuery, SignatureRecord};
 pub use signature_suite::GroupPublicKeyInfo;
 pub(crate) use signature_suite::SignatureSuite;
 pub use signature_suite::SignatureSuiteInfo;
+pub(crate) use signer_selection::SelectedIdentifiers;
+pub use signer_selection::SignerSelection;
 pub(crate) use validator::Validator;
 
 use crate::crypto::ValidatorIdentityIdentity;
@@ -4,10 +4,39 @@ use serde::{Deserialize, Serialize};
 
 use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
 
+/// Name of an application slot of AutoDKG.
+pub type AppId = String;
+/// The slot of the keys generated once all signers registered.
+pub const DEFAULT_APP: &str = "default";
+const MAX_APP_LENGTH: usize = 64;
+
+fn all_crypto_types() -> Vec<CryptoType> {
+    <CryptoType as strum::IntoEnumIterator>::iter().collect()
+}
+
+/// How the coordinator runs AutoDKG.
+#[derive(Debug, Clone)]
+pub struct AutoDKGOptions<VII: ValidatorIdentityIdentity> {
+    pub min_signers: u16,
+    /// Crypto types every slot gets a key of, all of them if empty.
+    pub crypto_types: Vec<CryptoType>,
+    /// Signers holding the keys, the signer whitelist if empty.
+    pub participants: HashSet<VII>,
+}
+
+/// Keys generated without an explicit DKG request. Every application slot gets its own
+/// key per crypto type, so applications do not share a base key and its tweaks; the
+/// keys of the default slot are generated once all signers registered, the ones of other
+/// slots when a node first asks for them.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AutoDKG<VII: ValidatorIdentityIdentity> {
     pub min_signers: u16,
     state: AutoDKGState<VII>,
+    // the signers holding the slot keys, known once all of them registered
+    #[serde(default)]
+    participants: Vec<(u16, VII)>,
+    #[serde(default = "all_crypto_types")]
+    crypto_types: Vec<CryptoType>,
 }
 
 impl<VII: ValidatorIdentityIdentity> std::fmt::Display for AutoDKG<VII> {
@@ -23,8 +52,58 @@ impl<VII: ValidatorIdentityIdentity> std::fmt::Display for AutoDKG<VII> {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AutoDKGState<VII: ValidatorIdentityIdentity> {
     WaitingForSignersRegistration(HashMap<VII, bool>),
-    WaitingForSignersDKG(HashMap<CryptoType, PkId>),
-    Done(HashMap<CryptoType, PkId>),
+    WaitingForSignersDKG(#[serde(with = "slots")] HashMap<(AppId, CryptoType), PkId>),
+    Done(#[serde(with = "slots")] HashMap<(AppId, CryptoType), PkId>),
+}
+
+// JSON maps need string keys, so the slots are stored as a list; files written before
+// there were slots hold a map of the default slot keys
+mod slots {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Slots(Vec<(AppId, CryptoType, PkId)>),
+        Default(HashMap<CryptoType, PkId>),
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        slots: &HashMap<(AppId, CryptoType), PkId>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut slots = slots
+            .iter()
+            .map(|((app, crypto_type), pkid)| (app, crypto_type, pkid))
+            .collect::<Vec<_>>();
+        slots.sort_by(|a, b| (a.0, a.1.to_string()).cmp(&(b.0, b.1.to_string())));
+        slots.serialize(serializer)
+    }
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(AppId, CryptoType), PkId>, D::Error> {
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Slots(slots) => slots
+                .into_iter()
+                .map(|(app, crypto_type, pkid)| ((app, crypto_type), pkid))
+                .collect(),
+            Stored::Default(keys) => keys
+                .into_iter()
+                .map(|(crypto_type, pkid)| ((DEFAULT_APP.to_string(), crypto_type), pkid))
+                .collect(),
+        })
+    }
+}
+
+fn write_slots(
+    f: &mut std::fmt::Formatter<'_>,
+    slots: &HashMap<(AppId, CryptoType), PkId>,
+) -> std::fmt::Result {
+    for ((app, crypto_type), pkid) in slots {
+        write!(f, "{}/{:?}: {}, ", app, crypto_type, pkid.to_string())?;
+    }
+    Ok(())
 }
 
 impl<VII: ValidatorIdentityIdentity> std::fmt::Display for AutoDKGState<VII> {
@@ -39,28 +118,35 @@ impl<VII: ValidatorIdentityIdentity> std::fmt::Display for AutoDKGState<VII> {
             }
             AutoDKGState::WaitingForSignersDKG(map) => {
                 write!(f, "WaitingForSignersDKG {{ ")?;
-                for (crypto_type, pkid) in map {
-                    write!(f, "{:?}: {}, ", crypto_type, pkid.to_string())?;
-                }
+                write_slots(f, map)?;
                 write!(f, "}}")
             }
             AutoDKGState::Done(map) => {
                 write!(f, "Done {{ ")?;
-                for (crypto_type, pkid) in map {
-                    write!(f, "{:?}: {}, ", crypto_type, pkid.to_string())?;
-                }
+                write_slots(f, map)?;
                 write!(f, "}}")
             }
         }
     }
 }
 impl<VII: ValidatorIdentityIdentity> AutoDKG<VII> {
-    pub(crate) fn new(min_signers: u16, signers: HashSet<VII>) -> Self {
+    pub(crate) fn new(
+        min_signers: u16,
+        signers: HashSet<VII>,
+        mut crypto_types: Vec<CryptoType>,
+    ) -> Self {
+        if crypto_types.is_empty() {
+            crypto_types = all_crypto_types();
+        }
+        let mut seen = HashSet::new();
+        crypto_types.retain(|crypto_type| seen.insert(*crypto_type));
         Self {
             min_signers,
             state: AutoDKGState::WaitingForSignersRegistration(
                 signers.into_iter().map(|signer| (signer, false)).collect(),
             ),
+            participants: Vec::new(),
+            crypto_types,
         }
     }
     pub(crate) fn register_signer(&mut self, signer: VII) -> Option<Vec<(u16, VII)>> {
@@ -70,43 +156,256 @@ impl<VII: ValidatorIdentityIdentity> AutoDKG<VII> {
             }
             // if all is true, then start dkg
             if state.values().all(|v| *v) {
-                let r = Some(
-                    state
-                        .iter()
-                        .enumerate()
-                        .map(|(i, (signer, _))| ((i + 1) as u16, signer.clone()))
-                        .collect(),
-                );
+                self.participants = state
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (signer, _))| ((i + 1) as u16, signer.clone()))
+                    .collect();
                 self.state = AutoDKGState::WaitingForSignersDKG(HashMap::new());
-                return r;
+                return Some(self.participants.clone());
             }
         }
         None
     }
-    // if all dkg is done first time, return true
+    // if all dkg of the slot is done first time, return true
     pub(crate) fn update_new_dkg_result(
         &mut self,
+        app: &str,
         crypto_type: CryptoType,
         pkid: PkId,
     ) -> (bool, Self) {
-        if let AutoDKGState::WaitingForSignersDKG(state) = &mut self.state {
-            if !state.contains_key(&crypto_type) {
-                state.insert(crypto_type, pkid);
+        let state = match &mut self.state {
+            AutoDKGState::WaitingForSignersDKG(state) if app == DEFAULT_APP => state,
+            AutoDKGState::Done(state) if app != DEFAULT_APP => state,
+            _ => return (false, self.clone()),
+        };
+        if state.contains_key(&(app.to_string(), crypto_type)) {
+            return (false, self.clone());
+        }
+        state.insert((app.to_string(), crypto_type), pkid);
+        if !Self::missing(state, &self.crypto_types, app).is_empty() {
+            return (false, self.clone());
+        }
+        if let AutoDKGState::WaitingForSignersDKG(state) = &self.state {
+            self.state = AutoDKGState::Done(state.clone());
+        }
+        (true, self.clone())
+    }
+    fn missing(
+        state: &HashMap<(AppId, CryptoType), PkId>,
+        crypto_types: &[CryptoType],
+        app: &str,
+    ) -> Vec<CryptoType> {
+        crypto_types
+            .iter()
+            .filter(|crypto_type| !state.contains_key(&(app.to_string(), **crypto_type)))
+            .copied()
+            .collect()
+    }
+    /// The crypto types of the slot `app` that have no key yet.
+    pub(crate) fn missing_crypto_types(&self, app: &str) -> Vec<CryptoType> {
+        match &self.state {
+            AutoDKGState::WaitingForSignersRegistration(_) => self.crypto_types.clone(),
+            AutoDKGState::WaitingForSignersDKG(state) | AutoDKGState::Done(state) => {
+                Self::missing(state, &self.crypto_types, app)
             }
-            if state.len() == <CryptoType as strum::EnumCount>::COUNT {
-                self.state = AutoDKGState::Done(state.clone());
-                return (true, self.clone());
+        }
+    }
+    /// Slots whose DKGs were started but not all finished, with the missing crypto types.
+    pub(crate) fn pending_slots(&self) -> Vec<(AppId, Vec<CryptoType>)> {
+        let state = match &self.state {
+            AutoDKGState::WaitingForSignersRegistration(_) => return Vec::new(),
+            AutoDKGState::WaitingForSignersDKG(_) => {
+                return vec![(
+                    DEFAULT_APP.to_string(),
+                    self.missing_crypto_types(DEFAULT_APP),
+                )];
             }
+            AutoDKGState::Done(state) => state,
+        };
+        let apps = state.keys().map(|(app, _)| app).collect::<HashSet<_>>();
+        apps.into_iter()
+            .map(|app| (app.clone(), self.missing_crypto_types(app)))
+            .filter(|(_, missing)| !missing.is_empty())
+            .collect()
+    }
+    /// The signers holding the keys, empty until all of them registered.
+    pub(crate) fn participants(&self) -> &[(u16, VII)] {
+        &self.participants
+    }
+    /// Whether `signer` holds the keys or is waited for to generate them.
+    pub(crate) fn is_participant(&self, signer: &VII) -> bool {
+        match &self.state {
+            AutoDKGState::WaitingForSignersRegistration(state) => state.contains_key(signer),
+            _ => self
+                .participants
+                .iter()
+                .any(|(_, participant)| participant == signer),
+        }
+    }
+    pub fn crypto_types(&self) -> &[CryptoType] {
+        &self.crypto_types
+    }
+    /// The signers that generate the keys of a new slot, the ones of the default slot.
+    pub(crate) fn slot_participants(&self) -> Result<Vec<(u16, VII)>, String> {
+        if !self.is_done() {
+            return Err(format!("auto dkg not done"));
+        }
+        if self.participants.is_empty() {
+            // states saved before there were slots
+            return Err(format!("signers of the auto dkg keys are unknown"));
+        }
+        Ok(self.participants.clone())
+    }
+    /// Errors unless `app` is 1 to 64 letters, digits, '-' or '_'.
+    pub fn check_app(app: &str) -> Result<(), String> {
+        if app.is_empty()
+            || app.len() > MAX_APP_LENGTH
+            || !app
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "app name {:?} must be 1 to {} letters, digits, '-' or '_'",
+                app, MAX_APP_LENGTH
+            ));
+        }
+        Ok(())
+    }
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, AutoDKGState::Done(_))
+    }
+    /// Whether every key of the slot `app` was generated.
+    pub fn has_app(&self, app: &str) -> bool {
+        match &self.state {
+            AutoDKGState::Done(state) => Self::missing(state, &self.crypto_types, app).is_empty(),
+            _ => false,
         }
-        (false, self.clone())
     }
     pub fn get_pkid_by_crypto_type(&self, crypto_type: CryptoType) -> Result<PkId, String> {
+        self.get_pkid_by_app(DEFAULT_APP, crypto_type)
+    }
+    pub fn get_pkid_by_app(&self, app: &str, crypto_type: CryptoType) -> Result<PkId, String> {
         if let AutoDKGState::Done(state) = &self.state {
             return state
-                .get(&crypto_type)
+                .get(&(app.to_string(), crypto_type))
                 .cloned()
-                .ok_or(format!("crypto_type {} not found", crypto_type));
+                .ok_or(format!(
+                    "crypto_type {} of app {} not found",
+                    crypto_type, app
+                ));
         }
         Err(format!("auto dkg not done"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_app_slots() {
+        let signers = (0..3).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let mut auto_dkg = AutoDKG::new(2, signers.iter().cloned().collect(), vec![]);
+        assert!(auto_dkg.is_participant(&signers[2]));
+        for signer in signers.iter().take(2) {
+            assert!(auto_dkg.register_signer(*signer).is_none());
+        }
+        let participants = auto_dkg.register_signer(signers[2]).unwrap();
+        assert_eq!(participants.len(), 3);
+        assert!(auto_dkg.is_participant(&signers[2]));
+        assert!(!auto_dkg.is_participant(&PeerId::random()));
+        assert!(auto_dkg.slot_participants().is_err());
+
+        let pkid =
+            |crypto_type: CryptoType, app: u8| PkId::new(vec![u8::from(crypto_type), app, 0, 0]);
+        // other slots wait for the default one
+        assert!(
+            !auto_dkg
+                .update_new_dkg_result("wallet", CryptoType::Ed25519, pkid(CryptoType::Ed25519, 1))
+                .0
+        );
+        let crypto_types = <CryptoType as strum::IntoEnumIterator>::iter().collect::<Vec<_>>();
+        for (i, crypto_type) in crypto_types.iter().enumerate() {
+            let (done, _) =
+                auto_dkg.update_new_dkg_result(DEFAULT_APP, *crypto_type, pkid(*crypto_type, 0));
+            assert_eq!(done, i == crypto_types.len() - 1);
+        }
+        assert_eq!(auto_dkg.slot_participants(), Ok(participants));
+        assert!(!auto_dkg.has_app("wallet"));
+        for crypto_type in crypto_types.iter() {
+            auto_dkg.update_new_dkg_result("wallet", *crypto_type, pkid(*crypto_type, 1));
+        }
+        assert!(auto_dkg.has_app("wallet"));
+        assert_eq!(
+            auto_dkg
+                .get_pkid_by_crypto_type(CryptoType::Ed25519)
+                .unwrap(),
+            pkid(CryptoType::Ed25519, 0)
+        );
+        assert_eq!(
+            auto_dkg
+                .get_pkid_by_app("wallet", CryptoType::Ed25519)
+                .unwrap(),
+            pkid(CryptoType::Ed25519, 1)
+        );
+        assert!(auto_dkg
+            .get_pkid_by_app("bridge", CryptoType::Ed25519)
+            .is_err());
+        assert!(AutoDKG::<PeerId>::check_app("../bridge").is_err());
+
+        let stored = serde_json::to_string(&auto_dkg).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AutoDKG<PeerId>>(&stored).unwrap(),
+            auto_dkg
+        );
+        // files written before there were slots
+        let legacy = serde_json::json!({
+            "min_signers": 2,
+            "state": { "Done": { "Ed25519": pkid(CryptoType::Ed25519, 0) } },
+        });
+        let legacy: AutoDKG<PeerId> = serde_json::from_value(legacy).unwrap();
+        assert_eq!(
+            legacy.get_pkid_by_crypto_type(CryptoType::Ed25519).unwrap(),
+            pkid(CryptoType::Ed25519, 0)
+        );
+        assert!(legacy.slot_participants().is_err());
+        assert_eq!(legacy.crypto_types().len(), crypto_types.len());
+    }
+
+    #[test]
+    fn test_crypto_type_subset() {
+        let signer = PeerId::random();
+        let crypto_types = vec![CryptoType::Ed25519, CryptoType::Secp256k1];
+        let mut auto_dkg = AutoDKG::new(1, [signer].into(), crypto_types.clone());
+        assert!(auto_dkg.pending_slots().is_empty());
+        auto_dkg.register_signer(signer).unwrap();
+        assert_eq!(
+            auto_dkg.pending_slots(),
+            vec![(DEFAULT_APP.to_string(), crypto_types)]
+        );
+        let pkid = PkId::new(vec![1, 0]);
+        assert!(
+            !auto_dkg
+                .update_new_dkg_result(DEFAULT_APP, CryptoType::Ed25519, pkid)
+                .0
+        );
+        let pkid = PkId::new(vec![2, 0]);
+        assert!(
+            auto_dkg
+                .update_new_dkg_result(DEFAULT_APP, CryptoType::Secp256k1, pkid)
+                .0
+        );
+        assert!(auto_dkg.pending_slots().is_empty());
+        let pkid = PkId::new(vec![1, 1]);
+        auto_dkg.update_new_dkg_result("wallet", CryptoType::Ed25519, pkid);
+        assert_eq!(
+            auto_dkg.pending_slots(),
+            vec![("wallet".to_string(), vec![CryptoType::Secp256k1])]
+        );
+        assert!(auto_dkg
+            .get_pkid_by_crypto_type(CryptoType::EcdsaSecp256k1)
+            .is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::SessionError;
+use crate::utils::now_millis;
+
+/// Absolute point in time, in milliseconds since the unix epoch, after which the node,
+/// the coordinator and the signers give up on a DKG or signing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Deadline(u64);
+
+impl Deadline {
+    pub fn from_unix_millis(millis: u64) -> Self {
+        Self(millis)
+    }
+    pub fn after(duration: Duration) -> Self {
+        Self(now_millis().saturating_add(duration.as_millis() as u64))
+    }
+    pub fn unix_millis(&self) -> u64 {
+        self.0
+    }
+    /// Time left until the deadline, `None` once it has passed.
+    pub fn remaining(&self) -> Option<Duration> {
+        let now = now_millis();
+        if now >= self.0 {
+            None
+        } else {
+            Some(Duration::from_millis(self.0 - now))
+        }
+    }
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_none()
+    }
+    pub(crate) fn check(deadline: &Option<Deadline>, what: &str) -> Result<(), SessionError> {
+        match deadline {
+            Some(deadline) if deadline.is_expired() => Err(SessionError::DeadlineExceeded(
+                format!("{} passed its deadline {}", what, deadline.0),
+            )),
+            _ => Ok(()),
+        }
+    }
+    /// Shortens a stage timeout so that it ends no later than the deadline.
+    pub(crate) fn clamp(deadline: &Option<Deadline>, timeout: Duration) -> Duration {
+        match deadline {
+            Some(deadline) => deadline.remaining().unwrap_or_default().min(timeout),
+            None => timeout,
+        }
+    }
+    /// Waits for `future`, failing with `DeadlineExceeded` if the deadline passes first.
+    pub(crate) async fn wait<F: Future>(
+        deadline: &Option<Deadline>,
+        what: &str,
+        future: F,
+    ) -> Result<F::Output, SessionError> {
+        match deadline {
+            Some(deadline) => {
+                tokio::time::timeout(deadline.remaining().unwrap_or_default(), future)
+                    .await
+                    .map_err(|_| {
+                        SessionError::DeadlineExceeded(format!(
+                            "{} passed its deadline {}",
+                            what, deadline.0
+                        ))
+                    })
+            }
+            None => Ok(future.await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline() {
+        let deadline = Deadline::after(Duration::from_secs(60));
+        assert!(!deadline.is_expired());
+        assert!(deadline.remaining().unwrap() <= Duration::from_secs(60));
+        assert_eq!(
+            Deadline::clamp(&Some(deadline), Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+        assert!(Deadline::check(&Some(deadline), "session").is_ok());
+
+        let expired = Deadline::from_unix_millis(1);
+        assert!(expired.is_expired());
+        assert_eq!(
+            Deadline::clamp(&Some(expired), Duration::from_secs(1)),
+            Duration::ZERO
+        );
+        assert!(matches!(
+            Deadline::check(&Some(expired), "session"),
+            Err(SessionError::DeadlineExceeded(_))
+        ));
+        assert!(Deadline::check(&None, "session").is_ok());
+    }
+}
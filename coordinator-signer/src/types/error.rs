@@ -46,6 +46,12 @@ pub(crate) enum SessionError {
     DeserializationError(String),
     #[error("signature suite error: {0}")]
     SignatureSuiteError(String),
+    #[error("Session cancelled: {0}")]
+    Cancelled(String),
+    #[error("Deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+    #[error("Denied by signing policy: {0}")]
+    PolicyDenied(String),
 }
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub(crate) enum SessionIdError {
@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+
+use super::GroupPublicKeyInfo;
+
+/// What the coordinator knows about a key besides its key material.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMetadata<VII: ValidatorIdentityIdentity> {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub tags: BTreeSet<String>,
+    /// unix timestamp in seconds, `None` for keys generated before metadata was kept
+    pub created_at: Option<u64>,
+    /// The node that asked for the key, `None` for the operator and AutoDKG.
+    pub requester: Option<VII>,
+    pub participants: Vec<VII>,
+    pub min_signers: u16,
+    #[serde(default)]
+    pub state: KeyState,
+    /// The namespace of the node that asked for the key, `None` for the default one.
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+impl<VII: ValidatorIdentityIdentity> KeyMetadata<VII> {
+    pub(crate) fn new(
+        created_at: Option<u64>,
+        requester: Option<VII>,
+        participants: Vec<VII>,
+        min_signers: u16,
+    ) -> Self {
+        Self {
+            label: None,
+            description: None,
+            tags: BTreeSet::new(),
+            created_at,
+            requester,
+            participants,
+            min_signers,
+            state: KeyState::Active,
+            namespace: None,
+        }
+    }
+    /// Sets the fields given in `update`, an empty label or description clears it.
+    pub(crate) fn apply(&mut self, update: KeyMetadataUpdate) {
+        if let Some(label) = update.label {
+            self.label = Some(label).filter(|label| !label.is_empty());
+        }
+        if let Some(description) = update.description {
+            self.description = Some(description).filter(|description| !description.is_empty());
+        }
+        if let Some(tags) = update.tags {
+            self.tags = tags;
+        }
+    }
+}
+
+/// Where a key is in its lifecycle.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumString,
+)]
+pub enum KeyState {
+    #[default]
+    #[strum(serialize = "active")]
+    Active,
+    /// Refuses to sign until it is activated again.
+    #[strum(serialize = "disabled")]
+    Disabled,
+    /// Signers erased their shares, only the public data is left.
+    #[strum(serialize = "retired")]
+    Retired,
+    /// Erased from the coordinator and from a quorum of signers.
+    #[strum(serialize = "destroyed")]
+    Destroyed,
+}
+impl KeyState {
+    /// Disabling can be undone, retiring and destroying cannot.
+    pub fn can_become(self, next: KeyState) -> bool {
+        use KeyState::*;
+        matches!(
+            (self, next),
+            (Active, Disabled | Retired | Destroyed)
+                | (Disabled, Active | Retired | Destroyed)
+                | (Retired, Destroyed)
+        )
+    }
+    /// Whether signers still hold shares of the key.
+    pub fn keeps_shares(self) -> bool {
+        matches!(self, KeyState::Active | KeyState::Disabled)
+    }
+}
+
+/// The editable part of [`KeyMetadata`], `None` fields are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMetadataUpdate {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<BTreeSet<String>>,
+}
+
+/// A key as listed by `lspk`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyInfo<VII: ValidatorIdentityIdentity> {
+    pub pkid: PkId,
+    pub metadata: KeyMetadata<VII>,
+}
+
+/// The (tweaked) public key of a key together with its metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyInfo<VII: ValidatorIdentityIdentity> {
+    pub group_public_key_info: GroupPublicKeyInfo,
+    pub metadata: KeyMetadata<VII>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_key_state_transitions() {
+        use KeyState::*;
+        assert!(Active.can_become(Disabled));
+        assert!(Disabled.can_become(Active));
+        assert!(Disabled.can_become(Retired));
+        assert!(Retired.can_become(Destroyed));
+        assert!(!Retired.can_become(Active));
+        assert!(!Destroyed.can_become(Active));
+        assert!(!Active.can_become(Active));
+        assert_eq!(KeyState::from_str("retired").unwrap(), Retired);
+        assert_eq!(Disabled.to_string(), "disabled");
+    }
+}
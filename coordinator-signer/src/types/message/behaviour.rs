@@ -9,7 +9,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     crypto::{CryptoType, PkId, ValidatorIdentityIdentity},
-    types::{AutoDKG, GroupPublicKeyInfo, SignatureSuiteInfo},
+    types::{
+        AppId, AutoDKG, CancelTarget, Deadline, KeyInfo, KeyMetadata, KeyMetadataUpdate, KeyState,
+        PublicKeyInfo, PublicKeyPackageInfo, SessionOutcome, SessionStatusReport, SignItem,
+        SignatureFormat, SignaturePage, SignatureQuery, SignatureSuiteInfo, SignerSelection,
+    },
 };
 
 use super::{
@@ -69,22 +73,73 @@ pub(crate) enum NodeToCoorRequest<VII: ValidatorIdentityIdentity> {
     },
     AutoDKGRequest {
         validator_identity: ValidatorIdentityRequest,
+        // slot whose keys to return, generating them first if needed; `None` for the
+        // default slot
+        #[serde(default)]
+        app: Option<AppId>,
     },
     PkTweakRequest {
         pkid: PkId,
         tweak_data: Option<Vec<u8>>,
         validator_identity: ValidatorIdentityRequest,
     },
+    PublicKeyPackageRequest {
+        pkid: PkId,
+        validator_identity: ValidatorIdentityRequest,
+    },
     DKGRequest {
         crypto_type: CryptoType,
         participants: Vec<VII>,
         min_signers: u16,
+        deadline: Option<Deadline>,
+        #[serde(default)]
+        metadata: KeyMetadataUpdate,
+        #[serde(default)]
+        idempotency_key: Option<String>,
         validator_identity: ValidatorIdentityRequest,
     },
     SigningRequest {
         pkid: PkId,
         msg: Vec<u8>,
         tweak_data: Option<Vec<u8>>,
+        deadline: Option<Deadline>,
+        selection: Option<SignerSelection<VII>>,
+        #[serde(default)]
+        idempotency_key: Option<String>,
+        #[serde(default)]
+        signature_format: SignatureFormat,
+        validator_identity: ValidatorIdentityRequest,
+    },
+    /// Signs every item, each one in its own session.
+    SigningBatchRequest {
+        items: Vec<SignItem>,
+        deadline: Option<Deadline>,
+        #[serde(default)]
+        selection: Option<SignerSelection<VII>>,
+        validator_identity: ValidatorIdentityRequest,
+    },
+    SessionOutcomeRequest {
+        validator_identity: ValidatorIdentityRequest,
+    },
+    SessionStatusRequest {
+        validator_identity: ValidatorIdentityRequest,
+    },
+    CancelRequest {
+        session_id: String,
+        validator_identity: ValidatorIdentityRequest,
+    },
+    SignatureHistoryRequest {
+        query: SignatureQuery,
+        validator_identity: ValidatorIdentityRequest,
+    },
+    KeyMetadataRequest {
+        pkid: PkId,
+        update: KeyMetadataUpdate,
+        validator_identity: ValidatorIdentityRequest,
+    },
+    KeyStateRequest {
+        pkid: PkId,
+        state: KeyState,
         validator_identity: ValidatorIdentityRequest,
     },
 }
@@ -92,16 +147,42 @@ impl<VII: ValidatorIdentityIdentity> NodeToCoorRequest<VII> {
     pub(crate) fn get_validator_identity(&self) -> ValidatorIdentityRequest {
         match self {
             NodeToCoorRequest::LsPkRequest { validator_identity } => validator_identity.clone(),
-            NodeToCoorRequest::AutoDKGRequest { validator_identity } => validator_identity.clone(),
+            NodeToCoorRequest::AutoDKGRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
             NodeToCoorRequest::PkTweakRequest {
                 validator_identity, ..
             } => validator_identity.clone(),
+            NodeToCoorRequest::PublicKeyPackageRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
             NodeToCoorRequest::DKGRequest {
                 validator_identity, ..
             } => validator_identity.clone(),
             NodeToCoorRequest::SigningRequest {
                 validator_identity, ..
             } => validator_identity.clone(),
+            NodeToCoorRequest::SigningBatchRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
+            NodeToCoorRequest::SessionOutcomeRequest { validator_identity } => {
+                validator_identity.clone()
+            }
+            NodeToCoorRequest::SessionStatusRequest { validator_identity } => {
+                validator_identity.clone()
+            }
+            NodeToCoorRequest::CancelRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
+            NodeToCoorRequest::SignatureHistoryRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
+            NodeToCoorRequest::KeyMetadataRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
+            NodeToCoorRequest::KeyStateRequest {
+                validator_identity, ..
+            } => validator_identity.clone(),
         }
     }
 }
@@ -117,11 +198,33 @@ pub(crate) enum NodeToCoorResponse<VII: ValidatorIdentityIdentity> {
     SigningResponse {
         signature_suite_info: SignatureSuiteInfo<VII>,
     },
+    /// One result per item of the batch, in the same order.
+    SigningBatchResponse {
+        results: Vec<Result<SignatureSuiteInfo<VII>, String>>,
+    },
     LsPkResponse {
-        pkids: HashMap<CryptoType, Vec<PkId>>,
+        keys: HashMap<CryptoType, Vec<KeyInfo<VII>>>,
     },
     PkTweakResponse {
-        group_public_key_info: GroupPublicKeyInfo,
+        public_key_info: PublicKeyInfo<VII>,
+    },
+    PublicKeyPackageResponse {
+        package: PublicKeyPackageInfo<VII>,
+    },
+    KeyMetadataResponse {
+        metadata: KeyMetadata<VII>,
+    },
+    SessionOutcomeResponse {
+        outcomes: Vec<SessionOutcome<VII>>,
+    },
+    SessionStatusResponse {
+        report: SessionStatusReport<VII>,
+    },
+    CancelResponse {
+        session_id: String,
+    },
+    SignatureHistoryResponse {
+        page: SignaturePage<VII>,
     },
     Failure(String),
 }
@@ -139,6 +242,13 @@ pub(crate) enum CoorToSigRequest<VII: ValidatorIdentityIdentity> {
     SigningRequest(SigningRequestWrap<VII>),
     DKGRequestEx(DKGRequestWrapEx<VII>),
     SigningRequestEx(SigningRequestWrapEx<VII>),
+    Cancel(CancelTarget),
+    /// Signers refuse to sign with keys that are not active and erase their share of
+    /// retired and destroyed keys.
+    KeyState {
+        pkid: PkId,
+        state: KeyState,
+    },
     Empty,
 }
 
@@ -148,6 +258,8 @@ pub(crate) enum CoorToSigResponse<VII: ValidatorIdentityIdentity> {
     SigningResponse(SigningResponseWrap<VII>),
     DKGResponseEx(DKGResponseWrapEx),
     SigningResponseEx(SigningResponseWrapEx),
+    Cancelled,
+    KeyStateApplied(Result<(), String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -7,7 +7,7 @@ use crate::{
         Cipher, CryptoType, Ed25519Sha512, Ed448Shake256, Identifier, P256Sha256,
         Ristretto255Sha512, Secp256K1Sha256, Secp256K1Sha256TR, ValidatorIdentityIdentity,
     },
-    types::{error::SessionError, Participants, SessionId},
+    types::{error::SessionError, Deadline, Participants, SessionId},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -18,8 +18,10 @@ pub(crate) struct DKGBaseMessage<VII: ValidatorIdentityIdentity, CI: Identifier>
     pub(crate) participants: Participants<VII, CI>,
     pub(crate) identifier: CI,
     pub(crate) identity: VII,
+    pub(crate) deadline: Option<Deadline>,
 }
 impl<VII: ValidatorIdentityIdentity, CI: Identifier> DKGBaseMessage<VII, CI> {
+    // the deadline only matters while the DKG runs, it is not stored with the key
     pub(crate) fn serialize(&self) -> Result<Vec<u8>, SessionError> {
         let data = (
             self.crypto_type,
@@ -45,6 +47,7 @@ impl<VII: ValidatorIdentityIdentity, CI: Identifier> DKGBaseMessage<VII, CI> {
                 .map_err(|e| SessionError::DeserializationError(e.to_string()))?,
             identity: VII::from_bytes(&data.5)
                 .map_err(|e| SessionError::DeserializationError(e.to_string()))?,
+            deadline: None,
         })
     }
 }
@@ -227,6 +230,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -238,6 +242,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -249,6 +254,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -260,6 +266,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -271,6 +278,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -282,6 +290,7 @@ impl<VII: ValidatorIdentityIdentity> DKGRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
                 },
                 stage: DKGResponseStage::Failure(msg),
             }),
@@ -7,7 +7,7 @@ use crate::{
         Cipher, CryptoType, Ed25519Sha512, Ed448Shake256, Identifier, P256Sha256, PkId,
         Ristretto255Sha512, Secp256K1Sha256, Secp256K1Sha256TR, ValidatorIdentityIdentity,
     },
-    types::{error::SessionError, Participants, SubsessionId},
+    types::{error::SessionError, Deadline, Participants, SubsessionId},
 };
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub(crate) struct SigningBaseMessage<VII: ValidatorIdentityIdentity, CI: Identifier, PK> {
@@ -19,6 +19,10 @@ pub(crate) struct SigningBaseMessage<VII: ValidatorIdentityIdentity, CI: Identif
     pub(crate) identifier: CI,
     pub(crate) identity: VII,
     pub(crate) public_key: PK,
+    pub(crate) deadline: Option<Deadline>,
+    /// The node that asked for the signature, if it came from one.
+    #[serde(default)]
+    pub(crate) requester: Option<VII>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +90,22 @@ fn try_cast_request<VII: ValidatorIdentityIdentity, C: Cipher, T: Cipher>(
 }
 
 impl<VII: ValidatorIdentityIdentity> SigningResponseWrap<VII> {
+    pub(crate) fn is_failure(&self) -> bool {
+        match self {
+            SigningResponseWrap::Ed25519(r) => matches!(r.stage, SigningResponseStage::Failure(_)),
+            SigningResponseWrap::Secp256k1(r) => {
+                matches!(r.stage, SigningResponseStage::Failure(_))
+            }
+            SigningResponseWrap::Secp256k1Tr(r) => {
+                matches!(r.stage, SigningResponseStage::Failure(_))
+            }
+            SigningResponseWrap::P256(r) => matches!(r.stage, SigningResponseStage::Failure(_)),
+            SigningResponseWrap::Ed448(r) => matches!(r.stage, SigningResponseStage::Failure(_)),
+            SigningResponseWrap::Ristretto255(r) => {
+                matches!(r.stage, SigningResponseStage::Failure(_))
+            }
+        }
+    }
     pub(crate) fn from<C: Cipher>(r: SigningResponse<VII, C>) -> Result<Self, SessionError> {
         match C::crypto_type() {
             CryptoType::Ed25519 => Ok(SigningResponseWrap::Ed25519(
@@ -157,6 +177,66 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
             SigningRequestWrap::Ristretto255(r) => r.message(),
         }
     }
+    pub(crate) fn pkid(&self) -> &PkId {
+        match self {
+            SigningRequestWrap::Ed25519(r) => &r.base_info.pkid,
+            SigningRequestWrap::Secp256k1(r) => &r.base_info.pkid,
+            SigningRequestWrap::Secp256k1Tr(r) => &r.base_info.pkid,
+            SigningRequestWrap::P256(r) => &r.base_info.pkid,
+            SigningRequestWrap::Ed448(r) => &r.base_info.pkid,
+            SigningRequestWrap::Ristretto255(r) => &r.base_info.pkid,
+        }
+    }
+    pub(crate) fn subsession_id(&self) -> SubsessionId {
+        match self {
+            SigningRequestWrap::Ed25519(r) => r.base_info.subsession_id,
+            SigningRequestWrap::Secp256k1(r) => r.base_info.subsession_id,
+            SigningRequestWrap::Secp256k1Tr(r) => r.base_info.subsession_id,
+            SigningRequestWrap::P256(r) => r.base_info.subsession_id,
+            SigningRequestWrap::Ed448(r) => r.base_info.subsession_id,
+            SigningRequestWrap::Ristretto255(r) => r.base_info.subsession_id,
+        }
+    }
+    pub(crate) fn deadline(&self) -> Option<Deadline> {
+        match self {
+            SigningRequestWrap::Ed25519(r) => r.base_info.deadline,
+            SigningRequestWrap::Secp256k1(r) => r.base_info.deadline,
+            SigningRequestWrap::Secp256k1Tr(r) => r.base_info.deadline,
+            SigningRequestWrap::P256(r) => r.base_info.deadline,
+            SigningRequestWrap::Ed448(r) => r.base_info.deadline,
+            SigningRequestWrap::Ristretto255(r) => r.base_info.deadline,
+        }
+    }
+    pub(crate) fn tweak_data(&self) -> Option<Vec<u8>> {
+        match self {
+            SigningRequestWrap::Ed25519(r) => r.tweak_data(),
+            SigningRequestWrap::Secp256k1(r) => r.tweak_data(),
+            SigningRequestWrap::Secp256k1Tr(r) => r.tweak_data(),
+            SigningRequestWrap::P256(r) => r.tweak_data(),
+            SigningRequestWrap::Ed448(r) => r.tweak_data(),
+            SigningRequestWrap::Ristretto255(r) => r.tweak_data(),
+        }
+    }
+    pub(crate) fn requester(&self) -> Option<&VII> {
+        match self {
+            SigningRequestWrap::Ed25519(r) => r.base_info.requester.as_ref(),
+            SigningRequestWrap::Secp256k1(r) => r.base_info.requester.as_ref(),
+            SigningRequestWrap::Secp256k1Tr(r) => r.base_info.requester.as_ref(),
+            SigningRequestWrap::P256(r) => r.base_info.requester.as_ref(),
+            SigningRequestWrap::Ed448(r) => r.base_info.requester.as_ref(),
+            SigningRequestWrap::Ristretto255(r) => r.base_info.requester.as_ref(),
+        }
+    }
+    pub(crate) fn joined_participants(&self) -> Vec<VII> {
+        match self {
+            SigningRequestWrap::Ed25519(r) => r.joined_participants(),
+            SigningRequestWrap::Secp256k1(r) => r.joined_participants(),
+            SigningRequestWrap::Secp256k1Tr(r) => r.joined_participants(),
+            SigningRequestWrap::P256(r) => r.joined_participants(),
+            SigningRequestWrap::Ed448(r) => r.joined_participants(),
+            SigningRequestWrap::Ristretto255(r) => r.joined_participants(),
+        }
+    }
     pub(crate) fn crypto_type(&self) -> CryptoType {
         match self {
             SigningRequestWrap::Ed25519(_) => CryptoType::Ed25519,
@@ -180,6 +260,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
+                    requester: r.base_info.requester.clone(),
                 },
                 stage: SigningResponseStage::Failure(msg),
             }),
@@ -193,6 +275,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
+                    requester: r.base_info.requester.clone(),
                 },
                 stage: SigningResponseStage::Failure(msg),
             }),
@@ -207,6 +291,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                         participants: r.base_info.participants.clone(),
                         identifier: r.base_info.identifier,
                         identity: r.base_info.identity.clone(),
+                        deadline: r.base_info.deadline,
+                        requester: r.base_info.requester.clone(),
                     },
                     stage: SigningResponseStage::Failure(msg),
                 })
@@ -221,6 +307,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
+                    requester: r.base_info.requester.clone(),
                 },
                 stage: SigningResponseStage::Failure(msg),
             }),
@@ -234,6 +322,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                     participants: r.base_info.participants.clone(),
                     identifier: r.base_info.identifier,
                     identity: r.base_info.identity.clone(),
+                    deadline: r.base_info.deadline,
+                    requester: r.base_info.requester.clone(),
                 },
                 stage: SigningResponseStage::Failure(msg),
             }),
@@ -248,6 +338,8 @@ impl<VII: ValidatorIdentityIdentity> SigningRequestWrap<VII> {
                         participants: r.base_info.participants.clone(),
                         identifier: r.base_info.identifier,
                         identity: r.base_info.identity.clone(),
+                        deadline: r.base_info.deadline,
+                        requester: r.base_info.requester.clone(),
                     },
                     stage: SigningResponseStage::Failure(msg),
                 })
@@ -311,6 +403,23 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SigningRequest<VII, C> {
         }
         None
     }
+    pub(crate) fn tweak_data(&self) -> Option<Vec<u8>> {
+        if let SigningRequestStage::Round2 { tweak_data, .. } = &self.stage {
+            return tweak_data.clone();
+        }
+        None
+    }
+    /// The signers that answered round 1, empty before round 2.
+    pub(crate) fn joined_participants(&self) -> Vec<VII> {
+        if let SigningRequestStage::Round2 {
+            joined_participants,
+            ..
+        } = &self.stage
+        {
+            return joined_participants.values().cloned().collect();
+        }
+        Vec::new()
+    }
     pub(crate) fn from(r: SigningRequestWrap<VII>) -> Result<SigningRequest<VII, C>, SessionError> {
         match r {
             SigningRequestWrap::Ed25519(r) => Ok(try_cast_request::<VII, Ed25519Sha512, C>(&r)
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{
+    pk_to_pkid, Cipher, CryptoType, Ed25519Sha512, Ed448Shake256, P256Sha256, PkId,
+    PublicKeyPackage, Ristretto255Sha512, Secp256K1Sha256, Secp256K1Sha256TR,
+    ValidatorIdentityIdentity,
+};
+
+use super::SignatureSuiteInfo;
+
+/// The full public key package of a key, so that nodes can check what the coordinator
+/// claims about the key instead of trusting it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackageInfo<VII: ValidatorIdentityIdentity> {
+    pub pkid: PkId,
+    pub crypto_type: CryptoType,
+    /// `PublicKeyPackage::serialize_binary` of FROST keys, which carries the group key and
+    /// the verifying share of every participant; the group public key of ecdsa keys.
+    pub public_key_package: Vec<u8>,
+    pub min_signers: u16,
+    /// Participant identifier bytes to the identity holding that share.
+    pub participants: BTreeMap<Vec<u8>, VII>,
+}
+impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>>
+    PublicKeyPackageInfo<VII>
+{
+    /// Recomputes the pkid from the package and checks it and the threshold against the
+    /// claimed ones.
+    pub fn verify(&self) -> Result<(), String> {
+        let pkid = match self.crypto_type {
+            CryptoType::Ed25519 => self.frost_pkid::<Ed25519Sha512>()?,
+            CryptoType::Secp256k1 => self.frost_pkid::<Secp256K1Sha256>()?,
+            CryptoType::Secp256k1Tr => self.frost_pkid::<Secp256K1Sha256TR>()?,
+            CryptoType::P256 => self.frost_pkid::<P256Sha256>()?,
+            CryptoType::Ed448 => self.frost_pkid::<Ed448Shake256>()?,
+            CryptoType::Ristretto255 => self.frost_pkid::<Ristretto255Sha512>()?,
+            CryptoType::EcdsaSecp256k1 => {
+                pk_to_pkid(self.crypto_type, &self.public_key_package).map_err(|e| e.to_string())?
+            }
+        };
+        if pkid != self.pkid {
+            return Err(format!(
+                "public key package hashes to {}, not {}",
+                pkid, self.pkid
+            ));
+        }
+        if self.min_signers == 0 || self.min_signers as usize > self.participants.len() {
+            return Err(format!(
+                "min signers {} out of range for {} participants",
+                self.min_signers,
+                self.participants.len()
+            ));
+        }
+        Ok(())
+    }
+    fn frost_pkid<C: Cipher>(&self) -> Result<PkId, String> {
+        let package =
+            <C::PublicKeyPackage as PublicKeyPackage>::deserialize_binary(&self.public_key_package)
+                .map_err(|e| e.to_string())?;
+        if package.verifying_shares().len() != self.participants.len() {
+            return Err(format!(
+                "public key package has {} verifying shares for {} participants",
+                package.verifying_shares().len(),
+                self.participants.len()
+            ));
+        }
+        package.pkid().map_err(|e| e.to_string())
+    }
+    /// Checks that `suite` was made with this key: the package passes [`Self::verify`],
+    /// the key and participants claimed by `suite` are the ones of the package, enough of
+    /// them joined, and the signature verifies. The tweaked key of ecdsa keys is derived
+    /// by the signers' tss service and cannot be recomputed here, for them only the
+    /// signature over it is checked.
+    pub fn verify_signature(&self, suite: &SignatureSuiteInfo<VII>) -> Result<(), String> {
+        self.verify()?;
+        if suite.crypto_type != self.crypto_type || suite.pkid != self.pkid {
+            return Err(format!(
+                "signature was made with {} key {}, not {} key {}",
+                suite.crypto_type, suite.pkid, self.crypto_type, self.pkid
+            ));
+        }
+        if suite.pk != self.public_key_package {
+            return Err("signature public key is not the one of the package".to_string());
+        }
+        if suite.participants != self.participants {
+            return Err("signature participants are not the ones of the package".to_string());
+        }
+        if suite.joined_participants.len() < self.min_signers as usize
+            || suite
+                .joined_participants
+                .iter()
+                .any(|(id, identity)| self.participants.get(id) != Some(identity))
+        {
+            return Err(format!(
+                "signature needs {} of the package participants to join",
+                self.min_signers
+            ));
+        }
+        suite.try_verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Participants, SubsessionId};
+    use k256::{ecdsa::SigningKey, SecretKey};
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_verify_signature() {
+        let signing_key = SigningKey::from(SecretKey::random(&mut OsRng));
+        let public_key = signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+        let pkid = pk_to_pkid(CryptoType::EcdsaSecp256k1, &public_key).unwrap();
+        let peers = (1..=3u16)
+            .map(|i| (i, libp2p::PeerId::random()))
+            .collect::<Vec<_>>();
+        let participants = peers
+            .iter()
+            .map(|(i, peer)| (i.to_le_bytes().to_vec(), *peer))
+            .collect::<BTreeMap<_, _>>();
+        let package = PublicKeyPackageInfo {
+            pkid: pkid.clone(),
+            crypto_type: CryptoType::EcdsaSecp256k1,
+            public_key_package: public_key.clone(),
+            min_signers: 2,
+            participants: participants.clone(),
+        };
+        package.verify().unwrap();
+
+        let message = b"testtesttesttesttesttesttesttest";
+        let (signature, _) = signing_key.sign_prehash_recoverable(message).unwrap();
+        let suite = SignatureSuiteInfo {
+            signature: signature.to_vec(),
+            pk: public_key.clone(),
+            pk_tweak: public_key.clone(),
+            pk_verifying_key: vec![],
+            pk_verifying_key_tweak: vec![],
+            tweak_data: None,
+            subsession_id: SubsessionId::new(
+                CryptoType::EcdsaSecp256k1,
+                2,
+                &Participants::<libp2p::PeerId, u16>::new(peers).unwrap(),
+                message.to_vec(),
+                None,
+                pkid.clone(),
+            )
+            .unwrap(),
+            participants: participants.clone(),
+            joined_participants: participants.clone().into_iter().take(2).collect(),
+            pkid: pkid.clone(),
+            message: message.to_vec(),
+            crypto_type: CryptoType::EcdsaSecp256k1,
+            original_serialized: "".to_string(),
+            formatted_signature: None,
+        };
+        package.verify_signature(&suite).unwrap();
+
+        let mut claimed = package.clone();
+        claimed.pkid = PkId::new(vec![0x06; 33]);
+        assert!(claimed.verify().is_err());
+        let mut lonely = suite.clone();
+        lonely.joined_participants = participants.into_iter().take(1).collect();
+        assert!(package.verify_signature(&lonely).is_err());
+        let mut forged = suite;
+        forged.message = b"othrothrothrothrothrothrothrothr".to_vec();
+        assert!(package.verify_signature(&forged).is_err());
+    }
+}
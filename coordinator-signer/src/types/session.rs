@@ -1,7 +1,9 @@
+mod cancel_target;
 mod session_id;
 mod subsession_id;
 use std::{collections::BTreeMap, ops::Deref};
 
+pub(crate) use cancel_target::CancelTarget;
 use serde::{Deserialize, Serialize};
 pub(crate) use session_id::SessionId;
 pub(crate) use subsession_id::SubsessionId;
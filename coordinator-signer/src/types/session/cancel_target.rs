@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{CryptoType, CryptoTypeError};
+use crate::types::error::SessionIdError;
+
+use super::{SessionId, SubsessionId};
+
+/// A running DKG session or signing subsession, as named in a cancel request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum CancelTarget {
+    Dkg(SessionId),
+    Signing(SubsessionId),
+}
+impl CancelTarget {
+    pub(crate) fn from_string(s: &str) -> Result<Self, SessionIdError> {
+        if s.starts_with("subsession-") {
+            Ok(CancelTarget::Signing(SubsessionId::from_string(s)?))
+        } else {
+            Ok(CancelTarget::Dkg(SessionId::from_string(s)?))
+        }
+    }
+    pub(crate) fn crypto_type(&self) -> Result<CryptoType, CryptoTypeError> {
+        match self {
+            CancelTarget::Dkg(session_id) => session_id.crypto_type(),
+            CancelTarget::Signing(subsession_id) => subsession_id.crypto_type(),
+        }
+    }
+    pub(crate) fn to_string(&self) -> String {
+        match self {
+            CancelTarget::Dkg(session_id) => session_id.to_string(),
+            CancelTarget::Signing(subsession_id) => subsession_id.to_string(),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::crypto::Identifier;
-use crate::crypto::{CryptoType, ValidatorIdentityIdentity};
+use crate::crypto::{CryptoType, CryptoTypeError, ValidatorIdentityIdentity};
 use crate::types::error::SessionIdError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
@@ -75,6 +75,10 @@ impl SessionId {
         Ok(SessionId(bytes))
     }
 
+    pub(crate) fn crypto_type(&self) -> Result<CryptoType, CryptoTypeError> {
+        CryptoType::try_from(self.0[0])
+    }
+
     pub fn to_string(&self) -> String {
         let crypto_type = format!("{:02x}", self.0[0]);
         let min_signers = format!("{:04x}", u16::from_be_bytes([self.0[1], self.0[2]]));
@@ -1,4 +1,4 @@
-use crate::crypto::{CryptoType, Identifier, PkId, ValidatorIdentityIdentity};
+use crate::crypto::{CryptoType, CryptoTypeError, Identifier, PkId, ValidatorIdentityIdentity};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
@@ -102,6 +102,13 @@ impl SubsessionId {
         Ok(SubsessionId(bytes))
     }
 
+    pub(crate) fn crypto_type(&self) -> Result<CryptoType, CryptoTypeError> {
+        CryptoType::try_from(self.0[0])
+    }
+    pub(crate) fn pkid(&self) -> PkId {
+        PkId::new(self.0[21..54].to_vec())
+    }
+
     pub fn to_string(&self) -> String {
         let crypto_type = format!("{:02x}", self.0[0]);
         let min_signers = format!("{:04x}", u16::from_be_bytes([self.0[1], self.0[2]]));
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+
+use super::{Deadline, SignatureFormat, SignatureSuiteInfo, SignerSelection};
+
+/// A node request the coordinator accepted, as recorded in its session journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionRequest<VII: ValidatorIdentityIdentity> {
+    Dkg {
+        crypto_type: CryptoType,
+        participants: Vec<(u16, VII)>,
+        min_signers: u16,
+        #[serde(default)]
+        deadline: Option<Deadline>,
+    },
+    Signing {
+        pkid: PkId,
+        msg: Vec<u8>,
+        tweak_data: Option<Vec<u8>>,
+        #[serde(default)]
+        deadline: Option<Deadline>,
+        #[serde(default)]
+        selection: Option<SignerSelection<VII>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SessionResult<VII: ValidatorIdentityIdentity> {
+    Dkg(Result<PkId, String>),
+    Signing(Result<SignatureSuiteInfo<VII>, String>),
+}
+impl<VII: ValidatorIdentityIdentity> SessionResult<VII> {
+    /// Encodes a signature as `signature_format`, one that cannot be encoded becomes an
+    /// error.
+    pub(crate) fn with_signature_format(self, signature_format: SignatureFormat) -> Self {
+        match self {
+            SessionResult::Signing(result) => {
+                SessionResult::Signing(result.and_then(|info| info.with_format(signature_format)))
+            }
+            result => result,
+        }
+    }
+}
+
+/// Final outcome of a session whose response never reached the requesting node,
+/// e.g. because the node disconnected or the coordinator restarted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionOutcome<VII: ValidatorIdentityIdentity> {
+    pub request: SessionRequest<VII>,
+    pub result: SessionResult<VII>,
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::crypto::{CryptoType, PkId, ValidatorIdentityIdentity};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgSessionStatus<VII: ValidatorIdentityIdentity> {
+    pub session_id: String,
+    pub crypto_type: CryptoType,
+    pub min_signers: u16,
+    pub participants: Vec<VII>,
+    /// `part1`, `part2` or `gen_public_key`; ECDSA sessions report `running`
+    pub round: String,
+    /// participants that answered the current round
+    pub responded: Vec<VII>,
+    /// unix timestamp in seconds
+    pub started_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningSessionStatus<VII: ValidatorIdentityIdentity> {
+    pub subsession_id: String,
+    pub pkid: PkId,
+    pub crypto_type: CryptoType,
+    pub min_signers: u16,
+    /// `round1` or `round2`; ECDSA sessions report `running`
+    pub round: String,
+    /// participants selected for round 2, empty before
+    pub joined_participants: Vec<VII>,
+    /// participants that answered the current round
+    pub responded: Vec<VII>,
+    pub started_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum SessionKind {
+    #[strum(serialize = "dkg")]
+    Dkg,
+    #[strum(serialize = "signing")]
+    Signing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinishedSessionStatus {
+    pub kind: SessionKind,
+    /// session id for DKG, subsession id for signing
+    pub id: String,
+    pub crypto_type: CryptoType,
+    /// generated key for DKG, signing key for signing, `None` for a failed DKG
+    pub pkid: Option<PkId>,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub finished_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStatusReport<VII: ValidatorIdentityIdentity> {
+    pub dkg_sessions: Vec<DkgSessionStatus<VII>>,
+    pub signing_sessions: Vec<SigningSessionStatus<VII>>,
+    /// most recently finished first
+    pub finished_sessions: Vec<FinishedSessionStatus>,
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+
+use super::{Deadline, KeyMetadataUpdate, SignatureFormat, SignerSelection};
+
+/// One message of a batch signed with [`crate::node::Node::sign_batch`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignItem {
+    pub pkid: PkId,
+    pub msg: Vec<u8>,
+    pub tweak_data: Option<Vec<u8>>,
+    /// Same as [`SignOptions::idempotency_key`], for this item alone.
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Same as [`SignOptions::signature_format`], for this item alone.
+    #[serde(default)]
+    pub signature_format: SignatureFormat,
+}
+impl SignItem {
+    pub fn new(pkid: PkId, msg: Vec<u8>, tweak_data: Option<Vec<u8>>) -> Self {
+        Self {
+            pkid,
+            msg,
+            tweak_data,
+            idempotency_key: None,
+            signature_format: SignatureFormat::Compact,
+        }
+    }
+}
+
+/// Optional parameters of a sign request sent by a node.
+#[derive(Debug, Clone)]
+pub struct SignOptions<VII: ValidatorIdentityIdentity> {
+    /// The coordinator and the signers give up once it has passed.
+    pub deadline: Option<Deadline>,
+    /// Which participants of the key may or must sign, the coordinator decides if `None`.
+    pub selection: Option<SignerSelection<VII>>,
+    /// Retrying with the same key returns the signature of the first attempt instead of
+    /// signing again, see [`DkgOptions::idempotency_key`].
+    pub idempotency_key: Option<String>,
+    /// Encoding of the returned signature, see
+    /// [`crate::SignatureSuiteInfo::formatted_signature`]. The request fails before
+    /// signing if it is not defined for the crypto type of the key.
+    pub signature_format: SignatureFormat,
+}
+impl<VII: ValidatorIdentityIdentity> Default for SignOptions<VII> {
+    fn default() -> Self {
+        Self {
+            deadline: None,
+            selection: None,
+            idempotency_key: None,
+            signature_format: SignatureFormat::Compact,
+        }
+    }
+}
+
+/// Optional parameters of a DKG request sent by a node.
+#[derive(Debug, Clone, Default)]
+pub struct DkgOptions {
+    /// The coordinator and the signers give up once it has passed.
+    pub deadline: Option<Deadline>,
+    /// Label, description and tags of the new key.
+    pub metadata: KeyMetadataUpdate,
+    /// Chosen by the node to make retries safe: while the coordinator remembers the key,
+    /// a request with the same key and parameters gets the result of the first one.
+    pub idempotency_key: Option<String>,
+}
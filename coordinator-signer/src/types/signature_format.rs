@@ -0,0 +1,336 @@
+//! Encodings of a signature for the systems that consume it, chosen by the node with
+//! [`crate::SignOptions::signature_format`] and applied by the coordinator before it
+//! returns the signature. Only ECDSA secp256k1 has encodings besides its native one.
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId, Signature};
+use secp256k1::{Message, PublicKey, Secp256k1};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::crypto::CryptoType;
+
+/// How the recovery id is carried in the `v` byte(s) of an `r || s || v` signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VOffset {
+    /// `v` is the recovery id, 0 or 1
+    Zero,
+    /// `v` is 27 or 28, as in pre EIP-155 Ethereum and `eth_sign`
+    TwentySeven,
+    /// `v` is `recovery id + chain_id * 2 + 35`, big-endian without leading zeros
+    Eip155 { chain_id: u64 },
+}
+impl VOffset {
+    fn encode(&self, recovery_id: u8) -> Result<Vec<u8>, String> {
+        let v = match self {
+            VOffset::Zero => recovery_id as u64,
+            VOffset::TwentySeven => 27 + recovery_id as u64,
+            VOffset::Eip155 { chain_id } => chain_id
+                .checked_mul(2)
+                .and_then(|v| v.checked_add(35 + recovery_id as u64))
+                .ok_or(format!("chain id {} is too large", chain_id))?,
+        };
+        let bytes = v.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+        Ok(bytes[skip..].to_vec())
+    }
+    fn decode(&self, v: &[u8]) -> Result<u8, String> {
+        if v.is_empty() || v.len() > 8 {
+            return Err(format!("Invalid v length: {}", v.len()));
+        }
+        let v = v.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+        let base = match self {
+            VOffset::Zero => 0,
+            VOffset::TwentySeven => 27,
+            VOffset::Eip155 { chain_id } => chain_id
+                .checked_mul(2)
+                .and_then(|v| v.checked_add(35))
+                .ok_or(format!("chain id {} is too large", chain_id))?,
+        };
+        match v.checked_sub(base) {
+            Some(recovery_id @ (0 | 1)) => Ok(recovery_id as u8),
+            _ => Err(format!("Invalid v {} for {:?}", v, self)),
+        }
+    }
+}
+
+/// Encoding of an ECDSA secp256k1 signature.
+///
+/// Every format except [`SignatureFormat::Compact`] uses the low-S form (BIP62, EIP-2),
+/// because that is what verifiers of the DER and recoverable formats require.
+/// FROST signatures only exist in their native encoding, [`SignatureFormat::Compact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SignatureFormat {
+    /// The signature as produced, for ECDSA `r || s` (64 bytes)
+    #[default]
+    Compact,
+    /// ASN.1 DER `ECDSA-Sig-Value`
+    Der,
+    /// `r || s` with `s` normalized to the lower half of the order
+    LowS,
+    /// `r || s || v`
+    Rsv(VOffset),
+    /// Bitcoin message signature: header `27 + recovery id (+ 4 for compressed keys)`,
+    /// then `r || s`
+    BitcoinCompact { compressed: bool },
+}
+impl SignatureFormat {
+    /// Whether signatures of `crypto_type` can be encoded as this format.
+    pub fn check(&self, crypto_type: CryptoType) -> Result<(), String> {
+        match (crypto_type, self) {
+            (_, SignatureFormat::Compact) | (CryptoType::EcdsaSecp256k1, _) => Ok(()),
+            (crypto_type, format) => Err(format!(
+                "Signature format {} is not defined for {}",
+                format, crypto_type
+            )),
+        }
+    }
+}
+impl Display for SignatureFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureFormat::Compact => write!(f, "compact"),
+            SignatureFormat::Der => write!(f, "der"),
+            SignatureFormat::LowS => write!(f, "low-s"),
+            SignatureFormat::Rsv(VOffset::Zero) => write!(f, "rsv"),
+            SignatureFormat::Rsv(VOffset::TwentySeven) => write!(f, "rsv27"),
+            SignatureFormat::Rsv(VOffset::Eip155 { chain_id }) => write!(f, "eip155:{}", chain_id),
+            SignatureFormat::BitcoinCompact { compressed: true } => write!(f, "bitcoin"),
+            SignatureFormat::BitcoinCompact { compressed: false } => {
+                write!(f, "bitcoin-uncompressed")
+            }
+        }
+    }
+}
+impl FromStr for SignatureFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(SignatureFormat::Compact),
+            "der" => Ok(SignatureFormat::Der),
+            "low-s" => Ok(SignatureFormat::LowS),
+            "rsv" => Ok(SignatureFormat::Rsv(VOffset::Zero)),
+            "rsv27" => Ok(SignatureFormat::Rsv(VOffset::TwentySeven)),
+            "bitcoin" => Ok(SignatureFormat::BitcoinCompact { compressed: true }),
+            "bitcoin-uncompressed" => Ok(SignatureFormat::BitcoinCompact { compressed: false }),
+            s => match s.strip_prefix("eip155:") {
+                Some(chain_id) => Ok(SignatureFormat::Rsv(VOffset::Eip155 {
+                    chain_id: chain_id
+                        .parse()
+                        .map_err(|e| format!("Invalid chain id {}: {}", chain_id, e))?,
+                })),
+                None => Err(format!(
+                    "Unknown signature format {}, expected one of compact, der, low-s, rsv, rsv27, eip155:<chain id>, bitcoin, bitcoin-uncompressed",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+fn parse_inputs(message: &[u8], public_key: &[u8]) -> Result<(Message, PublicKey), String> {
+    let digest: [u8; 32] = message
+        .try_into()
+        .map_err(|_| "Message must be 32 bytes".to_string())?;
+    let public_key = PublicKey::from_slice(public_key).map_err(|e| e.to_string())?;
+    Ok((Message::from_digest(digest), public_key))
+}
+fn recovery_id(
+    signature: &Signature,
+    message: &Message,
+    public_key: &PublicKey,
+) -> Result<u8, String> {
+    let secp = Secp256k1::verification_only();
+    (0..=1)
+        .find(|v| {
+            RecoveryId::try_from(*v as i32)
+                .and_then(|rec_id| {
+                    RecoverableSignature::from_compact(&signature.serialize_compact(), rec_id)
+                })
+                .and_then(|recoverable| secp.recover_ecdsa(message, &recoverable))
+                .is_ok_and(|recovered| recovered == *public_key)
+        })
+        .map(|v| v as u8)
+        .ok_or("Failed to recover signature".to_string())
+}
+
+/// Encodes a compact ECDSA secp256k1 signature of the 32-byte `message` under
+/// `public_key`. The signature is verified first, so recovery ids are always right.
+pub fn encode_ecdsa_signature(
+    format: SignatureFormat,
+    signature: &[u8],
+    message: &[u8],
+    public_key: &[u8],
+) -> Result<Vec<u8>, String> {
+    if format == SignatureFormat::Compact {
+        verify_ecdsa_signature(format, signature, message, public_key)?;
+        return Ok(signature.to_vec());
+    }
+    let (message, public_key) = parse_inputs(message, public_key)?;
+    let mut signature = Signature::from_compact(signature).map_err(|e| e.to_string())?;
+    signature.normalize_s();
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &public_key)
+        .map_err(|_| "Signature is invalid".to_string())?;
+    let compact = signature.serialize_compact().to_vec();
+    match format {
+        SignatureFormat::Compact | SignatureFormat::LowS => Ok(compact),
+        SignatureFormat::Der => Ok(signature.serialize_der().to_vec()),
+        SignatureFormat::Rsv(v_offset) => {
+            let recovery_id = recovery_id(&signature, &message, &public_key)?;
+            Ok([compact, v_offset.encode(recovery_id)?].concat())
+        }
+        SignatureFormat::BitcoinCompact { compressed } => {
+            let recovery_id = recovery_id(&signature, &message, &public_key)?;
+            let header = 27 + recovery_id + if compressed { 4 } else { 0 };
+            Ok([vec![header], compact].concat())
+        }
+    }
+}
+
+/// Verifies an ECDSA secp256k1 signature in any [`SignatureFormat`] against the 32-byte
+/// `message`. For recoverable formats the recovery id must recover `public_key`.
+pub fn verify_ecdsa_signature(
+    format: SignatureFormat,
+    signature: &[u8],
+    message: &[u8],
+    public_key: &[u8],
+) -> Result<(), String> {
+    let (message, public_key) = parse_inputs(message, public_key)?;
+    let (compact, recovery_id) = match format {
+        SignatureFormat::Compact | SignatureFormat::LowS => (signature.to_vec(), None),
+        SignatureFormat::Der => (
+            Signature::from_der(signature)
+                .map_err(|e| e.to_string())?
+                .serialize_compact()
+                .to_vec(),
+            None,
+        ),
+        SignatureFormat::Rsv(v_offset) => {
+            if signature.len() < 65 {
+                return Err("Signature must be at least 65 bytes".to_string());
+            }
+            (
+                signature[..64].to_vec(),
+                Some(v_offset.decode(&signature[64..])?),
+            )
+        }
+        SignatureFormat::BitcoinCompact { compressed } => {
+            if signature.len() != 65 {
+                return Err("Signature must be 65 bytes".to_string());
+            }
+            let base = if compressed { 31 } else { 27 };
+            let recovery_id = match signature[0].checked_sub(base) {
+                Some(recovery_id @ 0..=3) => recovery_id,
+                _ => return Err(format!("Invalid header byte {}", signature[0])),
+            };
+            (signature[1..].to_vec(), Some(recovery_id))
+        }
+    };
+    let mut signature = Signature::from_compact(&compact).map_err(|e| e.to_string())?;
+    let normalized = {
+        let mut normalized = signature;
+        normalized.normalize_s();
+        normalized
+    };
+    if format != SignatureFormat::Compact && normalized != signature {
+        return Err("Signature is not low-S".to_string());
+    }
+    // libsecp256k1 only verifies low-S signatures
+    signature.normalize_s();
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &public_key)
+        .map_err(|_| "Signature is invalid".to_string())?;
+    if let Some(recovery_id) = recovery_id {
+        let recoverable = RecoveryId::try_from(recovery_id as i32)
+            .and_then(|rec_id| RecoverableSignature::from_compact(&compact, rec_id))
+            .map_err(|e| e.to_string())?;
+        let recovered = Secp256k1::verification_only()
+            .recover_ecdsa(&message, &recoverable)
+            .map_err(|e| e.to_string())?;
+        if recovered != public_key {
+            return Err("Recovery id does not recover the public key".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    #[test]
+    fn test_signature_formats() {
+        let signing_key = SigningKey::from_slice(&[5u8; 32]).unwrap();
+        let public_key = signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec();
+        let message = [0x42u8; 32];
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&message).unwrap();
+        let compact = signature.to_bytes().to_vec();
+        let high_s = k256::ecdsa::Signature::from_scalars(signature.r(), -*signature.s().as_ref())
+            .unwrap()
+            .to_bytes()
+            .to_vec();
+
+        for format in [
+            "compact",
+            "der",
+            "low-s",
+            "rsv",
+            "rsv27",
+            "eip155:1",
+            "eip155:11155111",
+            "bitcoin",
+            "bitcoin-uncompressed",
+        ] {
+            let format = SignatureFormat::from_str(format).unwrap();
+            assert_eq!(SignatureFormat::from_str(&format.to_string()), Ok(format));
+            for input in [&compact, &high_s] {
+                let encoded = encode_ecdsa_signature(format, input, &message, &public_key).unwrap();
+                verify_ecdsa_signature(format, &encoded, &message, &public_key).unwrap();
+            }
+        }
+
+        let rsv = encode_ecdsa_signature(
+            SignatureFormat::Rsv(VOffset::TwentySeven),
+            &high_s,
+            &message,
+            &public_key,
+        )
+        .unwrap();
+        assert_eq!(&rsv[..64], compact.as_slice());
+        assert_eq!(rsv[64], 27 + recovery_id.to_byte());
+        let eip155 = encode_ecdsa_signature(
+            SignatureFormat::Rsv(VOffset::Eip155 { chain_id: 1 }),
+            &compact,
+            &message,
+            &public_key,
+        )
+        .unwrap();
+        assert_eq!(eip155[64], 37 + recovery_id.to_byte());
+        // the wrong recovery id is rejected
+        let mut wrong = rsv.clone();
+        wrong[64] ^= 1;
+        assert!(verify_ecdsa_signature(
+            SignatureFormat::Rsv(VOffset::TwentySeven),
+            &wrong,
+            &message,
+            &public_key
+        )
+        .is_err());
+        assert!(
+            verify_ecdsa_signature(SignatureFormat::LowS, &high_s, &message, &public_key).is_err()
+        );
+
+        assert!(SignatureFormat::Der
+            .check(CryptoType::EcdsaSecp256k1)
+            .is_ok());
+        assert!(SignatureFormat::Compact.check(CryptoType::Ed25519).is_ok());
+        assert!(SignatureFormat::Der.check(CryptoType::Ed25519).is_err());
+        assert!(SignatureFormat::LowS.check(CryptoType::Secp256k1).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{PkId, ValidatorIdentityIdentity};
+
+use super::SignatureSuiteInfo;
+
+/// Which signatures to read from the coordinator's signature history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureQuery {
+    /// The signature made by one signing subsession (`subsession-...`).
+    Subsession(String),
+    /// Every signature of `message` with `pkid` and exactly this tweak.
+    Message {
+        pkid: PkId,
+        message: Vec<u8>,
+        tweak_data: Option<Vec<u8>>,
+    },
+    /// A page of all signatures, or of the signatures of one key.
+    List {
+        pkid: Option<PkId>,
+        offset: usize,
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureRecord<VII: ValidatorIdentityIdentity> {
+    /// The node that asked for the signature.
+    pub requester: VII,
+    /// unix timestamp in seconds
+    pub signed_at: u64,
+    /// The namespace of the key, `None` for the default one and for signatures made
+    /// before the namespace was recorded.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    pub signature_suite_info: SignatureSuiteInfo<VII>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignaturePage<VII: ValidatorIdentityIdentity> {
+    /// most recent first
+    pub records: Vec<SignatureRecord<VII>>,
+    /// number of signatures matching the query, regardless of the page
+    pub total: usize,
+}
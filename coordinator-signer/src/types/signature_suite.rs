@@ -1,4 +1,5 @@
-use super::{Participants, SubsessionId, ValidatorIdentityIdentity};
+use super::signature_format::{encode_ecdsa_signature, verify_ecdsa_signature};
+use super::{Participants, SignatureFormat, SubsessionId, ValidatorIdentityIdentity};
 use crate::crypto::{
     Cipher, Ed25519Sha512, PkId, PublicKeyPackage, Secp256K1Sha256, Secp256K1Sha256TR, Signature,
     VerifyingKey,
@@ -101,6 +102,7 @@ impl<VII: ValidatorIdentityIdentity, C: Cipher> SignatureSuite<VII, C> {
             message: self.message.clone(),
             crypto_type: C::crypto_type(),
             original_serialized: self.pretty_print(),
+            formatted_signature: None,
         })
     }
 }
@@ -155,6 +157,10 @@ pub struct SignatureSuiteInfo<VII: ValidatorIdentityIdentity> {
     pub(crate) message: Vec<u8>,
     pub(crate) crypto_type: CryptoType,
     pub(crate) original_serialized: String,
+    /// Set by the coordinator when the node asked for a format other than
+    /// [`SignatureFormat::Compact`], `signature` stays in the native encoding.
+    #[serde(default)]
+    pub(crate) formatted_signature: Option<(SignatureFormat, Vec<u8>)>,
 }
 impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>>
     SignatureSuiteInfo<VII>
@@ -165,6 +171,12 @@ impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>>
             "signature".to_string(),
             serde_json::Value::String(hex::encode(&self.signature)),
         );
+        if let Some((format, signature)) = &self.formatted_signature {
+            pretty_map.insert(
+                format!("signature_{}", format),
+                serde_json::Value::String(hex::encode(signature)),
+            );
+        }
         pretty_map.insert(
             "pk".to_string(),
             serde_json::Value::String(hex::encode(&self.pk)),
@@ -343,6 +355,54 @@ impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>>
         signature_with_rsv.push(recovery_id);
         Ok(signature_with_rsv)
     }
+    /// The signature encoded as `format`. Formats other than [`SignatureFormat::Compact`]
+    /// are only defined for ecdsa-secp256k1.
+    pub fn signature_with_format(&self, format: SignatureFormat) -> Result<Vec<u8>, String> {
+        format.check(self.crypto_type)?;
+        match format {
+            SignatureFormat::Compact => Ok(self.signature.clone()),
+            format => {
+                encode_ecdsa_signature(format, &self.signature, &self.message, &self.pk_tweak)
+            }
+        }
+    }
+    /// The signature in the format requested with [`crate::SignOptions::signature_format`],
+    /// and that format.
+    pub fn formatted_signature(&self) -> (SignatureFormat, Vec<u8>) {
+        match &self.formatted_signature {
+            Some((format, signature)) => (*format, signature.clone()),
+            None => (SignatureFormat::Compact, self.signature.clone()),
+        }
+    }
+    pub(crate) fn with_format(mut self, format: SignatureFormat) -> Result<Self, String> {
+        self.formatted_signature = match format {
+            SignatureFormat::Compact => None,
+            format => Some((format, self.signature_with_format(format)?)),
+        };
+        Ok(self)
+    }
+    /// Verifies `signature`, encoded as `format`, over the message and tweaked key of this
+    /// suite.
+    pub fn verify_with_format(
+        &self,
+        signature: &[u8],
+        format: SignatureFormat,
+    ) -> Result<(), String> {
+        match (self.crypto_type, format) {
+            (CryptoType::EcdsaSecp256k1, format) => {
+                verify_ecdsa_signature(format, signature, &self.message, &self.pk_tweak)
+            }
+            (_, SignatureFormat::Compact) => {
+                let mut suite = self.clone();
+                suite.signature = signature.to_vec();
+                suite.try_verify()
+            }
+            (crypto_type, format) => Err(format!(
+                "Signature format {} is not defined for {}",
+                format, crypto_type
+            )),
+        }
+    }
 }
 impl<VII: ValidatorIdentityIdentity + Serialize + for<'de> Deserialize<'de>>
     SignatureSuiteInfo<VII>
@@ -425,6 +485,7 @@ mod tests {
             message: message.to_vec(),
             crypto_type: CryptoType::Secp256k1,
             original_serialized: "".to_string(),
+            formatted_signature: None,
         };
         let signature = suite.signature_with_rsv().unwrap();
         assert_eq!(signature.len(), 65);
@@ -436,6 +497,23 @@ mod tests {
         let signature_with_rsv_bytes = signature_with_rsv.to_vec();
 
         assert_eq!(sb, signature_with_rsv_bytes);
+
+        // the requested format is returned next to the native signature
+        let suite = SignatureSuiteInfo {
+            crypto_type: CryptoType::EcdsaSecp256k1,
+            ..suite
+        };
+        let rsv = SignatureFormat::Rsv(crate::types::VOffset::Zero);
+        let formatted = suite.clone().with_format(rsv).unwrap();
+        assert_eq!(formatted.formatted_signature(), (rsv, sb));
+        assert_eq!(formatted.signature, suite.signature);
+        assert_eq!(suite.formatted_signature().0, SignatureFormat::Compact);
+        assert!(SignatureSuiteInfo {
+            crypto_type: CryptoType::Ed25519,
+            ..suite
+        }
+        .with_format(rsv)
+        .is_err());
     }
     #[test]
     fn test_signature_with_rsv_for_v() {
@@ -467,6 +545,7 @@ mod tests {
             message: message.to_vec(),
             crypto_type: CryptoType::Secp256k1,
             original_serialized: "".to_string(),
+            formatted_signature: None,
         };
         let signature = suite.signature_with_rsv().unwrap();
         assert_eq!(signature.len(), 65);
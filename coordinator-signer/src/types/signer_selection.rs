@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{Identifier, ValidatorIdentityIdentity};
+
+use super::{error::SessionError, Participants};
+
+/// Constraints a sign request puts on which participants of the key take part in signing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerSelection<VII: ValidatorIdentityIdentity> {
+    /// Participants that must all sign.
+    pub required: BTreeSet<VII>,
+    /// Participants picked before the others when there is a choice.
+    pub preferred: BTreeSet<VII>,
+    /// Participants that are never asked to sign.
+    pub excluded: BTreeSet<VII>,
+}
+impl<VII: ValidatorIdentityIdentity> Default for SignerSelection<VII> {
+    fn default() -> Self {
+        Self {
+            required: BTreeSet::new(),
+            preferred: BTreeSet::new(),
+            excluded: BTreeSet::new(),
+        }
+    }
+}
+impl<VII: ValidatorIdentityIdentity> SignerSelection<VII> {
+    /// Checks the selection against the key's participants and maps it to their identifiers.
+    pub(crate) fn resolve<CI: Identifier>(
+        &self,
+        participants: &Participants<VII, CI>,
+        min_signers: u16,
+    ) -> Result<SelectedIdentifiers<CI>, SessionError> {
+        let identifiers = participants
+            .iter()
+            .map(|(id, identity)| (identity.clone(), id.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let to_identifiers = |identities: &BTreeSet<VII>| {
+            identities
+                .iter()
+                .map(|identity| {
+                    identifiers.get(identity).cloned().ok_or_else(|| {
+                        SessionError::InvalidRequest(format!(
+                            "{} is not a participant of the key",
+                            identity.to_fmt_string()
+                        ))
+                    })
+                })
+                .collect::<Result<BTreeSet<CI>, SessionError>>()
+        };
+        let selected = SelectedIdentifiers {
+            required: to_identifiers(&self.required)?,
+            preferred: to_identifiers(&self.preferred)?,
+            excluded: to_identifiers(&self.excluded)?,
+        };
+        if !selected.required.is_disjoint(&selected.excluded)
+            || !selected.preferred.is_disjoint(&selected.excluded)
+        {
+            return Err(SessionError::InvalidRequest(
+                "excluded signers can not be required or preferred".to_string(),
+            ));
+        }
+        if selected.required.len() > min_signers as usize {
+            return Err(SessionError::InvalidRequest(format!(
+                "{} signers are required but only {} sign",
+                selected.required.len(),
+                min_signers
+            )));
+        }
+        if participants.len() - selected.excluded.len() < min_signers as usize {
+            return Err(SessionError::InvalidRequest(format!(
+                "{} of {} participants are excluded, min signers is {}",
+                selected.excluded.len(),
+                participants.len(),
+                min_signers
+            )));
+        }
+        Ok(selected)
+    }
+}
+
+/// A [`SignerSelection`] resolved to the identifiers of a key's participants.
+#[derive(Debug, Clone)]
+pub(crate) struct SelectedIdentifiers<CI: Identifier> {
+    required: BTreeSet<CI>,
+    preferred: BTreeSet<CI>,
+    excluded: BTreeSet<CI>,
+}
+impl<CI: Identifier> Default for SelectedIdentifiers<CI> {
+    fn default() -> Self {
+        Self {
+            required: BTreeSet::new(),
+            preferred: BTreeSet::new(),
+            excluded: BTreeSet::new(),
+        }
+    }
+}
+impl<CI: Identifier> SelectedIdentifiers<CI> {
+    pub(crate) fn required(&self) -> &BTreeSet<CI> {
+        &self.required
+    }
+    pub(crate) fn is_excluded(&self, id: &CI) -> bool {
+        self.excluded.contains(id)
+    }
+    /// Order in which participants are asked: required, then preferred, then the rest.
+    pub(crate) fn priority(&self, id: &CI) -> u8 {
+        if self.required.contains(id) {
+            0
+        } else if self.preferred.contains(id) {
+            1
+        } else {
+            2
+        }
+    }
+    /// Whether `signers` contains every required and no excluded participant.
+    pub(crate) fn allows(&self, signers: &BTreeSet<CI>) -> bool {
+        self.required.is_subset(signers) && self.excluded.is_disjoint(signers)
+    }
+    pub(crate) fn preferred_count(&self, signers: &BTreeSet<CI>) -> usize {
+        self.preferred.intersection(signers).count()
+    }
+    /// Picks `n` of the `available` participants: all required ones first, then the
+    /// preferred ones, then the rest in order. `None` if no allowed set can be formed yet.
+    pub(crate) fn pick<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a CI>,
+        n: usize,
+    ) -> Option<BTreeSet<CI>>
+    where
+        CI: 'a,
+    {
+        let available = available
+            .into_iter()
+            .filter(|id| !self.excluded.contains(*id))
+            .collect::<BTreeSet<_>>();
+        if !self.required.iter().all(|id| available.contains(id)) {
+            return None;
+        }
+        let picked = self
+            .required
+            .iter()
+            .chain(
+                available
+                    .iter()
+                    .copied()
+                    .filter(|id| self.preferred.contains(*id)),
+            )
+            .chain(available.iter().copied())
+            .cloned()
+            .fold(BTreeSet::new(), |mut picked, id| {
+                if picked.len() < n {
+                    picked.insert(id);
+                }
+                picked
+            });
+        (picked.len() == n).then_some(picked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_signer_selection() {
+        let peers = (0..4).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let participants =
+            Participants::new(peers.iter().enumerate().map(|(i, p)| (i as u16 + 1, *p))).unwrap();
+        let selection = SignerSelection {
+            required: BTreeSet::from([peers[3]]),
+            preferred: BTreeSet::from([peers[2]]),
+            excluded: BTreeSet::from([peers[0]]),
+        };
+        let selected = selection.resolve(&participants, 2).unwrap();
+        assert_eq!(
+            selected.pick(participants.keys(), 2),
+            Some(BTreeSet::from([3, 4]))
+        );
+        assert_eq!(selected.pick([1u16, 2, 3].iter(), 2), None);
+        assert!(selected.allows(&BTreeSet::from([2, 4])));
+        assert!(!selected.allows(&BTreeSet::from([1, 4])));
+        assert!(selected.priority(&4) < selected.priority(&3));
+        assert!(selected.priority(&3) < selected.priority(&2));
+
+        let excluded_required = SignerSelection {
+            required: BTreeSet::from([peers[0]]),
+            excluded: BTreeSet::from([peers[0]]),
+            ..Default::default()
+        };
+        assert!(excluded_required.resolve(&participants, 2).is_err());
+        let too_many_excluded = SignerSelection {
+            excluded: BTreeSet::from([peers[0], peers[1], peers[2]]),
+            ..Default::default()
+        };
+        assert!(too_many_excluded.resolve(&participants, 2).is_err());
+        let outsider = SignerSelection {
+            preferred: BTreeSet::from([PeerId::random()]),
+            ..Default::default()
+        };
+        assert!(outsider.resolve(&participants, 2).is_err());
+    }
+}
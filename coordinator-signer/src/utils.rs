@@ -1,5 +1,6 @@
 use rand::Rng;
 use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::oneshot;
 
 /// Concatenates data and calculates their SHA256 hash
@@ -24,6 +25,20 @@ where
     // Return hash bytes
     hasher.finalize().to_vec()
 }
+/// Seconds since the unix epoch
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+/// Milliseconds since the unix epoch
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
 pub(crate) fn random_readable_string(length: usize) -> String {
     let mut rng = rand::thread_rng();
     let mut bytes = Vec::with_capacity(length);
@@ -1,5 +1,8 @@
 use clap::{command, Parser, Subcommand};
 use coordinator_signer::crypto::CryptoType;
+use coordinator_signer::export::PublicKeyFormat;
+use coordinator_signer::{KeyState, SignatureFormat};
+use std::path::PathBuf;
 
 // Define the structure for the command-line application
 #[derive(Parser)]
@@ -17,41 +20,150 @@ pub struct CommandLineApp {
 pub enum Commands {
     /// Run as a coordinator role
     Coordinator {
+        /// Generate keys automatically with this many min signers once all signers registered
         #[arg(short, long)]
         auto_dkg: Option<u16>,
+        /// Crypto type of the automatic keys, can be repeated; all of them if omitted
+        #[arg(long = "auto-dkg-crypto-type", value_parser = parse_crypto_type)]
+        auto_dkg_crypto_types: Vec<CryptoType>,
+        /// Peer id of a signer holding the automatic keys, can be repeated; the whole
+        /// whitelist if omitted
+        #[arg(long = "auto-dkg-participant")]
+        auto_dkg_participants: Vec<String>,
     },
     /// Run as a node role
     DKG {
         min_signer: u16,
         #[arg(value_parser = parse_crypto_type)]
         crypto_type: CryptoType,
+        /// Label of the new key
+        #[arg(long)]
+        label: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        /// Tag of the new key, can be repeated
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Retrying with the same key returns the key generated by the first attempt
+        #[arg(long)]
+        idempotency_key: Option<String>,
     },
     LoopSign {
         pkid: String,
         times: usize,
     },
-    AutoDKG,
+    /// Sign several messages with one key in a single request
+    SignBatch {
+        pkid: String,
+        messages: Vec<String>,
+        /// Send one request per message with at most this many in flight instead, and
+        /// print the signatures as they complete
+        #[arg(long)]
+        concurrency: Option<usize>,
+    },
+    AutoDKG {
+        /// Application slot whose keys to fetch, generated on first use; the default
+        /// slot if omitted
+        #[arg(long)]
+        app: Option<String>,
+    },
     Lspk,
     Pk {
         pkid: String,
         tweak: Option<String>,
+        /// Output format: hex, der, pem, jwk or openssh
+        #[arg(short, long, default_value = "hex")]
+        format: PublicKeyFormat,
+    },
+    /// Fetch the full public key package of a key and check that it hashes to the pkid
+    PkPackage {
+        pkid: String,
     },
     ///
     Sign {
         pkid: String,
         message: String,
         tweak: Option<String>,
+        /// Signature encoding: compact, der, low-s, rsv, rsv27, eip155:<chain id>, bitcoin or
+        /// bitcoin-uncompressed
+        #[arg(short, long, default_value = "compact")]
+        format: SignatureFormat,
+        /// Retrying with the same key returns the signature of the first attempt
+        #[arg(long)]
+        idempotency_key: Option<String>,
     },
 
     /// Run as a signer role and require the 'id' argument
     Signer {
         /// The ID of the signer
         id: u16,
+        /// PkId whose signatures need the operator's approval, can be repeated
+        #[arg(long = "approve")]
+        approve: Vec<String>,
+        /// Record every signing decision in a hash-chained audit log at this path
+        #[arg(long)]
+        audit_log: Option<PathBuf>,
     },
     Web {
         #[arg(short, long, default_value = "3030")]
         port: u16,
     },
+    /// Edit the label, description or tags of a key
+    KeyMeta {
+        pkid: String,
+        /// New label, an empty one clears it
+        #[arg(long)]
+        label: Option<String>,
+        /// New description, an empty one clears it
+        #[arg(long)]
+        description: Option<String>,
+        /// Replaces the tags of the key, can be repeated
+        #[arg(long = "tag")]
+        tags: Option<Vec<String>>,
+    },
+    /// Move a key to another lifecycle state: active, disabled, retired or destroyed
+    KeyState {
+        pkid: String,
+        state: KeyState,
+    },
+    /// Check an audit log written by the coordinator or a signer
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Manage the signer whitelist of the coordinator running on this host
+    Whitelist {
+        #[command(subcommand)]
+        command: WhitelistCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WhitelistCommand {
+    /// List the whitelisted signers, their labels and whether they are registered
+    List,
+    /// Allow a signer to register or change its label; starts a whitelist of the
+    /// registered signers if the coordinator has none
+    Add {
+        peer_id: String,
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Disallow a signer and disconnect it
+    Remove { peer_id: String },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Verify the hash chain and the checkpoint signatures
+    Verify {
+        /// The audit log to check, the coordinator's by default
+        path: Option<PathBuf>,
+        /// Peer id that must have signed the checkpoints, the configured coordinator
+        /// peer id when checking the coordinator's log
+        #[arg(long)]
+        signer: Option<String>,
+    },
 }
 
 fn parse_crypto_type(s: &str) -> Result<CryptoType, String> {
@@ -1,17 +1,26 @@
 mod commands;
 mod webserver;
 use common::Settings;
+use coordinator_signer::audit::verify_audit_log;
 use coordinator_signer::crypto::validator_identity::p2p_identity::P2pIdentity;
 use coordinator_signer::crypto::{PkId, ValidatorIdentity};
+use coordinator_signer::export::PublicKeyFormat;
 use coordinator_signer::node::Node;
 use coordinator_signer::signer::Signer;
 use coordinator_signer::{
     coordinator::Coordinator, crypto::validator_identity::ValidatorIdentityIdentity,
 };
+use coordinator_signer::{
+    AutoDKGOptions, DkgOptions, KeyMetadataUpdate, SignItem, SignOptions, SignatureFormat,
+};
+use futures::StreamExt;
 use libp2p::{Multiaddr, PeerId};
 use rand::Rng;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -28,6 +37,19 @@ pub fn load_keypair(path: &str) -> libp2p::identity::Keypair {
     f.read_to_end(&mut buffer).unwrap();
     libp2p::identity::Keypair::from_protobuf_encoding(buffer.as_slice()).unwrap()
 }
+// sends one command to the IPC socket of the coordinator and returns its reply
+fn coordinator_command(home_dir: &Path, command: &str) -> anyhow::Result<String> {
+    let path = home_dir.join(Settings::global().coordinator.ipc_socket_path);
+    let mut stream = UnixStream::connect(&path).map_err(|e| {
+        anyhow::anyhow!("cannot reach the coordinator at {}: {}", path.display(), e)
+    })?;
+    stream.write_all(command.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.shutdown(Shutdown::Write)?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(reply)
+}
 pub(crate) fn random_readable_string(length: usize) -> String {
     let mut rng = rand::rng();
     let mut bytes = Vec::with_capacity(length);
@@ -72,9 +94,26 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // let min_signer = whitelist.len() as u16 / 2 + 1;
     let cmd = commands::parse_args();
     match cmd {
-        commands::Commands::Coordinator { auto_dkg } => {
+        commands::Commands::Coordinator {
+            auto_dkg,
+            auto_dkg_crypto_types,
+            auto_dkg_participants,
+        } => {
             // default keypair = 12D3KooWB3LpKiErRF3byUAsCvY6JL8TtQeSCrF5Hw23UoKJ7F88
             let keypair = load_keypair(Settings::global().coordinator.keypair_path.as_str());
+            let auto_dkg = match auto_dkg {
+                Some(min_signers) => Some(AutoDKGOptions {
+                    min_signers,
+                    crypto_types: auto_dkg_crypto_types,
+                    participants: auto_dkg_participants
+                        .iter()
+                        .map(|peer_id| {
+                            <P2pIdentity as ValidatorIdentity>::Identity::from_fmt_str(peer_id)
+                        })
+                        .collect::<Result<_, _>>()?,
+                }),
+                None => None,
+            };
             let coordinator = Coordinator::<P2pIdentity>::new(
                 keypair,
                 home_dir,
@@ -85,7 +124,11 @@ async fn main() -> Result<(), Box<dyn Error>> {
             // coordinator.start_listening().await?;
             coordinator.start_listening().await?;
         }
-        commands::Commands::Signer { id } => {
+        commands::Commands::Signer {
+            id,
+            approve,
+            audit_log,
+        } => {
             let keypair = load_keypair(
                 Settings::global()
                     .signer
@@ -102,12 +145,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 coordinator_multiaddr,
                 coordinator_peer_id,
                 |_, _| true,
-            )?;
+            )?
+            .with_approval(approve.into_iter().map(PkId::from));
+            let signer = match audit_log {
+                Some(path) => signer.with_audit_log(path)?,
+                None => signer,
+            };
             signer.start_listening().await?;
         }
         commands::Commands::DKG {
             min_signer,
             crypto_type,
+            label,
+            description,
+            tags,
+            idempotency_key,
         } => {
             let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
             let node = Node::<P2pIdentity>::new(
@@ -122,8 +174,17 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .iter()
                 .map(|peer_id| libp2p::identity::PeerId::from_fmt_str(peer_id).unwrap())
                 .collect::<Vec<_>>();
+            let options = DkgOptions {
+                deadline: None,
+                metadata: KeyMetadataUpdate {
+                    label,
+                    description,
+                    tags: Some(tags.into_iter().collect()),
+                },
+                idempotency_key,
+            };
             let resp = node
-                .key_generate(crypto_type, participants, min_signer)
+                .key_generate_with_options(crypto_type, participants, min_signer, options)
                 .unwrap();
             let r = resp.await.unwrap().unwrap();
             tracing::info!("{:?}", r.to_string());
@@ -133,6 +194,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
             pkid,
             message,
             tweak,
+            format,
+            idempotency_key,
         } => {
             println!("pkid: {}", pkid);
             println!("message: {}", message);
@@ -144,16 +207,27 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 coordinator_multiaddr,
                 coordinator_peer_id,
             )?;
+            let options = SignOptions {
+                idempotency_key,
+                signature_format: format,
+                ..Default::default()
+            };
             let resp = node
-                .sign(
+                .sign_with_options(
                     PkId::new(hex::decode(&pkid).unwrap()),
                     message.as_bytes().to_vec(),
                     tweak.map(|t| t.as_bytes().to_vec()),
+                    options,
                 )
                 .unwrap();
             let r = resp.await.unwrap().unwrap();
             println!("{}", r.pretty_print());
             println!("{:?}", r._verify());
+            if format != SignatureFormat::Compact {
+                let (format, signature) = r.formatted_signature();
+                println!("{} signature: {}", format, hex::encode(&signature));
+                println!("{:?}", r.verify_with_format(&signature, format));
+            }
         }
         commands::Commands::Web { port } => {
             let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
@@ -165,6 +239,45 @@ async fn main() -> Result<(), Box<dyn Error>> {
             )?;
             start_webserver(Arc::new(node), port).await?;
         }
+        commands::Commands::SignBatch {
+            pkid,
+            messages,
+            concurrency,
+        } => {
+            let pkid = PkId::new(hex::decode(&pkid)?);
+            let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
+            let node = Node::<P2pIdentity>::new(
+                keypair,
+                home_dir,
+                coordinator_multiaddr,
+                coordinator_peer_id,
+            )?;
+            let items = messages
+                .iter()
+                .map(|message| SignItem::new(pkid.clone(), message.as_bytes().to_vec(), None))
+                .collect::<Vec<_>>();
+            let timeout = Some(Duration::from_secs(60));
+            match concurrency {
+                Some(concurrency) => {
+                    let mut results = Box::pin(node.sign_stream(items, concurrency, timeout));
+                    while let Some(result) = results.next().await {
+                        match result {
+                            Ok(r) => println!("{}", r.pretty_print()),
+                            Err(e) => println!("error: {}", e),
+                        }
+                    }
+                }
+                None => {
+                    let results = node.sign_batch_async(items, timeout).await?;
+                    for (message, result) in messages.iter().zip(results) {
+                        match result {
+                            Ok(r) => println!("{}: {}", message, r.pretty_print()),
+                            Err(e) => println!("{}: error: {}", message, e),
+                        }
+                    }
+                }
+            }
+        }
         commands::Commands::LoopSign { pkid, times } => {
             let pkid = PkId::new(hex::decode(&pkid).unwrap());
             let tweak = random_readable_string(100);
@@ -214,11 +327,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
             )?;
             let r = node.lspk_async().await.unwrap();
             for (k, v) in r {
-                let v = v.iter().map(|pkid| pkid.to_string()).collect::<Vec<_>>();
-                println!("{}: {:?}", k, v);
+                for key in v {
+                    println!(
+                        "{}: {} state: {} label: {} tags: {:?} threshold: {}/{}",
+                        k,
+                        key.pkid,
+                        key.metadata.state,
+                        key.metadata.label.as_deref().unwrap_or("-"),
+                        key.metadata.tags,
+                        key.metadata.min_signers,
+                        key.metadata.participants.len()
+                    );
+                }
             }
         }
-        commands::Commands::AutoDKG => {
+        commands::Commands::AutoDKG { app } => {
             let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
             let node = Node::<P2pIdentity>::new(
                 keypair,
@@ -226,10 +349,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 coordinator_multiaddr,
                 coordinator_peer_id,
             )?;
-            let r = node.auto_dkg_async(None).await.unwrap();
+            let r = node.auto_dkg_for_app_async(app, None).await.unwrap();
             println!("{}", r);
         }
-        commands::Commands::Pk { pkid, tweak } => {
+        commands::Commands::Pk {
+            pkid,
+            tweak,
+            format,
+        } => {
             let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
             let node = Node::<P2pIdentity>::new(
                 keypair,
@@ -237,19 +364,106 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 coordinator_multiaddr,
                 coordinator_peer_id,
             )?;
+            let pkid = PkId::new(hex::decode(&pkid).unwrap());
             let r = node
-                .pk_async(
-                    PkId::new(hex::decode(&pkid).unwrap()),
-                    tweak.map(|t| t.as_bytes().to_vec()),
-                    None,
-                )
+                .pk_async(pkid.clone(), tweak.map(|t| t.as_bytes().to_vec()), None)
                 .await
                 .unwrap();
-            println!(
-                "tweak: {:?},group_public_key_tweak: {:?}",
-                r.tweak_data.map(hex::encode),
-                hex::encode(r.group_public_key_tweak)
-            );
+            let metadata = r.metadata;
+            let r = r.group_public_key_info;
+            match format {
+                PublicKeyFormat::Hex => {
+                    println!(
+                        "tweak: {:?},group_public_key_tweak: {:?}",
+                        r.tweak_data.map(hex::encode),
+                        hex::encode(r.group_public_key_tweak)
+                    );
+                    println!("metadata: {}", serde_json::to_string(&metadata)?);
+                }
+                format => println!("{}", r.export(&pkid, format)?),
+            }
+        }
+        commands::Commands::KeyMeta {
+            pkid,
+            label,
+            description,
+            tags,
+        } => {
+            let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
+            let node = Node::<P2pIdentity>::new(
+                keypair,
+                home_dir,
+                coordinator_multiaddr,
+                coordinator_peer_id,
+            )?;
+            let update = KeyMetadataUpdate {
+                label,
+                description,
+                tags: tags.map(|tags| tags.into_iter().collect()),
+            };
+            let metadata = node
+                .update_key_metadata_async(PkId::new(hex::decode(&pkid)?), update, None)
+                .await?;
+            println!("{}", serde_json::to_string(&metadata)?);
+        }
+        commands::Commands::PkPackage { pkid } => {
+            let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
+            let node = Node::<P2pIdentity>::new(
+                keypair,
+                home_dir,
+                coordinator_multiaddr,
+                coordinator_peer_id,
+            )?;
+            let package = node
+                .public_key_package_async(PkId::new(hex::decode(&pkid)?), None)
+                .await?;
+            println!("{}", serde_json::to_string(&package)?);
+        }
+        commands::Commands::KeyState { pkid, state } => {
+            let keypair = load_keypair(Settings::global().node.keypair_path.as_str());
+            let node = Node::<P2pIdentity>::new(
+                keypair,
+                home_dir,
+                coordinator_multiaddr,
+                coordinator_peer_id,
+            )?;
+            let metadata = node
+                .set_key_state_async(PkId::new(hex::decode(&pkid)?), state, None)
+                .await?;
+            println!("{}", serde_json::to_string(&metadata)?);
+        }
+        commands::Commands::Audit {
+            command: commands::AuditCommand::Verify { path, signer },
+        } => {
+            let (path, signer) = match path {
+                Some(path) => (path, signer),
+                None => (
+                    home_dir.join(Settings::global().coordinator.audit_log_path),
+                    signer.or(Some(Settings::global().coordinator.peer_id)),
+                ),
+            };
+            let signer = signer.map(|s| PeerId::from_str(&s)).transpose()?;
+            let summary = verify_audit_log::<P2pIdentity>(&path, signer.as_ref())?;
+            println!("Audit log {} is intact: {}", path.display(), summary);
+        }
+        commands::Commands::Whitelist { command } => {
+            let command = match command {
+                commands::WhitelistCommand::List => "whitelist list".to_string(),
+                commands::WhitelistCommand::Add { peer_id, label } => {
+                    // the IPC socket reads one command per line
+                    if label.as_deref().is_some_and(|label| label.contains('\n')) {
+                        return Err("the label may not contain a newline".into());
+                    }
+                    match label {
+                        Some(label) => format!("whitelist add {} {}", peer_id, label),
+                        None => format!("whitelist add {}", peer_id),
+                    }
+                }
+                commands::WhitelistCommand::Remove { peer_id } => {
+                    format!("whitelist remove {}", peer_id)
+                }
+            };
+            print!("{}", coordinator_command(&home_dir, &command)?);
         }
     }
     Ok(())
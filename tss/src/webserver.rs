@@ -1,6 +1,10 @@
 use coordinator_signer::crypto::p2p_identity::P2pIdentity;
 use coordinator_signer::crypto::{CryptoType, PkId};
+use coordinator_signer::export::PublicKeyFormat;
 use coordinator_signer::node::Node;
+use coordinator_signer::{
+    Deadline, KeyInfo, KeyMetadataUpdate, KeyState, SignOptions, SignatureFormat, SignatureQuery,
+};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -11,6 +15,10 @@ use warp::Filter;
 struct GetPublicKeyRequest {
     crypto_type: String,
     tweak: Option<String>,
+    /// hex (default), der, pem, jwk or openssh
+    format: Option<String>,
+    label: Option<String>,
+    tag: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -18,6 +26,36 @@ struct SignRequest {
     crypto_type: String,
     message: String,
     tweak: Option<String>,
+    /// compact (default), der, low-s, rsv, rsv27, eip155:<chain id>, bitcoin or
+    /// bitcoin-uncompressed
+    signature_format: Option<String>,
+    label: Option<String>,
+    tag: Option<String>,
+    /// Retrying with the same key returns the signature of the first attempt
+    idempotency_key: Option<String>,
+}
+
+/// `subsession` looks up one signing, `message` (with `tweak`) every signature of a
+/// message, anything else lists a page of the history.
+#[derive(Deserialize)]
+struct SignaturesRequest {
+    subsession: Option<String>,
+    /// hex, or the first key of `crypto_type` matching `label` and `tag`
+    pkid: Option<String>,
+    crypto_type: Option<String>,
+    label: Option<String>,
+    tag: Option<String>,
+    message: Option<String>,
+    tweak: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct KeysRequest {
+    crypto_type: Option<String>,
+    label: Option<String>,
+    tag: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -32,21 +70,25 @@ struct SignResponse {
     signature: String,
     verification: String,
 }
-async fn get_first_pk(
+fn key_matches(key: &KeyInfo<P2pIdentity>, label: Option<&str>, tag: Option<&str>) -> bool {
+    label.map_or(true, |label| key.metadata.label.as_deref() == Some(label))
+        && tag.map_or(true, |tag| key.metadata.tags.contains(tag))
+}
+/// The first key of `crypto_type` with the given label and tag, if any.
+async fn find_pk(
     node: Arc<Node<P2pIdentity>>,
     crypto_type: CryptoType,
+    label: Option<&str>,
+    tag: Option<&str>,
 ) -> Result<PkId, anyhow::Error> {
     let resp = node.lspk_async().await?;
-    let pkid = resp.get(&crypto_type);
-    if let Some(pkid) = pkid {
-        if pkid.len() > 0 {
-            Ok(pkid[0].clone())
-        } else {
-            Err(anyhow::anyhow!("no pkid found"))
-        }
-    } else {
-        Err(anyhow::anyhow!("crypto type not found"))
-    }
+    let keys = resp
+        .get(&crypto_type)
+        .ok_or_else(|| anyhow::anyhow!("crypto type not found"))?;
+    keys.iter()
+        .find(|key| key_matches(key, label, tag))
+        .map(|key| key.pkid.clone())
+        .ok_or_else(|| anyhow::anyhow!("no pkid found"))
 }
 pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<(), anyhow::Error> {
     let node_sign = node.clone();
@@ -59,10 +101,30 @@ pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<
             let node = node_pk.clone(); // Clone the Arc, not the inner Node
 
             async move {
+                let format = request
+                    .format
+                    .as_deref()
+                    .map(PublicKeyFormat::from_str)
+                    .transpose();
+                let format = match format {
+                    Ok(format) => format.unwrap_or_default(),
+                    Err(e) => {
+                        return Ok(warp::reply::json(&GetPublicKeyResponse {
+                            status: "error".to_string(),
+                            result: e.to_string(),
+                        }) as Json);
+                    }
+                };
                 let crypto_type = CryptoType::from_str(&request.crypto_type);
                 match crypto_type {
                     Ok(crypto_type) => {
-                        let pkid = get_first_pk(node.clone(), crypto_type).await;
+                        let pkid = find_pk(
+                            node.clone(),
+                            crypto_type,
+                            request.label.as_deref(),
+                            request.tag.as_deref(),
+                        )
+                        .await;
                         if let Err(e) = pkid {
                             return Ok(warp::reply::json(&GetPublicKeyResponse {
                                 status: "error".to_string(),
@@ -72,17 +134,18 @@ pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<
                         let pkid = pkid.unwrap();
                         let resp = node
                             .pk_async(
-                                pkid,
+                                pkid.clone(),
                                 request.tweak.map(|t| t.as_bytes().to_vec()),
                                 Some(Duration::from_secs(10)),
                             )
                             .await;
-                        let result = resp;
+                        let result =
+                            resp.and_then(|r| Ok(r.group_public_key_info.export(&pkid, format)?));
                         match result {
                             Ok(result) => {
                                 Ok::<_, warp::Rejection>(warp::reply::json(&GetPublicKeyResponse {
                                     status: "success".to_string(),
-                                    result: hex::encode(result.group_public_key_tweak),
+                                    result,
                                 }) as Json)
                             } // Ensure the return type implements Reply
                             Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
@@ -115,7 +178,26 @@ pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<
                     }) as Json);
                 }
                 let crypto_type = crypto_type.unwrap();
-                let pkid = get_first_pk(node.clone(), crypto_type).await;
+                let format = request
+                    .signature_format
+                    .as_deref()
+                    .map(SignatureFormat::from_str)
+                    .transpose();
+                if let Err(e) = format {
+                    return Ok(warp::reply::json(&SignResponse {
+                        status: "error".to_string(),
+                        signature: "".to_string(),
+                        verification: e,
+                    }) as Json);
+                }
+                let format = format.unwrap().unwrap_or_default();
+                let pkid = find_pk(
+                    node.clone(),
+                    crypto_type,
+                    request.label.as_deref(),
+                    request.tag.as_deref(),
+                )
+                .await;
                 if let Err(e) = pkid {
                     return Ok(warp::reply::json(&SignResponse {
                         status: "error".to_string(),
@@ -126,23 +208,35 @@ pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<
                 let pkid = pkid.unwrap();
                 let tweak = request.tweak.map(|t| t.as_bytes().to_vec());
 
-                let resp = node
-                    .sign_async(
+                let timeout = Duration::from_secs(10);
+                let options = SignOptions {
+                    deadline: Some(Deadline::after(timeout)),
+                    idempotency_key: request.idempotency_key,
+                    signature_format: format,
+                    ..Default::default()
+                };
+                let resp = async {
+                    let r = node.sign_with_options(
                         pkid,
                         request.message.as_bytes().to_vec(),
                         tweak,
-                        Some(Duration::from_secs(10)),
-                    )
-                    .await;
-                let result = resp;
-                match result {
-                    Ok(result) => Ok(warp::reply::json(&SignResponse {
-                        status: "success".to_string(),
-                        signature: hex::encode(result.signature()),
-                        verification: result
-                            ._verify()
-                            .map_or_else(|e| e.to_string(), |_| "success".to_string()),
-                    }) as Json),
+                        options,
+                    )?;
+                    let r = tokio::time::timeout(timeout, r).await??;
+                    r.map_err(|e| anyhow::anyhow!("sign error: {:?}", e))
+                }
+                .await;
+                match resp {
+                    Ok(result) => {
+                        let (format, signature) = result.formatted_signature();
+                        Ok(warp::reply::json(&SignResponse {
+                            status: "success".to_string(),
+                            signature: hex::encode(&signature),
+                            verification: result
+                                .verify_with_format(&signature, format)
+                                .map_or_else(|e| e.to_string(), |_| "success".to_string()),
+                        }) as Json)
+                    }
                     Err(e) => Ok(warp::reply::json(&SignResponse {
                         status: "error".to_string(),
                         signature: e.to_string(),
@@ -152,8 +246,238 @@ pub async fn start_webserver(node: Arc<Node<P2pIdentity>>, port: u16) -> Result<
             }
         });
 
+    let node_jwks = node.clone();
+    let handle_jwks = warp::path!(".well-known" / "jwks.json")
+        .or(warp::path("jwks"))
+        .unify()
+        .and(warp::get())
+        .and_then(move || {
+            let node = node_jwks.clone();
+            async move {
+                match coordinator_signer::jws::jwks(node.as_ref()).await {
+                    Ok(jwks) => Ok::<_, warp::Rejection>(warp::reply::json(&jwks) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
+    let node_outcomes = node.clone();
+    let handle_session_outcomes =
+        warp::path("session_outcomes")
+            .and(warp::get())
+            .and_then(move || {
+                let node = node_outcomes.clone();
+                async move {
+                    match node
+                        .session_outcomes_async(Some(Duration::from_secs(10)))
+                        .await
+                    {
+                        Ok(outcomes) => {
+                            Ok::<_, warp::Rejection>(warp::reply::json(&outcomes) as Json)
+                        }
+                        Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                            status: "error".to_string(),
+                            result: e.to_string(),
+                        }) as Json),
+                    }
+                }
+            });
+
+    let node_status = node.clone();
+    let handle_session_status = warp::path("session_status")
+        .and(warp::get())
+        .and_then(move || {
+            let node = node_status.clone();
+            async move {
+                match node
+                    .session_status_async(Some(Duration::from_secs(10)))
+                    .await
+                {
+                    Ok(report) => Ok::<_, warp::Rejection>(warp::reply::json(&report) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
+    let node_cancel = node.clone();
+    let handle_cancel =
+        warp::path!("cancel" / String)
+            .and(warp::post())
+            .and_then(move |session_id: String| {
+                let node = node_cancel.clone();
+                async move {
+                    let result = node
+                        .cancel_async(session_id.clone(), Some(Duration::from_secs(10)))
+                        .await;
+                    Ok::<_, warp::Rejection>(warp::reply::json(&match result {
+                        Ok(()) => GetPublicKeyResponse {
+                            status: "success".to_string(),
+                            result: session_id,
+                        },
+                        Err(e) => GetPublicKeyResponse {
+                            status: "error".to_string(),
+                            result: e.to_string(),
+                        },
+                    }) as Json)
+                }
+            });
+
+    let node_signatures = node.clone();
+    let handle_signatures = warp::path("signatures")
+        .and(warp::get())
+        .and(warp::query::<SignaturesRequest>())
+        .and_then(move |request: SignaturesRequest| {
+            let node = node_signatures.clone();
+            async move {
+                let result = async {
+                    let pkid = match (request.pkid, request.crypto_type) {
+                        (Some(pkid), _) => Some(PkId::new(hex::decode(pkid)?)),
+                        (None, Some(crypto_type)) => Some(
+                            find_pk(
+                                node.clone(),
+                                CryptoType::from_str(&crypto_type)?,
+                                request.label.as_deref(),
+                                request.tag.as_deref(),
+                            )
+                            .await?,
+                        ),
+                        (None, None) => None,
+                    };
+                    let query = match (request.subsession, request.message) {
+                        (Some(subsession_id), _) => SignatureQuery::Subsession(subsession_id),
+                        (None, Some(message)) => SignatureQuery::Message {
+                            pkid: pkid
+                                .ok_or_else(|| anyhow::anyhow!("pkid or crypto_type required"))?,
+                            message: message.as_bytes().to_vec(),
+                            tweak_data: request.tweak.map(|t| t.as_bytes().to_vec()),
+                        },
+                        (None, None) => SignatureQuery::List {
+                            pkid,
+                            offset: request.offset.unwrap_or(0),
+                            limit: request.limit.unwrap_or(20),
+                        },
+                    };
+                    let page = node
+                        .signature_history_async(query, Some(Duration::from_secs(10)))
+                        .await?;
+                    Ok::<_, anyhow::Error>(page)
+                }
+                .await;
+                match result {
+                    Ok(page) => Ok::<_, warp::Rejection>(warp::reply::json(&page) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
+    let node_keys = node.clone();
+    let handle_keys = warp::path("keys")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<KeysRequest>())
+        .and_then(move |request: KeysRequest| {
+            let node = node_keys.clone();
+            async move {
+                let result = async {
+                    let crypto_type = request
+                        .crypto_type
+                        .as_deref()
+                        .map(CryptoType::from_str)
+                        .transpose()?;
+                    let keys = node
+                        .lspk_async()
+                        .await?
+                        .into_iter()
+                        .filter(|(k, _)| crypto_type.map_or(true, |crypto_type| *k == crypto_type))
+                        .flat_map(|(_, keys)| keys)
+                        .filter(|key| {
+                            key_matches(key, request.label.as_deref(), request.tag.as_deref())
+                        })
+                        .collect::<Vec<_>>();
+                    Ok::<_, anyhow::Error>(keys)
+                }
+                .await;
+                match result {
+                    Ok(keys) => Ok::<_, warp::Rejection>(warp::reply::json(&keys) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
+    let node_key_metadata = node.clone();
+    let handle_key_metadata = warp::path!("keys" / String)
+        .and(warp::post())
+        .and(warp::body::json::<KeyMetadataUpdate>())
+        .and_then(move |pkid: String, update: KeyMetadataUpdate| {
+            let node = node_key_metadata.clone();
+            async move {
+                let result = async {
+                    let pkid = PkId::new(hex::decode(pkid)?);
+                    let metadata = node
+                        .update_key_metadata_async(pkid, update, Some(Duration::from_secs(10)))
+                        .await?;
+                    Ok::<_, anyhow::Error>(metadata)
+                }
+                .await;
+                match result {
+                    Ok(metadata) => Ok::<_, warp::Rejection>(warp::reply::json(&metadata) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
+    let node_key_state = node.clone();
+    let handle_key_state = warp::path!("keys" / String / "state" / String)
+        .and(warp::post())
+        .and_then(move |pkid: String, state: String| {
+            let node = node_key_state.clone();
+            async move {
+                let result = async {
+                    let pkid = PkId::new(hex::decode(pkid)?);
+                    let state = KeyState::from_str(&state)?;
+                    // destroying waits for the signers to erase their shares
+                    let metadata = node
+                        .set_key_state_async(pkid, state, Some(Duration::from_secs(60)))
+                        .await?;
+                    Ok::<_, anyhow::Error>(metadata)
+                }
+                .await;
+                match result {
+                    Ok(metadata) => Ok::<_, warp::Rejection>(warp::reply::json(&metadata) as Json),
+                    Err(e) => Ok(warp::reply::json(&GetPublicKeyResponse {
+                        status: "error".to_string(),
+                        result: e.to_string(),
+                    }) as Json),
+                }
+            }
+        });
+
     // Combine all routes
-    let routes = handle_pk.or(handle_sign);
+    let routes = handle_pk
+        .or(handle_sign)
+        .or(handle_jwks)
+        .or(handle_session_outcomes)
+        .or(handle_session_status)
+        .or(handle_cancel)
+        .or(handle_signatures)
+        .or(handle_keys)
+        .or(handle_key_metadata)
+        .or(handle_key_state);
 
     // Start web server
     warp::serve(routes).run(([127, 0, 0, 1], port)).await;